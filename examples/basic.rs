@@ -14,10 +14,7 @@ fn main() {
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
 
-    commands.spawn_bundle(LdtkWorldBundle {
-        ldtk_handle: asset_server.load("my_project.ldtk"),
-        ..Default::default()
-    });
+    commands.spawn_bundle(LdtkWorldBundle::from_path(&asset_server, "my_project.ldtk"));
 }
 
 #[derive(Default, Component)]
@@ -1,13 +1,19 @@
-use proc_macro;
-use proc_macro2;
 use quote::quote;
-use syn;
 
 static SPRITE_BUNDLE_ATTRIBUTE_NAME: &str = "sprite_bundle";
 static SPRITE_SHEET_BUNDLE_ATTRIBUTE_NAME: &str = "sprite_sheet_bundle";
 static WORLDLY_ATRIBUTE_NAME: &str = "worldly";
+static GRID_COORDS_ATTRIBUTE_NAME: &str = "grid_coords";
 static LDTK_ENTITY_ATTRIBUTE_NAME: &str = "ldtk_entity";
 static FROM_ENTITY_INSTANCE_ATTRIBUTE_NAME: &str = "from_entity_instance";
+static WITH_ATTRIBUTE_NAME: &str = "with";
+static LDTK_ENUM_FIELD_ATTRIBUTE_NAME: &str = "ldtk_enum_field";
+static LDTK_POINT_FIELD_ATTRIBUTE_NAME: &str = "ldtk_point_field";
+static LDTK_POINT_FIELD_TRANSLATION_ATTRIBUTE_NAME: &str = "ldtk_point_field_translation";
+static LDTK_TILE_FIELD_ATTRIBUTE_NAME: &str = "ldtk_tile_field";
+static LDTK_COLOR_FIELD_ATTRIBUTE_NAME: &str = "ldtk_color_field";
+static LDTK_POINTS_FIELD_ATTRIBUTE_NAME: &str = "ldtk_points_field";
+static LDTK_POINTS_FIELD_TRANSLATION_ATTRIBUTE_NAME: &str = "ldtk_points_field_translation";
 
 pub fn expand_ldtk_entity_derive(ast: &syn::DeriveInput) -> proc_macro::TokenStream {
     let struct_name = &ast.ident;
@@ -56,6 +62,17 @@ pub fn expand_ldtk_entity_derive(ast: &syn::DeriveInput) -> proc_macro::TokenStr
             continue;
         }
 
+        let grid_coords = field
+            .attrs
+            .iter()
+            .find(|a| *a.path.get_ident().as_ref().unwrap() == GRID_COORDS_ATTRIBUTE_NAME);
+        if let Some(attribute) = grid_coords {
+            field_constructions.push(expand_grid_coords_attribute(
+                attribute, field_name, field_type,
+            ));
+            continue;
+        }
+
         let ldtk_entity = field
             .attrs
             .iter()
@@ -78,6 +95,90 @@ pub fn expand_ldtk_entity_derive(ast: &syn::DeriveInput) -> proc_macro::TokenStr
             continue;
         }
 
+        let with = field
+            .attrs
+            .iter()
+            .find(|a| *a.path.get_ident().as_ref().unwrap() == WITH_ATTRIBUTE_NAME);
+        if let Some(attribute) = with {
+            field_constructions.push(expand_with_attribute(attribute, field_name, field_type));
+            continue;
+        }
+
+        let ldtk_enum_field = field
+            .attrs
+            .iter()
+            .find(|a| *a.path.get_ident().as_ref().unwrap() == LDTK_ENUM_FIELD_ATTRIBUTE_NAME);
+        if let Some(attribute) = ldtk_enum_field {
+            field_constructions.push(expand_ldtk_enum_field_attribute(
+                attribute, field_name, field_type,
+            ));
+            continue;
+        }
+
+        let ldtk_point_field = field
+            .attrs
+            .iter()
+            .find(|a| *a.path.get_ident().as_ref().unwrap() == LDTK_POINT_FIELD_ATTRIBUTE_NAME);
+        if let Some(attribute) = ldtk_point_field {
+            field_constructions.push(expand_ldtk_point_field_attribute(
+                attribute, field_name, field_type,
+            ));
+            continue;
+        }
+
+        let ldtk_point_field_translation = field.attrs.iter().find(|a| {
+            *a.path.get_ident().as_ref().unwrap() == LDTK_POINT_FIELD_TRANSLATION_ATTRIBUTE_NAME
+        });
+        if let Some(attribute) = ldtk_point_field_translation {
+            field_constructions.push(expand_ldtk_point_field_translation_attribute(
+                attribute, field_name, field_type,
+            ));
+            continue;
+        }
+
+        let ldtk_tile_field = field
+            .attrs
+            .iter()
+            .find(|a| *a.path.get_ident().as_ref().unwrap() == LDTK_TILE_FIELD_ATTRIBUTE_NAME);
+        if let Some(attribute) = ldtk_tile_field {
+            field_constructions.push(expand_ldtk_tile_field_attribute(
+                attribute, field_name, field_type,
+            ));
+            continue;
+        }
+
+        let ldtk_points_field = field
+            .attrs
+            .iter()
+            .find(|a| *a.path.get_ident().as_ref().unwrap() == LDTK_POINTS_FIELD_ATTRIBUTE_NAME);
+        if let Some(attribute) = ldtk_points_field {
+            field_constructions.push(expand_ldtk_points_field_attribute(
+                attribute, field_name, field_type,
+            ));
+            continue;
+        }
+
+        let ldtk_points_field_translation = field.attrs.iter().find(|a| {
+            *a.path.get_ident().as_ref().unwrap() == LDTK_POINTS_FIELD_TRANSLATION_ATTRIBUTE_NAME
+        });
+        if let Some(attribute) = ldtk_points_field_translation {
+            field_constructions.push(expand_ldtk_points_field_translation_attribute(
+                attribute, field_name, field_type,
+            ));
+            continue;
+        }
+
+        let ldtk_color_field = field
+            .attrs
+            .iter()
+            .find(|a| *a.path.get_ident().as_ref().unwrap() == LDTK_COLOR_FIELD_ATTRIBUTE_NAME);
+        if let Some(attribute) = ldtk_color_field {
+            field_constructions.push(expand_ldtk_color_field_attribute(
+                attribute, field_name, field_type,
+            ));
+            continue;
+        }
+
         field_constructions.push(quote! {
             #field_name: <#field_type as std::default::Default>::default(),
         });
@@ -95,6 +196,18 @@ pub fn expand_ldtk_entity_derive(ast: &syn::DeriveInput) -> proc_macro::TokenStr
                 tileset_definition: Option<&bevy_ecs_ldtk::prelude::TilesetDefinition>,
                 asset_server: &bevy::prelude::AssetServer,
                 texture_atlases: &mut bevy::prelude::Assets<bevy::prelude::TextureAtlas>,
+            ) -> Self {
+                bevy_ecs_ldtk::app::LdtkEntityContext::with_tileset(tileset, tileset_definition, |context| {
+                    <Self as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity_with_context(entity_instance, layer_instance, context, asset_server, texture_atlases)
+                })
+            }
+
+            fn bundle_entity_with_context(
+                entity_instance: &bevy_ecs_ldtk::prelude::EntityInstance,
+                layer_instance: &bevy_ecs_ldtk::prelude::LayerInstance,
+                context: &bevy_ecs_ldtk::app::LdtkEntityContext,
+                asset_server: &bevy::prelude::AssetServer,
+                texture_atlases: &mut bevy::prelude::Assets<bevy::prelude::TextureAtlas>,
             ) -> Self {
                 Self {
                     #(#field_constructions)*
@@ -114,7 +227,7 @@ fn expand_sprite_bundle_attribute(
     match field_type {
         syn::Type::Path(syn::TypePath { path: syn::Path { segments, .. }, .. }) => {
             if let Some(last) = segments.last() {
-                if last.ident.to_string() != "SpriteBundle".to_string() {
+                if last.ident != "SpriteBundle" {
                     panic!("#[sprite_bundle...] attribute should apply to a field of type bevy::prelude::SpriteBundle")
                 }
             }
@@ -132,9 +245,13 @@ fn expand_sprite_bundle_attribute(
                     let asset_path = &asset.value();
 
                     quote! {
-                        #field_name: bevy::prelude::SpriteBundle {
-                            texture: asset_server.load(#asset_path),
-                            ..Default::default()
+                        #field_name: if context.headless {
+                            Default::default()
+                        } else {
+                            bevy::prelude::SpriteBundle {
+                                texture: asset_server.load(#asset_path),
+                                ..Default::default()
+                            }
                         },
                     }
                 },
@@ -143,7 +260,7 @@ fn expand_sprite_bundle_attribute(
         },
         syn::Meta::Path(_) => {
             quote! {
-                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity(entity_instance, layer_instance, tileset, tileset_definition, asset_server, texture_atlases),
+                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity_with_context(entity_instance, layer_instance, context, asset_server, texture_atlases),
             }
         },
         _ => panic!("#[sprite_bundle...] attribute should take the form #[sprite_bundle(\"asset/path.png\")] or #[sprite_bundle]"),
@@ -159,7 +276,7 @@ fn expand_sprite_sheet_bundle_attribute(
     match field_type {
         syn::Type::Path(syn::TypePath { path: syn::Path { segments, .. }, .. }) => {
             if let Some(last) = segments.last() {
-                if last.ident.to_string() != "SpriteSheetBundle".to_string() {
+                if last.ident != "SpriteSheetBundle" {
                     panic!("#[sprite_sheet_bundle...] attribute should apply to a field of type bevy::prelude::SpriteSheetBundle")
                 }
             }
@@ -204,25 +321,29 @@ fn expand_sprite_sheet_bundle_attribute(
             };
 
             quote! {
-                #field_name: bevy::prelude::SpriteSheetBundle {
-                    texture_atlas: texture_atlases.add(
-                        bevy::prelude::TextureAtlas::from_grid_with_padding(
-                            asset_server.load(#asset_path).into(),
-                            bevy::prelude::Vec2::new(#tile_width, #tile_height),
-                            #columns, #rows, bevy::prelude::Vec2::splat(#padding),
-                        )
-                    ),
-                    sprite: bevy::prelude::TextureAtlasSprite {
-                        index: #index,
+                #field_name: if context.headless {
+                    Default::default()
+                } else {
+                    bevy::prelude::SpriteSheetBundle {
+                        texture_atlas: texture_atlases.add(
+                            bevy::prelude::TextureAtlas::from_grid_with_padding(
+                                asset_server.load(#asset_path).into(),
+                                bevy::prelude::Vec2::new(#tile_width, #tile_height),
+                                #columns, #rows, bevy::prelude::Vec2::splat(#padding),
+                            )
+                        ),
+                        sprite: bevy::prelude::TextureAtlasSprite {
+                            index: #index,
+                            ..Default::default()
+                        },
                         ..Default::default()
-                    },
-                    ..Default::default()
+                    }
                 },
             }
         },
         syn::Meta::Path(_) => {
             quote! {
-                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity(entity_instance, layer_instance, tileset, tileset_definition, asset_server, texture_atlases),
+                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity_with_context(entity_instance, layer_instance, context, asset_server, texture_atlases),
             }
         },
         _ => panic!("#[sprite_sheet_bundle...] attribute should take the form #[sprite_sheet_bundle(\"asset/path.png\", tile_width, tile_height, columns, rows, padding, index)] or #[sprite_sheet_bundle]"),
@@ -240,13 +361,31 @@ fn expand_worldly_attribute(
     {
         syn::Meta::Path(_) => {
             quote! {
-                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity(entity_instance, layer_instance, tileset, tileset_definition, asset_server, texture_atlases),
+                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity_with_context(entity_instance, layer_instance, context, asset_server, texture_atlases),
             }
         }
         _ => panic!("#[worldly] attribute should take the form #[worldly]"),
     }
 }
 
+fn expand_grid_coords_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    match attribute
+        .parse_meta()
+        .expect("Cannot parse #[grid_coords] attribute")
+    {
+        syn::Meta::Path(_) => {
+            quote! {
+                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity_with_context(entity_instance, layer_instance, context, asset_server, texture_atlases),
+            }
+        }
+        _ => panic!("#[grid_coords] attribute should take the form #[grid_coords]"),
+    }
+}
+
 fn expand_ldtk_entity_attribute(
     attribute: &syn::Attribute,
     field_name: &syn::Ident,
@@ -258,7 +397,7 @@ fn expand_ldtk_entity_attribute(
     {
         syn::Meta::Path(_) => {
             quote! {
-                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity(entity_instance, layer_instance, tileset, tileset_definition, asset_server, texture_atlases),
+                #field_name: <#field_type as bevy_ecs_ldtk::prelude::LdtkEntity>::bundle_entity_with_context(entity_instance, layer_instance, context, asset_server, texture_atlases),
             }
         }
         _ => panic!("#[ldtk_entity] attribute should take the form #[ldtk_entity]"),
@@ -284,3 +423,320 @@ fn expand_from_entity_instance_attribute(
         }
     }
 }
+
+fn expand_with_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    _: &syn::Type,
+) -> proc_macro2::TokenStream {
+    match attribute
+        .parse_meta()
+        .expect("Cannot parse #[with...] attribute")
+    {
+        syn::Meta::List(syn::MetaList { nested, .. }) if !nested.is_empty() => {
+            let mut nested = nested.iter();
+
+            let path = match nested.next().unwrap() {
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) => path,
+                _ => panic!("Expected function as the first argument of #[with(...)]"),
+            };
+
+            let mut listed = Vec::new();
+            let arguments: Vec<proc_macro2::TokenStream> = nested
+                .map(|argument| {
+                    let argument = match argument {
+                        syn::NestedMeta::Meta(syn::Meta::Path(argument)) => argument
+                            .get_ident()
+                            .map(|ident| ident.to_string())
+                            .unwrap_or_default(),
+                        _ => String::new(),
+                    };
+
+                    if listed.contains(&argument) {
+                        panic!("#[with(...)] argument `{}` is listed twice", argument);
+                    }
+                    listed.push(argument.clone());
+
+                    match argument.as_str() {
+                        "layer" => quote! { layer_instance },
+                        "level" => quote! { context.level },
+                        _ => panic!(
+                            "Expected `layer` or `level` after the function of #[with(...)]"
+                        ),
+                    }
+                })
+                .collect();
+
+            quote! {
+                #field_name: #path(entity_instance, #(#arguments),*),
+            }
+        }
+        _ => panic!(
+            "#[with...] attribute should take the form #[with(function_name)], optionally followed by `layer` and/or `level`"
+        ),
+    }
+}
+
+fn expand_ldtk_enum_field_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let identifier = parse_field_identifier_attribute(attribute, LDTK_ENUM_FIELD_ATTRIBUTE_NAME);
+
+    let field_value = expand_field_value(
+        field_name,
+        &identifier,
+        quote! {
+            bevy_ecs_ldtk::ldtk::LdtkFields::get_maybe_enum_field_as::<#field_type>(entity_instance, #identifier)
+        },
+        quote! { value },
+    );
+
+    quote! {
+        #field_name: #field_value,
+    }
+}
+
+/// Expands to the construction of a field from `maybe_value`, a `Result<Option<_>, LdtkFieldsError>`
+/// of an LDtk field, using `construction` on its `value`.
+///
+/// Null fields and errors fall back to the default of the field, with a warning, since leaving an
+/// optional field null in LDtk is valid.
+fn expand_field_value(
+    field_name: &syn::Ident,
+    identifier: &syn::LitStr,
+    maybe_value: proc_macro2::TokenStream,
+    construction: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote! {
+        match #maybe_value {
+            Ok(Some(value)) => #construction,
+            Ok(None) => {
+                bevy::log::warn!("Could not construct {} from LDtk entity {}: field \"{}\" is null, using the default instead", stringify!(#field_name), entity_instance.identifier, #identifier);
+                Default::default()
+            }
+            Err(e) => {
+                bevy::log::warn!("Could not construct {} from LDtk entity {}: {}, using the default instead", stringify!(#field_name), entity_instance.identifier, e);
+                Default::default()
+            }
+        }
+    }
+}
+
+fn parse_field_identifier_attribute(
+    attribute: &syn::Attribute,
+    attribute_name: &str,
+) -> syn::LitStr {
+    match attribute
+        .parse_meta()
+        .unwrap_or_else(|_| panic!("Cannot parse #[{}...] attribute", attribute_name))
+    {
+        syn::Meta::List(syn::MetaList { nested, .. }) if nested.len() == 1 => {
+            match nested.first().unwrap() {
+                syn::NestedMeta::Lit(syn::Lit::Str(identifier)) => identifier.clone(),
+                _ => panic!(
+                    "Expected field identifier as the only argument of #[{}(...)]",
+                    attribute_name
+                ),
+            }
+        }
+        _ => panic!(
+            "#[{0}...] attribute should take the form #[{0}(\"FieldIdentifier\")]",
+            attribute_name
+        ),
+    }
+}
+
+fn expand_ldtk_point_field_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let identifier = parse_field_identifier_attribute(attribute, LDTK_POINT_FIELD_ATTRIBUTE_NAME);
+
+    let field_value = expand_field_value(
+        field_name,
+        &identifier,
+        quote! {
+            bevy_ecs_ldtk::ldtk::LdtkFields::get_maybe_point_field(entity_instance, #identifier)
+        },
+        quote! {
+            <#field_type as From<bevy_ecs_ldtk::GridCoords>>::from(
+                bevy_ecs_ldtk::utils::ldtk_grid_coords_to_grid_coords(value, layer_instance.c_hei)
+            )
+        },
+    );
+
+    quote! {
+        #field_name: #field_value,
+    }
+}
+
+fn expand_ldtk_point_field_translation_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let identifier =
+        parse_field_identifier_attribute(attribute, LDTK_POINT_FIELD_TRANSLATION_ATTRIBUTE_NAME);
+
+    let field_value = expand_field_value(
+        field_name,
+        &identifier,
+        quote! {
+            bevy_ecs_ldtk::ldtk::LdtkFields::get_maybe_point_field(entity_instance, #identifier)
+        },
+        quote! {
+            <#field_type as From<bevy::prelude::Vec2>>::from(
+                bevy_ecs_ldtk::utils::ldtk_grid_coords_to_translation_pivoted(
+                    value,
+                    layer_instance.c_hei,
+                    layer_instance.grid_size,
+                    bevy::prelude::IVec2::new(entity_instance.width, entity_instance.height),
+                    entity_instance.pivot,
+                )
+            )
+        },
+    );
+
+    quote! {
+        #field_name: #field_value,
+    }
+}
+
+fn expand_ldtk_tile_field_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    match field_type {
+        syn::Type::Path(syn::TypePath { path: syn::Path { segments, .. }, .. }) => {
+            if let Some(last) = segments.last() {
+                if last.ident != *"SpriteSheetBundle" {
+                    panic!("#[ldtk_tile_field...] attribute should apply to a field of type bevy::prelude::SpriteSheetBundle")
+                }
+            }
+        }
+        _ => panic!("#[ldtk_tile_field...] attribute should apply to a field of type bevy::prelude::SpriteSheetBundle")
+    }
+
+    let identifier = parse_field_identifier_attribute(attribute, LDTK_TILE_FIELD_ATTRIBUTE_NAME);
+
+    let field_value = expand_field_value(
+        field_name,
+        &identifier,
+        quote! {
+            bevy_ecs_ldtk::ldtk::LdtkFields::get_maybe_tile_field(entity_instance, #identifier)
+        },
+        quote! {
+            match context.get_tileset(value.tileset_uid) {
+                Some((tileset, tileset_definition)) => {
+                    bevy_ecs_ldtk::app::sprite_sheet_bundle_from_tile(value, tileset, tileset_definition, texture_atlases)
+                }
+                None => {
+                    bevy::log::warn!("Could not construct {} from LDtk entity {}: the tileset of field \"{}\" isn't loaded", stringify!(#field_name), entity_instance.identifier, #identifier);
+                    Default::default()
+                }
+            }
+        },
+    );
+
+    quote! {
+        #field_name: if context.headless {
+            Default::default()
+        } else {
+            #field_value
+        },
+    }
+}
+
+fn expand_ldtk_points_field_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let identifier = parse_field_identifier_attribute(attribute, LDTK_POINTS_FIELD_ATTRIBUTE_NAME);
+
+    let field_value = expand_field_value(
+        field_name,
+        &identifier,
+        quote! {
+            bevy_ecs_ldtk::ldtk::LdtkFields::get_points_field(entity_instance, #identifier).map(Some)
+        },
+        quote! {
+            <#field_type as From<Vec<bevy_ecs_ldtk::GridCoords>>>::from(
+                value
+                    .iter()
+                    .flatten()
+                    .map(|point| bevy_ecs_ldtk::utils::ldtk_grid_coords_to_grid_coords(*point, layer_instance.c_hei))
+                    .collect()
+            )
+        },
+    );
+
+    quote! {
+        #field_name: #field_value,
+    }
+}
+
+fn expand_ldtk_points_field_translation_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let identifier =
+        parse_field_identifier_attribute(attribute, LDTK_POINTS_FIELD_TRANSLATION_ATTRIBUTE_NAME);
+
+    let field_value = expand_field_value(
+        field_name,
+        &identifier,
+        quote! {
+            bevy_ecs_ldtk::ldtk::LdtkFields::get_points_field(entity_instance, #identifier).map(Some)
+        },
+        quote! {
+            <#field_type as From<Vec<bevy::prelude::Vec2>>>::from(
+                value
+                    .iter()
+                    .flatten()
+                    .map(|point| {
+                        bevy_ecs_ldtk::utils::ldtk_grid_coords_to_translation_pivoted(
+                            *point,
+                            layer_instance.c_hei,
+                            layer_instance.grid_size,
+                            bevy::prelude::IVec2::new(entity_instance.width, entity_instance.height),
+                            entity_instance.pivot,
+                        )
+                    })
+                    .collect()
+            )
+        },
+    );
+
+    quote! {
+        #field_name: #field_value,
+    }
+}
+
+fn expand_ldtk_color_field_attribute(
+    attribute: &syn::Attribute,
+    field_name: &syn::Ident,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let identifier = parse_field_identifier_attribute(attribute, LDTK_COLOR_FIELD_ATTRIBUTE_NAME);
+
+    let field_value = expand_field_value(
+        field_name,
+        &identifier,
+        quote! {
+            bevy_ecs_ldtk::ldtk::LdtkFields::get_color_field(entity_instance, #identifier).map(Some)
+        },
+        quote! {
+            <#field_type as From<bevy::prelude::Color>>::from(value)
+        },
+    );
+
+    quote! {
+        #field_name: #field_value,
+    }
+}
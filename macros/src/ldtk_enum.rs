@@ -0,0 +1,38 @@
+use quote::quote;
+
+pub fn expand_ldtk_enum_derive(ast: &syn::DeriveInput) -> proc_macro::TokenStream {
+    let enum_name = &ast.ident;
+
+    let variants = match &ast.data {
+        syn::Data::Enum(syn::DataEnum { variants, .. }) => variants,
+        _ => panic!("Expected an enum."),
+    };
+
+    let mut variant_matches = Vec::new();
+    for variant in variants {
+        let variant_name = &variant.ident;
+        let ldtk_value = variant_name.to_string();
+
+        match variant.fields {
+            syn::Fields::Unit => variant_matches.push(quote! {
+                #ldtk_value => Some(#enum_name::#variant_name),
+            }),
+            _ => panic!("Expected an enum with only unit variants."),
+        }
+    }
+
+    let generics = &ast.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let gen = quote! {
+        impl #impl_generics bevy_ecs_ldtk::ldtk::LdtkEnum for #enum_name #ty_generics #where_clause {
+            fn from_ldtk_enum_value(value: &str) -> Option<Self> {
+                match value {
+                    #(#variant_matches)*
+                    _ => None,
+                }
+            }
+        }
+    };
+    gen.into()
+}
@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
-use syn;
 
 mod ldtk_entity;
+mod ldtk_enum;
 mod ldtk_int_cell;
 
 #[proc_macro_derive(
@@ -10,8 +10,17 @@ mod ldtk_int_cell;
         sprite_bundle,
         sprite_sheet_bundle,
         worldly,
+        grid_coords,
         ldtk_entity,
-        from_entity_instance
+        from_entity_instance,
+        with,
+        ldtk_enum_field,
+        ldtk_point_field,
+        ldtk_point_field_translation,
+        ldtk_tile_field,
+        ldtk_points_field,
+        ldtk_points_field_translation,
+        ldtk_color_field
     )
 )]
 pub fn ldtk_entity_derive(input: TokenStream) -> TokenStream {
@@ -20,9 +29,16 @@ pub fn ldtk_entity_derive(input: TokenStream) -> TokenStream {
     ldtk_entity::expand_ldtk_entity_derive(&ast)
 }
 
-#[proc_macro_derive(LdtkIntCell, attributes(ldtk_int_cell, from_int_grid_cell))]
+#[proc_macro_derive(LdtkIntCell, attributes(ldtk_int_cell, from_int_grid_cell, with))]
 pub fn ldtk_int_cell_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).unwrap();
 
     ldtk_int_cell::expand_ldtk_int_cell_derive(&ast)
 }
+
+#[proc_macro_derive(LdtkEnum)]
+pub fn ldtk_enum_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+
+    ldtk_enum::expand_ldtk_enum_derive(&ast)
+}
@@ -0,0 +1,77 @@
+use crate::{
+    components::LdtkFileHandles,
+    ldtk::{FieldInstance, FieldValue},
+};
+use bevy::{asset::Asset, ecs::system::EntityCommands, prelude::*};
+use std::{any::Any, collections::HashMap, marker::PhantomData, path::Path};
+
+#[allow(unused_imports)]
+use crate::app::register_ldtk_objects::RegisterLdtkObjects;
+
+/// The `FilePath` fields of an LDtk entity or level that are loaded as assets of type `A`.
+pub struct FilePathFields<A: Asset> {
+    pub field_identifiers: Vec<String>,
+    asset: PhantomData<A>,
+}
+
+impl<A: Asset> FilePathFields<A> {
+    pub fn new(field_identifiers: Vec<String>) -> Self {
+        FilePathFields {
+            field_identifiers,
+            asset: PhantomData,
+        }
+    }
+}
+
+pub trait FilePathFieldsTrait {
+    /// Loads the files of the fields, and inserts their handles in an [LdtkFileHandles]
+    /// component.
+    ///
+    /// The paths are relative to `ldtk_directory`, the directory of the LDtk project, like the
+    /// paths of its tilesets.
+    ///
+    /// Null fields and fields of other types are skipped.
+    fn evaluate<'w, 's, 'a, 'b>(
+        &self,
+        entity_commands: &'b mut EntityCommands<'w, 's, 'a>,
+        field_instances: &[FieldInstance],
+        asset_server: &AssetServer,
+        ldtk_directory: &Path,
+    ) -> &'b mut EntityCommands<'w, 's, 'a>;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<A: Asset> FilePathFieldsTrait for FilePathFields<A> {
+    fn evaluate<'w, 's, 'a, 'b>(
+        &self,
+        entity_commands: &'b mut EntityCommands<'w, 's, 'a>,
+        field_instances: &[FieldInstance],
+        asset_server: &AssetServer,
+        ldtk_directory: &Path,
+    ) -> &'b mut EntityCommands<'w, 's, 'a> {
+        let handles = field_instances
+            .iter()
+            .filter(|f| self.field_identifiers.contains(&f.identifier))
+            .filter_map(|f| match &f.value {
+                FieldValue::FilePath(Some(path)) => Some((
+                    f.identifier.clone(),
+                    asset_server.load(ldtk_directory.join(path)),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        entity_commands.insert(LdtkFileHandles::<A> { handles })
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Used by [RegisterLdtkObjects] to associate LDtk entity identifiers with the `FilePath` fields
+/// loaded for them.
+///
+/// The fields of levels are stored with a [None] key.
+pub type LdtkFilePathFieldMap = HashMap<Option<String>, Vec<Box<dyn FilePathFieldsTrait>>>;
@@ -0,0 +1,117 @@
+use crate::{
+    app::ldtk_entity::PhantomLdtkEntityTrait,
+    ldtk::{EntityInstance, EntityInstanceTile, LayerInstance, TilesetDefinition},
+};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+#[allow(unused_imports)]
+use crate::{
+    app::{register_ldtk_objects::RegisterLdtkObjects, LdtkEntity},
+    components::IntGridCell,
+};
+
+/// A tile of an LDtk tileset, used as the visual of promoted IntGrid cells.
+///
+/// See [RegisterLdtkObjects::register_ldtk_int_cell_promotion] for more details.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct PromotedTile {
+    /// Identifier of the tileset in LDtk.
+    pub tileset_identifier: String,
+    /// Id of the tile in the tileset.
+    pub tile_id: i32,
+}
+
+impl PromotedTile {
+    pub fn new(tileset_identifier: &str, tile_id: i32) -> Self {
+        PromotedTile {
+            tileset_identifier: tileset_identifier.to_string(),
+            tile_id,
+        }
+    }
+}
+
+/// An IntGrid value registered to spawn an [LdtkEntity] instead of an [IntGridCell].
+pub struct IntCellPromotion {
+    pub tile: Option<PromotedTile>,
+    pub ldtk_entity: Box<dyn PhantomLdtkEntityTrait>,
+}
+
+/// Used by [RegisterLdtkObjects] to associate Ldtk IntGrid values with promoted [LdtkEntity]s.
+pub type LdtkIntCellPromotionMap = HashMap<(Option<String>, i32), IntCellPromotion>;
+
+/// Calculates the `src_rect` of a tile in the format used by [EntityInstanceTile].
+pub(crate) fn tile_id_to_src_rect(
+    tile_id: i32,
+    tileset_definition: &TilesetDefinition,
+) -> Vec<i32> {
+    let stride = tileset_definition.tile_grid_size + tileset_definition.spacing;
+    let columns = tileset_definition.c_wid.max(1);
+
+    vec![
+        tileset_definition.padding + (tile_id % columns) * stride,
+        tileset_definition.padding + (tile_id / columns) * stride,
+        tileset_definition.tile_grid_size,
+        tileset_definition.tile_grid_size,
+    ]
+}
+
+/// Creates the [EntityInstance] that a promoted IntGrid cell is spawned from.
+///
+/// The instance is centered on the cell, has the identifier of the layer, and no fields.
+pub(crate) fn promoted_entity_instance(
+    layer_instance: &LayerInstance,
+    int_grid_index: usize,
+    tile: Option<(i32, &TilesetDefinition)>,
+) -> EntityInstance {
+    let grid_size = layer_instance.grid_size;
+    let grid = IVec2::new(
+        int_grid_index as i32 % layer_instance.c_wid,
+        int_grid_index as i32 / layer_instance.c_wid,
+    );
+
+    EntityInstance {
+        grid,
+        identifier: layer_instance.identifier.clone(),
+        pivot: Vec2::splat(0.5),
+        tile: tile.map(|(tile_id, tileset_definition)| EntityInstanceTile {
+            src_rect: tile_id_to_src_rect(tile_id, tileset_definition),
+            tileset_uid: tileset_definition.uid,
+        }),
+        px: grid * grid_size
+            + IVec2::splat(grid_size / 2)
+            + IVec2::new(
+                layer_instance.px_total_offset_x,
+                layer_instance.px_total_offset_y,
+            ),
+        width: grid_size,
+        height: grid_size,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_id_to_src_rect() {
+        let tileset_definition = TilesetDefinition {
+            c_wid: 4,
+            c_hei: 4,
+            tile_grid_size: 16,
+            spacing: 2,
+            padding: 1,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            tile_id_to_src_rect(0, &tileset_definition),
+            vec![1, 1, 16, 16]
+        );
+        assert_eq!(
+            tile_id_to_src_rect(6, &tileset_definition),
+            vec![37, 19, 16, 16]
+        );
+    }
+}
@@ -1,9 +1,15 @@
 use crate::{
-    components::{EntityInstanceBundle, Worldly},
-    ldtk::{EntityInstance, LayerInstance, TilesetDefinition},
+    components::{DespawnPolicy, EntityInstanceBundle, GridCoords, Worldly},
+    ldtk::{EntityInstance, LayerInstance, Level, TilesetDefinition, TilesetRectangle},
+    utils::ldtk_grid_coords_to_grid_coords,
+};
+use bevy::{asset::HandleId, ecs::system::EntityCommands, prelude::*, reflect::TypeUuid};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    path::Path,
 };
-use bevy::{ecs::system::EntityCommands, prelude::*};
-use std::{collections::HashMap, marker::PhantomData};
 
 #[allow(unused_imports)]
 use crate::app::register_ldtk_objects::RegisterLdtkObjects;
@@ -100,6 +106,7 @@ use crate::app::register_ldtk_objects::RegisterLdtkObjects;
 /// Similar to using [TextureAtlas::from_grid()].
 /// - `#[sprite_sheet_bundle]` will create the field using information from the LDtk Editor visual,
 /// if it has one.
+/// Entities sharing a tileset and tile size share the same [TextureAtlas].
 /// ```
 /// # use bevy::prelude::*;
 /// # use bevy_ecs_ldtk::prelude::*;
@@ -149,6 +156,25 @@ use crate::app::register_ldtk_objects::RegisterLdtkObjects;
 /// }
 /// ```
 ///
+/// ### `#[grid_coords]`
+/// Indicates that a component is [GridCoords], storing the grid coordinates of the entity.
+///
+/// The coordinates are those of the entity's grid cell in LDtk, converted to the y-up space of
+/// [GridCoords].
+/// Note that they are not updated when the entity moves.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// # #[derive(Component, Default)]
+/// # struct Block;
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct BlockBundle {
+///     block: Block,
+///     #[grid_coords]
+///     grid_coords: GridCoords,
+/// }
+/// ```
+///
 /// ### `#[ldtk_entity]`
 /// Indicates that a component or bundle that implements [LdtkEntity] should be created with
 /// [LdtkEntity::bundle_entity], allowing for nested [LdtkEntity]s.
@@ -180,6 +206,41 @@ use crate::app::register_ldtk_objects::RegisterLdtkObjects;
 /// }
 /// ```
 ///
+/// This also makes it easy to share a sub-bundle across many entity types, since the nested
+/// bundle is constructed from the same [EntityInstance] as its parent:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// # #[derive(Component, Default)]
+/// # struct Highlight;
+/// # #[derive(Component, Default)]
+/// # struct Chest;
+/// # #[derive(Component, Default)]
+/// # struct Door;
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct Interactable {
+///     highlight: Highlight,
+///     #[from_entity_instance]
+///     entity_instance: EntityInstance,
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct ChestBundle {
+///     chest: Chest,
+///     #[ldtk_entity]
+///     #[bundle]
+///     interactable: Interactable,
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct DoorBundle {
+///     door: Door,
+///     #[ldtk_entity]
+///     #[bundle]
+///     interactable: Interactable,
+/// }
+/// ```
+///
 /// ### `#[from_entity_instance]`
 /// Indicates that a component or bundle that implements [From<EntityInstance>] should be created
 /// using that conversion.
@@ -217,6 +278,230 @@ use crate::app::register_ldtk_objects::RegisterLdtkObjects;
 ///     entity_instance: EntityInstance,
 /// }
 /// ```
+///
+/// The [EntityInstance] is cloned for each field using this attribute.
+/// If a component only needs to read it, implementing [From<&EntityInstance>] avoids the clone.
+/// Such a component can be constructed with [`#[with(...)]`](#with) instead:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// # #[derive(Component, Default)]
+/// # struct Health(i32);
+/// impl From<&EntityInstance> for Health {
+///     fn from(entity_instance: &EntityInstance) -> Health {
+///         Health(entity_instance.width)
+///     }
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct SlimeBundle {
+///     #[with(Health::from)]
+///     health: Health,
+/// }
+/// ```
+///
+/// ### `#[with(...)]`
+/// Indicates that a component or bundle should be created with the given function, which takes
+/// the [EntityInstance] by reference.
+/// This is useful for computed fields, like reading a value from the fields of the entity,
+/// without implementing [From<EntityInstance>] for that component.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// # #[derive(Component, Default)]
+/// # struct Goblin;
+/// #[derive(Component)]
+/// struct Health(i32);
+///
+/// fn health_from_field(entity_instance: &EntityInstance) -> Health {
+///     let hp = entity_instance
+///         .field_instances
+///         .iter()
+///         .find_map(|f| match (f.identifier.as_str(), &f.value) {
+///             ("hp", FieldValue::Int(Some(hp))) => Some(*hp),
+///             _ => None,
+///         })
+///         .unwrap_or(10);
+///
+///     Health(hp)
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct GoblinBundle {
+///     goblin: Goblin,
+///     #[with(health_from_field)]
+///     health: Health,
+/// }
+/// ```
+/// If the construction needs the layer or the level the entity is on, list `layer` and/or `level`
+/// after the function, like `#[with(function_name, layer, level)]`.
+/// The function then also takes the [LayerInstance] by reference and the [Level] as an
+/// `Option<&Level>`, see [LdtkEntityContext::level], in the order they're listed:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::{prelude::*, ldtk::{LayerInstance, Level}};
+/// #[derive(Component)]
+/// struct SpawnInfo {
+///     layer: String,
+///     level: String,
+/// }
+///
+/// fn spawn_info(
+///     _: &EntityInstance,
+///     layer_instance: &LayerInstance,
+///     level: Option<&Level>,
+/// ) -> SpawnInfo {
+///     SpawnInfo {
+///         layer: layer_instance.identifier.clone(),
+///         level: level.map(|l| l.identifier.clone()).unwrap_or_default(),
+///     }
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct SignBundle {
+///     #[with(spawn_info, layer, level)]
+///     spawn_info: SpawnInfo,
+/// }
+/// ```
+/// For anything else, consider implementing [LdtkEntity] for that component and using
+/// `#[ldtk_entity]` instead.
+///
+/// ### `#[ldtk_enum_field("identifier")]`
+/// Indicates that a component should be converted from the enum field with the given identifier,
+/// using its [LdtkEnum](crate::ldtk::LdtkEnum) implementation.
+/// Leaving an optional field null in LDtk is valid, so the component falls back to its [Default]
+/// if the field is null, with a warning.
+/// The same goes for a missing field, or a value with no matching variant, so mismatches between
+/// the LDtk project and the game show up in the log without crashing the game.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// #[derive(Component, LdtkEnum)]
+/// enum Faction {
+///     Goblins,
+///     Skeletons,
+/// }
+///
+/// impl Default for Faction {
+///     fn default() -> Faction {
+///         Faction::Goblins
+///     }
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct EnemyBundle {
+///     #[ldtk_enum_field("faction")]
+///     faction: Faction,
+/// }
+/// ```
+///
+/// ### `#[ldtk_point_field("identifier")]` and `#[ldtk_point_field_translation("identifier")]`
+/// Indicate that a component should be converted from the `Point` field with the given
+/// identifier, using [From<GridCoords>](crate::GridCoords) or [From<Vec2>] respectively.
+/// The [Vec2] is the translation the entity would have if it were placed on the point in LDtk,
+/// relative to the level like the entity's own translation, taking its size and pivot into
+/// account.
+/// Like `#[ldtk_enum_field(...)]`, a missing or null field falls back to the [Default] of the
+/// component, with a warning.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::{prelude::*, GridCoords};
+/// #[derive(Component, Default)]
+/// struct PatrolTarget(GridCoords);
+///
+/// impl From<GridCoords> for PatrolTarget {
+///     fn from(grid_coords: GridCoords) -> Self {
+///         PatrolTarget(grid_coords)
+///     }
+/// }
+///
+/// #[derive(Component, Default)]
+/// struct TeleportTarget(Vec2);
+///
+/// impl From<Vec2> for TeleportTarget {
+///     fn from(translation: Vec2) -> Self {
+///         TeleportTarget(translation)
+///     }
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct TeleporterBundle {
+///     #[ldtk_point_field("patrol_target")]
+///     patrol_target: PatrolTarget,
+///     #[ldtk_point_field_translation("destination")]
+///     teleport_target: TeleportTarget,
+/// }
+/// ```
+///
+/// ### `#[ldtk_color_field("identifier")]`
+/// Indicates that a component should be converted from the `Color` field with the given
+/// identifier, using [From<Color>].
+/// Like `#[ldtk_enum_field(...)]`, a missing field falls back to the [Default] of the component,
+/// with a warning.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// #[derive(Component, Default)]
+/// struct LightColor(Color);
+///
+/// impl From<Color> for LightColor {
+///     fn from(color: Color) -> Self {
+///         LightColor(color)
+///     }
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct LampBundle {
+///     #[ldtk_color_field("light_color")]
+///     light_color: LightColor,
+/// }
+/// ```
+///
+/// ### `#[ldtk_points_field("identifier")]` and `#[ldtk_points_field_translation("identifier")]`
+/// Like `#[ldtk_point_field(...)]` and `#[ldtk_point_field_translation(...)]`, but for
+/// `Array<Point>` fields, using [From<Vec<GridCoords>>](crate::GridCoords) or [From<Vec<Vec2>>]
+/// respectively.
+/// Null points are skipped, and the rest keep the order they have in LDtk.
+/// A missing field falls back to the [Default] of the component, with a warning.
+///
+/// The other array fields can be read in a `#[with(...)]` function, using the accessors of
+/// [LdtkFields](crate::ldtk::LdtkFields) like
+/// [get_ints_field](crate::ldtk::LdtkFields::get_ints_field).
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::{prelude::*, GridCoords};
+/// #[derive(Component, Default)]
+/// struct PatrolRoute(Vec<GridCoords>);
+///
+/// impl From<Vec<GridCoords>> for PatrolRoute {
+///     fn from(route: Vec<GridCoords>) -> Self {
+///         PatrolRoute(route)
+///     }
+/// }
+///
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct GuardBundle {
+///     #[ldtk_points_field("route")]
+///     patrol_route: PatrolRoute,
+/// }
+/// ```
+///
+/// ### `#[ldtk_tile_field("identifier")]`
+/// Indicates that a [SpriteSheetBundle] field should display the tile of the `Tile` field with the
+/// given identifier, like an icon chosen in LDtk.
+/// The tile can be from any tileset of the project, see [LdtkEntityContext].
+/// A missing or null field, or a tile whose tileset isn't loaded, is replaced by a default
+/// [SpriteSheetBundle] with a warning.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// #[derive(Bundle, LdtkEntity)]
+/// pub struct PickupBundle {
+///     #[ldtk_tile_field("icon")]
+///     #[bundle]
+///     sprite_sheet_bundle: SpriteSheetBundle,
+/// }
+/// ```
 pub trait LdtkEntity {
     /// The constructor used by the plugin when spawning entities from an LDtk file.
     /// Has access to resources/assets most commonly used for spawning 2d objects.
@@ -229,6 +514,11 @@ pub trait LdtkEntity {
     /// Note: whether or not the entity is registered to the app, the plugin will insert [Transform],
     /// [GlobalTransform], and [Parent] components to the entity **after** this bundle is inserted.
     /// So, any custom implementations of these components within this trait will be overwritten.
+    /// On the other hand, an [Aabb](bevy::render::primitives::Aabb) for frustum culling is inserted
+    /// **before** this bundle, so it can be replaced if the entity's visuals are larger than its
+    /// size in LDtk.
+    /// The same goes for the entity's [GridCoords](crate::GridCoords) and
+    /// [PixelCoords](crate::PixelCoords).
     fn bundle_entity(
         entity_instance: &EntityInstance,
         layer_instance: &LayerInstance,
@@ -237,6 +527,99 @@ pub trait LdtkEntity {
         asset_server: &AssetServer,
         texture_atlases: &mut Assets<TextureAtlas>,
     ) -> Self;
+
+    /// Like [LdtkEntity::bundle_entity], but with an [LdtkEntityContext] giving access to every
+    /// tileset of the project, for bundles built from tiles of other tilesets than the entity's
+    /// own tile.
+    ///
+    /// This is the constructor called by the plugin.
+    /// By default, it calls [LdtkEntity::bundle_entity] with the entity's own tileset, while the
+    /// derive macro implements both.
+    fn bundle_entity_with_context(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        context: &LdtkEntityContext,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::bundle_entity(
+            entity_instance,
+            layer_instance,
+            context.tileset,
+            context.tileset_definition,
+            asset_server,
+            texture_atlases,
+        )
+    }
+}
+
+/// The tilesets of the project an entity is spawned from, and how it's spawned, given to
+/// [LdtkEntity::bundle_entity_with_context].
+#[derive(Copy, Clone, Debug)]
+pub struct LdtkEntityContext<'a> {
+    /// The tileset of the entity's own tile, or of its first non-null `Tile` field if it has no
+    /// tile of its own, like the one given to [LdtkEntity::bundle_entity].
+    pub tileset: Option<&'a Handle<Image>>,
+    pub tileset_definition: Option<&'a TilesetDefinition>,
+    /// Every loaded tileset of the project, by uid.
+    pub tileset_map: &'a HashMap<i32, Handle<Image>>,
+    pub tileset_definition_map: &'a HashMap<i32, &'a TilesetDefinition>,
+    /// Whether the entity is spawned without rendering, see
+    /// [LdtkSettings::headless](crate::resources::LdtkSettings::headless).
+    ///
+    /// Visual bundles should be left at their default in that case.
+    pub headless: bool,
+    /// The directory of the LDtk project, which the paths of `FilePath` fields are relative to.
+    pub ldtk_directory: &'a Path,
+    /// The level the entity is spawned in.
+    ///
+    /// [None] when the context is made without a level, like with
+    /// [LdtkEntityContext::with_tileset].
+    pub level: Option<&'a Level>,
+}
+
+impl<'a> LdtkEntityContext<'a> {
+    /// Calls `f` with a context only containing the given tileset, with the asset root as the
+    /// directory of the project.
+    ///
+    /// Used by the [LdtkEntity::bundle_entity] implementations of the derive macro.
+    pub fn with_tileset<R>(
+        tileset: Option<&Handle<Image>>,
+        tileset_definition: Option<&TilesetDefinition>,
+        f: impl FnOnce(&LdtkEntityContext) -> R,
+    ) -> R {
+        let tileset_map = match (tileset, tileset_definition) {
+            (Some(tileset), Some(tileset_definition)) => {
+                HashMap::from([(tileset_definition.uid, tileset.clone())])
+            }
+            _ => HashMap::new(),
+        };
+        let tileset_definition_map = tileset_definition
+            .map(|tileset_definition| (tileset_definition.uid, tileset_definition))
+            .into_iter()
+            .collect();
+
+        f(&LdtkEntityContext {
+            tileset,
+            tileset_definition,
+            tileset_map: &tileset_map,
+            tileset_definition_map: &tileset_definition_map,
+            headless: false,
+            ldtk_directory: Path::new(""),
+            level: None,
+        })
+    }
+
+    /// Returns the image and definition of the tileset with the given uid, if it's loaded.
+    pub fn get_tileset(&self, uid: i32) -> Option<(&'a Handle<Image>, &'a TilesetDefinition)> {
+        Some((
+            self.tileset_map.get(&uid)?,
+            *self.tileset_definition_map.get(&uid)?,
+        ))
+    }
 }
 
 impl LdtkEntity for EntityInstanceBundle {
@@ -276,6 +659,66 @@ impl LdtkEntity for SpriteBundle {
             ..Default::default()
         }
     }
+
+    fn bundle_entity_with_context(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        context: &LdtkEntityContext,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        if context.headless {
+            return SpriteBundle::default();
+        }
+
+        SpriteBundle::bundle_entity(
+            entity_instance,
+            layer_instance,
+            context.tileset,
+            context.tileset_definition,
+            asset_server,
+            texture_atlases,
+        )
+    }
+}
+
+/// Returns a [TextureAtlas] dividing the tileset into tiles of the given size.
+///
+/// The atlas is created on first use, and reused by every entity with the same tileset, tile size
+/// and tileset dimensions for as long as one of them holds onto it.
+/// When a tileset is resized, entities spawned afterwards get a new atlas, while the old one lives
+/// on until the entities using it are gone.
+fn tileset_texture_atlas(
+    tileset: &Handle<Image>,
+    tile_size: IVec2,
+    tileset_definition: &TilesetDefinition,
+    texture_atlases: &mut Assets<TextureAtlas>,
+) -> Handle<TextureAtlas> {
+    let mut hasher = DefaultHasher::new();
+    (
+        tileset.id,
+        tile_size,
+        tileset_definition.c_wid,
+        tileset_definition.c_hei,
+        tileset_definition.spacing,
+    )
+        .hash(&mut hasher);
+    let atlas_id = HandleId::new(TextureAtlas::TYPE_UUID, hasher.finish());
+
+    if texture_atlases.get(atlas_id).is_some() {
+        texture_atlases.get_handle(atlas_id)
+    } else {
+        texture_atlases.set(
+            atlas_id,
+            TextureAtlas::from_grid_with_padding(
+                tileset.clone(),
+                tile_size.as_vec2(),
+                tileset_definition.c_wid as usize,
+                tileset_definition.c_hei as usize,
+                Vec2::splat(tileset_definition.spacing as f32),
+            ),
+        )
+    }
 }
 
 impl LdtkEntity for SpriteSheetBundle {
@@ -288,30 +731,79 @@ impl LdtkEntity for SpriteSheetBundle {
         texture_atlases: &mut Assets<TextureAtlas>,
     ) -> Self {
         match (tileset, &entity_instance.tile, tileset_definition) {
-            (Some(tileset), Some(tile), Some(tileset_definition)) => SpriteSheetBundle {
-                texture_atlas: texture_atlases.add(TextureAtlas::from_grid_with_padding(
-                    tileset.clone(),
-                    Vec2::new(tile.src_rect[2] as f32, tile.src_rect[3] as f32),
-                    tileset_definition.c_wid as usize,
-                    tileset_definition.c_hei as usize,
-                    Vec2::splat(tileset_definition.spacing as f32),
-                )),
-                sprite: TextureAtlasSprite {
-                    index: (tile.src_rect[1] / (tile.src_rect[3] + tileset_definition.spacing))
-                        as usize
-                        * tileset_definition.c_wid as usize
-                        + (tile.src_rect[0] / (tile.src_rect[2] + tileset_definition.spacing))
-                            as usize,
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
+            (Some(tileset), Some(tile), Some(tileset_definition)) => sprite_sheet_bundle_from_tile(
+                &TilesetRectangle::from(tile),
+                tileset,
+                tileset_definition,
+                texture_atlases,
+            ),
             _ => {
                 warn!("EntityInstance needs a tile, an associated tileset, and an associated tileset definition to be bundled as a SpriteSheetBundle");
                 SpriteSheetBundle::default()
             }
         }
     }
+
+    fn bundle_entity_with_context(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        context: &LdtkEntityContext,
+        _: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        if context.headless {
+            return SpriteSheetBundle::default();
+        }
+
+        // The tileset of the entity's own tile, which may differ from the context's tileset if it
+        // was taken from a Tile field
+        let tileset = entity_instance
+            .tile
+            .as_ref()
+            .and_then(|tile| Some((tile, context.get_tileset(tile.tileset_uid)?)));
+
+        match tileset {
+            Some((tile, (tileset, tileset_definition))) => sprite_sheet_bundle_from_tile(
+                &TilesetRectangle::from(tile),
+                tileset,
+                tileset_definition,
+                texture_atlases,
+            ),
+            None => {
+                warn!("EntityInstance needs a tile, an associated tileset, and an associated tileset definition to be bundled as a SpriteSheetBundle");
+                SpriteSheetBundle::default()
+            }
+        }
+    }
+}
+
+/// Creates a [SpriteSheetBundle] displaying the given rectangle of a tileset, like the tile of an
+/// [EntityInstance] or the value of a `Tile` field.
+///
+/// The `tileset` and `tileset_definition` should be those of the tile's `tileset_uid`.
+/// Like entities with `#[sprite_sheet_bundle]`, the [TextureAtlas] is shared by all sprites with
+/// the same tileset and tile size.
+pub fn sprite_sheet_bundle_from_tile(
+    tile: &TilesetRectangle,
+    tileset: &Handle<Image>,
+    tileset_definition: &TilesetDefinition,
+    texture_atlases: &mut Assets<TextureAtlas>,
+) -> SpriteSheetBundle {
+    SpriteSheetBundle {
+        texture_atlas: tileset_texture_atlas(
+            tileset,
+            IVec2::new(tile.w, tile.h),
+            tileset_definition,
+            texture_atlases,
+        ),
+        sprite: TextureAtlasSprite {
+            index: (tile.y / (tile.h + tileset_definition.spacing)) as usize
+                * tileset_definition.c_wid as usize
+                + (tile.x / (tile.w + tileset_definition.spacing)) as usize,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
 }
 
 impl LdtkEntity for Worldly {
@@ -332,6 +824,19 @@ impl LdtkEntity for Worldly {
     }
 }
 
+impl LdtkEntity for GridCoords {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> GridCoords {
+        ldtk_grid_coords_to_grid_coords(entity_instance.grid, layer_instance.c_hei)
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
 pub struct PhantomLdtkEntity<B: LdtkEntity + Bundle> {
     ldtk_entity: PhantomData<B>,
@@ -352,8 +857,7 @@ pub trait PhantomLdtkEntityTrait {
         commands: &'b mut EntityCommands<'w, 's, 'a>,
         entity_instance: &EntityInstance,
         layer_instance: &LayerInstance,
-        tileset: Option<&Handle<Image>>,
-        tileset_definition: Option<&TilesetDefinition>,
+        context: &LdtkEntityContext,
         asset_server: &AssetServer,
         texture_atlases: &mut Assets<TextureAtlas>,
     ) -> &'b mut EntityCommands<'w, 's, 'a>;
@@ -365,16 +869,14 @@ impl<B: LdtkEntity + Bundle> PhantomLdtkEntityTrait for PhantomLdtkEntity<B> {
         entity_commands: &'b mut EntityCommands<'w, 's, 'a>,
         entity_instance: &EntityInstance,
         layer_instance: &LayerInstance,
-        tileset: Option<&Handle<Image>>,
-        tileset_definition: Option<&TilesetDefinition>,
+        context: &LdtkEntityContext,
         asset_server: &AssetServer,
         texture_atlases: &mut Assets<TextureAtlas>,
     ) -> &'b mut EntityCommands<'w, 's, 'a> {
-        entity_commands.insert_bundle(B::bundle_entity(
+        entity_commands.insert_bundle(B::bundle_entity_with_context(
             entity_instance,
             layer_instance,
-            tileset,
-            tileset_definition,
+            context,
             asset_server,
             texture_atlases,
         ))
@@ -383,3 +885,87 @@ impl<B: LdtkEntity + Bundle> PhantomLdtkEntityTrait for PhantomLdtkEntity<B> {
 
 /// Used by [RegisterLdtkObjects] to associate Ldtk entity identifiers with [LdtkEntity]s.
 pub type LdtkEntityMap = HashMap<(Option<String>, Option<String>), Box<dyn PhantomLdtkEntityTrait>>;
+
+/// A plugin's claim on an [LdtkEntityMap] entry, made with
+/// [RegisterLdtkObjects::register_namespaced_ldtk_entity_for_layer_optional].
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct NamespacedRegistration {
+    /// Name of the plugin that made the registration.
+    pub namespace: String,
+    /// Registrations with a higher priority replace those with a lower one.
+    pub priority: i32,
+}
+
+impl NamespacedRegistration {
+    pub fn new(namespace: &str, priority: i32) -> Self {
+        NamespacedRegistration {
+            namespace: namespace.to_string(),
+            priority,
+        }
+    }
+
+    /// Whether this registration should replace the `existing` one for the same identifiers.
+    ///
+    /// Registrations replace those of their own namespace and those with a lower priority.
+    /// Between different namespaces with the same priority, the first registration is kept, so the
+    /// outcome doesn't depend on which one is made last.
+    pub fn replaces(&self, existing: &NamespacedRegistration) -> bool {
+        self.namespace == existing.namespace || self.priority > existing.priority
+    }
+}
+
+/// Used by [RegisterLdtkObjects] to track which namespace owns each [LdtkEntityMap] entry.
+///
+/// Entries registered without a namespace don't appear here.
+pub type LdtkEntityNamespaceMap = HashMap<(Option<String>, Option<String>), NamespacedRegistration>;
+
+/// Used by [RegisterLdtkObjects] to associate Ldtk entity identifiers with [DespawnPolicy]s.
+pub type LdtkDespawnPolicyMap = HashMap<String, DespawnPolicy>;
+
+/// Used by [RegisterLdtkObjects] to associate Ldtk entity identifiers with the identifiers of the
+/// IntGrid layers whose cells they're anchored to.
+pub type LdtkEntityAnchorMap = HashMap<String, String>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ldtk_entity_context_get_tileset() {
+        let tileset_a = TilesetDefinition {
+            uid: 1,
+            ..Default::default()
+        };
+        let tileset_b = TilesetDefinition {
+            uid: 2,
+            ..Default::default()
+        };
+        let image_a = Handle::weak(HandleId::random::<Image>());
+        let image_b = Handle::weak(HandleId::random::<Image>());
+
+        let tileset_map = HashMap::from([(1, image_a.clone()), (2, image_b.clone())]);
+        let tileset_definition_map = HashMap::from([(1, &tileset_a), (2, &tileset_b)]);
+
+        let context = LdtkEntityContext {
+            tileset: Some(&image_a),
+            tileset_definition: Some(&tileset_a),
+            tileset_map: &tileset_map,
+            tileset_definition_map: &tileset_definition_map,
+            headless: false,
+            ldtk_directory: Path::new(""),
+            level: None,
+        };
+
+        assert_eq!(context.get_tileset(2), Some((&image_b, &tileset_b)));
+        assert_eq!(context.get_tileset(3), None);
+
+        LdtkEntityContext::with_tileset(Some(&image_a), Some(&tileset_a), |context| {
+            assert_eq!(context.get_tileset(1), Some((&image_a, &tileset_a)));
+            assert_eq!(context.get_tileset(2), None);
+        });
+
+        LdtkEntityContext::with_tileset(None, Some(&tileset_a), |context| {
+            assert_eq!(context.get_tileset(1), None);
+        });
+    }
+}
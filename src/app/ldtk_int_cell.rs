@@ -123,6 +123,34 @@ use crate::app::register_ldtk_objects::RegisterLdtkObjects;
 ///     damage: Damage,
 /// }
 /// ```
+///
+/// ### `#[with(...)]`
+/// Indicates that a component or bundle should be created with the given function, which takes
+/// the [IntGridCell] and a reference to the [LayerInstance].
+/// This is useful for components that need to know which value or layer they came from, without
+/// implementing [From<IntGridCell>] for them.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// #[derive(Component)]
+/// struct Collider {
+///     layer: String,
+///     value: i32,
+/// }
+///
+/// fn collider_from_cell(int_grid_cell: IntGridCell, layer_instance: &LayerInstance) -> Collider {
+///     Collider {
+///         layer: layer_instance.identifier.clone(),
+///         value: int_grid_cell.value,
+///     }
+/// }
+///
+/// #[derive(Bundle, LdtkIntCell)]
+/// pub struct WallBundle {
+///     #[with(collider_from_cell)]
+///     collider: Collider,
+/// }
+/// ```
 pub trait LdtkIntCell {
     /// The constructor used by the plugin when spawning additional components on IntGrid tiles.
     /// If you need access to more of the [World], you can create a system that queries for
@@ -180,3 +208,10 @@ impl<B: LdtkIntCell + Bundle> PhantomLdtkIntCellTrait for PhantomLdtkIntCell<B>
 
 /// Used by [RegisterLdtkObjects] to associate Ldtk IntGrid values with [LdtkIntCell]s.
 pub type LdtkIntCellMap = HashMap<(Option<String>, Option<i32>), Box<dyn PhantomLdtkIntCellTrait>>;
+
+/// Used by [RegisterLdtkObjects] to associate the identifiers of Ldtk IntGrid values with
+/// [LdtkIntCell]s.
+///
+/// The identifiers are resolved to values against the project definitions when levels spawn.
+pub type LdtkIntCellIdentifierMap =
+    HashMap<(Option<String>, String), Box<dyn PhantomLdtkIntCellTrait>>;
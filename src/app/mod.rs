@@ -1,9 +1,17 @@
 //! Types and traits for hooking into the ldtk loading process via [bevy::app::App].
 
+mod file_path_field;
+mod int_cell_promotion;
 mod ldtk_entity;
 mod ldtk_int_cell;
+mod reflected_entity;
 mod register_ldtk_objects;
+mod tile_enum_tag;
 
+pub use file_path_field::*;
+pub use int_cell_promotion::*;
 pub use ldtk_entity::*;
 pub use ldtk_int_cell::*;
+pub use reflected_entity::*;
 pub use register_ldtk_objects::*;
+pub use tile_enum_tag::*;
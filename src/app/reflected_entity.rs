@@ -0,0 +1,183 @@
+use bevy::{
+    ecs::system::Command,
+    prelude::*,
+    reflect::{serde::ReflectDeserializer, FromType, TypeRegistry, TypeRegistryArc},
+};
+use serde::de::DeserializeSeed;
+use std::collections::HashMap;
+
+#[allow(unused_imports)]
+use crate::app::register_ldtk_objects::RegisterLdtkObjects;
+
+/// Used by [RegisterLdtkObjects] to associate LDtk entity identifiers with reflected components.
+pub type LdtkReflectedEntityMap = HashMap<String, Vec<Box<dyn Reflect>>>;
+
+/// Type data creating default values of a reflected component type, so the type can be named
+/// by its type path alone in [parse_reflected_entity_map].
+///
+/// Added to the type's registration with `#[reflect(Component, LdtkDefault)]`.
+#[derive(Clone)]
+pub struct ReflectLdtkDefault {
+    create_default: fn() -> Box<dyn Reflect>,
+}
+
+impl ReflectLdtkDefault {
+    /// Creates a default value of the type.
+    pub fn create_default(&self) -> Box<dyn Reflect> {
+        (self.create_default)()
+    }
+}
+
+impl<T: Reflect + Default> FromType<T> for ReflectLdtkDefault {
+    fn from_type() -> Self {
+        ReflectLdtkDefault {
+            create_default: || Box::new(T::default()),
+        }
+    }
+}
+
+/// Creates the default value of the component type registered with the given type path.
+///
+/// Returns `None` if the type isn't registered, or its registration has no [ReflectLdtkDefault].
+pub fn default_reflected_component(
+    type_path: &str,
+    type_registry: &TypeRegistry,
+) -> Option<Box<dyn Reflect>> {
+    type_registry
+        .get_with_name(type_path)?
+        .data::<ReflectLdtkDefault>()
+        .map(ReflectLdtkDefault::create_default)
+}
+
+/// Parses a JSON object mapping LDtk entity identifiers to lists of reflected components.
+///
+/// Each component is either the type path of a registered component type reflecting
+/// [ReflectLdtkDefault], which is inserted with its default value, or a value in the format of
+/// bevy's [ReflectDeserializer]:
+/// ```json
+/// {
+///     "Goblin": [
+///         "my_game::Enemy",
+///         {
+///             "type": "my_game::Health",
+///             "struct": { "value": { "type": "f32", "value": 30.0 } }
+///         }
+///     ]
+/// }
+/// ```
+/// Component types must be registered in the `type_registry` and reflect [Component].
+/// Bundles aren't supported, so list each of their components instead.
+pub fn parse_reflected_entity_map(
+    json: &str,
+    type_registry: &TypeRegistry,
+) -> anyhow::Result<LdtkReflectedEntityMap> {
+    let entries: HashMap<String, Vec<serde_json::Value>> = serde_json::from_str(json)?;
+
+    let mut reflected_entity_map = LdtkReflectedEntityMap::new();
+    for (entity_identifier, values) in entries {
+        let mut components = Vec::new();
+        for value in values {
+            let component = match value {
+                serde_json::Value::String(type_path) => {
+                    if type_registry.get_with_name(&type_path).is_none() {
+                        anyhow::bail!("type {} is not registered", type_path);
+                    }
+                    default_reflected_component(&type_path, type_registry).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "type {} doesn't reflect LdtkDefault, so it needs a value",
+                            type_path
+                        )
+                    })?
+                }
+                value => ReflectDeserializer::new(type_registry).deserialize(value)?,
+            };
+
+            if type_registry
+                .get_with_name(component.type_name())
+                .and_then(|r| r.data::<ReflectComponent>())
+                .is_none()
+            {
+                anyhow::bail!("type {} doesn't reflect Component", component.type_name());
+            }
+
+            components.push(component);
+        }
+        reflected_entity_map.insert(entity_identifier, components);
+    }
+
+    Ok(reflected_entity_map)
+}
+
+/// [Command] inserting reflected components on an entity through the app's type registry.
+pub(crate) struct InsertReflectedComponents {
+    pub entity: Entity,
+    pub components: Vec<Box<dyn Reflect>>,
+}
+
+impl Command for InsertReflectedComponents {
+    fn write(self, world: &mut World) {
+        let type_registry = match world.get_resource::<TypeRegistryArc>() {
+            Some(type_registry) => type_registry.clone(),
+            None => return,
+        };
+        let type_registry = type_registry.read();
+
+        for component in self.components {
+            match type_registry
+                .get_with_name(component.type_name())
+                .and_then(|r| r.data::<ReflectComponent>())
+            {
+                Some(reflect_component) => {
+                    reflect_component.add_component(world, self.entity, &*component)
+                }
+                None => error!(
+                    "Reflected LDtk entity component {} is not a registered component",
+                    component.type_name()
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Copy, Clone, PartialEq, Debug, Default, Component, Reflect)]
+    #[reflect(Component, LdtkDefault)]
+    struct Enemy {
+        strength: f32,
+    }
+
+    #[test]
+    fn test_parse_reflected_entity_map() {
+        let mut type_registry = TypeRegistry::default();
+        type_registry.register::<Enemy>();
+        type_registry.register::<Transform>();
+
+        let enemy_path = std::any::type_name::<Enemy>();
+
+        let reflected_entity_map = parse_reflected_entity_map(
+            &format!(r#"{{ "Goblin": ["{}"] }}"#, enemy_path),
+            &type_registry,
+        )
+        .unwrap();
+
+        assert_eq!(reflected_entity_map["Goblin"].len(), 1);
+        assert_eq!(
+            reflected_entity_map["Goblin"][0].downcast_ref::<Enemy>(),
+            Some(&Enemy::default())
+        );
+
+        assert!(
+            parse_reflected_entity_map(r#"{ "Goblin": ["Unknown"] }"#, &type_registry).is_err()
+        );
+
+        // Transform has no ReflectLdtkDefault, so it can't be named by its type path alone
+        assert!(parse_reflected_entity_map(
+            r#"{ "Goblin": ["bevy_transform::components::transform::Transform"] }"#,
+            &type_registry
+        )
+        .is_err());
+    }
+}
@@ -1,5 +1,9 @@
-use crate::{app::ldtk_entity::*, app::ldtk_int_cell::*};
-use bevy::prelude::*;
+use crate::{
+    app::file_path_field::*, app::int_cell_promotion::*, app::ldtk_entity::*,
+    app::ldtk_int_cell::*, app::reflected_entity::*, app::tile_enum_tag::*,
+    components::DespawnPolicy,
+};
+use bevy::{asset::Asset, prelude::*, reflect::TypeRegistryArc};
 
 /// Provides functions to register [Bundle]s to bevy's [App] for particular LDtk layer identifiers,
 /// entity identifiers, and IntGrid values.
@@ -96,6 +100,79 @@ pub trait RegisterLdtkObjects {
         self.register_ldtk_entity_for_layer_optional::<B>(None, None)
     }
 
+    /// Used internally by all the other namespaced LDtk entity registration functions.
+    ///
+    /// Similar to [RegisterLdtkObjects::register_ldtk_entity_for_layer_optional], except the
+    /// registration is claimed by a `namespace`, like the name of the plugin making it.
+    /// This lets several content plugins register entities in the same app without silently
+    /// overwriting each other:
+    /// - A plugin can replace its own registrations.
+    /// - Between different plugins, the registration with the higher `priority` wins.
+    /// - Between different plugins with the same `priority`, the first registration wins.
+    /// - Registrations made without a namespace, usually by the game itself, always win.
+    ///
+    /// Conflicts between namespaces are logged as warnings.
+    /// See [NamespacedRegistration::replaces] for the exact rules.
+    fn register_namespaced_ldtk_entity_for_layer_optional<B: LdtkEntity + Bundle>(
+        &mut self,
+        registration: NamespacedRegistration,
+        layer_identifier: Option<String>,
+        entity_identifier: Option<String>,
+    ) -> &mut Self;
+
+    /// Registers [LdtkEntity] types for a given Entity identifier on all layers, on behalf of the
+    /// given plugin `namespace`.
+    ///
+    /// See [RegisterLdtkObjects::register_namespaced_ldtk_entity_for_layer_optional] for how
+    /// conflicts between plugins are resolved.
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_ecs_ldtk::prelude::*;
+    ///
+    /// pub struct DoorsPlugin;
+    ///
+    /// impl Plugin for DoorsPlugin {
+    ///     fn build(&self, app: &mut App) {
+    ///         app.register_namespaced_ldtk_entity::<DoorBundle>("doors", 0, "Door");
+    ///     }
+    /// }
+    ///
+    /// # #[derive(Component, Default)]
+    /// # struct Door;
+    /// #[derive(Bundle, LdtkEntity)]
+    /// pub struct DoorBundle {
+    ///     door: Door,
+    /// }
+    /// ```
+    fn register_namespaced_ldtk_entity<B: LdtkEntity + Bundle>(
+        &mut self,
+        namespace: &str,
+        priority: i32,
+        entity_identifier: &str,
+    ) -> &mut Self {
+        self.register_namespaced_ldtk_entity_for_layer_optional::<B>(
+            NamespacedRegistration::new(namespace, priority),
+            None,
+            Some(entity_identifier.to_string()),
+        )
+    }
+
+    /// Similar to [RegisterLdtkObjects::register_namespaced_ldtk_entity], except it only applies
+    /// the registration to the given layer.
+    fn register_namespaced_ldtk_entity_for_layer<B: LdtkEntity + Bundle>(
+        &mut self,
+        namespace: &str,
+        priority: i32,
+        layer_identifier: &str,
+        entity_identifier: &str,
+    ) -> &mut Self {
+        self.register_namespaced_ldtk_entity_for_layer_optional::<B>(
+            NamespacedRegistration::new(namespace, priority),
+            Some(layer_identifier.to_string()),
+            Some(entity_identifier.to_string()),
+        )
+    }
+
     /// Used internally by all the other LDtk int cell registration functions.
     ///
     /// Similar to [RegisterLdtkObjects::register_ldtk_int_cell_for_layer], except it provides
@@ -182,6 +259,347 @@ pub trait RegisterLdtkObjects {
     fn register_default_ldtk_int_cell<B: LdtkIntCell + Bundle>(&mut self) -> &mut Self {
         self.register_ldtk_int_cell_for_layer_optional::<B>(None, None)
     }
+
+    /// Used internally by all the other LDtk int cell registration functions that take IntGrid
+    /// value identifiers.
+    ///
+    /// Similar to [RegisterLdtkObjects::register_ldtk_int_cell_for_layer_by_identifier], except
+    /// setting `layer_identifier` to [None] will make the registration apply to any IntGrid layer.
+    ///
+    /// These registrations have the same priority as those made with the equivalent IntGrid
+    /// value.
+    /// If a tile matches both, the registration made with the value takes precedence.
+    fn register_ldtk_int_cell_for_layer_by_identifier_optional<B: LdtkIntCell + Bundle>(
+        &mut self,
+        layer_identifier: Option<String>,
+        value_identifier: String,
+    ) -> &mut Self;
+
+    /// Similar to [RegisterLdtkObjects::register_ldtk_int_cell_for_layer], except the IntGrid
+    /// value is given by its identifier in the LDtk editor.
+    ///
+    /// The identifier is resolved against the project definitions whenever a level spawns, so the
+    /// registration keeps working when IntGrid values are reordered or renumbered in the editor.
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_ecs_ldtk::prelude::*;
+    ///
+    /// fn main() {
+    ///     App::empty()
+    ///         .add_plugin(LdtkPlugin)
+    ///         .register_ldtk_int_cell_for_layer_by_identifier::<WallBundle>("Collisions", "wall")
+    ///         // add other systems, plugins, resources...
+    ///         .run();
+    /// }
+    ///
+    /// # #[derive(Component, Default)]
+    /// # struct Wall;
+    /// #[derive(Bundle, LdtkIntCell)]
+    /// pub struct WallBundle {
+    ///     wall: Wall,
+    /// }
+    /// ```
+    fn register_ldtk_int_cell_for_layer_by_identifier<B: LdtkIntCell + Bundle>(
+        &mut self,
+        layer_identifier: &str,
+        value_identifier: &str,
+    ) -> &mut Self {
+        self.register_ldtk_int_cell_for_layer_by_identifier_optional::<B>(
+            Some(layer_identifier.to_string()),
+            value_identifier.to_string(),
+        )
+    }
+
+    /// Similar to [RegisterLdtkObjects::register_ldtk_int_cell_for_layer_by_identifier], except it
+    /// applies the registration to all layers.
+    fn register_ldtk_int_cell_by_identifier<B: LdtkIntCell + Bundle>(
+        &mut self,
+        value_identifier: &str,
+    ) -> &mut Self {
+        self.register_ldtk_int_cell_for_layer_by_identifier_optional::<B>(
+            None,
+            value_identifier.to_string(),
+        )
+    }
+
+    /// Used internally by all the other IntGrid promotion functions.
+    ///
+    /// Similar to [RegisterLdtkObjects::register_ldtk_int_cell_promotion_for_layer], except
+    /// setting `layer_identifier` to [None] will make the registration apply to any IntGrid layer,
+    /// and the `tile` is optional.
+    ///
+    /// Registrations with a `layer_identifier` take priority over those without one.
+    fn register_ldtk_int_cell_promotion_for_layer_optional<B: LdtkEntity + Bundle>(
+        &mut self,
+        layer_identifier: Option<String>,
+        value: i32,
+        tile: Option<PromotedTile>,
+    ) -> &mut Self;
+
+    /// Registers [LdtkEntity] types to be spawned in place of IntGrid tiles with the given value
+    /// and layer identifier in an LDtk file.
+    ///
+    /// This is useful for projects that paint pickups or hazards as IntGrid values rather than
+    /// placing entity instances.
+    /// Promoted tiles are spawned like LDtk entities: as children of the level, centered on their
+    /// cell, and with an [IntGridCell] component.
+    /// The [EntityInstance](crate::ldtk::EntityInstance) given to [LdtkEntity::bundle_entity] is
+    /// synthesized from the cell, with the layer identifier as its identifier and the given `tile`
+    /// as its visual, so `#[sprite_bundle]` and `#[sprite_sheet_bundle]` fields work as usual.
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_ecs_ldtk::prelude::*;
+    /// use bevy_ecs_ldtk::app::PromotedTile;
+    ///
+    /// fn main() {
+    ///     App::empty()
+    ///         .add_plugin(LdtkPlugin)
+    ///         .register_ldtk_int_cell_promotion_for_layer::<Coin>(
+    ///             "Pickups",
+    ///             1,
+    ///             PromotedTile::new("Items", 12),
+    ///         )
+    ///         // add other systems, plugins, resources...
+    ///         .run();
+    /// }
+    ///
+    /// # #[derive(Component, Default)]
+    /// # struct CoinComponent;
+    /// #[derive(Bundle, LdtkEntity)]
+    /// pub struct Coin {
+    ///     coin: CoinComponent,
+    ///     #[sprite_sheet_bundle]
+    ///     #[bundle]
+    ///     sprite_sheet_bundle: SpriteSheetBundle,
+    /// }
+    /// ```
+    fn register_ldtk_int_cell_promotion_for_layer<B: LdtkEntity + Bundle>(
+        &mut self,
+        layer_identifier: &str,
+        value: i32,
+        tile: PromotedTile,
+    ) -> &mut Self {
+        self.register_ldtk_int_cell_promotion_for_layer_optional::<B>(
+            Some(layer_identifier.to_string()),
+            value,
+            Some(tile),
+        )
+    }
+
+    /// Similar to [RegisterLdtkObjects::register_ldtk_int_cell_promotion_for_layer], except it
+    /// applies the registration to all layers.
+    fn register_ldtk_int_cell_promotion<B: LdtkEntity + Bundle>(
+        &mut self,
+        value: i32,
+        tile: PromotedTile,
+    ) -> &mut Self {
+        self.register_ldtk_int_cell_promotion_for_layer_optional::<B>(None, value, Some(tile))
+    }
+
+    /// Registers the [DespawnPolicy] of LDtk entities with the given identifier.
+    ///
+    /// With [DespawnPolicy::AfterEvent], these entities outlive their level, so the game can play
+    /// exit animations:
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_ecs_ldtk::{prelude::*, DespawnPolicy, PendingDespawn, PendingDespawnEvent};
+    ///
+    /// fn main() {
+    ///     App::empty()
+    ///         .add_plugin(LdtkPlugin)
+    ///         .register_ldtk_entity_despawn_policy("Torch", DespawnPolicy::AfterEvent)
+    ///         .add_system(extinguish_torches)
+    ///         // add other systems, plugins, resources...
+    ///         .run();
+    /// }
+    ///
+    /// fn extinguish_torches(
+    ///     mut commands: Commands,
+    ///     mut pending_despawn_events: EventReader<PendingDespawnEvent>,
+    /// ) {
+    ///     for event in pending_despawn_events.iter() {
+    ///         // play an animation, then remove PendingDespawn when it's done
+    ///         commands.entity(event.entity).remove::<PendingDespawn>();
+    ///     }
+    /// }
+    /// ```
+    fn register_ldtk_entity_despawn_policy(
+        &mut self,
+        entity_identifier: &str,
+        despawn_policy: DespawnPolicy,
+    ) -> &mut Self;
+
+    /// Registers LDtk entities with the given identifier to be anchored to the IntGrid cells at
+    /// their grid position on the given IntGrid layer.
+    ///
+    /// Anchored entities are spawned as children of the [IntGridCell](crate::IntGridCell) entity
+    /// they're on, rather than of the level, so despawning the cell also despawns them.
+    /// This is useful for props sitting on destructible terrain.
+    /// Entities that aren't on a non-zero cell of the layer are spawned as children of the level
+    /// as usual.
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_ecs_ldtk::prelude::*;
+    ///
+    /// fn main() {
+    ///     App::empty()
+    ///         .add_plugin(LdtkPlugin)
+    ///         .register_ldtk_entity_anchor("Torch", "Terrain")
+    ///         // add other systems, plugins, resources...
+    ///         .run();
+    /// }
+    /// ```
+    fn register_ldtk_entity_anchor(
+        &mut self,
+        entity_identifier: &str,
+        int_grid_layer_identifier: &str,
+    ) -> &mut Self;
+
+    /// Used internally by all the other `FilePath` field registration functions.
+    ///
+    /// Setting `entity_identifier` to [None] makes the registration apply to the fields of levels
+    /// instead of entities.
+    fn register_ldtk_file_path_field_optional<A: Asset>(
+        &mut self,
+        entity_identifier: Option<String>,
+        field_identifier: &str,
+    ) -> &mut Self;
+
+    /// Registers a `FilePath` field of LDtk entities with the given identifier to be loaded as an
+    /// asset of type `A` when they spawn.
+    ///
+    /// The path is relative to the `.ldtk` file, like the paths of tilesets, or to the
+    /// [LdtkAssetRoot](crate::LdtkAssetRoot) override of the world.
+    /// The handle is inserted in an [LdtkFileHandles](crate::LdtkFileHandles) component.
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_ecs_ldtk::{prelude::*, LdtkFileHandles};
+    ///
+    /// fn main() {
+    ///     App::empty()
+    ///         .add_plugin(LdtkPlugin)
+    ///         .register_ldtk_file_path_field::<Image>("Sign", "portrait")
+    ///         .add_system(show_portraits)
+    ///         // add other systems, plugins, resources...
+    ///         .run();
+    /// }
+    ///
+    /// fn show_portraits(
+    ///     sign_query: Query<&LdtkFileHandles<Image>, Added<LdtkFileHandles<Image>>>,
+    /// ) {
+    ///     for file_handles in sign_query.iter() {
+    ///         if let Some(_portrait) = file_handles.get("portrait") {
+    ///             // display the portrait...
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn register_ldtk_file_path_field<A: Asset>(
+        &mut self,
+        entity_identifier: &str,
+        field_identifier: &str,
+    ) -> &mut Self {
+        self.register_ldtk_file_path_field_optional::<A>(
+            Some(entity_identifier.to_string()),
+            field_identifier,
+        )
+    }
+
+    /// Similar to [RegisterLdtkObjects::register_ldtk_file_path_field], except it applies to a
+    /// `FilePath` field of levels, and the handle is inserted on the level entity.
+    fn register_ldtk_level_file_path_field<A: Asset>(
+        &mut self,
+        field_identifier: &str,
+    ) -> &mut Self {
+        self.register_ldtk_file_path_field_optional::<A>(None, field_identifier)
+    }
+
+    /// Registers a [Bundle] to be inserted on the tiles tagged with the given enum value in their
+    /// tileset, like "Ladder" or "Spike".
+    ///
+    /// Tile layers spawn their tiles in batches, so the bundle is inserted shortly after the level
+    /// spawns rather than while spawning it.
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_ecs_ldtk::prelude::*;
+    ///
+    /// fn main() {
+    ///     App::empty()
+    ///         .add_plugin(LdtkPlugin)
+    ///         .register_ldtk_tile_enum_tag::<Spike>("Spike")
+    ///         .add_system(hurt_on_spikes)
+    ///         // add other systems, plugins, resources...
+    ///         .run();
+    /// }
+    ///
+    /// #[derive(Component, Default)]
+    /// struct Spike;
+    ///
+    /// fn hurt_on_spikes(spike_query: Query<&GlobalTransform, With<Spike>>) {
+    ///     for spike_transform in spike_query.iter() {
+    ///         // check for players overlapping the spike...
+    ///     }
+    /// }
+    /// ```
+    fn register_ldtk_tile_enum_tag<B: Bundle + Default>(&mut self, enum_value: &str) -> &mut Self;
+
+    /// Registers a reflected component to be inserted on LDtk entities with the given identifier,
+    /// in addition to the bundle they're registered with.
+    ///
+    /// Unlike the other registrations, this doesn't need the component type at compile time, only
+    /// its registration in bevy's type registry.
+    /// The component type must reflect [Component], and is inserted through its
+    /// [ReflectComponent](bevy::ecs::reflect::ReflectComponent) data.
+    /// Bundles can't be registered this way, so register each of their components instead.
+    /// See [default_reflected_component] for creating a default component from a type path.
+    fn register_ldtk_reflected_entity(
+        &mut self,
+        entity_identifier: &str,
+        component: Box<dyn Reflect>,
+    ) -> &mut Self;
+
+    /// Registers the reflected components of a JSON config, like one shipped with a mod, using
+    /// [RegisterLdtkObjects::register_ldtk_reflected_entity].
+    ///
+    /// See [parse_reflected_entity_map] for the format of the config.
+    /// The component types need to be registered before calling this.
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_ecs_ldtk::prelude::*;
+    ///
+    /// #[derive(Component, Default, Reflect)]
+    /// #[reflect(Component, LdtkDefault)]
+    /// struct Enemy;
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     App::empty()
+    ///         .add_plugin(LdtkPlugin)
+    ///         .register_type::<Enemy>()
+    ///         .register_ldtk_reflected_entities_from_json(
+    ///             &std::fs::read_to_string("assets/entities.json")?,
+    ///         )?
+    ///         // add other systems, plugins, resources...
+    ///         .run();
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    fn register_ldtk_reflected_entities_from_json(
+        &mut self,
+        json: &str,
+    ) -> anyhow::Result<&mut Self>;
+}
+
+/// Returns the registry `R` of the app, initializing it if it doesn't exist yet.
+///
+/// Registries are initialized lazily like this, and [LdtkPlugin](crate::LdtkPlugin) only
+/// initializes the ones that don't exist yet, so registrations are honored whether they're made
+/// before or after the plugin is added, including by other plugins.
+fn registry_mut<R: Default + 'static>(app: &mut App) -> Mut<R> {
+    app.world.init_non_send_resource::<R>();
+    app.world
+        .get_non_send_resource_mut::<R>()
+        .expect("registry should have been initialized")
 }
 
 impl RegisterLdtkObjects for App {
@@ -191,16 +609,52 @@ impl RegisterLdtkObjects for App {
         entity_identifier: Option<String>,
     ) -> &mut Self {
         let new_entry = Box::new(PhantomLdtkEntity::<B>::new());
-        match self.world.get_non_send_resource_mut::<LdtkEntityMap>() {
-            Some(mut entries) => {
-                entries.insert((layer_identifier, entity_identifier), new_entry);
+        registry_mut::<LdtkEntityNamespaceMap>(self)
+            .remove(&(layer_identifier.clone(), entity_identifier.clone()));
+        registry_mut::<LdtkEntityMap>(self)
+            .insert((layer_identifier, entity_identifier), new_entry);
+        self
+    }
+
+    fn register_namespaced_ldtk_entity_for_layer_optional<B: LdtkEntity + Bundle>(
+        &mut self,
+        registration: NamespacedRegistration,
+        layer_identifier: Option<String>,
+        entity_identifier: Option<String>,
+    ) -> &mut Self {
+        let key = (layer_identifier, entity_identifier);
+
+        let registered_without_namespace = registry_mut::<LdtkEntityMap>(self).contains_key(&key);
+        let mut namespace_map = registry_mut::<LdtkEntityNamespaceMap>(self);
+        match namespace_map.get(&key) {
+            Some(existing) if !registration.replaces(existing) => {
+                warn!(
+                    "{:?} is already registered by {:?}, ignoring registration by {:?}",
+                    key, existing.namespace, registration.namespace
+                );
+                return self;
+            }
+            Some(existing) => {
+                if existing.namespace != registration.namespace {
+                    warn!(
+                        "{:?} is already registered by {:?}, replacing it with registration by {:?}",
+                        key, existing.namespace, registration.namespace
+                    );
+                }
             }
-            None => {
-                let mut bundle_map = LdtkEntityMap::new();
-                bundle_map.insert((layer_identifier, entity_identifier), new_entry);
-                self.world.insert_non_send::<LdtkEntityMap>(bundle_map);
+            None if registered_without_namespace => {
+                warn!(
+                    "{:?} is already registered without a namespace, ignoring registration by {:?}",
+                    key, registration.namespace
+                );
+                return self;
             }
+            None => (),
         }
+        namespace_map.insert(key.clone(), registration);
+
+        let new_entry = Box::new(PhantomLdtkEntity::<B>::new());
+        registry_mut::<LdtkEntityMap>(self).insert(key, new_entry);
         self
     }
 
@@ -210,18 +664,125 @@ impl RegisterLdtkObjects for App {
         value: Option<i32>,
     ) -> &mut Self {
         let new_entry = Box::new(PhantomLdtkIntCell::<B>::new());
-        match self.world.get_non_send_resource_mut::<LdtkIntCellMap>() {
-            Some(mut entries) => {
-                entries.insert((layer_identifier, value), new_entry);
-            }
-            None => {
-                let mut bundle_map = LdtkIntCellMap::new();
-                bundle_map.insert((layer_identifier, value), new_entry);
-                self.world.insert_non_send::<LdtkIntCellMap>(bundle_map);
+        registry_mut::<LdtkIntCellMap>(self).insert((layer_identifier, value), new_entry);
+        self
+    }
+
+    fn register_ldtk_int_cell_for_layer_by_identifier_optional<B: LdtkIntCell + Bundle>(
+        &mut self,
+        layer_identifier: Option<String>,
+        value_identifier: String,
+    ) -> &mut Self {
+        let new_entry = Box::new(PhantomLdtkIntCell::<B>::new());
+        registry_mut::<LdtkIntCellIdentifierMap>(self)
+            .insert((layer_identifier, value_identifier), new_entry);
+        self
+    }
+
+    fn register_ldtk_int_cell_promotion_for_layer_optional<B: LdtkEntity + Bundle>(
+        &mut self,
+        layer_identifier: Option<String>,
+        value: i32,
+        tile: Option<PromotedTile>,
+    ) -> &mut Self {
+        let new_entry = IntCellPromotion {
+            tile,
+            ldtk_entity: Box::new(PhantomLdtkEntity::<B>::new()),
+        };
+        registry_mut::<LdtkIntCellPromotionMap>(self).insert((layer_identifier, value), new_entry);
+        self
+    }
+
+    fn register_ldtk_entity_despawn_policy(
+        &mut self,
+        entity_identifier: &str,
+        despawn_policy: DespawnPolicy,
+    ) -> &mut Self {
+        registry_mut::<LdtkDespawnPolicyMap>(self)
+            .insert(entity_identifier.to_string(), despawn_policy);
+        self
+    }
+
+    fn register_ldtk_entity_anchor(
+        &mut self,
+        entity_identifier: &str,
+        int_grid_layer_identifier: &str,
+    ) -> &mut Self {
+        registry_mut::<LdtkEntityAnchorMap>(self).insert(
+            entity_identifier.to_string(),
+            int_grid_layer_identifier.to_string(),
+        );
+        self
+    }
+
+    fn register_ldtk_file_path_field_optional<A: Asset>(
+        &mut self,
+        entity_identifier: Option<String>,
+        field_identifier: &str,
+    ) -> &mut Self {
+        let mut file_path_field_map = registry_mut::<LdtkFilePathFieldMap>(self);
+        let entries = file_path_field_map.entry(entity_identifier).or_default();
+
+        let existing = entries
+            .iter_mut()
+            .find_map(|e| e.as_any_mut().downcast_mut::<FilePathFields<A>>());
+        match existing {
+            Some(file_path_fields) => {
+                if !file_path_fields
+                    .field_identifiers
+                    .iter()
+                    .any(|f| f == field_identifier)
+                {
+                    file_path_fields
+                        .field_identifiers
+                        .push(field_identifier.to_string());
+                }
             }
+            None => entries.push(Box::new(FilePathFields::<A>::new(vec![
+                field_identifier.to_string()
+            ]))),
         }
         self
     }
+
+    fn register_ldtk_tile_enum_tag<B: Bundle + Default>(&mut self, enum_value: &str) -> &mut Self {
+        let new_entry = Box::new(PhantomTileEnumTagBundle::<B>::new());
+        registry_mut::<LdtkTileEnumTagMap>(self).insert(enum_value.to_string(), new_entry);
+        self
+    }
+
+    fn register_ldtk_reflected_entity(
+        &mut self,
+        entity_identifier: &str,
+        component: Box<dyn Reflect>,
+    ) -> &mut Self {
+        registry_mut::<LdtkReflectedEntityMap>(self)
+            .entry(entity_identifier.to_string())
+            .or_default()
+            .push(component);
+        self
+    }
+
+    fn register_ldtk_reflected_entities_from_json(
+        &mut self,
+        json: &str,
+    ) -> anyhow::Result<&mut Self> {
+        let reflected_entity_map = {
+            let type_registry = self
+                .world
+                .get_resource_or_insert_with(TypeRegistryArc::default);
+            let type_registry = type_registry.read();
+            parse_reflected_entity_map(json, &type_registry)?
+        };
+
+        for (entity_identifier, components) in reflected_entity_map {
+            for component in components {
+                self.register_ldtk_reflected_entity(&entity_identifier, component);
+            }
+        }
+
+        Ok(self)
+    }
 }
 
 #[cfg(test)]
@@ -291,6 +852,96 @@ mod tests {
         assert!(ldtk_entity_map.contains_key(&(None, None)));
     }
 
+    #[test]
+    fn test_registrations_survive_registry_initialization() {
+        let mut app = App::new();
+        app.register_ldtk_entity::<LdtkEntityBundle>("entity");
+
+        // This is what LdtkPlugin does when it's added after the registration
+        app.init_non_send_resource::<LdtkEntityMap>();
+
+        app.register_ldtk_entity::<LdtkEntityBundle>("other_entity");
+
+        let ldtk_entity_map = app.world.get_non_send_resource::<LdtkEntityMap>().unwrap();
+
+        assert!(ldtk_entity_map.contains_key(&(None, Some("entity".to_string()))));
+        assert!(ldtk_entity_map.contains_key(&(None, Some("other_entity".to_string()))));
+    }
+
+    #[test]
+    fn test_namespaced_registration_replaces() {
+        let doors = NamespacedRegistration::new("doors", 0);
+
+        assert!(NamespacedRegistration::new("doors", -1).replaces(&doors));
+        assert!(NamespacedRegistration::new("npcs", 1).replaces(&doors));
+        assert!(!NamespacedRegistration::new("npcs", 0).replaces(&doors));
+        assert!(!NamespacedRegistration::new("npcs", -1).replaces(&doors));
+    }
+
+    #[test]
+    fn test_namespaced_ldtk_entity_registrations() {
+        let key = (None, Some("Door".to_string()));
+
+        let mut app = App::new();
+        app.register_namespaced_ldtk_entity::<LdtkEntityBundle>("doors", 0, "Door")
+            .register_namespaced_ldtk_entity::<LdtkEntityBundle>("npcs", 0, "Door");
+
+        let namespace_map = app
+            .world
+            .get_non_send_resource::<LdtkEntityNamespaceMap>()
+            .unwrap();
+        assert_eq!(namespace_map[&key].namespace, "doors");
+
+        app.register_namespaced_ldtk_entity::<LdtkEntityBundle>("npcs", 1, "Door");
+
+        let namespace_map = app
+            .world
+            .get_non_send_resource::<LdtkEntityNamespaceMap>()
+            .unwrap();
+        assert_eq!(namespace_map[&key].namespace, "npcs");
+
+        app.register_ldtk_entity::<LdtkEntityBundle>("Door")
+            .register_namespaced_ldtk_entity::<LdtkEntityBundle>("doors", 2, "Door");
+
+        let namespace_map = app
+            .world
+            .get_non_send_resource::<LdtkEntityNamespaceMap>()
+            .unwrap();
+        assert!(!namespace_map.contains_key(&key));
+
+        let ldtk_entity_map = app.world.get_non_send_resource::<LdtkEntityMap>().unwrap();
+        assert!(ldtk_entity_map.contains_key(&key));
+    }
+
+    #[test]
+    fn test_ldtk_file_path_field_registrations() {
+        let mut app = App::new();
+        app.register_ldtk_file_path_field::<Image>("Sign", "portrait")
+            .register_ldtk_file_path_field::<Image>("Sign", "background")
+            .register_ldtk_file_path_field::<Mesh>("Sign", "model")
+            .register_ldtk_level_file_path_field::<Image>("backdrop");
+
+        let mut file_path_field_map = app
+            .world
+            .get_non_send_resource_mut::<LdtkFilePathFieldMap>()
+            .unwrap();
+
+        let sign_entries = file_path_field_map
+            .get_mut(&Some("Sign".to_string()))
+            .unwrap();
+        assert_eq!(sign_entries.len(), 2);
+        assert_eq!(
+            sign_entries[0]
+                .as_any_mut()
+                .downcast_mut::<FilePathFields<Image>>()
+                .unwrap()
+                .field_identifiers,
+            vec!["portrait".to_string(), "background".to_string()]
+        );
+
+        assert_eq!(file_path_field_map[&None].len(), 1);
+    }
+
     #[test]
     fn test_ldtk_int_cell_registrations() {
         let mut app = App::new();
@@ -313,4 +964,30 @@ mod tests {
 
         assert!(ldtk_int_cell_map.contains_key(&(None, None)));
     }
+
+    #[test]
+    fn test_ldtk_int_cell_promotion_registrations() {
+        let mut app = App::new();
+        app.register_ldtk_int_cell_promotion_for_layer::<LdtkEntityBundle>(
+            "layer",
+            1,
+            PromotedTile::new("tileset", 3),
+        )
+        .register_ldtk_int_cell_promotion::<LdtkEntityBundle>(2, PromotedTile::new("tileset", 4));
+
+        let promotion_map = app
+            .world
+            .get_non_send_resource::<LdtkIntCellPromotionMap>()
+            .unwrap();
+
+        assert_eq!(
+            promotion_map[&(Some("layer".to_string()), 1)].tile,
+            Some(PromotedTile::new("tileset", 3))
+        );
+
+        assert_eq!(
+            promotion_map[&(None, 2)].tile,
+            Some(PromotedTile::new("tileset", 4))
+        );
+    }
 }
@@ -0,0 +1,37 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use std::{collections::HashMap, marker::PhantomData};
+
+#[allow(unused_imports)]
+use crate::app::register_ldtk_objects::RegisterLdtkObjects;
+
+pub struct PhantomTileEnumTagBundle<B: Bundle + Default> {
+    bundle: PhantomData<B>,
+}
+
+impl<B: Bundle + Default> PhantomTileEnumTagBundle<B> {
+    pub fn new() -> PhantomTileEnumTagBundle<B> {
+        PhantomTileEnumTagBundle::<B> {
+            bundle: PhantomData,
+        }
+    }
+}
+
+pub trait PhantomTileEnumTagBundleTrait {
+    fn evaluate<'w, 's, 'a, 'b>(
+        &self,
+        entity_commands: &'b mut EntityCommands<'w, 's, 'a>,
+    ) -> &'b mut EntityCommands<'w, 's, 'a>;
+}
+
+impl<B: Bundle + Default> PhantomTileEnumTagBundleTrait for PhantomTileEnumTagBundle<B> {
+    fn evaluate<'w, 's, 'a, 'b>(
+        &self,
+        entity_commands: &'b mut EntityCommands<'w, 's, 'a>,
+    ) -> &'b mut EntityCommands<'w, 's, 'a> {
+        entity_commands.insert_bundle(B::default())
+    }
+}
+
+/// Used by [RegisterLdtkObjects] to associate the enum values tiles are tagged with in their
+/// tileset with the bundles inserted on those tiles.
+pub type LdtkTileEnumTagMap = HashMap<String, Box<dyn PhantomTileEnumTagBundleTrait>>;
@@ -1,38 +1,166 @@
 //! Assets and AssetLoaders for loading ldtk files.
 
 use crate::{
-    ldtk::{LdtkJson, Level},
-    resources::LevelSelection,
+    ldtk::{
+        EntityDefinition, EntityInstance, IntGridValueDefinition, IntGridValueGroupDefinition,
+        LayerDefinition, LdtkJson, Level, NeighbourLevel, TilesetDefinition,
+    },
+    resources::{LdtkSettings, LevelSelection},
+    utils::layer_settings_and_scale,
+    views::LevelData,
 };
 use bevy::{
-    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    asset::{Asset, AssetLoader, AssetPath, LoadContext, LoadedAsset},
     prelude::*,
     reflect::TypeUuid,
     utils::BoxedFuture,
 };
-use std::{collections::HashMap, path::Path};
+use bevy_ecs_tilemap::prelude::{LayerSettings, MapSize};
+use serde::Serialize;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    path::Path,
+};
 
 #[allow(unused_imports)]
 use crate::components::LdtkWorldBundle;
 
-fn ldtk_path_to_asset_path<'a, 'b>(
+/// Directory the relative paths of the LDtk project at `ldtk_path` are resolved against.
+pub(crate) fn ldtk_directory(ldtk_path: &Path) -> &Path {
+    ldtk_path.parent().unwrap_or_else(|| Path::new(""))
+}
+
+pub(crate) fn ldtk_path_to_asset_path<'a, 'b>(
     load_context: &LoadContext<'a>,
     rel_path: &str,
 ) -> AssetPath<'b> {
-    load_context
-        .path()
-        .parent()
-        .unwrap()
+    ldtk_directory(load_context.path())
         .join(Path::new(rel_path))
         .into()
 }
 
+/// Hashes LDtk data, for [LdtkAsset::checksum] and [LdtkLevel::checksum].
+///
+/// The data is converted to a [serde_json::Value] first, whose objects are ordered by key, so the
+/// [HashMap]s of the data don't make the checksum vary between identical assets.
+pub(crate) fn content_checksum(value: &impl Serialize, hasher: &mut impl Hasher) {
+    match serde_json::to_value(value) {
+        Ok(value) => value.to_string().hash(hasher),
+        Err(e) => warn!("LDtk data couldn't be hashed: {}", e),
+    }
+}
+
+/// Hashes the handles of an asset map in a consistent order.
+fn handle_map_checksum<T: Asset>(handle_map: &HashMap<i32, Handle<T>>, hasher: &mut impl Hasher) {
+    let mut handles: Vec<(&i32, &Handle<T>)> = handle_map.iter().collect();
+    handles.sort_by_key(|(uid, _)| **uid);
+    for (uid, handle) in handles {
+        (uid, handle.id).hash(hasher);
+    }
+}
+
+pub(crate) fn find_entities_in_levels<'a>(
+    levels: impl IntoIterator<Item = &'a Level>,
+    identifier: &str,
+) -> Vec<(&'a str, &'a EntityInstance)> {
+    levels
+        .into_iter()
+        .flat_map(|level| {
+            level
+                .layer_instances
+                .iter()
+                .flatten()
+                .flat_map(|layer_instance| &layer_instance.entity_instances)
+                .filter(|entity_instance| entity_instance.identifier == identifier)
+                .map(move |entity_instance| (level.iid.as_str(), entity_instance))
+        })
+        .collect()
+}
+
 /// Used in [LdtkAsset]. Key is the tileset definition uid.
 pub type TilesetMap = HashMap<i32, Handle<Image>>;
 
 /// Used in [LdtkAsset]. Key is the level uid.
 pub type LevelMap = HashMap<i32, Handle<LdtkLevel>>;
 
+/// Used in [LdtkAsset]. Key is the level uid, value is the level's background image.
+pub type LevelBackgroundMap = HashMap<i32, Handle<Image>>;
+
+/// Used in [LdtkAsset]. Key is the layer definition uid and the tileset definition uid of the
+/// layer.
+///
+/// Values are the [LayerSettings] and scale of tile layers using those definitions.
+/// The [MapSize] of the settings is zero, since that depends on the level.
+pub type LayerSettingsCache = HashMap<(i32, Option<i32>), (LayerSettings, Vec3)>;
+
+/// Calculates the [LayerSettingsCache] entries of each tile layer definition and the tilesets it
+/// uses by default, so spawning levels that share these definitions skips the setup math.
+pub(crate) fn create_layer_settings_cache(project: &LdtkJson) -> LayerSettingsCache {
+    let mut layer_settings_cache = LayerSettingsCache::new();
+
+    for layer_definition in &project.defs.layers {
+        if layer_definition.layer_definition_type == "Entities" {
+            continue;
+        }
+
+        let tileset_uids = [
+            None,
+            layer_definition.tileset_def_uid,
+            layer_definition.auto_tileset_def_uid,
+        ];
+
+        for tileset_uid in tileset_uids {
+            let tileset_definition = match tileset_uid {
+                Some(uid) => match project.defs.tilesets.iter().find(|t| t.uid == uid) {
+                    Some(tileset_definition) => Some(tileset_definition),
+                    None => continue,
+                },
+                None => None,
+            };
+
+            layer_settings_cache.insert(
+                (layer_definition.uid, tileset_uid),
+                layer_settings_and_scale(
+                    MapSize(0, 0),
+                    layer_definition.grid_size,
+                    tileset_definition,
+                ),
+            );
+        }
+    }
+
+    layer_settings_cache
+}
+
+/// An IntGrid value of a layer, with the name, color, and group given to it in LDtk.
+///
+/// See [LdtkAsset::get_int_grid_value].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct IntGridValueInfo<'a> {
+    pub value: i32,
+    pub identifier: Option<&'a str>,
+    pub color: Color,
+    pub group: Option<&'a IntGridValueGroupDefinition>,
+}
+
+impl<'a> IntGridValueInfo<'a> {
+    fn new(
+        int_grid_value_definition: &'a IntGridValueDefinition,
+        groups: &'a [IntGridValueGroupDefinition],
+    ) -> Self {
+        IntGridValueInfo {
+            value: int_grid_value_definition.value,
+            identifier: int_grid_value_definition.identifier.as_deref(),
+            color: Color::hex(int_grid_value_definition.color.trim_start_matches('#'))
+                .unwrap_or_default(),
+            group: groups
+                .iter()
+                .find(|g| g.uid == int_grid_value_definition.group_uid),
+        }
+    }
+}
+
 /// Main asset for loading ldtk files.
 ///
 /// Load your ldtk project with the asset server, then insert the handle into the
@@ -43,9 +171,25 @@ pub struct LdtkAsset {
     pub project: LdtkJson,
     pub tileset_map: TilesetMap,
     pub level_map: LevelMap,
+    pub level_background_map: LevelBackgroundMap,
+    pub layer_settings_cache: LayerSettingsCache,
 }
 
 impl LdtkAsset {
+    /// Returns a hash of the project and of the handles of its tilesets, levels and backgrounds.
+    ///
+    /// When the asset is modified, worlds are only respawned if this has changed, so saves that
+    /// don't change the file don't reset them.
+    /// This covers edits through `Assets<LdtkAsset>::get_mut` as well as reloads of the file.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        content_checksum(&self.project, &mut hasher);
+        handle_map_checksum(&self.tileset_map, &mut hasher);
+        handle_map_checksum(&self.level_map, &mut hasher);
+        handle_map_checksum(&self.level_background_map, &mut hasher);
+        hasher.finish()
+    }
+
     pub fn world_height(&self) -> i32 {
         let mut world_height = 0;
         for level in &self.project.levels {
@@ -63,10 +207,368 @@ impl LdtkAsset {
             .find(|(i, l)| level_selection.is_match(i, l))
             .map(|(_, l)| l)
     }
+
+    /// Looks up the definition of a layer by its uid, like the `layer_def_uid` of a
+    /// [LayerMetadata](crate::LayerMetadata).
+    pub fn get_layer_definition(&self, uid: i32) -> Option<&LayerDefinition> {
+        self.project.defs.layers.iter().find(|l| l.uid == uid)
+    }
+
+    /// Looks up the definition of an entity by its uid.
+    pub fn get_entity_definition(&self, uid: i32) -> Option<&EntityDefinition> {
+        self.project.defs.entities.iter().find(|e| e.uid == uid)
+    }
+
+    /// Looks up the definition of a tileset by its uid.
+    pub fn get_tileset_definition(&self, uid: i32) -> Option<&TilesetDefinition> {
+        self.project.defs.tilesets.iter().find(|t| t.uid == uid)
+    }
+
+    /// Returns the levels of the project in order, with their layers.
+    ///
+    /// Levels stored in external files are taken from `level_assets`, and skipped if they aren't
+    /// loaded yet.
+    pub fn iter_levels_with_layers<'a>(
+        &'a self,
+        level_assets: &'a Assets<LdtkLevel>,
+    ) -> impl Iterator<Item = &'a Level> {
+        self.project.levels.iter().filter_map(|level| {
+            if level.layer_instances.is_some() {
+                Some(level)
+            } else {
+                self.level_map
+                    .get(&level.uid)
+                    .and_then(|h| level_assets.get(h))
+                    .map(|ldtk_level| &ldtk_level.level)
+            }
+        })
+    }
+
+    /// Finds all the entities of the project with the given identifier, along with the iid of
+    /// their level, for enumerating authored content like collectibles or quest givers.
+    ///
+    /// Levels are searched in the order of the project, see [LdtkAsset::iter_levels_with_layers].
+    pub fn find_entities<'a>(
+        &'a self,
+        level_assets: &'a Assets<LdtkLevel>,
+        identifier: &str,
+    ) -> Vec<(&'a str, &'a EntityInstance)> {
+        find_entities_in_levels(self.iter_levels_with_layers(level_assets), identifier)
+    }
+
+    /// Finds all the levels of the project containing at least one entity with the given
+    /// identifier.
+    ///
+    /// Levels are searched in the order of the project, see [LdtkAsset::iter_levels_with_layers].
+    pub fn find_levels_with_entity<'a>(
+        &'a self,
+        level_assets: &'a Assets<LdtkLevel>,
+        identifier: &str,
+    ) -> Vec<&'a Level> {
+        self.iter_levels_with_layers(level_assets)
+            .filter(|level| !find_entities_in_levels([*level], identifier).is_empty())
+            .collect()
+    }
+
+    /// Looks up the definition of an IntGrid value of the layer with the given identifier.
+    ///
+    /// This maps the values of [IntGridCell](crate::IntGridCell)s to the names and colors they
+    /// have in the editor, e.g. for debug overlays or data-driven collision tables.
+    pub fn get_int_grid_value(
+        &self,
+        layer_identifier: &str,
+        value: i32,
+    ) -> Option<IntGridValueInfo> {
+        self.int_grid_values(layer_identifier)
+            .into_iter()
+            .find(|i| i.value == value)
+    }
+
+    /// Returns the definitions of all the IntGrid values of the layer with the given identifier,
+    /// sorted by value.
+    ///
+    /// Returns an empty [Vec] if there's no such layer, or if it isn't an IntGrid layer.
+    pub fn int_grid_values(&self, layer_identifier: &str) -> Vec<IntGridValueInfo> {
+        self.project
+            .defs
+            .layers
+            .iter()
+            .find(|l| l.identifier == layer_identifier)
+            .map(|layer_definition| {
+                layer_definition
+                    .int_grid_values
+                    .iter()
+                    .map(|d| IntGridValueInfo::new(d, &layer_definition.int_grid_values_groups))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Merges the levels of an overlay project into this one, like a DLC or mod level pack.
+    ///
+    /// Overlay levels are matched to the levels of this project by iid.
+    /// Matching levels are replaced by their overlay version but keep their uid, while the other
+    /// levels are added with new uids.
+    /// Neighbors are updated in both directions, so added levels integrate with the
+    /// [LevelSelection] and [LdtkSettings::load_level_neighbors](crate::resources::LdtkSettings)
+    /// logic.
+    /// Overlay levels without an iid are ignored.
+    ///
+    /// The overlay is expected to share the definitions of this project, like a copy of it would.
+    /// Only tilesets missing from this project are added.
+    /// All levels of the overlay must be loaded, which is the case once the overlay itself is.
+    ///
+    /// Since this modifies the asset, worlds using it respawn afterwards.
+    /// Merging the same overlay again is idempotent.
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_ecs_ldtk::prelude::*;
+    /// struct LevelPack {
+    ///     base: Handle<LdtkAsset>,
+    ///     overlay: Handle<LdtkAsset>,
+    ///     merged: bool,
+    /// }
+    ///
+    /// fn merge_level_pack(
+    ///     mut level_pack: ResMut<LevelPack>,
+    ///     mut ldtk_assets: ResMut<Assets<LdtkAsset>>,
+    ///     mut level_assets: ResMut<Assets<LdtkLevel>>,
+    /// ) {
+    ///     if level_pack.merged {
+    ///         return;
+    ///     }
+    ///
+    ///     if let Some(overlay) = ldtk_assets.remove(&level_pack.overlay) {
+    ///         if let Some(base) = ldtk_assets.get_mut(&level_pack.base) {
+    ///             level_pack.merged = base.merge_overlay(&overlay, &mut level_assets).is_ok();
+    ///         }
+    ///         ldtk_assets.set_untracked(&level_pack.overlay, overlay);
+    ///     }
+    /// }
+    /// ```
+    pub fn merge_overlay(
+        &mut self,
+        overlay: &LdtkAsset,
+        level_assets: &mut Assets<LdtkLevel>,
+    ) -> anyhow::Result<()> {
+        let overlay_levels = overlay
+            .project
+            .levels
+            .iter()
+            .map(|level| {
+                overlay
+                    .level_map
+                    .get(&level.uid)
+                    .and_then(|h| level_assets.get(h))
+                    .map(|ldtk_level| ldtk_level.level.clone())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("overlay level {} is not loaded", level.identifier)
+                    })
+            })
+            .collect::<anyhow::Result<Vec<Level>>>()?;
+
+        self.level_background_map.extend(
+            overlay
+                .level_background_map
+                .iter()
+                .map(|(uid, handle)| (*uid, handle.clone())),
+        );
+
+        for level in merge_levels(&mut self.project.levels, overlay_levels) {
+            self.set_level_asset(level, level_assets);
+        }
+
+        for tileset in &overlay.project.defs.tilesets {
+            if let (false, Some(handle)) = (
+                self.tileset_map.contains_key(&tileset.uid),
+                overlay.tileset_map.get(&tileset.uid),
+            ) {
+                self.project.defs.tilesets.push(tileset.clone());
+                self.tileset_map.insert(tileset.uid, handle.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a level to the project, replacing the level with the same uid if there is one.
+    ///
+    /// The level spawns like the levels of the file once its uid is in the
+    /// [LevelSet](crate::LevelSet) of a world, so this is how levels that don't come from an LDtk
+    /// file, like procedurally generated ones, are spawned.
+    /// They can be described with a [GridLevel](crate::GridLevel), which only needs the layers:
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_ecs_ldtk::{prelude::*, GridLevel};
+    /// fn add_generated_level(
+    ///     ldtk_handle: Res<Handle<LdtkAsset>>,
+    ///     mut ldtk_assets: ResMut<Assets<LdtkAsset>>,
+    ///     mut level_assets: ResMut<Assets<LdtkLevel>>,
+    ///     mut level_set_query: Query<&mut LevelSet>,
+    /// ) {
+    ///     let layer_instances: Vec<LayerInstance> = Vec::new(); // generate the layers...
+    ///
+    ///     if let Some(ldtk_asset) = ldtk_assets.get_mut(&*ldtk_handle) {
+    ///         let grid_level = GridLevel {
+    ///             uid: 1000,
+    ///             px_wid: 256,
+    ///             px_hei: 256,
+    ///             layer_instances: &layer_instances,
+    ///         };
+    ///         ldtk_asset.insert_level(grid_level, &mut level_assets);
+    ///
+    ///         for mut level_set in level_set_query.iter_mut() {
+    ///             level_set.uids.insert(1000);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// Since this modifies the asset, worlds using it respawn afterwards.
+    pub fn insert_level(&mut self, level: impl Into<Level>, level_assets: &mut Assets<LdtkLevel>) {
+        let level = level.into();
+
+        match self.project.levels.iter_mut().find(|l| l.uid == level.uid) {
+            Some(existing_level) => *existing_level = level.clone(),
+            None => self.project.levels.push(level.clone()),
+        }
+
+        self.set_level_asset(level, level_assets);
+    }
+
+    /// Stores a level of the project in its [LdtkLevel] asset.
+    ///
+    /// Levels that already have an asset keep their handle, so storing the same level again
+    /// doesn't change the [LdtkAsset::checksum].
+    fn set_level_asset(&mut self, level: Level, level_assets: &mut Assets<LdtkLevel>) {
+        match self.level_map.get(&level.uid) {
+            Some(handle) => {
+                level_assets.set_untracked(handle, LdtkLevel { level });
+            }
+            None => {
+                self.level_map
+                    .insert(level.uid, level_assets.add(LdtkLevel { level }));
+            }
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
-pub struct LdtkLoader;
+/// Merges `overlay` levels into `base` levels by iid, returning the merged overlay levels.
+///
+/// See [LdtkAsset::merge_overlay] for more details.
+fn merge_levels(base: &mut Vec<Level>, overlay: Vec<Level>) -> Vec<Level> {
+    let mut next_uid = base.iter().map(|l| l.uid + 1).max().unwrap_or(0);
+
+    let uid_map: HashMap<i32, i32> = overlay
+        .iter()
+        .filter(|l| !l.iid.is_empty())
+        .map(|overlay_level| {
+            let uid = match base.iter().find(|l| l.iid == overlay_level.iid) {
+                Some(base_level) => base_level.uid,
+                None => {
+                    next_uid += 1;
+                    next_uid - 1
+                }
+            };
+            (overlay_level.uid, uid)
+        })
+        .collect();
+
+    let mut merged_levels = Vec::new();
+    for mut level in overlay {
+        let uid = match uid_map.get(&level.uid) {
+            Some(uid) => *uid,
+            None => {
+                warn!(
+                    "Overlay level {} has no iid and will not be merged",
+                    level.identifier
+                );
+                continue;
+            }
+        };
+
+        level.uid = uid;
+        level.neighbours = level
+            .neighbours
+            .into_iter()
+            .filter_map(|n| {
+                Some(NeighbourLevel {
+                    level_uid: *uid_map.get(&n.level_uid)?,
+                    dir: n.dir,
+                })
+            })
+            .collect();
+
+        match base.iter_mut().find(|l| l.uid == uid) {
+            Some(base_level) => {
+                // The overlay doesn't know about the levels that are only in the base, so the
+                // replaced level's links to them are kept
+                for neighbour in &base_level.neighbours {
+                    if !level
+                        .neighbours
+                        .iter()
+                        .any(|n| n.level_uid == neighbour.level_uid)
+                    {
+                        level.neighbours.push(neighbour.clone());
+                    }
+                }
+                *base_level = level.clone();
+            }
+            None => base.push(level.clone()),
+        }
+        merged_levels.push(level);
+    }
+
+    // Links to levels that aren't in the merged set can't be followed
+    let merged_uids: Vec<i32> = base.iter().map(|l| l.uid).collect();
+    for level in base.iter_mut() {
+        level
+            .neighbours
+            .retain(|n| merged_uids.contains(&n.level_uid));
+    }
+
+    for merged_level in &merged_levels {
+        for neighbour in &merged_level.neighbours {
+            let opposite_dir = match neighbour.dir.as_str() {
+                "n" => "s",
+                "s" => "n",
+                "e" => "w",
+                "w" => "e",
+                _ => continue,
+            };
+
+            if let Some(base_level) = base.iter_mut().find(|l| l.uid == neighbour.level_uid) {
+                if !base_level
+                    .neighbours
+                    .iter()
+                    .any(|n| n.level_uid == merged_level.uid)
+                {
+                    base_level.neighbours.push(NeighbourLevel {
+                        dir: opposite_dir.to_string(),
+                        level_uid: merged_level.uid,
+                    });
+                }
+            }
+        }
+    }
+
+    merged_levels
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct LdtkLoader {
+    /// Skips loading the tileset and background images, see [LdtkSettings::headless].
+    pub headless: bool,
+}
+
+impl FromWorld for LdtkLoader {
+    fn from_world(world: &mut World) -> Self {
+        LdtkLoader {
+            headless: world
+                .get_resource::<LdtkSettings>()
+                .map_or(false, |ldtk_settings| ldtk_settings.headless),
+        }
+    }
+}
 
 impl AssetLoader for LdtkLoader {
     fn load<'a>(
@@ -103,22 +605,40 @@ impl AssetLoader for LdtkLoader {
 
             let mut tileset_rel_paths = Vec::new();
             let mut tileset_map = HashMap::new();
-            for tileset in &project.defs.tilesets {
-                let asset_path = ldtk_path_to_asset_path(load_context, &tileset.rel_path);
+            if !self.headless {
+                for tileset in &project.defs.tilesets {
+                    let asset_path = ldtk_path_to_asset_path(load_context, &tileset.rel_path);
+
+                    tileset_rel_paths.push(asset_path.clone());
+                    tileset_map.insert(tileset.uid, load_context.get_handle(asset_path));
+                }
+            }
+
+            let mut background_rel_paths = Vec::new();
+            let mut level_background_map = HashMap::new();
+            if !self.headless {
+                for level in &project.levels {
+                    if let Some(bg_rel_path) = &level.bg_rel_path {
+                        let asset_path = ldtk_path_to_asset_path(load_context, bg_rel_path);
 
-                tileset_rel_paths.push(asset_path.clone());
-                tileset_map.insert(tileset.uid, load_context.get_handle(asset_path));
+                        background_rel_paths.push(asset_path.clone());
+                        level_background_map.insert(level.uid, load_context.get_handle(asset_path));
+                    }
+                }
             }
 
             let ldtk_asset = LdtkAsset {
+                layer_settings_cache: create_layer_settings_cache(&project),
                 project,
                 tileset_map,
                 level_map,
+                level_background_map,
             };
             load_context.set_default_asset(
                 LoadedAsset::new(ldtk_asset)
                     .with_dependencies(tileset_rel_paths)
-                    .with_dependencies(external_level_paths),
+                    .with_dependencies(external_level_paths)
+                    .with_dependencies(background_rel_paths),
             );
             Ok(())
         })
@@ -141,6 +661,23 @@ pub struct LdtkLevel {
     pub level: Level,
 }
 
+impl LdtkLevel {
+    /// Returns a hash of the level.
+    ///
+    /// Like [LdtkAsset::checksum], levels are only respawned when their external level file is
+    /// modified if this has changed.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        content_checksum(&self.level, &mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns a [LevelData] view of the level, which is stable across LDtk releases.
+    pub fn data(&self) -> LevelData {
+        LevelData::from(&self.level)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Default)]
 pub struct LdtkLevelLoader;
 
@@ -163,3 +700,212 @@ impl AssetLoader for LdtkLevelLoader {
         &["ldtkl"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{ldtk_json, LayerInstanceBuilder, LevelBuilder},
+        grid_level::GridLevel,
+        ldtk::Type,
+    };
+    use bevy::asset::AssetPlugin;
+
+    #[test]
+    fn test_level_checksum() {
+        let level = LdtkLevel {
+            level: LevelBuilder::new(0).build(),
+        };
+        let reloaded = LdtkLevel {
+            level: serde_json::from_value(serde_json::to_value(&level.level).unwrap()).unwrap(),
+        };
+        assert_eq!(level.checksum(), reloaded.checksum());
+
+        let mut edited = LdtkLevel {
+            level: level.level.clone(),
+        };
+        edited.level.px_wid = 512;
+        assert_ne!(level.checksum(), edited.checksum());
+    }
+
+    #[test]
+    fn test_find_entities_in_levels() {
+        let coin = |x| EntityInstance {
+            identifier: "Coin".to_string(),
+            grid: IVec2::new(x, 0),
+            ..Default::default()
+        };
+        let chest = EntityInstance {
+            identifier: "Chest".to_string(),
+            ..Default::default()
+        };
+
+        let levels = vec![
+            LevelBuilder::new(0)
+                .iid("a")
+                .entity(coin(0))
+                .entity(chest.clone())
+                .entity(coin(2))
+                .build(),
+            LevelBuilder::new(1).iid("b").entity(chest).build(),
+            LevelBuilder::new(2).iid("c").entity(coin(1)).build(),
+        ];
+
+        let coins = find_entities_in_levels(&levels, "Coin");
+        assert_eq!(
+            coins
+                .iter()
+                .map(|(level_iid, entity_instance)| (*level_iid, entity_instance.grid.x))
+                .collect::<Vec<_>>(),
+            vec![("a", 0), ("a", 2), ("c", 1)]
+        );
+
+        assert!(find_entities_in_levels(&levels, "Door").is_empty());
+    }
+
+    #[test]
+    fn test_int_grid_value_info() {
+        let groups = vec![IntGridValueGroupDefinition {
+            identifier: Some("Hazards".to_string()),
+            uid: 1,
+            ..Default::default()
+        }];
+
+        let lava = IntGridValueDefinition {
+            color: "#FF0000".to_string(),
+            identifier: Some("Lava".to_string()),
+            value: 3,
+            group_uid: 1,
+        };
+
+        let info = IntGridValueInfo::new(&lava, &groups);
+
+        assert_eq!(info.value, 3);
+        assert_eq!(info.identifier, Some("Lava"));
+        assert_eq!(info.color, Color::rgb(1., 0., 0.));
+        assert_eq!(info.group, Some(&groups[0]));
+
+        let unnamed = IntGridValueDefinition {
+            color: "#00FF00".to_string(),
+            value: 4,
+            ..Default::default()
+        };
+
+        assert_eq!(IntGridValueInfo::new(&unnamed, &groups).group, None);
+    }
+
+    #[test]
+    fn test_merge_levels() {
+        let mut base = vec![
+            LevelBuilder::new(0).iid("a").neighbour("e", 1).build(),
+            LevelBuilder::new(1).iid("b").neighbour("w", 0).build(),
+        ];
+
+        let overlay = vec![
+            LevelBuilder::new(7).iid("b").neighbour("e", 8).build(),
+            LevelBuilder::new(8).iid("c").neighbour("w", 7).build(),
+            LevelBuilder::new(9).build(),
+        ];
+
+        let merged = merge_levels(&mut base, overlay.clone());
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(base.len(), 3);
+        assert_eq!(base[1].identifier, "Level_7");
+        assert_eq!(base[1].uid, 1);
+        assert_eq!(base[2].uid, 2);
+        assert_eq!(base[2].neighbours[0].level_uid, 1);
+
+        // The replaced level keeps its link to the base-only level, which still links back
+        let neighbour_uids =
+            |level: &Level| -> Vec<i32> { level.neighbours.iter().map(|n| n.level_uid).collect() };
+        assert_eq!(neighbour_uids(&base[1]), vec![2, 0]);
+        assert_eq!(neighbour_uids(&base[0]), vec![1]);
+
+        let merged_once = base.clone();
+        merge_levels(&mut base, overlay);
+        assert_eq!(base, merged_once);
+    }
+
+    #[test]
+    fn test_insert_level() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<LdtkLevel>();
+        let mut level_assets = app.world.get_resource_mut::<Assets<LdtkLevel>>().unwrap();
+
+        let mut ldtk_asset = LdtkAsset {
+            project: ldtk_json(Vec::new()),
+            tileset_map: HashMap::new(),
+            level_map: HashMap::new(),
+            level_background_map: HashMap::new(),
+            layer_settings_cache: LayerSettingsCache::new(),
+        };
+
+        let layer_instances = vec![LayerInstanceBuilder::new("Ground", Type::IntGrid, 2, 2, 16)
+            .int_grid_csv(vec![1, 0, 0, 1])
+            .build()];
+        let grid_level = GridLevel {
+            uid: 5,
+            px_wid: 32,
+            px_hei: 32,
+            layer_instances: &layer_instances,
+        };
+
+        ldtk_asset.insert_level(grid_level, &mut level_assets);
+        let handle = ldtk_asset.level_map[&5].clone();
+        let ldtk_level = level_assets.get(&handle).unwrap();
+        assert_eq!(GridLevel::from(&ldtk_level.level), grid_level);
+        assert_eq!(ldtk_asset.project.levels, vec![ldtk_level.level.clone()]);
+
+        ldtk_asset.insert_level(grid_level, &mut level_assets);
+        assert_eq!(ldtk_asset.project.levels.len(), 1);
+        assert_eq!(ldtk_asset.level_map[&5], handle);
+    }
+
+    #[test]
+    fn test_merge_overlay_reuses_handles() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<LdtkLevel>();
+        let mut level_assets = app.world.get_resource_mut::<Assets<LdtkLevel>>().unwrap();
+
+        let mut ldtk_asset = |levels: Vec<Level>| LdtkAsset {
+            level_map: levels
+                .iter()
+                .map(|level| {
+                    let handle = level_assets.add(LdtkLevel {
+                        level: level.clone(),
+                    });
+                    (level.uid, handle)
+                })
+                .collect(),
+            project: ldtk_json(levels),
+            tileset_map: HashMap::new(),
+            level_background_map: HashMap::new(),
+            layer_settings_cache: LayerSettingsCache::new(),
+        };
+
+        let mut base = ldtk_asset(vec![LevelBuilder::new(0).iid("a").build()]);
+        let overlay = ldtk_asset(vec![
+            LevelBuilder::new(3).iid("a").build(),
+            LevelBuilder::new(4).iid("b").build(),
+        ]);
+        let base_handle = base.level_map[&0].clone();
+
+        base.merge_overlay(&overlay, &mut level_assets).unwrap();
+        assert_eq!(base.level_map[&0], base_handle);
+        assert_eq!(
+            level_assets.get(&base_handle).unwrap().level.identifier,
+            "Level_3"
+        );
+
+        let level_map = base.level_map.clone();
+        let checksum = base.checksum();
+        base.merge_overlay(&overlay, &mut level_assets).unwrap();
+        assert_eq!(base.level_map, level_map);
+        assert_eq!(base.checksum(), checksum);
+    }
+}
@@ -0,0 +1,118 @@
+//! Support for placing ambient audio in LDtk via a "sound emitter" entity convention.
+//!
+//! *Requires the "audio" feature*
+//!
+//! Any LDtk entity can become a sound emitter by registering [SoundEmitterBundle] for its
+//! identifier:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{prelude::*, audio::SoundEmitterBundle};
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .add_plugin(LdtkPlugin)
+//!         .register_ldtk_entity::<SoundEmitterBundle>("SoundEmitter")
+//!         .run();
+//! }
+//! ```
+//! The `clip` field of the LDtk entity, a `FilePath` or `String` field with the path of the clip
+//! relative to the LDtk file, is then loaded into a [SoundEmitter] component, and played once
+//! when the entity spawns.
+//!
+//! `bevy_audio` can't change the volume of a clip or loop it, so other settings aren't part of the
+//! convention.
+//! Games using a richer backend, like `bevy_kira_audio`, can query for `Added<SoundEmitter>` and
+//! read their own settings from the entity's fields with [LdtkFields](crate::ldtk::LdtkFields).
+
+use crate::{
+    app::{LdtkEntity, LdtkEntityContext},
+    ldtk::{EntityInstance, FieldValue, LayerInstance, TilesetDefinition},
+};
+use bevy::{
+    audio::{Audio, AudioSource},
+    prelude::*,
+};
+
+/// Field identifier for the clip path of a [SoundEmitter].
+pub const SOUND_EMITTER_CLIP_FIELD: &str = "clip";
+
+/// [Component] describing an ambient sound authored in LDtk.
+///
+/// The plugin plays the clip of newly spawned emitters once, at full volume.
+#[derive(Clone, PartialEq, Debug, Default, Component)]
+pub struct SoundEmitter {
+    pub clip: Handle<AudioSource>,
+}
+
+/// [Bundle] implementing [LdtkEntity] by reading the sound emitter fields of the LDtk entity.
+///
+/// See the [module-level documentation](self) for the field convention.
+#[derive(Clone, Default, Bundle)]
+pub struct SoundEmitterBundle {
+    pub sound_emitter: SoundEmitter,
+}
+
+impl LdtkEntity for SoundEmitterBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        tileset: Option<&Handle<Image>>,
+        tileset_definition: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        LdtkEntityContext::with_tileset(tileset, tileset_definition, |context| {
+            SoundEmitterBundle::bundle_entity_with_context(
+                entity_instance,
+                layer_instance,
+                context,
+                asset_server,
+                texture_atlases,
+            )
+        })
+    }
+
+    fn bundle_entity_with_context(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        context: &LdtkEntityContext,
+        asset_server: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let clip = entity_instance
+            .field_instances
+            .iter()
+            .find(|f| f.identifier == SOUND_EMITTER_CLIP_FIELD)
+            .and_then(|f| match &f.value {
+                FieldValue::FilePath(Some(path)) | FieldValue::String(Some(path)) => {
+                    Some(asset_server.load(context.ldtk_directory.join(path)))
+                }
+                _ => None,
+            });
+
+        let clip = clip.unwrap_or_else(|| {
+            warn!(
+                "Sound emitter \"{}\" has no \"{}\" field, it will not play anything",
+                entity_instance.identifier, SOUND_EMITTER_CLIP_FIELD
+            );
+            Handle::default()
+        });
+
+        SoundEmitterBundle {
+            sound_emitter: SoundEmitter { clip },
+        }
+    }
+}
+
+/// Plays the clips of newly spawned [SoundEmitter]s with `bevy_audio`.
+pub fn play_sound_emitters(
+    sound_emitter_query: Query<&SoundEmitter, Added<SoundEmitter>>,
+    audio: Res<Audio>,
+) {
+    for sound_emitter in sound_emitter_query.iter() {
+        if sound_emitter.clip != Handle::default() {
+            audio.play(sound_emitter.clip.clone());
+        }
+    }
+}
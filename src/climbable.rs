@@ -0,0 +1,380 @@
+//! Climbable regions, like ladders or vines, extracted from IntGrid values or tile tags.
+//!
+//! Checking a character against every ladder tile is tedious and slow.
+//! When the [ClimbableSettings] resource is inserted, the plugin merges vertical runs of climbable
+//! cells into a single [ClimbableRegion] entity per strip whenever a level spawns, so ladder logic
+//! can use region overlap checks instead:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{climbable::*, prelude::*};
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .add_plugin(LdtkPlugin)
+//!         .insert_resource(ClimbableSettings {
+//!             int_grid_values: vec![2],
+//!             tile_tags: vec!["Ladder".to_string()],
+//!         })
+//!         .add_system(climb)
+//!         .run();
+//! }
+//!
+//! # #[derive(Component)]
+//! # struct Player;
+//! fn climb(
+//!     player_query: Query<&GlobalTransform, With<Player>>,
+//!     climbable_query: Query<&ClimbableRegion>,
+//! ) {
+//!     for player_transform in player_query.iter() {
+//!         let position = player_transform.translation.truncate();
+//!         if climbable_query.iter().any(|c| c.contains(position)) {
+//!             // let the player climb...
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    assets::{LdtkAsset, LdtkLevel},
+    ldtk::{LayerInstance, TilesetDefinition, Type},
+    resources::LevelEvent,
+};
+use bevy::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Optional resource choosing which IntGrid values and tile tags are climbable.
+///
+/// Tile tags are the enum values tiles are tagged with in their tileset's definition in LDtk.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ClimbableSettings {
+    pub int_grid_values: Vec<i32>,
+    pub tile_tags: Vec<String>,
+}
+
+/// A vertical run of climbable cells in a single column, in grid coordinates.
+///
+/// Like [GridCoords](crate::GridCoords), the origin is the bottom-left cell of the layer, and y
+/// increases upwards.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct ClimbableStrip {
+    pub column: i32,
+    /// The y of the lowest cell of the strip.
+    pub bottom: i32,
+    /// One more than the y of the highest cell of the strip.
+    pub top: i32,
+}
+
+/// Merges climbable cells into vertical strips, sorted by column then from the bottom up.
+pub fn find_climbable_strips(cells: impl IntoIterator<Item = IVec2>) -> Vec<ClimbableStrip> {
+    let cells: BTreeSet<(i32, i32)> = cells.into_iter().map(|c| (c.x, c.y)).collect();
+
+    let mut strips: Vec<ClimbableStrip> = Vec::new();
+    for (column, y) in cells {
+        match strips.last_mut() {
+            Some(strip) if strip.column == column && strip.top == y => strip.top += 1,
+            _ => strips.push(ClimbableStrip {
+                column,
+                bottom: y,
+                top: y + 1,
+            }),
+        }
+    }
+
+    strips
+}
+
+/// [Component] for a climbable strip, in world space.
+///
+/// The region is calculated from the [ClimbableArea] of its entity and the [GlobalTransform] of its
+/// level, and kept up to date by the plugin when the level moves.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
+pub struct ClimbableRegion {
+    /// The x of the center of the strip's column.
+    pub x: f32,
+    pub half_width: f32,
+    pub y_min: f32,
+    pub y_max: f32,
+}
+
+impl ClimbableRegion {
+    /// Calculates the region of a [ClimbableArea] in world space, for the given [GlobalTransform]
+    /// of its level.
+    pub fn from_area(area: &ClimbableArea, level_transform: &GlobalTransform) -> Self {
+        let bottom_left = level_transform.mul_vec3(area.min.extend(0.));
+        let top_right = level_transform.mul_vec3(area.max.extend(0.));
+
+        ClimbableRegion {
+            x: (bottom_left.x + top_right.x) / 2.,
+            half_width: (top_right.x - bottom_left.x).abs() / 2.,
+            y_min: bottom_left.y.min(top_right.y),
+            y_max: bottom_left.y.max(top_right.y),
+        }
+    }
+
+    /// Returns whether the given point is within the region.
+    pub fn contains(&self, point: Vec2) -> bool {
+        (point.x - self.x).abs() <= self.half_width
+            && point.y >= self.y_min
+            && point.y <= self.y_max
+    }
+
+    /// Returns whether the region overlaps the rectangle with the given corners.
+    pub fn overlaps(&self, min: Vec2, max: Vec2) -> bool {
+        min.x <= self.x + self.half_width
+            && max.x >= self.x - self.half_width
+            && min.y <= self.y_max
+            && max.y >= self.y_min
+    }
+}
+
+/// [Component] for a climbable strip, in the space of its level.
+///
+/// Inserted alongside [ClimbableRegion]s, which are recalculated from it.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
+pub struct ClimbableArea {
+    /// The bottom-left corner of the strip.
+    pub min: Vec2,
+    /// The top-right corner of the strip.
+    pub max: Vec2,
+}
+
+fn tile_ids_with_enum_tags(
+    tileset_definition: &TilesetDefinition,
+    tags: &[String],
+) -> HashSet<i32> {
+    tileset_definition
+        .enum_tags
+        .iter()
+        .filter(|enum_tag| {
+            enum_tag
+                .get("enumValueId")
+                .and_then(|v| v.as_ref())
+                .and_then(|v| v.as_str())
+                .map_or(false, |v| tags.iter().any(|t| t == v))
+        })
+        .filter_map(|enum_tag| enum_tag.get("tileIds")?.as_ref()?.as_array())
+        .flatten()
+        .filter_map(|t| t.as_i64())
+        .map(|t| t as i32)
+        .collect()
+}
+
+fn layer_climbable_cells(
+    layer_instance: &LayerInstance,
+    climbable_settings: &ClimbableSettings,
+    tileset_definitions: &HashMap<i32, &TilesetDefinition>,
+) -> Vec<IVec2> {
+    let to_grid_coords =
+        |ldtk_coords: IVec2| IVec2::new(ldtk_coords.x, layer_instance.c_hei - 1 - ldtk_coords.y);
+
+    let mut cells: Vec<IVec2> = layer_instance
+        .int_grid_csv
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| climbable_settings.int_grid_values.contains(v))
+        .map(|(i, _)| {
+            to_grid_coords(IVec2::new(
+                i as i32 % layer_instance.c_wid,
+                i as i32 / layer_instance.c_wid,
+            ))
+        })
+        .collect();
+
+    let tileset_uid = layer_instance
+        .override_tileset_uid
+        .or(layer_instance.tileset_def_uid);
+
+    if let Some(tileset_definition) = tileset_uid.and_then(|u| tileset_definitions.get(&u)) {
+        let tagged_ids = tile_ids_with_enum_tags(tileset_definition, &climbable_settings.tile_tags);
+
+        cells.extend(
+            layer_instance
+                .grid_tiles
+                .iter()
+                .chain(layer_instance.auto_layer_tiles.iter())
+                .filter(|tile| tagged_ids.contains(&tile.t))
+                .map(|tile| to_grid_coords(tile.px / layer_instance.grid_size)),
+        );
+    }
+
+    cells
+}
+
+/// Spawns the [ClimbableRegion]s of newly spawned levels as their children.
+///
+/// Regions are spawned on [LevelEvent::Transformed], when the [GlobalTransform] of the level is
+/// up to date.
+/// Does nothing unless the [ClimbableSettings] resource is inserted.
+pub fn spawn_climbable_regions(
+    mut commands: Commands,
+    climbable_settings: Option<Res<ClimbableSettings>>,
+    mut level_events: EventReader<LevelEvent>,
+    level_query: Query<(Entity, &Handle<LdtkLevel>, &GlobalTransform, &Parent)>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+) {
+    let climbable_settings = match climbable_settings {
+        Some(climbable_settings) => climbable_settings,
+        None => return,
+    };
+
+    let transformed_uids: HashSet<i32> = level_events
+        .iter()
+        .filter_map(|e| match e {
+            LevelEvent::Transformed(uid) => Some(*uid),
+            _ => None,
+        })
+        .collect();
+
+    if transformed_uids.is_empty() {
+        return;
+    }
+
+    for (level_entity, level_handle, level_transform, parent) in level_query.iter() {
+        let level = match level_assets.get(level_handle) {
+            Some(ldtk_level) if transformed_uids.contains(&ldtk_level.level.uid) => {
+                &ldtk_level.level
+            }
+            _ => continue,
+        };
+
+        let tileset_definitions: HashMap<i32, &TilesetDefinition> = ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|ldtk_handle| ldtk_assets.get(ldtk_handle))
+            .map(|ldtk_asset| {
+                ldtk_asset
+                    .project
+                    .defs
+                    .tilesets
+                    .iter()
+                    .map(|t| (t.uid, t))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for layer_instance in level.layer_instances.iter().flatten() {
+            if layer_instance.layer_instance_type == Type::Entities {
+                continue;
+            }
+
+            let cells =
+                layer_climbable_cells(layer_instance, &climbable_settings, &tileset_definitions);
+
+            let grid_size = layer_instance.grid_size as f32;
+            let layer_offset = Vec2::new(
+                layer_instance.px_total_offset_x as f32,
+                -layer_instance.px_total_offset_y as f32,
+            );
+
+            commands.entity(level_entity).with_children(|commands| {
+                for strip in find_climbable_strips(cells) {
+                    let area = ClimbableArea {
+                        min: layer_offset
+                            + Vec2::new(strip.column as f32, strip.bottom as f32) * grid_size,
+                        max: layer_offset
+                            + Vec2::new(strip.column as f32 + 1., strip.top as f32) * grid_size,
+                    };
+
+                    commands
+                        .spawn()
+                        .insert(ClimbableRegion::from_area(&area, level_transform))
+                        .insert(area)
+                        .insert(Transform::default())
+                        .insert(GlobalTransform::default());
+                }
+            });
+        }
+    }
+}
+
+/// Recalculates the [ClimbableRegion]s of levels that have moved since they spawned.
+pub fn update_climbable_regions(
+    mut region_query: Query<
+        (&ClimbableArea, &GlobalTransform, &mut ClimbableRegion),
+        Changed<GlobalTransform>,
+    >,
+) {
+    for (area, level_transform, mut region) in region_query.iter_mut() {
+        let new_region = ClimbableRegion::from_area(area, level_transform);
+        if *region != new_region {
+            *region = new_region;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_climbable_region_from_area() {
+        let area = ClimbableArea {
+            min: Vec2::new(16., 0.),
+            max: Vec2::new(32., 64.),
+        };
+
+        let region = ClimbableRegion::from_area(&area, &GlobalTransform::from_xyz(100., 10., 0.));
+        assert_eq!(
+            region,
+            ClimbableRegion {
+                x: 124.,
+                half_width: 8.,
+                y_min: 10.,
+                y_max: 74.,
+            }
+        );
+        assert!(region.contains(Vec2::new(120., 50.)));
+        assert!(!region.contains(Vec2::new(24., 50.)));
+    }
+
+    #[test]
+    fn test_find_climbable_strips() {
+        let cells = vec![
+            IVec2::new(2, 3),
+            IVec2::new(0, 0),
+            IVec2::new(2, 1),
+            IVec2::new(2, 2),
+            IVec2::new(0, 1),
+            IVec2::new(2, 5),
+        ];
+
+        assert_eq!(
+            find_climbable_strips(cells),
+            vec![
+                ClimbableStrip {
+                    column: 0,
+                    bottom: 0,
+                    top: 2
+                },
+                ClimbableStrip {
+                    column: 2,
+                    bottom: 1,
+                    top: 4
+                },
+                ClimbableStrip {
+                    column: 2,
+                    bottom: 5,
+                    top: 6
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_climbable_region_overlap() {
+        let climbable_region = ClimbableRegion {
+            x: 8.,
+            half_width: 8.,
+            y_min: 0.,
+            y_max: 48.,
+        };
+
+        assert!(climbable_region.contains(Vec2::new(4., 40.)));
+        assert!(!climbable_region.contains(Vec2::new(20., 40.)));
+        assert!(climbable_region.overlaps(Vec2::new(12., 44.), Vec2::new(28., 60.)));
+        assert!(!climbable_region.overlaps(Vec2::new(12., 50.), Vec2::new(28., 60.)));
+    }
+}
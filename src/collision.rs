@@ -0,0 +1,171 @@
+//! Physics-engine agnostic collision geometry generated from IntGrid layers.
+//!
+//! These functions only produce shapes in grid units, so they can be scaled by the layer's grid
+//! size and fed to the collider types of any physics engine.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Traces the outlines of the solid regions of an IntGrid layer.
+///
+/// Filling regions with one rectangle per cell (or even merged rectangles) leaves internal seams
+/// that character controllers can get snagged on.
+/// Edge or polyline colliders built from these outlines don't have that problem.
+///
+/// Cells are solid if `is_solid` returns true for their IntGrid value.
+/// Each outline is a closed loop of grid corners, in the same coordinate space as
+/// [TilePos](bevy_ecs_tilemap::TilePos): the origin is the bottom-left corner of the layer, and
+/// one unit is one cell.
+/// The last point connects back to the first one, and collinear points are removed.
+///
+/// Outer outlines wind counter-clockwise, while the outlines of holes wind clockwise, so the solid
+/// region is always on the left of each edge.
+pub fn trace_int_grid_outlines(
+    int_grid_csv: &[i32],
+    layer_width_in_tiles: i32,
+    layer_height_in_tiles: i32,
+    is_solid: impl Fn(i32) -> bool,
+) -> Vec<Vec<IVec2>> {
+    let solid = |x: i32, y: i32| -> bool {
+        x >= 0
+            && y >= 0
+            && x < layer_width_in_tiles
+            && y < layer_height_in_tiles
+            && int_grid_csv
+                .get(((layer_height_in_tiles - 1 - y) * layer_width_in_tiles + x) as usize)
+                .map_or(false, |v| is_solid(*v))
+    };
+
+    let mut edges: HashMap<IVec2, Vec<IVec2>> = HashMap::new();
+    let mut add_edge = |from: IVec2, to: IVec2| edges.entry(from).or_default().push(to);
+
+    for y in 0..layer_height_in_tiles {
+        for x in 0..layer_width_in_tiles {
+            if !solid(x, y) {
+                continue;
+            }
+
+            if !solid(x, y - 1) {
+                add_edge(IVec2::new(x, y), IVec2::new(x + 1, y));
+            }
+            if !solid(x + 1, y) {
+                add_edge(IVec2::new(x + 1, y), IVec2::new(x + 1, y + 1));
+            }
+            if !solid(x, y + 1) {
+                add_edge(IVec2::new(x + 1, y + 1), IVec2::new(x, y + 1));
+            }
+            if !solid(x - 1, y) {
+                add_edge(IVec2::new(x, y + 1), IVec2::new(x, y));
+            }
+        }
+    }
+
+    let mut starts: Vec<IVec2> = edges.keys().copied().collect();
+    starts.sort_by_key(|v| (v.y, v.x));
+
+    let mut outlines = Vec::new();
+    for start in starts {
+        while let Some(mut current) = pop_edge(&mut edges, start) {
+            let mut outline = vec![start];
+
+            while current != start {
+                outline.push(current);
+                current = pop_edge(&mut edges, current)
+                    .expect("every corner of an outline should have an outgoing edge");
+            }
+
+            outlines.push(remove_collinear_points(outline));
+        }
+    }
+
+    outlines
+}
+
+fn pop_edge(edges: &mut HashMap<IVec2, Vec<IVec2>>, from: IVec2) -> Option<IVec2> {
+    edges.get_mut(&from).and_then(|ends| ends.pop())
+}
+
+fn remove_collinear_points(outline: Vec<IVec2>) -> Vec<IVec2> {
+    let len = outline.len();
+    (0..len)
+        .filter(|i| {
+            let previous = outline[(i + len - 1) % len];
+            let current = outline[*i];
+            let next = outline[(i + 1) % len];
+            let (a, b) = (current - previous, next - current);
+            a.x * b.y - a.y * b.x != 0
+        })
+        .map(|i| outline[i])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_square() {
+        let int_grid_csv = vec![
+            0, 0, 0, 0, //
+            0, 1, 1, 0, //
+            0, 1, 1, 0, //
+            0, 0, 0, 0,
+        ];
+
+        let outlines = trace_int_grid_outlines(&int_grid_csv, 4, 4, |v| v == 1);
+
+        assert_eq!(
+            outlines,
+            vec![vec![
+                IVec2::new(1, 1),
+                IVec2::new(3, 1),
+                IVec2::new(3, 3),
+                IVec2::new(1, 3),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_trace_ring_with_hole() {
+        let int_grid_csv = vec![
+            1, 1, 1, //
+            1, 0, 1, //
+            1, 1, 1,
+        ];
+
+        let outlines = trace_int_grid_outlines(&int_grid_csv, 3, 3, |v| v == 1);
+
+        assert_eq!(outlines.len(), 2);
+        assert!(outlines.iter().all(|o| o.len() == 4));
+        assert!(outlines.contains(&vec![
+            IVec2::new(0, 0),
+            IVec2::new(3, 0),
+            IVec2::new(3, 3),
+            IVec2::new(0, 3),
+        ]));
+    }
+
+    #[test]
+    fn test_trace_l_shape_uses_predicate() {
+        let int_grid_csv = vec![
+            2, 0, //
+            2, 1,
+        ];
+
+        let outlines = trace_int_grid_outlines(&int_grid_csv, 2, 2, |v| v != 0);
+
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].len(), 6);
+
+        let solid_twos = trace_int_grid_outlines(&int_grid_csv, 2, 2, |v| v == 2);
+        assert_eq!(
+            solid_twos,
+            vec![vec![
+                IVec2::new(0, 0),
+                IVec2::new(1, 0),
+                IVec2::new(1, 2),
+                IVec2::new(0, 2),
+            ]]
+        );
+    }
+}
@@ -1,9 +1,13 @@
 //! [Component]s and [Bundle]s used by the plugin.
 
 pub use crate::ldtk::EntityInstance;
-use bevy::prelude::*;
+use bevy::{asset::Asset, ecs::system::EntityCommands, prelude::*};
 
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
 
 #[allow(unused_imports)]
 use crate::{
@@ -12,11 +16,16 @@ use crate::{
     resources::{LdtkSettings, LevelSelection},
 };
 
+use crate::merged_shapes::GridRect;
+
 #[allow(unused_imports)]
 use bevy_ecs_tilemap::Map;
+use bevy_ecs_tilemap::TilePos;
 
 /// [Component] added to any `IntGrid` tile by default.
 ///
+/// IntGrid tiles are children of an [IntGridChunk] entity on their layer.
+///
 /// When loading levels, you can flesh out `IntGrid` entities in your own system by querying for
 /// `Added<IntGridCell>`.
 /// Or, you can hook into the entity's spawning process using [LdtkIntCell].
@@ -25,6 +34,222 @@ pub struct IntGridCell {
     pub value: i32,
 }
 
+/// [Component] storing the grid coordinates of IntGrid tiles, inserted on them by default.
+///
+/// It can also be added to [LdtkEntity]s with the `#[grid_coords]` attribute.
+/// See [LdtkEntity#grid_coords] for more details.
+///
+/// Unlike LDtk's grid coordinates, these use the same coordinate space as [TilePos]: the origin is
+/// the bottom-left tile of the layer, and y increases upwards.
+/// See the [utils](crate::utils) module for conversions to and from translations and LDtk's
+/// coordinates.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct GridCoords {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl GridCoords {
+    pub fn new(x: i32, y: i32) -> GridCoords {
+        GridCoords { x, y }
+    }
+}
+
+impl From<IVec2> for GridCoords {
+    fn from(i_vec_2: IVec2) -> Self {
+        GridCoords::new(i_vec_2.x, i_vec_2.y)
+    }
+}
+
+impl From<GridCoords> for IVec2 {
+    fn from(grid_coords: GridCoords) -> Self {
+        IVec2::new(grid_coords.x, grid_coords.y)
+    }
+}
+
+impl From<TilePos> for GridCoords {
+    fn from(tile_pos: TilePos) -> Self {
+        GridCoords::new(tile_pos.0 as i32, tile_pos.1 as i32)
+    }
+}
+
+/// [Component] storing the pixel coordinates of the pivot of LDtk entities, inserted on them by
+/// default, along with their [GridCoords].
+///
+/// These are the exact coordinates the entity was placed at in LDtk, while its [GridCoords] are
+/// those of the cell containing its pivot.
+/// Like [GridCoords], the origin is the bottom-left corner of the level, and y increases upwards,
+/// so they're the same as the translation of the entity's pivot relative to the level.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct PixelCoords {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl PixelCoords {
+    pub fn new(x: i32, y: i32) -> PixelCoords {
+        PixelCoords { x, y }
+    }
+}
+
+impl From<IVec2> for PixelCoords {
+    fn from(i_vec_2: IVec2) -> Self {
+        PixelCoords::new(i_vec_2.x, i_vec_2.y)
+    }
+}
+
+impl From<PixelCoords> for IVec2 {
+    fn from(pixel_coords: PixelCoords) -> Self {
+        IVec2::new(pixel_coords.x, pixel_coords.y)
+    }
+}
+
+/// [Component] added to the entities that [IntGridCell]s are parented to, grouping the cells of an
+/// IntGrid layer into square regions.
+///
+/// These chunks are children of the layer and follow the size of the tilemap chunks
+/// ([CHUNK_SIZE](crate::CHUNK_SIZE) by default, see
+/// [LayerMeshSettings](crate::LayerMeshSettings)).
+/// This allows you to despawn or disable a region of a large IntGrid layer, like a collision
+/// layer, by operating on one entity per chunk rather than one entity per cell.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct IntGridChunk {
+    /// Position of the chunk in chunk coordinates, starting from the bottom-left of the layer.
+    pub position: UVec2,
+}
+
+/// [Component] added to IntGrid layer entities, associating the positions of their
+/// [IntGridChunk]s with the chunk entities.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct IntGridChunks {
+    pub chunks: HashMap<UVec2, Entity>,
+}
+
+/// [Component] marking entities that the distance-based chunk activation is centered on, like the
+/// player.
+///
+/// See [ChunkActivationSettings](crate::resources::ChunkActivationSettings) for more details.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct ChunkActivationTarget;
+
+/// [Component] marking entities that levels are streamed around, like the camera or the player.
+///
+/// See [LevelStreamingSettings](crate::resources::LevelStreamingSettings) for more details.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LevelStreamingTarget;
+
+/// [Component] added to tilemap chunks and [IntGridChunk]s that are out of range of every
+/// [ChunkActivationTarget].
+///
+/// Inactive tilemap chunks are hidden, and so are the IntGrid cells of inactive [IntGridChunk]s
+/// that have a [Visibility], like the sprites of registered bundles, which are shown again when
+/// their chunk is reactivated.
+/// The cells are still there though, so systems working with them can skip the children of
+/// inactive [IntGridChunk]s.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct InactiveChunk;
+
+/// [Component] marking level entities that have been triggered to spawn, but haven't spawned yet.
+///
+/// The plugin spawns the contents of these levels as soon as their [LdtkLevel] asset is
+/// available, and removes this component when it does.
+/// Despawning a pending level entity cancels its spawning, so levels that leave the [LevelSet]
+/// before they finish spawning don't leave orphaned entities behind.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct PendingLevelSpawn {
+    pub level_uid: i32,
+}
+
+/// [Component] marking spawned levels that have left the [LevelSet], but are kept until the levels
+/// replacing them have spawned, with [LevelSwap::DoubleBuffered](crate::LevelSwap).
+///
+/// These levels are despawned together once no level of their world is pending anymore.
+/// If they're added back to the [LevelSet] before then, they're kept and this component is
+/// removed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct SwappedOutLevel {
+    pub level_uid: i32,
+}
+
+/// [Component] that causes a level or world entity to despawn and spawn again from its LDtk data,
+/// like when restarting a level.
+///
+/// Insert it on a level entity to respawn that level, or on an [LdtkWorldBundle] entity to respawn
+/// all of its levels.
+/// The component is removed once the respawn has been triggered.
+///
+/// Respawning a level replaces its entity with a new one, while respawning a world keeps the world
+/// entity.
+/// [Worldly] entities are only respawned when their world is.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// fn restart_levels(mut commands: Commands, level_query: Query<Entity, With<Handle<LdtkLevel>>>) {
+///     for level_entity in level_query.iter() {
+///         commands.entity(level_entity).insert(Respawn);
+///     }
+/// }
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct Respawn;
+
+/// [Component] storing the iid of a level, inserted on level entities when they're triggered to
+/// spawn.
+///
+/// This allows finding the entity of a particular level without accessing its [LdtkLevel] asset:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// fn find_boss_room(level_query: Query<(Entity, &LevelIid)>) {
+///     for (level_entity, level_iid) in level_query.iter() {
+///         if level_iid.0 == "a2c0e1b0-3b70-11ec-b7b4-7d4b5dc4e1a8" {
+///             println!("the boss room is {:?}", level_entity);
+///         }
+///     }
+/// }
+/// ```
+///
+/// Not inserted for files saved before LDtk introduced iids.
+/// In that case, [LevelIdentifier] can be used instead.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LevelIid(pub String);
+
+/// [Component] storing the identifier of a level, inserted on level entities when they're triggered
+/// to spawn.
+///
+/// Identifiers are readable, like `Level_3`, but change when levels are renamed in LDtk.
+/// Prefer [LevelIid] to reference levels persistently.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LevelIdentifier(pub String);
+
+/// [Component] added to levels whose tiles are fading in after spawning.
+///
+/// See [LdtkSettings::level_fade_in_frames] for more details.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LevelFadeIn {
+    /// Number of frames elapsed since the level spawned.
+    pub elapsed_frames: u32,
+}
+
+/// [Component] marking the background sprites of levels, spawned as their children when
+/// [LdtkSettings::level_background_color] or [LdtkSettings::level_background_image] is enabled.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LevelBackground;
+
+/// [Component] added to the background image sprites of levels, describing the part of the image
+/// they show.
+///
+/// Cropping the image requires its size, so the texture atlas of the sprite is only added by the
+/// plugin once the image has loaded.
+#[derive(Clone, PartialEq, Debug, Default, Component)]
+pub struct LevelBackgroundImage {
+    pub image: Handle<Image>,
+    /// Top-left corner of the cropped part of the image, in pixels.
+    pub crop_position: Vec2,
+    /// Size of the cropped part of the image, in pixels.
+    pub crop_size: Vec2,
+}
+
 /// [Component] that determines the desired levels to be loaded for an [LdtkWorldBundle].
 ///
 /// There is an abstraction for this in the form of the [LevelSelection] resource.
@@ -34,11 +259,355 @@ pub struct IntGridCell {
 /// If not, [LevelSet] allows you to have more direct control over the levels you spawn.
 ///
 /// Changes to this component are idempotent, so levels won't be respawned greedily.
+/// Only the levels added to or removed from the set are spawned or despawned.
+///
+/// Levels can also be added and removed by iid or identifier with
+/// [LevelSet::insert_selection] and [LevelSet::remove_selection].
 #[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct LevelSet {
     pub uids: HashSet<i32>,
 }
 
+impl LevelSet {
+    /// Adds the level matching `level_selection` to the set, so it can be referred to by its iid
+    /// or identifier rather than its uid.
+    ///
+    /// Returns `false` if no level of the `ldtk_asset` matches the selection.
+    pub fn insert_selection(
+        &mut self,
+        level_selection: &LevelSelection,
+        ldtk_asset: &crate::assets::LdtkAsset,
+    ) -> bool {
+        match ldtk_asset.get_level(level_selection) {
+            Some(level) => {
+                self.uids.insert(level.uid);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the level matching `level_selection` from the set.
+    ///
+    /// Returns `false` if the level wasn't in the set.
+    pub fn remove_selection(
+        &mut self,
+        level_selection: &LevelSelection,
+        ldtk_asset: &crate::assets::LdtkAsset,
+    ) -> bool {
+        match ldtk_asset.get_level(level_selection) {
+            Some(level) => self.uids.remove(&level.uid),
+            None => false,
+        }
+    }
+}
+
+/// [Component] selecting a level to add to the [LevelSet] of an [LdtkWorldBundle] once its project
+/// is loaded.
+///
+/// Unlike the uids of a [LevelSet], this can refer to the level by iid, identifier or index before
+/// the project is available.
+/// The selection is cleared once it has been applied, or once the project turns out not to have a
+/// matching level, in which case a warning is logged.
+/// The [LevelSelection] resource still overrides the [LevelSet] when it changes.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct InitialLevelSelection(pub Option<LevelSelection>);
+
+/// [Component] added to the tilemap layer entities of spawned levels, identifying the LDtk layer
+/// they were spawned from.
+///
+/// The iid and definition uid of a layer don't change when it's renamed in LDtk, so tools syncing
+/// with the LDtk file should match layers with them rather than with the identifier.
+/// The definition can be looked up with
+/// [LdtkAsset::get_layer_definition](crate::assets::LdtkAsset::get_layer_definition).
+///
+/// A single LDtk layer may be spawned as several tilemap layers, which all have the same metadata.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LayerMetadata {
+    pub identifier: String,
+    /// Empty for files saved before LDtk introduced layer iids.
+    pub iid: String,
+    pub layer_def_uid: i32,
+}
+
+/// [Component] storing the opacity of a tilemap layer, from 0 to 1, inserted on the tilemap layer
+/// entities of spawned levels with the opacity the layer has in LDtk.
+///
+/// Changing it updates the alpha of the layer's tiles, so it can be animated at runtime.
+/// IntGrid layers without a tileset stay invisible regardless.
+#[derive(Copy, Clone, PartialEq, Debug, Component)]
+pub struct LayerOpacity(pub f32);
+
+impl Default for LayerOpacity {
+    fn default() -> Self {
+        LayerOpacity(1.)
+    }
+}
+
+/// [Component] storing the slot of a tilemap layer or LDtk entity in [LdtkSettings::layer_z],
+/// inserted on the tilemap layers and the entities of spawned levels.
+///
+/// When [LdtkSettings::layer_z] changes, the z translations of spawned layers and entities are
+/// updated from their slot, without respawning.
+/// Entities that were reparented away from their level, like [Worldly] entities, keep their z.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LayerZSlot {
+    pub layer_identifier: String,
+    /// The id of the tilemap layer, or of the layer spawned next for entities, which share the z
+    /// of the layer drawn above them.
+    pub layer_id: u32,
+}
+
+/// [Component] adjusting the z translation of an entity by its y translation, so that entities
+/// further down are drawn in front, like in top-down games.
+///
+/// Inserted on the entities of Entities layers when [LdtkSettings::y_sort_entities] is enabled.
+/// The z is updated whenever the [Transform] or this component changes, so moving entities stay
+/// sorted, and it can be inserted on other entities, like a player spawned in code, to sort them
+/// along with the level's entities.
+///
+/// The y translation is relative to the parent of the entity, which is the level for LDtk
+/// entities.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
+pub struct YSort {
+    /// The z of the entity at the top of the level.
+    pub base_z: f32,
+    /// How much the z increases from the top of the level to its bottom.
+    pub spread: f32,
+    /// The height of the level, in pixels.
+    pub height: f32,
+}
+
+impl YSort {
+    /// Returns the z of an entity at the given y translation.
+    ///
+    /// Entities above or below the level are sorted as if they were at its edge.
+    pub fn z(&self, y: f32) -> f32 {
+        let depth = if self.height > 0. {
+            (1. - y / self.height).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        self.base_z + self.spread * depth
+    }
+}
+
+/// [Component] indexing the entities of a level by their [GridCoords], inserted on levels when they
+/// start spawning.
+///
+/// Covers the LDtk entities spawned from the entity layers of the level, and is kept up to date as
+/// their [GridCoords] change, so interaction systems can look up what's on a cell or in an area
+/// without iterating over every entity:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::{prelude::*, merged_shapes::GridRect};
+/// fn explode(level_query: Query<&LevelEntityIndex>, mut commands: Commands) {
+///     let blast_radius = GridRect {
+///         min: IVec2::new(4, 2),
+///         max: IVec2::new(7, 5),
+///     };
+///
+///     for level_entity_index in level_query.iter() {
+///         for entity in level_entity_index.entities_in_rect(blast_radius) {
+///             commands.entity(entity).despawn_recursive();
+///         }
+///     }
+/// }
+/// ```
+/// The coordinates of entities are in the grid of their own layer, so layers with different grid
+/// sizes don't line up.
+/// [Worldly] entities aren't children of their level, so they aren't indexed.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct LevelEntityIndex {
+    cells: HashMap<GridCoords, Vec<Entity>>,
+    grid_coords: HashMap<Entity, GridCoords>,
+}
+
+impl LevelEntityIndex {
+    /// Returns the entities on the given cell, in the order they were indexed.
+    pub fn entities_at(&self, grid_coords: GridCoords) -> &[Entity] {
+        self.cells
+            .get(&grid_coords)
+            .map(|entities| entities.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns the entities on the cells of `rect`.
+    pub fn entities_in_rect(&self, rect: GridRect) -> Vec<Entity> {
+        let area = rect.size().x.max(0) as usize * rect.size().y.max(0) as usize;
+
+        // Small rectangles are looked up cell by cell, large ones by scanning every entity
+        if area <= self.grid_coords.len() {
+            rect.cells()
+                .flat_map(|cell| self.entities_at(cell.into()))
+                .copied()
+                .collect()
+        } else {
+            self.grid_coords
+                .iter()
+                .filter(|(_, grid_coords)| rect.contains(IVec2::from(**grid_coords)))
+                .map(|(entity, _)| *entity)
+                .collect()
+        }
+    }
+
+    /// Returns the coordinates an entity is indexed at.
+    pub fn grid_coords(&self, entity: Entity) -> Option<GridCoords> {
+        self.grid_coords.get(&entity).copied()
+    }
+
+    /// Indexes an entity at the given coordinates, moving it if it's already indexed.
+    pub fn insert(&mut self, entity: Entity, grid_coords: GridCoords) {
+        if self.grid_coords(entity) != Some(grid_coords) {
+            self.remove(entity);
+            self.cells.entry(grid_coords).or_default().push(entity);
+            self.grid_coords.insert(entity, grid_coords);
+        }
+    }
+
+    /// Removes an entity from the index, returning the coordinates it was indexed at.
+    pub fn remove(&mut self, entity: Entity) -> Option<GridCoords> {
+        let grid_coords = self.grid_coords.remove(&entity)?;
+
+        if let Some(entities) = self.cells.get_mut(&grid_coords) {
+            entities.retain(|e| *e != entity);
+            if entities.is_empty() {
+                self.cells.remove(&grid_coords);
+            }
+        }
+
+        Some(grid_coords)
+    }
+
+    /// Removes the entities for which `f` returns `false` from the index.
+    pub fn retain(&mut self, mut f: impl FnMut(Entity) -> bool) {
+        let removed: Vec<Entity> = self
+            .grid_coords
+            .keys()
+            .copied()
+            .filter(|entity| !f(*entity))
+            .collect();
+
+        for entity in removed {
+            self.remove(entity);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.grid_coords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.grid_coords.is_empty()
+    }
+}
+
+/// [Component] added to spawned levels, associating the ids of their tilemap layers with the uid
+/// of the tileset each layer was spawned with.
+///
+/// Layers without a tileset, like IntGrid layers without AutoTile functionality, are absent.
+///
+/// Inserted once the level has spawned completely, replacing the previous one when the level
+/// respawns, so `Changed<LayerTilesets>` finds the levels that have just finished spawning.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct LayerTilesets {
+    pub tileset_uids: HashMap<u16, i32>,
+}
+
+impl LayerTilesets {
+    /// Returns the ids of the layers spawned with one of the given tilesets.
+    pub fn layers_using<'a>(
+        &'a self,
+        tileset_uids: &'a HashSet<i32>,
+    ) -> impl Iterator<Item = u16> + 'a {
+        self.tileset_uids
+            .iter()
+            .filter(move |(_, tileset_uid)| tileset_uids.contains(tileset_uid))
+            .map(|(layer_id, _)| *layer_id)
+    }
+}
+
+/// [Component] for re-skinning the levels of an [LdtkWorldBundle] with alternate tilesets.
+///
+/// Maps the uid of a tileset to the uid of another tileset in the same project.
+/// Layers and entity tiles using the former are spawned with the latter instead, so the
+/// alternate tileset should share the layout of the original one.
+///
+/// This is read when levels spawn, so changes only affect levels spawned afterwards.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct TilesetRemap {
+    pub tileset_uids: HashMap<i32, i32>,
+}
+
+impl TilesetRemap {
+    /// Returns the uid of the tileset used in place of the given one.
+    pub fn target(&self, tileset_uid: i32) -> i32 {
+        *self.tileset_uids.get(&tileset_uid).unwrap_or(&tileset_uid)
+    }
+}
+
+/// [Component] overriding the directory that the tilesets and external levels of an
+/// [LdtkWorldBundle] are loaded from.
+///
+/// By default, the paths in an LDtk project are relative to the project file.
+/// With an override, they are relative to the given directory instead, relative to the assets
+/// folder.
+/// This allows a world to use a copy of the project's files located elsewhere, like in a mod or
+/// user-generated content folder, while sharing the same [LdtkAsset](crate::assets::LdtkAsset).
+///
+/// This is read when levels spawn, so changes only affect levels spawned afterwards.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LdtkAssetRoot(pub Option<String>);
+
+impl LdtkAssetRoot {
+    /// Resolves a path of the LDtk project against the override, if there is one.
+    pub fn resolve(&self, rel_path: &str) -> Option<String> {
+        self.0
+            .as_ref()
+            .map(|root| format!("{}/{}", root.trim_end_matches('/'), rel_path))
+    }
+}
+
+/// [Component] for showing the levels of an [LdtkWorldBundle] that aren't spawned as baked
+/// images.
+///
+/// Every level of the world that isn't spawned gets a sprite child of the world, positioned
+/// where the level would spawn.
+/// Without [LdtkSettings::use_level_world_translations], all levels spawn at the origin of the
+/// world, so the images overlap there too.
+/// The images are drawn at [LEVEL_LOD_Z](crate::systems::LEVEL_LOD_Z), below spawned levels.
+///
+/// When a level enters the [LevelSet], its image is kept until the level has spawned, so there's
+/// no gap while it's pending, and is shown again once the level despawns.
+/// Combined with [LdtkSettings::load_level_neighbors], this gives a view of the whole world at
+/// a low cost, with only the levels around the player spawned in full.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct LevelLod {
+    /// Directory of pre-baked level images, relative to the assets folder, with one image per
+    /// level at `<image_directory>/<level identifier>.png`.
+    /// This matches the layout of LDtk's PNG export of levels.
+    ///
+    /// If [None], the images are baked from the tiles of the levels with
+    /// [bake_level_image](crate::utils::bake_level_image) once their tilesets are loaded.
+    pub image_directory: Option<String>,
+}
+
+/// [Component] added to the baked image sprites spawned for a [LevelLod].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LevelLodImage {
+    pub level_uid: i32,
+}
+
+/// [Component] marking the children of an [LdtkWorldBundle] that the plugin spawned or adopted,
+/// like its levels, [Worldly] entities and [LevelLodImage]s.
+///
+/// When a world respawns or its [LdtkAsset](crate::assets::LdtkAsset) changes, only these children
+/// are despawned, so other children of the world, like camera rigs or audio emitters, are left
+/// intact.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct SpawnedByLdtk;
+
 /// [Component] that indicates that an ldtk entity should be a child of the world, not the level.
 ///
 /// By default, [LdtkEntity]s are children of the level they spawn in.
@@ -62,6 +631,122 @@ pub struct Worldly {
     pub spawn_px: IVec2,
 }
 
+/// [Component] storing the iid of an LDtk entity, inserted on spawned LDtk entities by default.
+///
+/// Unlike [Entity]s, iids are stable across level respawns and app runs, which makes them suitable
+/// for entity references and save files.
+/// The spawned entity of an iid can be looked up with the
+/// [EntityIidMap](crate::resources::EntityIidMap) resource.
+///
+/// Not inserted for files saved before LDtk introduced iids.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct EntityIid(pub String);
+
+/// [Component] storing the handles of the assets loaded from the `FilePath` fields of an LDtk
+/// entity or level, by field identifier.
+///
+/// Inserted for the fields registered with
+/// [RegisterLdtkObjects::register_ldtk_file_path_field](crate::app::RegisterLdtkObjects), or
+/// its level equivalent.
+/// There's one component per asset type, so fields of different types can be registered for the
+/// same entity.
+///
+/// Like other asset paths in LDtk entity fields, the paths are loaded relative to the assets
+/// folder.
+#[derive(Clone, PartialEq, Debug, Component)]
+pub struct LdtkFileHandles<A: Asset> {
+    pub handles: HashMap<String, Handle<A>>,
+}
+
+impl<A: Asset> LdtkFileHandles<A> {
+    /// The handle of the asset loaded from the field with the given identifier.
+    pub fn get(&self, field_identifier: &str) -> Option<&Handle<A>> {
+        self.handles.get(field_identifier)
+    }
+}
+
+/// [Component] mapping the `EntityRef` fields of an LDtk entity to the [Entity]s they reference.
+///
+/// Inserted by the plugin once every entity referenced by the fields has spawned, which may be
+/// later than the entity itself if they're in another level.
+/// It's removed again if a referenced entity despawns, and updated when it respawns.
+/// So, doors, switches, and their targets can be wired up by querying for `Added<EntityRefs>`:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::{prelude::*, EntityRefs};
+/// # #[derive(Component)]
+/// # struct Target(Entity);
+/// fn wire_switches(
+///     mut commands: Commands,
+///     switch_query: Query<(Entity, &EntityRefs), Added<EntityRefs>>,
+/// ) {
+///     for (entity, entity_refs) in switch_query.iter() {
+///         if let Some(door) = entity_refs.get("door") {
+///             commands.entity(entity).insert(Target(door));
+///         }
+///     }
+/// }
+/// ```
+///
+/// Only inserted on entities with an [EntityInstance] component and `EntityRef` fields, and not
+/// for files saved before LDtk introduced iids.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct EntityRefs {
+    /// The referenced entities of each field identifier, with one entity per non-null reference,
+    /// in order, for array fields.
+    pub fields: HashMap<String, Vec<Entity>>,
+}
+
+impl EntityRefs {
+    /// The entity referenced by the field with the given identifier, or the first one for array
+    /// fields.
+    pub fn get(&self, field_identifier: &str) -> Option<Entity> {
+        self.get_all(field_identifier).first().copied()
+    }
+
+    /// All the entities referenced by the field with the given identifier.
+    pub fn get_all(&self, field_identifier: &str) -> &[Entity] {
+        self.fields
+            .get(field_identifier)
+            .map(|e| e.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// [Component] determining how an LDtk entity is despawned when its level leaves the [LevelSet].
+///
+/// Can be registered for an entity identifier with
+/// [RegisterLdtkObjects::register_ldtk_entity_despawn_policy](crate::app::RegisterLdtkObjects),
+/// or added to an entity directly.
+///
+/// Levels despawned for other reasons, like a [Respawn] or a modification of the LDtk asset, always
+/// despawn their entities immediately.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Component)]
+pub enum DespawnPolicy {
+    /// The entity is despawned along with its level.
+    Immediate,
+    /// The entity is moved out of its level before the level despawns, and receives a
+    /// [PendingDespawn] component along with a
+    /// [PendingDespawnEvent](crate::resources::PendingDespawnEvent).
+    ///
+    /// This gives the game a chance to play exit animations.
+    /// The plugin despawns the entity once its [PendingDespawn] component is removed.
+    AfterEvent,
+}
+
+impl Default for DespawnPolicy {
+    fn default() -> Self {
+        DespawnPolicy::Immediate
+    }
+}
+
+/// [Component] marking LDtk entities whose level has despawned, but that wait for the game before
+/// being despawned themselves.
+///
+/// Remove this component to despawn the entity. See [DespawnPolicy::AfterEvent] for more details.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct PendingDespawn;
+
 #[derive(Clone, Default, Bundle)]
 pub(crate) struct IntGridCellBundle {
     pub int_grid_cell: IntGridCell,
@@ -72,18 +757,260 @@ pub(crate) struct EntityInstanceBundle {
     pub entity_instance: EntityInstance,
 }
 
+/// [Component] that tags everything spawned under an [LdtkWorldBundle] with a marker component of
+/// your choice.
+///
+/// This is useful for cleanup patterns where all entities with a given marker are despawned,
+/// like when leaving a game state:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// #[derive(Clone, Component)]
+/// struct InGameState;
+///
+/// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     commands
+///         .spawn_bundle(LdtkWorldBundle {
+///             ldtk_handle: asset_server.load("my_project.ldtk"),
+///             content_marker: ContentMarker::new(InGameState),
+///             ..Default::default()
+///         })
+///         .insert(InGameState);
+/// }
+/// ```
+///
+/// The marker is applied to the descendants of levels whenever they finish spawning, including
+/// the levels themselves, layers, LDtk entities, and IntGrid tiles, and to all descendants of the
+/// world entity whenever the marker changes.
+/// It is not applied to the world entity itself.
+#[derive(Clone, Default, Component)]
+pub struct ContentMarker {
+    insert_marker: Option<Arc<dyn Fn(&mut EntityCommands) + Send + Sync>>,
+}
+
+impl ContentMarker {
+    /// Creates a [ContentMarker] that inserts clones of the given component.
+    pub fn new<C: Component + Clone>(marker: C) -> ContentMarker {
+        ContentMarker {
+            insert_marker: Some(Arc::new(move |entity_commands: &mut EntityCommands| {
+                entity_commands.insert(marker.clone());
+            })),
+        }
+    }
+
+    /// Inserts the marker component on the given entity, if there is one.
+    pub fn apply(&self, entity_commands: &mut EntityCommands) {
+        if let Some(insert_marker) = &self.insert_marker {
+            insert_marker(entity_commands);
+        }
+    }
+}
+
+impl fmt::Debug for ContentMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentMarker")
+            .field("has_marker", &self.insert_marker.is_some())
+            .finish()
+    }
+}
+
 /// [Bundle] for spawning LDtk worlds and their levels. The main bundle for using this plugin.
 ///
 /// After the ldtk file is done loading, the levels you've chosen with [LevelSelection] or
 /// [LevelSet] will begin to spawn.
 /// Each level is its own entity, with the [LdtkWorldBundle] as its parent.
-/// Each level has `Handle<LdtkLevel>`, [Map], [Transform], and [GlobalTransform] components.
+/// Each level has `Handle<LdtkLevel>`, [Map], [LevelIdentifier], [LevelIid], [Transform], and
+/// [GlobalTransform] components.
 /// Finally, all tiles and entities in the level are spawned as children to the level unless marked
 /// by a [Worldly] component.
+///
+/// Tilesets can be swapped out for these levels with a [TilesetRemap], and the files of the
+/// project can be loaded from another directory with an [LdtkAssetRoot].
+///
+/// Optionally, a [ContentMarker] can be provided to tag all of this content with a component of
+/// your choice.
+///
+/// The constructors and builder methods cover the common cases in one expression:
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     commands.spawn_bundle(
+///         LdtkWorldBundle::from_path(&asset_server, "my_project.ldtk")
+///             .with_level_selection(LevelSelection::Identifier("Level_0".to_string()))
+///             .with_transform(Transform::from_xyz(-256., -128., 0.)),
+///     );
+/// }
+/// ```
 #[derive(Clone, Default, Bundle)]
 pub struct LdtkWorldBundle {
     pub ldtk_handle: Handle<crate::assets::LdtkAsset>,
     pub level_set: LevelSet,
+    pub initial_level_selection: InitialLevelSelection,
+    pub content_marker: ContentMarker,
+    pub tileset_remap: TilesetRemap,
+    pub asset_root: LdtkAssetRoot,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
 }
+
+impl LdtkWorldBundle {
+    /// Creates a bundle for the LDtk project of `ldtk_handle`, with defaults for everything else.
+    pub fn new(ldtk_handle: Handle<crate::assets::LdtkAsset>) -> Self {
+        LdtkWorldBundle {
+            ldtk_handle,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a bundle for the LDtk project at `path`, loading it with the `asset_server`.
+    pub fn from_path<'a>(
+        asset_server: &AssetServer,
+        path: impl Into<bevy::asset::AssetPath<'a>>,
+    ) -> Self {
+        LdtkWorldBundle::new(asset_server.load(path))
+    }
+
+    /// Adds the level matching `level_selection` to the [LevelSet] of the world once the project
+    /// is loaded.
+    ///
+    /// See [InitialLevelSelection].
+    pub fn with_level_selection(mut self, level_selection: LevelSelection) -> Self {
+        self.initial_level_selection = InitialLevelSelection(Some(level_selection));
+        self
+    }
+
+    pub fn with_level_set(mut self, level_set: LevelSet) -> Self {
+        self.level_set = level_set;
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_y_sort_z() {
+        let y_sort = YSort {
+            base_z: 2.,
+            spread: 0.5,
+            height: 100.,
+        };
+
+        assert_eq!(y_sort.z(100.), 2.);
+        assert_eq!(y_sort.z(50.), 2.25);
+        assert_eq!(y_sort.z(0.), 2.5);
+
+        // Lower entities are drawn in front
+        assert!(y_sort.z(10.) > y_sort.z(90.));
+
+        // Entities beyond the edges of the level are sorted as if they were on them
+        assert_eq!(y_sort.z(150.), 2.);
+        assert_eq!(y_sort.z(-20.), 2.5);
+
+        let flat_y_sort = YSort {
+            height: 0.,
+            ..y_sort
+        };
+        assert_eq!(flat_y_sort.z(0.), 2.);
+        assert_eq!(flat_y_sort.z(-20.), 2.);
+    }
+
+    #[test]
+    fn test_layer_tilesets_layers_using() {
+        let layer_tilesets = LayerTilesets {
+            tileset_uids: HashMap::from([(0, 1), (1, 2), (2, 1)]),
+        };
+
+        let mut layers: Vec<u16> = layer_tilesets.layers_using(&HashSet::from([1])).collect();
+        layers.sort_unstable();
+        assert_eq!(layers, vec![0, 2]);
+
+        assert_eq!(layer_tilesets.layers_using(&HashSet::new()).count(), 0);
+        assert_eq!(layer_tilesets.layers_using(&HashSet::from([3])).count(), 0);
+    }
+
+    #[test]
+    fn test_level_entity_index() {
+        let (a, b, c) = (
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+            Entity::from_raw(3),
+        );
+
+        let mut level_entity_index = LevelEntityIndex::default();
+        level_entity_index.insert(a, GridCoords::new(1, 1));
+        level_entity_index.insert(b, GridCoords::new(1, 1));
+        level_entity_index.insert(c, GridCoords::new(5, 5));
+
+        assert_eq!(level_entity_index.len(), 3);
+        assert_eq!(
+            level_entity_index.entities_at(GridCoords::new(1, 1)),
+            &[a, b]
+        );
+
+        // Moving
+        level_entity_index.insert(a, GridCoords::new(2, 1));
+        assert_eq!(level_entity_index.entities_at(GridCoords::new(1, 1)), &[b]);
+        assert_eq!(level_entity_index.entities_at(GridCoords::new(2, 1)), &[a]);
+        assert_eq!(
+            level_entity_index.grid_coords(a),
+            Some(GridCoords::new(2, 1))
+        );
+        assert_eq!(level_entity_index.len(), 3);
+
+        // Removing
+        assert_eq!(level_entity_index.remove(b), Some(GridCoords::new(1, 1)));
+        assert_eq!(level_entity_index.remove(b), None);
+        assert!(level_entity_index
+            .entities_at(GridCoords::new(1, 1))
+            .is_empty());
+
+        level_entity_index.retain(|entity| entity != c);
+        assert_eq!(level_entity_index.grid_coords(c), None);
+        assert_eq!(level_entity_index.len(), 1);
+    }
+
+    #[test]
+    fn test_level_entity_index_entities_in_rect() {
+        let (a, b, c) = (
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+            Entity::from_raw(3),
+        );
+
+        let mut level_entity_index = LevelEntityIndex::default();
+        level_entity_index.insert(a, GridCoords::new(1, 1));
+        level_entity_index.insert(b, GridCoords::new(1, 3));
+        level_entity_index.insert(c, GridCoords::new(8, 8));
+
+        let entities_in_rect = |min: IVec2, max: IVec2| {
+            let mut entities = level_entity_index.entities_in_rect(GridRect { min, max });
+            entities.sort();
+            entities
+        };
+
+        // Covers fewer cells than there are entities, so it's looked up cell by cell
+        assert_eq!(
+            entities_in_rect(IVec2::new(1, 1), IVec2::new(2, 4)),
+            vec![a, b]
+        );
+        assert_eq!(entities_in_rect(IVec2::new(1, 2), IVec2::new(2, 3)), vec![]);
+
+        // Covers more cells than there are entities, so every entity is checked
+        assert_eq!(
+            entities_in_rect(IVec2::new(0, 0), IVec2::new(8, 8)),
+            vec![a, b]
+        );
+        assert_eq!(
+            entities_in_rect(IVec2::new(0, 0), IVec2::new(9, 9)),
+            vec![a, b, c]
+        );
+    }
+}
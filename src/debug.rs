@@ -0,0 +1,299 @@
+//! Debug rendering for inspecting the collision geometry authored via IntGrid layers.
+//!
+//! *Requires the "debug" feature*
+//!
+//! When a level spawns, the plugin builds a wireframe mesh of the outlines traced by
+//! [trace_int_grid_outlines] for each of its IntGrid layers.
+//! These meshes are hidden until enabled via the [CollisionDebugSettings] resource, so the
+//! geometry can be inspected without a full physics debug renderer:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{debug::CollisionDebugSettings, prelude::*};
+//!
+//! fn toggle_collision_debug(
+//!     input: Res<Input<KeyCode>>,
+//!     mut settings: ResMut<CollisionDebugSettings>,
+//! ) {
+//!     if input.just_pressed(KeyCode::F1) {
+//!         settings.enabled = !settings.enabled;
+//!     }
+//! }
+//! ```
+
+use crate::{
+    assets::LdtkLevel,
+    collision::trace_int_grid_outlines,
+    ldtk::{LayerInstance, Level, Type},
+    resources::LevelEvent,
+};
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    sprite::MaterialMesh2dBundle,
+};
+use std::collections::HashSet;
+
+/// Resource for toggling and configuring the collision debug meshes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CollisionDebugSettings {
+    /// Whether the meshes are visible.
+    pub enabled: bool,
+    /// The IntGrid values considered solid.
+    ///
+    /// If empty, all non-zero values are considered solid.
+    pub solid_int_grid_values: Vec<i32>,
+    pub color: Color,
+}
+
+impl Default for CollisionDebugSettings {
+    fn default() -> Self {
+        CollisionDebugSettings {
+            enabled: false,
+            solid_int_grid_values: Vec::new(),
+            color: Color::LIME_GREEN,
+        }
+    }
+}
+
+impl CollisionDebugSettings {
+    fn is_solid(&self, value: i32) -> bool {
+        if self.solid_int_grid_values.is_empty() {
+            value != 0
+        } else {
+            self.solid_int_grid_values.contains(&value)
+        }
+    }
+}
+
+/// [Component] marking the collision debug meshes spawned by the plugin.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct CollisionDebugMesh;
+
+/// Z translation of the debug meshes relative to their level, so they're drawn above its layers.
+pub const COLLISION_DEBUG_MESH_Z: f32 = 999.;
+
+/// Builds a [PrimitiveTopology::LineList] mesh of closed outlines given in grid units, like those
+/// of [trace_int_grid_outlines].
+pub fn outlines_to_wireframe_mesh(outlines: &[Vec<IVec2>], grid_size: f32) -> Mesh {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    for outline in outlines {
+        let first_index = positions.len() as u32;
+        positions.extend(
+            outline
+                .iter()
+                .map(|p| (p.as_vec2() * grid_size).extend(0.).to_array()),
+        );
+
+        let len = outline.len() as u32;
+        for i in 0..len {
+            indices.push(first_index + i);
+            indices.push(first_index + (i + 1) % len);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; positions.len()]);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; positions.len()]);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+fn layer_wireframe_mesh(
+    layer_instance: &LayerInstance,
+    collision_debug_settings: &CollisionDebugSettings,
+) -> Mesh {
+    let outlines = trace_int_grid_outlines(
+        &layer_instance.int_grid_csv,
+        layer_instance.c_wid,
+        layer_instance.c_hei,
+        |value| collision_debug_settings.is_solid(value),
+    );
+
+    outlines_to_wireframe_mesh(&outlines, layer_instance.grid_size as f32)
+}
+
+fn spawn_level_collision_debug_meshes(
+    commands: &mut Commands,
+    level_entity: Entity,
+    level: &Level,
+    collision_debug_settings: &CollisionDebugSettings,
+    material: &Handle<ColorMaterial>,
+    meshes: &mut Assets<Mesh>,
+) {
+    for layer_instance in level.layer_instances.iter().flatten() {
+        if layer_instance.layer_instance_type != Type::IntGrid {
+            continue;
+        }
+
+        let mesh = meshes.add(layer_wireframe_mesh(
+            layer_instance,
+            collision_debug_settings,
+        ));
+
+        commands.entity(level_entity).with_children(|commands| {
+            commands
+                .spawn_bundle(MaterialMesh2dBundle {
+                    mesh: mesh.into(),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(
+                        layer_instance.px_total_offset_x as f32,
+                        -layer_instance.px_total_offset_y as f32,
+                        COLLISION_DEBUG_MESH_Z,
+                    ),
+                    visibility: Visibility {
+                        is_visible: collision_debug_settings.enabled,
+                    },
+                    ..Default::default()
+                })
+                .insert(CollisionDebugMesh);
+        });
+    }
+}
+
+/// Spawns the collision debug meshes of newly spawned levels as their children.
+pub fn spawn_collision_debug_meshes(
+    mut commands: Commands,
+    collision_debug_settings: Res<CollisionDebugSettings>,
+    mut level_events: EventReader<LevelEvent>,
+    level_query: Query<(Entity, &Handle<LdtkLevel>)>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let spawned_uids: HashSet<i32> = level_events
+        .iter()
+        .filter_map(|e| match e {
+            LevelEvent::Spawned(uid) => Some(*uid),
+            _ => None,
+        })
+        .collect();
+
+    if spawned_uids.is_empty() {
+        return;
+    }
+
+    let material = materials.add(ColorMaterial::from(collision_debug_settings.color));
+
+    for (level_entity, level_handle) in level_query.iter() {
+        let level = match level_assets.get(level_handle) {
+            Some(ldtk_level) if spawned_uids.contains(&ldtk_level.level.uid) => &ldtk_level.level,
+            _ => continue,
+        };
+
+        spawn_level_collision_debug_meshes(
+            &mut commands,
+            level_entity,
+            level,
+            &collision_debug_settings,
+            &material,
+            &mut meshes,
+        );
+    }
+}
+
+/// Applies changes of the [CollisionDebugSettings] to the existing collision debug meshes.
+///
+/// Visibility and color are updated in place, while changing
+/// [CollisionDebugSettings::solid_int_grid_values] rebuilds the meshes of every level with the
+/// same material.
+#[allow(clippy::too_many_arguments)]
+pub fn toggle_collision_debug_meshes(
+    mut commands: Commands,
+    collision_debug_settings: Res<CollisionDebugSettings>,
+    mut previous_settings: Local<Option<CollisionDebugSettings>>,
+    mut debug_mesh_query: Query<
+        (Entity, &mut Visibility, &Handle<ColorMaterial>),
+        With<CollisionDebugMesh>,
+    >,
+    level_query: Query<(Entity, &Handle<LdtkLevel>)>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !collision_debug_settings.is_changed() {
+        return;
+    }
+
+    let previous_settings = match previous_settings.replace(collision_debug_settings.clone()) {
+        Some(previous_settings) => previous_settings,
+        None => return,
+    };
+
+    if previous_settings.solid_int_grid_values != collision_debug_settings.solid_int_grid_values {
+        let material = match debug_mesh_query.iter().next() {
+            Some((_, _, material)) => material.clone(),
+            None => materials.add(ColorMaterial::from(collision_debug_settings.color)),
+        };
+
+        if previous_settings.color != collision_debug_settings.color {
+            if let Some(material) = materials.get_mut(&material) {
+                material.color = collision_debug_settings.color;
+            }
+        }
+
+        for (entity, _, _) in debug_mesh_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        for (level_entity, level_handle) in level_query.iter() {
+            if let Some(ldtk_level) = level_assets.get(level_handle) {
+                spawn_level_collision_debug_meshes(
+                    &mut commands,
+                    level_entity,
+                    &ldtk_level.level,
+                    &collision_debug_settings,
+                    &material,
+                    &mut meshes,
+                );
+            }
+        }
+
+        return;
+    }
+
+    for (_, mut visibility, material_handle) in debug_mesh_query.iter_mut() {
+        visibility.is_visible = collision_debug_settings.enabled;
+
+        if previous_settings.color != collision_debug_settings.color {
+            if let Some(material) = materials.get_mut(material_handle) {
+                material.color = collision_debug_settings.color;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::mesh::VertexAttributeValues;
+
+    #[test]
+    fn test_outlines_to_wireframe_mesh() {
+        let outlines = vec![vec![
+            IVec2::new(0, 0),
+            IVec2::new(2, 0),
+            IVec2::new(2, 1),
+            IVec2::new(0, 1),
+        ]];
+
+        let mesh = outlines_to_wireframe_mesh(&outlines, 16.);
+
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => assert_eq!(
+                positions,
+                &vec![[0., 0., 0.], [32., 0., 0.], [32., 16., 0.], [0., 16., 0.]]
+            ),
+            _ => panic!("mesh should have 3d positions"),
+        }
+
+        match mesh.indices() {
+            Some(Indices::U32(indices)) => {
+                assert_eq!(indices, &vec![0, 1, 1, 2, 2, 3, 3, 0])
+            }
+            _ => panic!("mesh should have u32 indices"),
+        }
+    }
+}
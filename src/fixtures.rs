@@ -0,0 +1,394 @@
+//! Builders for synthetic LDtk data, for testing custom tile makers and spawning hooks without
+//! real LDtk files.
+//!
+//! Coordinates given to these builders are grid coordinates in LDtk's y-down space, like the
+//! `grid` of an [EntityInstance], and the pixel coordinates LDtk stores are derived from them:
+//! ```
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{fixtures::*, ldtk::Type};
+//!
+//! let tileset_definition = tileset_definition(1, "Tiles", 4, 4, 16);
+//!
+//! let layer_instance = LayerInstanceBuilder::new("Ground", Type::Tiles, 2, 2, 16)
+//!     .tileset(&tileset_definition)
+//!     .tile(IVec2::new(0, 1), 5)
+//!     .flipped_tile(IVec2::new(1, 1), 5, true, false)
+//!     .build();
+//!
+//! assert_eq!(layer_instance.grid_tiles[0].px, IVec2::new(0, 16));
+//! assert_eq!(layer_instance.grid_tiles[0].src, IVec2::new(16, 16));
+//! ```
+
+use crate::ldtk::{
+    Definitions, EntityInstance, ImageExportMode, LayerInstance, LdtkJson, Level, NeighbourLevel,
+    TileInstance, TilesetDefinition, Type, WorldLayout,
+};
+use bevy::prelude::*;
+
+/// Creates a [TilesetDefinition] of `c_wid` by `c_hei` tiles, without spacing or padding.
+pub fn tileset_definition(
+    uid: i32,
+    identifier: &str,
+    c_wid: i32,
+    c_hei: i32,
+    tile_grid_size: i32,
+) -> TilesetDefinition {
+    TilesetDefinition {
+        uid,
+        identifier: identifier.to_string(),
+        c_wid,
+        c_hei,
+        tile_grid_size,
+        px_wid: c_wid * tile_grid_size,
+        px_hei: c_hei * tile_grid_size,
+        ..Default::default()
+    }
+}
+
+/// Creates an [LdtkJson] of a free layout world with the given levels, and the defaults of a new
+/// project in LDtk otherwise.
+pub fn ldtk_json(levels: Vec<Level>) -> LdtkJson {
+    LdtkJson {
+        backup_limit: 10,
+        backup_on_save: false,
+        bg_color: "#40465B".to_string(),
+        default_grid_size: 16,
+        default_level_bg_color: "#696A79".to_string(),
+        default_level_height: 256,
+        default_level_width: 256,
+        default_pivot_x: 0.,
+        default_pivot_y: 0.,
+        defs: Definitions::default(),
+        export_png: None,
+        export_tiled: false,
+        external_levels: false,
+        flags: Vec::new(),
+        image_export_mode: ImageExportMode::None,
+        json_version: "0.9.3".to_string(),
+        level_name_pattern: "Level_%idx".to_string(),
+        levels,
+        minify_json: false,
+        next_uid: 0,
+        png_file_pattern: None,
+        world_grid_height: 256,
+        world_grid_width: 256,
+        world_layout: WorldLayout::Free,
+    }
+}
+
+/// Creates the [TileInstance] of the tile `tile_id` of the tileset, placed at the given grid
+/// coordinates of a layer with the given `grid_size`.
+pub fn tile_instance(
+    grid_coords: IVec2,
+    grid_size: i32,
+    tile_id: i32,
+    tileset_definition: &TilesetDefinition,
+) -> TileInstance {
+    let stride = tileset_definition.tile_grid_size + tileset_definition.spacing;
+    let columns = tileset_definition.c_wid.max(1);
+
+    TileInstance {
+        px: grid_coords * grid_size,
+        src: IVec2::new(tile_id % columns, tile_id / columns) * stride
+            + IVec2::splat(tileset_definition.padding),
+        t: tile_id,
+        ..Default::default()
+    }
+}
+
+/// Builder for [LayerInstance]s.
+///
+/// Unspecified fields are empty, or have the defaults of a new layer in LDtk.
+#[derive(Clone, Debug)]
+pub struct LayerInstanceBuilder {
+    layer_instance: LayerInstance,
+    tileset_definition: Option<TilesetDefinition>,
+}
+
+impl LayerInstanceBuilder {
+    /// Starts building a layer of `c_wid` by `c_hei` cells of `grid_size` pixels.
+    pub fn new(
+        identifier: &str,
+        layer_instance_type: Type,
+        c_wid: i32,
+        c_hei: i32,
+        grid_size: i32,
+    ) -> Self {
+        LayerInstanceBuilder {
+            layer_instance: LayerInstance {
+                c_hei,
+                c_wid,
+                grid_size,
+                identifier: identifier.to_string(),
+                opacity: 1.,
+                px_total_offset_x: 0,
+                px_total_offset_y: 0,
+                tileset_def_uid: None,
+                tileset_rel_path: None,
+                layer_instance_type,
+                auto_layer_tiles: Vec::new(),
+                entity_instances: Vec::new(),
+                grid_tiles: Vec::new(),
+                iid: String::new(),
+                int_grid: None,
+                int_grid_csv: Vec::new(),
+                layer_def_uid: 0,
+                level_id: 0,
+                optional_rules: Vec::new(),
+                override_tileset_uid: None,
+                px_offset_x: 0,
+                px_offset_y: 0,
+                seed: 0,
+                visible: true,
+            },
+            tileset_definition: None,
+        }
+    }
+
+    /// Sets the tileset of the layer, which is used for the tiles added after this.
+    pub fn tileset(mut self, tileset_definition: &TilesetDefinition) -> Self {
+        self.layer_instance.tileset_def_uid = Some(tileset_definition.uid);
+        self.layer_instance.tileset_rel_path = Some(tileset_definition.rel_path.clone());
+        self.tileset_definition = Some(tileset_definition.clone());
+        self
+    }
+
+    /// Sets the pixel offset of the layer, in LDtk's y-down coordinates.
+    pub fn offset(mut self, offset: IVec2) -> Self {
+        self.layer_instance.px_offset_x = offset.x;
+        self.layer_instance.px_offset_y = offset.y;
+        self.layer_instance.px_total_offset_x = offset.x;
+        self.layer_instance.px_total_offset_y = offset.y;
+        self
+    }
+
+    /// Sets the IntGrid values of the layer, row by row from the top-left cell.
+    ///
+    /// # Panics
+    /// Panics if the number of values doesn't match the size of the layer.
+    pub fn int_grid_csv(mut self, int_grid_csv: Vec<i32>) -> Self {
+        assert_eq!(
+            int_grid_csv.len() as i32,
+            self.layer_instance.c_wid * self.layer_instance.c_hei,
+            "int_grid_csv should have one value per cell of the layer"
+        );
+        self.layer_instance.int_grid_csv = int_grid_csv;
+        self
+    }
+
+    /// Adds a tile of the layer's tileset at the given grid coordinates.
+    ///
+    /// Tiles are added to the `auto_layer_tiles` of AutoLayer and IntGrid layers, and to the
+    /// `grid_tiles` of other layers.
+    ///
+    /// # Panics
+    /// Panics if the layer has no tileset.
+    pub fn tile(self, grid_coords: IVec2, tile_id: i32) -> Self {
+        self.flipped_tile(grid_coords, tile_id, false, false)
+    }
+
+    /// Similar to [LayerInstanceBuilder::tile], except the tile can be flipped.
+    pub fn flipped_tile(
+        mut self,
+        grid_coords: IVec2,
+        tile_id: i32,
+        flip_x: bool,
+        flip_y: bool,
+    ) -> Self {
+        let tileset_definition = self
+            .tileset_definition
+            .as_ref()
+            .expect("tiles should be added after setting the layer's tileset");
+
+        let mut tile_instance = tile_instance(
+            grid_coords,
+            self.layer_instance.grid_size,
+            tile_id,
+            tileset_definition,
+        );
+        tile_instance.f = flip_x as i32 | (flip_y as i32) << 1;
+
+        match self.layer_instance.layer_instance_type {
+            Type::AutoLayer | Type::IntGrid => {
+                self.layer_instance.auto_layer_tiles.push(tile_instance)
+            }
+            _ => self.layer_instance.grid_tiles.push(tile_instance),
+        }
+        self
+    }
+
+    /// Adds an entity to the layer.
+    ///
+    /// If the entity's pixel coordinates are unset, they're derived from its grid coordinates.
+    pub fn entity(mut self, mut entity_instance: EntityInstance) -> Self {
+        if entity_instance.px == IVec2::ZERO {
+            entity_instance.px = entity_instance.grid * self.layer_instance.grid_size;
+        }
+        self.layer_instance.entity_instances.push(entity_instance);
+        self
+    }
+
+    /// Finishes building the layer.
+    pub fn build(self) -> LayerInstance {
+        self.layer_instance
+    }
+}
+
+/// Builder for [Level]s.
+///
+/// Levels are 256 by 256 pixels without layers, like new levels in LDtk, unless specified
+/// otherwise.
+#[derive(Clone, Debug)]
+pub struct LevelBuilder {
+    level: Level,
+    entities: Vec<EntityInstance>,
+}
+
+impl LevelBuilder {
+    /// Starts building the level with the given uid, identified as `Level_<uid>`.
+    pub fn new(uid: i32) -> Self {
+        LevelBuilder {
+            level: Level {
+                uid,
+                identifier: format!("Level_{}", uid),
+                px_wid: 256,
+                px_hei: 256,
+                ..Default::default()
+            },
+            entities: Vec::new(),
+        }
+    }
+
+    /// Sets the iid of the level, which is empty by default like in projects saved before LDtk
+    /// 1.0.
+    pub fn iid(mut self, iid: &str) -> Self {
+        self.level.iid = iid.to_string();
+        self
+    }
+
+    /// Adds a neighbour to the level, in the direction `dir` (`n`, `s`, `w` or `e`).
+    pub fn neighbour(mut self, dir: &str, level_uid: i32) -> Self {
+        self.level.neighbours.push(NeighbourLevel {
+            dir: dir.to_string(),
+            level_uid,
+        });
+        self
+    }
+
+    /// Adds a layer to the level, above the layers added before it.
+    pub fn layer(mut self, layer_instance: LayerInstance) -> Self {
+        self.level
+            .layer_instances
+            .get_or_insert_with(Vec::new)
+            .insert(0, layer_instance);
+        self
+    }
+
+    /// Adds an entity to the level, in an `Entities` layer with a grid size of 16 pixels that's
+    /// added on top of the other layers when the level is built.
+    pub fn entity(mut self, entity_instance: EntityInstance) -> Self {
+        self.entities.push(entity_instance);
+        self
+    }
+
+    /// Finishes building the level.
+    pub fn build(self) -> Level {
+        let LevelBuilder {
+            mut level,
+            entities,
+        } = self;
+
+        if !entities.is_empty() {
+            let mut layer_builder = LayerInstanceBuilder::new(
+                "Entities",
+                Type::Entities,
+                level.px_wid / 16,
+                level.px_hei / 16,
+                16,
+            );
+            for entity_instance in entities {
+                layer_builder = layer_builder.entity(entity_instance);
+            }
+
+            level
+                .layer_instances
+                .get_or_insert_with(Vec::new)
+                .insert(0, layer_builder.build());
+        }
+
+        level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_instance_src() {
+        let tileset_definition = TilesetDefinition {
+            spacing: 2,
+            padding: 1,
+            ..tileset_definition(0, "Tiles", 4, 4, 16)
+        };
+
+        let tile_instance = tile_instance(IVec2::new(3, 2), 8, 6, &tileset_definition);
+
+        assert_eq!(tile_instance.px, IVec2::new(24, 16));
+        assert_eq!(tile_instance.src, IVec2::new(37, 19));
+        assert_eq!(tile_instance.t, 6);
+    }
+
+    #[test]
+    fn test_layer_instance_builder() {
+        let tileset_definition = tileset_definition(3, "Tiles", 2, 2, 16);
+
+        let layer_instance = LayerInstanceBuilder::new("Walls", Type::IntGrid, 2, 1, 16)
+            .tileset(&tileset_definition)
+            .int_grid_csv(vec![1, 0])
+            .flipped_tile(IVec2::new(0, 0), 3, false, true)
+            .entity(EntityInstance {
+                grid: IVec2::new(1, 0),
+                ..Default::default()
+            })
+            .build();
+
+        assert_eq!(layer_instance.tileset_def_uid, Some(3));
+        assert_eq!(layer_instance.int_grid_csv, vec![1, 0]);
+        assert!(layer_instance.grid_tiles.is_empty());
+        assert_eq!(layer_instance.auto_layer_tiles[0].f, 2);
+        assert_eq!(layer_instance.auto_layer_tiles[0].src, IVec2::new(16, 16));
+        assert_eq!(layer_instance.entity_instances[0].px, IVec2::new(16, 0));
+    }
+
+    #[test]
+    fn test_level_builder() {
+        let tileset_definition = tileset_definition(1, "Tiles", 2, 2, 16);
+
+        let level = LevelBuilder::new(3)
+            .neighbour("e", 4)
+            .layer(
+                LayerInstanceBuilder::new("Ground", Type::Tiles, 16, 16, 16)
+                    .tileset(&tileset_definition)
+                    .build(),
+            )
+            .entity(EntityInstance {
+                grid: IVec2::new(2, 1),
+                ..Default::default()
+            })
+            .build();
+
+        assert_eq!(level.identifier, "Level_3");
+        assert!(level.iid.is_empty());
+        assert_eq!(level.neighbours[0].level_uid, 4);
+
+        let layer_instances = level.layer_instances.unwrap();
+        assert_eq!(layer_instances.len(), 2);
+        assert_eq!(layer_instances[0].identifier, "Entities");
+        assert_eq!(
+            layer_instances[0].entity_instances[0].px,
+            IVec2::new(32, 16)
+        );
+        assert_eq!(layer_instances[1].identifier, "Ground");
+    }
+}
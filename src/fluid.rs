@@ -0,0 +1,289 @@
+//! Fluid volumes generated from IntGrid layers, like water or lava.
+//!
+//! Buoyancy and swimming systems need to know the extent of a body of fluid and where its surface
+//! is, which is tedious to derive from individual [IntGridCell](crate::IntGridCell)s.
+//! When the [FluidVolumeSettings] resource is inserted, the plugin merges the connected cells of
+//! each configured IntGrid value into a single [FluidVolume] entity whenever a level spawns:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{fluid::*, prelude::*};
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .add_plugin(LdtkPlugin)
+//!         .insert_resource(FluidVolumeSettings {
+//!             int_grid_values: vec![3, 4],
+//!         })
+//!         .add_system(float)
+//!         .run();
+//! }
+//!
+//! fn float(fluid_query: Query<(&FluidVolume, &GlobalTransform)>) {
+//!     for (fluid_volume, transform) in fluid_query.iter() {
+//!         if let Some(surface) = fluid_volume.surface_translation_y(0) {
+//!             // push things below `transform.translation.y + surface` upwards...
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    assets::LdtkLevel,
+    components::GridCoords,
+    ldtk::{LayerInstance, Type},
+    resources::LevelEvent,
+};
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// Optional resource listing the IntGrid values that are spawned as [FluidVolume]s.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct FluidVolumeSettings {
+    pub int_grid_values: Vec<i32>,
+}
+
+/// A connected region of IntGrid cells with the same value, in grid coordinates.
+///
+/// Like [GridCoords], the origin is the bottom-left cell of the layer, and y increases upwards.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct FluidRegion {
+    /// The IntGrid value of the cells.
+    pub value: i32,
+    /// The cells of the region, sorted by row then column.
+    pub cells: Vec<GridCoords>,
+    /// The top edge of the region in each column it covers, i.e. one more than the y of the
+    /// highest cell in that column.
+    pub surface: BTreeMap<i32, i32>,
+}
+
+/// Finds the regions of 4-connected cells of an IntGrid layer whose value satisfies `is_fluid`.
+///
+/// Neighboring cells with different values, like water next to lava, form separate regions.
+pub fn find_fluid_regions(
+    int_grid_csv: &[i32],
+    layer_width_in_tiles: i32,
+    layer_height_in_tiles: i32,
+    is_fluid: impl Fn(i32) -> bool,
+) -> Vec<FluidRegion> {
+    let value_at = |coords: IVec2| -> Option<i32> {
+        if coords.x < 0
+            || coords.y < 0
+            || coords.x >= layer_width_in_tiles
+            || coords.y >= layer_height_in_tiles
+        {
+            return None;
+        }
+
+        int_grid_csv
+            .get(
+                ((layer_height_in_tiles - 1 - coords.y) * layer_width_in_tiles + coords.x) as usize,
+            )
+            .copied()
+    };
+
+    let mut visited = HashSet::new();
+    let mut regions = Vec::new();
+
+    for y in 0..layer_height_in_tiles {
+        for x in 0..layer_width_in_tiles {
+            let start = IVec2::new(x, y);
+            let value = match value_at(start) {
+                Some(value) if is_fluid(value) && !visited.contains(&start) => value,
+                _ => continue,
+            };
+
+            let mut region = FluidRegion {
+                value,
+                ..Default::default()
+            };
+
+            visited.insert(start);
+            let mut to_visit = vec![start];
+            while let Some(coords) = to_visit.pop() {
+                region.cells.push(coords.into());
+
+                let top = region.surface.entry(coords.x).or_insert(coords.y + 1);
+                *top = (*top).max(coords.y + 1);
+
+                for offset in [IVec2::X, -IVec2::X, IVec2::Y, -IVec2::Y] {
+                    let neighbor = coords + offset;
+                    if value_at(neighbor) == Some(value) && visited.insert(neighbor) {
+                        to_visit.push(neighbor);
+                    }
+                }
+            }
+
+            region.cells.sort_by_key(|c| (c.y, c.x));
+            regions.push(region);
+        }
+    }
+
+    regions
+}
+
+/// [Component] for a body of fluid merged from the cells of an IntGrid layer.
+///
+/// The entity is a child of the level, and its [Transform] places it at the bottom-left corner of
+/// its layer, so the translations given by its methods are relative to that [Transform].
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct FluidVolume {
+    /// The identifier of the IntGrid layer the volume was generated from.
+    pub layer_identifier: String,
+    /// The size of the cells of the layer, in pixels.
+    pub grid_size: i32,
+    pub region: FluidRegion,
+}
+
+impl FluidVolume {
+    /// Returns the y translation of the surface of the volume above the given grid column.
+    pub fn surface_translation_y(&self, column: i32) -> Option<f32> {
+        self.region
+            .surface
+            .get(&column)
+            .map(|top| (top * self.grid_size) as f32)
+    }
+
+    /// Returns whether the given translation is within one of the cells of the volume.
+    pub fn contains(&self, translation: Vec2) -> bool {
+        let coords = (translation / self.grid_size as f32).floor().as_ivec2();
+        self.region.cells.contains(&coords.into())
+    }
+}
+
+/// [Bundle] of the [FluidVolume] entities spawned by the plugin.
+#[derive(Clone, Bundle)]
+pub struct FluidVolumeBundle {
+    pub fluid_volume: FluidVolume,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+fn layer_fluid_volumes(
+    layer_instance: &LayerInstance,
+    fluid_volume_settings: &FluidVolumeSettings,
+) -> Vec<FluidVolumeBundle> {
+    let transform = Transform::from_xyz(
+        layer_instance.px_total_offset_x as f32,
+        -layer_instance.px_total_offset_y as f32,
+        0.,
+    );
+
+    find_fluid_regions(
+        &layer_instance.int_grid_csv,
+        layer_instance.c_wid,
+        layer_instance.c_hei,
+        |value| fluid_volume_settings.int_grid_values.contains(&value),
+    )
+    .into_iter()
+    .map(|region| FluidVolumeBundle {
+        fluid_volume: FluidVolume {
+            layer_identifier: layer_instance.identifier.clone(),
+            grid_size: layer_instance.grid_size,
+            region,
+        },
+        transform,
+        global_transform: GlobalTransform::default(),
+    })
+    .collect()
+}
+
+/// Spawns the [FluidVolume]s of newly spawned levels as their children.
+///
+/// Does nothing unless the [FluidVolumeSettings] resource is inserted.
+pub fn spawn_fluid_volumes(
+    mut commands: Commands,
+    fluid_volume_settings: Option<Res<FluidVolumeSettings>>,
+    mut level_events: EventReader<LevelEvent>,
+    level_query: Query<(Entity, &Handle<LdtkLevel>)>,
+    level_assets: Res<Assets<LdtkLevel>>,
+) {
+    let fluid_volume_settings = match fluid_volume_settings {
+        Some(fluid_volume_settings) => fluid_volume_settings,
+        None => return,
+    };
+
+    let spawned_uids: HashSet<i32> = level_events
+        .iter()
+        .filter_map(|e| match e {
+            LevelEvent::Spawned(uid) => Some(*uid),
+            _ => None,
+        })
+        .collect();
+
+    if spawned_uids.is_empty() {
+        return;
+    }
+
+    for (level_entity, level_handle) in level_query.iter() {
+        let level = match level_assets.get(level_handle) {
+            Some(ldtk_level) if spawned_uids.contains(&ldtk_level.level.uid) => &ldtk_level.level,
+            _ => continue,
+        };
+
+        for layer_instance in level.layer_instances.iter().flatten() {
+            if layer_instance.layer_instance_type != Type::IntGrid {
+                continue;
+            }
+
+            commands.entity(level_entity).with_children(|commands| {
+                for bundle in layer_fluid_volumes(layer_instance, &fluid_volume_settings) {
+                    commands.spawn_bundle(bundle);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_fluid_regions() {
+        let int_grid_csv = vec![
+            0, 0, 0, 0, 0, //
+            3, 0, 0, 3, 0, //
+            3, 3, 0, 3, 4, //
+            1, 1, 1, 3, 4,
+        ];
+
+        let regions = find_fluid_regions(&int_grid_csv, 5, 4, |v| v == 3 || v == 4);
+
+        assert_eq!(regions.len(), 3);
+
+        assert_eq!(regions[0].value, 3);
+        assert_eq!(regions[0].cells.len(), 3);
+        assert_eq!(regions[0].surface, BTreeMap::from([(3, 3)]));
+
+        assert_eq!(regions[1].value, 4);
+        assert_eq!(regions[1].surface, BTreeMap::from([(4, 2)]));
+
+        assert_eq!(regions[2].value, 3);
+        assert_eq!(
+            regions[2].cells,
+            vec![
+                GridCoords::new(0, 1),
+                GridCoords::new(1, 1),
+                GridCoords::new(0, 2)
+            ]
+        );
+        assert_eq!(regions[2].surface, BTreeMap::from([(0, 3), (1, 2)]));
+    }
+
+    #[test]
+    fn test_fluid_volume_queries() {
+        let fluid_volume = FluidVolume {
+            grid_size: 16,
+            region: find_fluid_regions(&[0, 0, 5, 5], 2, 2, |v| v == 5)
+                .pop()
+                .unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(fluid_volume.surface_translation_y(1), Some(16.));
+        assert_eq!(fluid_volume.surface_translation_y(2), None);
+        assert!(fluid_volume.contains(Vec2::new(20., 8.)));
+        assert!(!fluid_volume.contains(Vec2::new(20., 24.)));
+    }
+}
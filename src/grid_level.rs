@@ -0,0 +1,54 @@
+//! The level model used by the layer spawning pipeline.
+
+use crate::ldtk::{LayerInstance, Level};
+
+/// The data the plugin needs to spawn the layers of a level: its dimensions and its layers.
+///
+/// The layer spawning pipeline (layer building, [LdtkEntity](crate::app::LdtkEntity) and
+/// [LdtkIntCell](crate::app::LdtkIntCell) dispatch, IntGrid handling) only operates on this
+/// model, rather than the full [Level].
+/// Per-level features, like backgrounds and level fields, still read the rest of the [Level].
+/// It borrows its layers, so converting a [Level] with [From] is cheap.
+///
+/// Levels that don't come from an LDtk file, like procedurally generated or imported ones, can be
+/// described with this model and added to a project with
+/// [LdtkAsset::insert_level](crate::LdtkAsset::insert_level).
+/// They're converted to a [Level] with defaults for everything else, and spawn through the same
+/// code path as the levels of the file.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GridLevel<'a> {
+    /// Unique identifier of the level, used as the id of its [bevy_ecs_tilemap::Map].
+    pub uid: i32,
+    /// Width of the level in pixels.
+    pub px_wid: i32,
+    /// Height of the level in pixels.
+    pub px_hei: i32,
+    /// Layers of the level, sorted in display order: the first layer is the top-most.
+    pub layer_instances: &'a [LayerInstance],
+}
+
+impl<'a> From<&'a Level> for GridLevel<'a> {
+    fn from(level: &'a Level) -> Self {
+        GridLevel {
+            uid: level.uid,
+            px_wid: level.px_wid,
+            px_hei: level.px_hei,
+            layer_instances: level.layer_instances.as_deref().unwrap_or(&[]),
+        }
+    }
+}
+
+impl<'a> From<GridLevel<'a>> for Level {
+    fn from(grid_level: GridLevel<'a>) -> Self {
+        Level {
+            uid: grid_level.uid,
+            identifier: format!("Level_{}", grid_level.uid),
+            px_wid: grid_level.px_wid,
+            px_hei: grid_level.px_hei,
+            // LDtk's default background color for new levels
+            bg_color: "#696A79".to_string(),
+            layer_instances: Some(grid_level.layer_instances.to_vec()),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Layers as textured quads in 3D space, for 2.5D projects with a perspective camera.
+//!
+//! *Requires the "3d" feature*
+//!
+//! The tilemaps spawned by the plugin are only drawn by 2D cameras.
+//! When the [Layer3dSettings] resource is inserted, every tile layer of a spawned level gets a
+//! [LayerQuad] instead: a child entity of the level with a quad mesh, textured with an image of
+//! all the tiles of the layer.
+//! The tilemaps themselves aren't spawned then, but IntGrid cells still are, as plain entities.
+//! The quads are spaced along the z axis like the [LayerZ] of [LdtkSettings::layer_z], so a
+//! perspective camera shows the depth between them:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{layers_3d::Layer3dSettings, prelude::*};
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .add_plugin(LdtkPlugin)
+//!         .insert_resource(LevelSelection::Index(0))
+//!         .insert_resource(Layer3dSettings {
+//!             depth_separation: 24.,
+//!             ..Default::default()
+//!         })
+//!         .add_startup_system(setup)
+//!         .run();
+//! }
+//!
+//! fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+//!     commands.spawn_bundle(PerspectiveCameraBundle {
+//!         transform: Transform::from_xyz(128., 128., 400.),
+//!         ..Default::default()
+//!     });
+//!
+//!     commands.spawn_bundle(LdtkWorldBundle::from_path(&asset_server, "my_project.ldtk"));
+//! }
+//! ```
+//!
+//! Entities are still spawned as usual, but sprites are only drawn by 2D cameras too, so they need
+//! 3D visuals of their own.
+//! The layer images are made when levels spawn, so changes to tiles at runtime aren't reflected on
+//! the quads.
+
+use crate::{
+    assets::{LdtkAsset, LdtkLevel},
+    ldtk::Type,
+    resources::{LayerZ, LdtkSettings, LevelEvent},
+    utils::{
+        auto_rule_tileset_uids, draw_layer_tiles, group_tiles_by_tileset, is_rgba8,
+        layer_draw_order,
+    },
+};
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, FilterMode, TextureDimension, TextureFormat},
+};
+use std::collections::HashSet;
+
+/// Optional resource enabling the [LayerQuad]s of levels.
+///
+/// Only levels spawned after it's inserted get quads instead of tilemaps.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Layer3dSettings {
+    /// Distance between the quads of consecutive layers along the z axis, in pixels.
+    pub depth_separation: f32,
+    /// Whether the quads ignore lighting, showing the colors of their tiles as they are.
+    pub unlit: bool,
+}
+
+impl Default for Layer3dSettings {
+    fn default() -> Self {
+        Layer3dSettings {
+            depth_separation: 16.,
+            unlit: true,
+        }
+    }
+}
+
+/// [Component] added to the quads spawned for the layers of levels when the [Layer3dSettings]
+/// resource is inserted.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LayerQuad {
+    pub layer_identifier: String,
+}
+
+/// Returns the z of the quad of a layer, from the z [LayerZ] gives it, in steps of
+/// [Layer3dSettings::depth_separation] rather than [LayerZ::step].
+fn layer_quad_depth(
+    layer_z: &LayerZ,
+    layer_identifier: &str,
+    layer_index: u32,
+    depth_separation: f32,
+) -> f32 {
+    if layer_z.step == 0. {
+        return layer_index as f32 * depth_separation;
+    }
+
+    (layer_z.z(layer_identifier, layer_index) - layer_z.base) / layer_z.step * depth_separation
+}
+
+/// Spawns a [LayerQuad] for every tile layer of newly spawned levels, as children of the level.
+///
+/// Does nothing unless the [Layer3dSettings] resource is inserted, or if
+/// [LdtkSettings::headless] is enabled.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_layer_quads(
+    mut commands: Commands,
+    layer_3d_settings: Option<Res<Layer3dSettings>>,
+    ldtk_settings: Res<LdtkSettings>,
+    mut level_events: EventReader<LevelEvent>,
+    level_query: Query<(Entity, &Handle<LdtkLevel>, &Parent)>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let layer_3d_settings = match layer_3d_settings {
+        Some(layer_3d_settings) if !ldtk_settings.headless => layer_3d_settings,
+        _ => return,
+    };
+
+    let spawned_uids: HashSet<i32> = level_events
+        .iter()
+        .filter_map(|e| match e {
+            LevelEvent::Spawned(uid) => Some(*uid),
+            _ => None,
+        })
+        .collect();
+
+    if spawned_uids.is_empty() {
+        return;
+    }
+
+    for (level_entity, level_handle, parent) in level_query.iter() {
+        let level = match level_assets.get(level_handle) {
+            Some(ldtk_level) if spawned_uids.contains(&ldtk_level.level.uid) => &ldtk_level.level,
+            _ => continue,
+        };
+
+        let ldtk_asset = match ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|h| ldtk_assets.get(h))
+        {
+            Some(ldtk_asset) => ldtk_asset,
+            None => continue,
+        };
+
+        let layer_instances = level.layer_instances.as_deref().unwrap_or_default();
+
+        for (layer_index, layer_instance) in
+            layer_draw_order(layer_instances, &ldtk_settings.layer_draw_order)
+                .into_iter()
+                .enumerate()
+        {
+            if layer_instance.layer_instance_type == Type::Entities
+                || (layer_instance.grid_tiles.is_empty()
+                    && layer_instance.auto_layer_tiles.is_empty())
+            {
+                continue;
+            }
+
+            let size = IVec2::new(
+                layer_instance.c_wid * layer_instance.grid_size,
+                layer_instance.c_hei * layer_instance.grid_size,
+            );
+
+            let mut layer_image = Image::new_fill(
+                Extent3d {
+                    width: size.x as u32,
+                    height: size.y as u32,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[0, 0, 0, 0],
+                TextureFormat::Rgba8UnormSrgb,
+            );
+            layer_image.sampler_descriptor.mag_filter = FilterMode::Nearest;
+            layer_image.sampler_descriptor.min_filter = FilterMode::Nearest;
+
+            let rule_tileset_uids = ldtk_asset
+                .get_layer_definition(layer_instance.layer_def_uid)
+                .map(auto_rule_tileset_uids)
+                .unwrap_or_default();
+
+            for (tileset_uid, tiles) in group_tiles_by_tileset(layer_instance, &rule_tileset_uids) {
+                let tileset = tileset_uid.and_then(|uid| {
+                    Some((
+                        images.get(ldtk_asset.tileset_map.get(&uid)?)?,
+                        ldtk_asset.get_tileset_definition(uid)?,
+                    ))
+                });
+
+                match tileset {
+                    Some((tileset_image, tileset_definition)) if is_rgba8(tileset_image) => {
+                        draw_layer_tiles(
+                            &mut layer_image,
+                            &tiles,
+                            layer_instance.grid_size,
+                            tileset_image,
+                            tileset_definition,
+                            layer_instance.opacity,
+                        )
+                    }
+                    Some(_) => warn!(
+                        "The tileset of layer {} isn't in a 4-byte RGBA format, so it can't be \
+                        drawn on its quad",
+                        layer_instance.identifier
+                    ),
+                    None => (),
+                }
+            }
+
+            let layer_offset = Vec2::new(
+                layer_instance.px_total_offset_x as f32,
+                -layer_instance.px_total_offset_y as f32,
+            );
+
+            let depth = layer_quad_depth(
+                &ldtk_settings.layer_z,
+                &layer_instance.identifier,
+                layer_index as u32,
+                layer_3d_settings.depth_separation,
+            );
+
+            let material = materials.add(StandardMaterial {
+                base_color_texture: Some(images.add(layer_image)),
+                alpha_mode: AlphaMode::Blend,
+                unlit: layer_3d_settings.unlit,
+                ..Default::default()
+            });
+
+            commands.entity(level_entity).with_children(|commands| {
+                commands
+                    .spawn_bundle(PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Quad::new(size.as_vec2()))),
+                        material,
+                        transform: Transform::from_translation(
+                            (layer_offset + size.as_vec2() / 2.).extend(depth),
+                        ),
+                        ..Default::default()
+                    })
+                    .insert(LayerQuad {
+                        layer_identifier: layer_instance.identifier.clone(),
+                    });
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_quad_depth() {
+        let layer_z = LayerZ {
+            base: 1.,
+            step: 0.5,
+            overrides: [("Foreground".to_string(), 4.)].into_iter().collect(),
+        };
+
+        assert_eq!(layer_quad_depth(&layer_z, "Ground", 0, 16.), 0.);
+        assert_eq!(layer_quad_depth(&layer_z, "Ground", 2, 16.), 32.);
+        assert_eq!(layer_quad_depth(&layer_z, "Foreground", 1, 16.), 96.);
+
+        let flat_layer_z = LayerZ {
+            step: 0.,
+            ..layer_z
+        };
+        assert_eq!(layer_quad_depth(&flat_layer_z, "Ground", 2, 16.), 32.);
+    }
+}
@@ -1,8 +1,9 @@
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
+use super::EntityInstanceTile;
 #[allow(unused_imports)]
 use super::{EntityInstance, Level};
-use bevy::{prelude::*, render::color::HexColorError};
+use bevy::prelude::*;
 use regex::Regex;
 
 #[derive(PartialEq, Debug, Clone, Serialize)]
@@ -70,18 +71,7 @@ impl<'de> Deserialize<'de> for FieldInstance {
             "String" => FieldValue::String(
                 Option::<String>::deserialize(helper.value).map_err(de::Error::custom)?,
             ),
-            "Color" => {
-                let value = String::deserialize(helper.value).map_err(de::Error::custom)?;
-
-                let hex = match value.strip_prefix('#') {
-                    Some(h) => h.to_string(),
-                    None => value,
-                };
-
-                FieldValue::Color(
-                    Color::hex(hex).map_err(|_| de::Error::custom("Encountered HexColorError"))?,
-                )
-            }
+            "Color" => FieldValue::Color(color_from_value(helper.value)?),
             "FilePath" => FieldValue::FilePath(
                 Option::<String>::deserialize(helper.value).map_err(de::Error::custom)?,
             ),
@@ -104,24 +94,17 @@ impl<'de> Deserialize<'de> for FieldInstance {
                 Vec::<Option<String>>::deserialize(helper.value).map_err(de::Error::custom)?,
             ),
             "Array<Color>" => {
-                let values = Vec::<String>::deserialize(helper.value).map_err(de::Error::custom)?;
+                let values = Vec::<serde_json::Value>::deserialize(helper.value)
+                    .map_err(de::Error::custom)?;
 
-                let colors = values
-                    .into_iter()
-                    .map(|value| {
-                        let hex = match value.strip_prefix('#') {
-                            Some(h) => h.to_string(),
-                            None => value,
-                        };
-
-                        Color::hex(hex)
-                    })
-                    .collect::<Result<Vec<Color>, HexColorError>>()
-                    .map_err(|_| de::Error::custom("Encountered HexColorError"))?;
-
-                FieldValue::Colors(colors)
+                FieldValue::Colors(
+                    values
+                        .into_iter()
+                        .map(color_from_value)
+                        .collect::<Result<Vec<Color>, _>>()?,
+                )
             }
-            "Array<FilePath>" => FieldValue::Strings(
+            "Array<FilePath>" => FieldValue::FilePaths(
                 Vec::<Option<String>>::deserialize(helper.value).map_err(de::Error::custom)?,
             ),
             "Array<Point>" => {
@@ -135,6 +118,21 @@ impl<'de> Deserialize<'de> for FieldInstance {
 
                 FieldValue::Points(points)
             }
+            "Tile" => FieldValue::Tile(
+                Option::<TilesetRectangle>::deserialize(helper.value).map_err(de::Error::custom)?,
+            ),
+            "Array<Tile>" => FieldValue::Tiles(
+                Vec::<Option<TilesetRectangle>>::deserialize(helper.value)
+                    .map_err(de::Error::custom)?,
+            ),
+            "EntityRef" => FieldValue::EntityRef(
+                Option::<FieldInstanceEntityReference>::deserialize(helper.value)
+                    .map_err(de::Error::custom)?,
+            ),
+            "Array<EntityRef>" => FieldValue::EntityRefs(
+                Vec::<Option<FieldInstanceEntityReference>>::deserialize(helper.value)
+                    .map_err(de::Error::custom)?,
+            ),
             t => {
                 let enum_regex =
                     Regex::new(r"^(LocalEnum|ExternEnum)\.").expect("enum regex should be valid");
@@ -166,6 +164,26 @@ impl<'de> Deserialize<'de> for FieldInstance {
     }
 }
 
+/// Parses a color in one of the formats LDtk uses: a hex string with or without a leading `#`,
+/// or an integer like `0xRRGGBB`.
+fn color_from_value<E: de::Error>(value: serde_json::Value) -> Result<Color, E> {
+    let hex = match value {
+        serde_json::Value::String(value) => match value.strip_prefix('#') {
+            Some(h) => h.to_string(),
+            None => value,
+        },
+        serde_json::Value::Number(value) => {
+            let value = value
+                .as_u64()
+                .ok_or_else(|| de::Error::custom("Encountered invalid integer color"))?;
+            format!("{:06X}", value)
+        }
+        _ => return Err(de::Error::custom("Expected a string or integer color")),
+    };
+
+    Color::hex(hex).map_err(|_| de::Error::custom("Encountered HexColorError"))
+}
+
 #[derive(PartialEq, Debug, Clone, Serialize)]
 #[serde(untagged)]
 /// The actual value of a field instance on a [Level] or [EntityInstance].
@@ -196,6 +214,64 @@ pub enum FieldValue {
     Enums(Vec<Option<String>>),
     #[serde(serialize_with = "serialize_points")]
     Points(Vec<Option<IVec2>>),
+    EntityRef(Option<FieldInstanceEntityReference>),
+    EntityRefs(Vec<Option<FieldInstanceEntityReference>>),
+    /// A rectangle of a tileset.
+    Tile(Option<TilesetRectangle>),
+    Tiles(Vec<Option<TilesetRectangle>>),
+}
+
+/// The value of an `EntityRef` field: a reference to another entity instance by its iid, which may
+/// be in another level.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Serialize, Deserialize)]
+pub struct FieldInstanceEntityReference {
+    /// Iid of the referenced entity instance.
+    #[serde(rename = "entityIid")]
+    pub entity_iid: String,
+
+    /// Iid of the layer containing the referenced entity instance.
+    #[serde(rename = "layerIid")]
+    pub layer_iid: String,
+
+    /// Iid of the level containing the referenced entity instance.
+    #[serde(rename = "levelIid")]
+    pub level_iid: String,
+
+    /// Iid of the world containing the referenced entity instance.
+    #[serde(rename = "worldIid", default)]
+    pub world_iid: String,
+}
+
+/// The value of a `Tile` field: a rectangle of a tileset, in pixels.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Serialize, Deserialize)]
+pub struct TilesetRectangle {
+    /// Uid of the tileset.
+    #[serde(rename = "tilesetUid")]
+    pub tileset_uid: i32,
+
+    /// X coordinate of the top-left corner of the rectangle in the tileset image.
+    pub x: i32,
+
+    /// Y coordinate of the top-left corner of the rectangle in the tileset image.
+    pub y: i32,
+
+    /// Width of the rectangle.
+    pub w: i32,
+
+    /// Height of the rectangle.
+    pub h: i32,
+}
+
+impl From<&EntityInstanceTile> for TilesetRectangle {
+    fn from(tile: &EntityInstanceTile) -> Self {
+        TilesetRectangle {
+            tileset_uid: tile.tileset_uid,
+            x: tile.src_rect[0],
+            y: tile.src_rect[1],
+            w: tile.src_rect[2],
+            h: tile.src_rect[3],
+        }
+    }
 }
 
 fn serialize_color<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
@@ -223,3 +299,83 @@ fn serialize_points<S: Serializer>(
     let field_values: Vec<FieldValue> = points.iter().map(|p| FieldValue::Point(*p)).collect();
     field_values.serialize(serializer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_field_formats() {
+        let field_instance: FieldInstance = serde_json::from_str(
+            r##"{"__identifier":"tint","__type":"Color","__value":"#FF0000","defUid":0,"realEditorValues":[]}"##,
+        )
+        .unwrap();
+        assert_eq!(
+            field_instance.value,
+            FieldValue::Color(Color::rgb(1., 0., 0.))
+        );
+
+        let field_instance: FieldInstance = serde_json::from_str(
+            r#"{"__identifier":"tints","__type":"Array<Color>","__value":["00FF00",255],"defUid":0,"realEditorValues":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            field_instance.value,
+            FieldValue::Colors(vec![Color::rgb(0., 1., 0.), Color::rgb(0., 0., 1.)])
+        );
+    }
+
+    #[test]
+    fn test_tile_fields() {
+        let field_instance: FieldInstance = serde_json::from_str(
+            r#"{"__identifier":"icon","__type":"Tile","__value":{"tilesetUid":2,"x":16,"y":32,"w":16,"h":8},"defUid":0,"realEditorValues":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            field_instance.value,
+            FieldValue::Tile(Some(TilesetRectangle {
+                tileset_uid: 2,
+                x: 16,
+                y: 32,
+                w: 16,
+                h: 8,
+            }))
+        );
+
+        let field_instance: FieldInstance = serde_json::from_str(
+            r#"{"__identifier":"frames","__type":"Array<Tile>","__value":[{"tilesetUid":2,"x":0,"y":0,"w":16,"h":16},null],"defUid":0,"realEditorValues":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            field_instance.value,
+            FieldValue::Tiles(vec![
+                Some(TilesetRectangle {
+                    tileset_uid: 2,
+                    x: 0,
+                    y: 0,
+                    w: 16,
+                    h: 16,
+                }),
+                None,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_file_paths_round_trip() {
+        let json = r#"{"__identifier":"sounds","__type":"Array<FilePath>","__value":["sfx/jump.ogg",null],"defUid":0,"realEditorValues":[]}"#;
+
+        let field_instance: FieldInstance = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            field_instance.value,
+            FieldValue::FilePaths(vec![Some("sfx/jump.ogg".to_string()), None])
+        );
+
+        let serialized = serde_json::to_string(&field_instance).unwrap();
+        assert_eq!(serialized, json);
+        assert_eq!(
+            serde_json::from_str::<FieldInstance>(&serialized).unwrap(),
+            field_instance
+        );
+    }
+}
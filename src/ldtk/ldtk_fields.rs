@@ -0,0 +1,578 @@
+use super::{
+    EntityInstance, FieldInstance, FieldInstanceEntityReference, FieldValue, Level,
+    TilesetRectangle,
+};
+use bevy::prelude::*;
+use std::{error::Error, fmt};
+
+/// Error returned by the accessors of [LdtkFields].
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum LdtkFieldsError {
+    /// No field with the given identifier exists.
+    FieldNotFound { identifier: String },
+    /// The field exists, but has a different type than the one requested.
+    WrongFieldType {
+        identifier: String,
+        field_type: String,
+    },
+    /// The field has the requested type, but its value is null.
+    UnexpectedNull { identifier: String },
+    /// The value of the enum field isn't a variant of the requested [LdtkEnum].
+    UnknownEnumVariant { identifier: String, value: String },
+}
+
+impl LdtkFieldsError {
+    fn wrong_field_type(field_instance: &FieldInstance) -> Self {
+        LdtkFieldsError::WrongFieldType {
+            identifier: field_instance.identifier.clone(),
+            field_type: field_instance.field_instance_type.clone(),
+        }
+    }
+
+    fn unexpected_null(identifier: &str) -> Self {
+        LdtkFieldsError::UnexpectedNull {
+            identifier: identifier.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LdtkFieldsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LdtkFieldsError::FieldNotFound { identifier } => {
+                write!(f, "could not find field \"{}\"", identifier)
+            }
+            LdtkFieldsError::WrongFieldType {
+                identifier,
+                field_type,
+            } => write!(
+                f,
+                "field \"{}\" has a different type than requested: {}",
+                identifier, field_type
+            ),
+            LdtkFieldsError::UnexpectedNull { identifier } => {
+                write!(f, "field \"{}\" is null", identifier)
+            }
+            LdtkFieldsError::UnknownEnumVariant { identifier, value } => write!(
+                f,
+                "field \"{}\" has a value with no matching enum variant: {}",
+                identifier, value
+            ),
+        }
+    }
+}
+
+impl Error for LdtkFieldsError {}
+
+/// Rust enums that mirror an LDtk enum, so enum fields can be converted to them.
+///
+/// This can be derived for enums with unit variants named exactly like the values of the LDtk
+/// enum:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// #[derive(Copy, Clone, Debug, Component, LdtkEnum)]
+/// enum Faction {
+///     Goblins,
+///     Skeletons,
+/// }
+///
+/// fn faction(entity_instance: &EntityInstance) -> Faction {
+///     entity_instance
+///         .get_enum_field_as::<Faction>("faction")
+///         .expect("entity should have a valid faction")
+/// }
+/// ```
+/// The derived [LdtkEntity](crate::app::LdtkEntity) also supports constructing these from an
+/// entity's fields with the `#[ldtk_enum_field("identifier")]` attribute.
+pub trait LdtkEnum: Sized {
+    /// Converts the value of an LDtk enum field to the variant of the same name, if any.
+    fn from_ldtk_enum_value(value: &str) -> Option<Self>;
+}
+
+/// Typed accessors for the fields of LDtk objects that have them, like [EntityInstance] and
+/// [Level].
+///
+/// Each field type has two accessors:
+/// - `get_maybe_*_field` returns the value of nullable fields as an [Option].
+/// - `get_*_field` treats a null value as an error, which is convenient for required fields.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// # use bevy_ecs_ldtk::ldtk::LdtkFieldsError;
+/// #[derive(Component)]
+/// struct Health(i32);
+///
+/// fn health(entity_instance: &EntityInstance) -> Result<Health, LdtkFieldsError> {
+///     Ok(Health(entity_instance.get_int_field("hp")?))
+/// }
+/// ```
+pub trait LdtkFields {
+    /// The raw field instances of the object.
+    fn field_instances(&self) -> &[FieldInstance];
+
+    /// Finds the field instance with the given identifier.
+    fn get_field_instance(&self, identifier: &str) -> Result<&FieldInstance, LdtkFieldsError> {
+        self.field_instances()
+            .iter()
+            .find(|f| f.identifier == identifier)
+            .ok_or_else(|| LdtkFieldsError::FieldNotFound {
+                identifier: identifier.to_string(),
+            })
+    }
+
+    /// Finds the value of the field with the given identifier, whatever its type.
+    fn get_field(&self, identifier: &str) -> Result<&FieldValue, LdtkFieldsError> {
+        Ok(&self.get_field_instance(identifier)?.value)
+    }
+
+    /// Gets the value of an `Int` field.
+    fn get_maybe_int_field(&self, identifier: &str) -> Result<Option<i32>, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Int(value),
+                ..
+            } => Ok(*value),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a non-null `Int` field.
+    fn get_int_field(&self, identifier: &str) -> Result<i32, LdtkFieldsError> {
+        self.get_maybe_int_field(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Gets the value of a `Float` field.
+    fn get_maybe_float_field(&self, identifier: &str) -> Result<Option<f32>, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Float(value),
+                ..
+            } => Ok(*value),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a non-null `Float` field.
+    fn get_float_field(&self, identifier: &str) -> Result<f32, LdtkFieldsError> {
+        self.get_maybe_float_field(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Gets the value of a `Bool` field, which is never null.
+    fn get_bool_field(&self, identifier: &str) -> Result<bool, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Bool(value),
+                ..
+            } => Ok(*value),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a `String` or `Multilines` field.
+    fn get_maybe_string_field(&self, identifier: &str) -> Result<Option<&str>, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::String(value),
+                ..
+            } => Ok(value.as_deref()),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a non-null `String` or `Multilines` field.
+    fn get_string_field(&self, identifier: &str) -> Result<&str, LdtkFieldsError> {
+        self.get_maybe_string_field(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Gets the value of a `Color` field, which is never null.
+    fn get_color_field(&self, identifier: &str) -> Result<Color, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Color(value),
+                ..
+            } => Ok(*value),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a `FilePath` field.
+    fn get_maybe_file_path_field(&self, identifier: &str) -> Result<Option<&str>, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::FilePath(value),
+                ..
+            } => Ok(value.as_deref()),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a non-null `FilePath` field.
+    fn get_file_path_field(&self, identifier: &str) -> Result<&str, LdtkFieldsError> {
+        self.get_maybe_file_path_field(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Gets the variant name of an enum field.
+    fn get_maybe_enum_field(&self, identifier: &str) -> Result<Option<&str>, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Enum(value),
+                ..
+            } => Ok(value.as_deref()),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the variant name of a non-null enum field.
+    fn get_enum_field(&self, identifier: &str) -> Result<&str, LdtkFieldsError> {
+        self.get_maybe_enum_field(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Converts the value of an enum field to an [LdtkEnum].
+    fn get_maybe_enum_field_as<E: LdtkEnum>(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<E>, LdtkFieldsError> {
+        self.get_maybe_enum_field(identifier)?
+            .map(|value| {
+                E::from_ldtk_enum_value(value).ok_or_else(|| LdtkFieldsError::UnknownEnumVariant {
+                    identifier: identifier.to_string(),
+                    value: value.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Converts the value of a non-null enum field to an [LdtkEnum].
+    fn get_enum_field_as<E: LdtkEnum>(&self, identifier: &str) -> Result<E, LdtkFieldsError> {
+        self.get_maybe_enum_field_as(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Gets the value of a `Point` field, in LDtk's y-down grid coordinates.
+    fn get_maybe_point_field(&self, identifier: &str) -> Result<Option<IVec2>, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Point(value),
+                ..
+            } => Ok(*value),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a non-null `Point` field, in LDtk's y-down grid coordinates.
+    fn get_point_field(&self, identifier: &str) -> Result<IVec2, LdtkFieldsError> {
+        self.get_maybe_point_field(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Gets the value of an `EntityRef` field.
+    fn get_maybe_entity_ref_field(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<&FieldInstanceEntityReference>, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::EntityRef(value),
+                ..
+            } => Ok(value.as_ref()),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a non-null `EntityRef` field.
+    fn get_entity_ref_field(
+        &self,
+        identifier: &str,
+    ) -> Result<&FieldInstanceEntityReference, LdtkFieldsError> {
+        self.get_maybe_entity_ref_field(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Gets the value of a `Tile` field.
+    fn get_maybe_tile_field(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<&TilesetRectangle>, LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Tile(value),
+                ..
+            } => Ok(value.as_ref()),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the value of a non-null `Tile` field.
+    fn get_tile_field(&self, identifier: &str) -> Result<&TilesetRectangle, LdtkFieldsError> {
+        self.get_maybe_tile_field(identifier)?
+            .ok_or_else(|| LdtkFieldsError::unexpected_null(identifier))
+    }
+
+    /// Gets the values of an `Array<Int>` field.
+    fn get_ints_field(&self, identifier: &str) -> Result<&[Option<i32>], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Ints(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the values of an `Array<Float>` field.
+    fn get_floats_field(&self, identifier: &str) -> Result<&[Option<f32>], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Floats(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the values of an `Array<Bool>` field.
+    fn get_bools_field(&self, identifier: &str) -> Result<&[bool], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Bools(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the values of an `Array<String>` or `Array<Multilines>` field.
+    fn get_strings_field(&self, identifier: &str) -> Result<&[Option<String>], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Strings(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the values of an `Array<Color>` field.
+    fn get_colors_field(&self, identifier: &str) -> Result<&[Color], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Colors(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the values of an `Array<FilePath>` field.
+    fn get_file_paths_field(&self, identifier: &str) -> Result<&[Option<String>], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::FilePaths(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the variant names of an enum array field.
+    fn get_enums_field(&self, identifier: &str) -> Result<&[Option<String>], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Enums(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the values of an `Array<Point>` field, in LDtk's y-down grid coordinates.
+    fn get_points_field(&self, identifier: &str) -> Result<&[Option<IVec2>], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Points(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the values of an `Array<EntityRef>` field.
+    fn get_entity_refs_field(
+        &self,
+        identifier: &str,
+    ) -> Result<&[Option<FieldInstanceEntityReference>], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::EntityRefs(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+
+    /// Gets the values of an `Array<Tile>` field.
+    fn get_tiles_field(
+        &self,
+        identifier: &str,
+    ) -> Result<&[Option<TilesetRectangle>], LdtkFieldsError> {
+        match self.get_field_instance(identifier)? {
+            FieldInstance {
+                value: FieldValue::Tiles(values),
+                ..
+            } => Ok(values),
+            field_instance => Err(LdtkFieldsError::wrong_field_type(field_instance)),
+        }
+    }
+}
+
+impl LdtkFields for EntityInstance {
+    fn field_instances(&self) -> &[FieldInstance] {
+        &self.field_instances
+    }
+}
+
+impl LdtkFields for Level {
+    fn field_instances(&self) -> &[FieldInstance] {
+        &self.field_instances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_instance(
+        identifier: &str,
+        field_instance_type: &str,
+        value: FieldValue,
+    ) -> FieldInstance {
+        FieldInstance {
+            identifier: identifier.to_string(),
+            field_instance_type: field_instance_type.to_string(),
+            value,
+            def_uid: 0,
+            real_editor_values: Vec::new(),
+        }
+    }
+
+    #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+    enum Faction {
+        Goblins,
+        Skeletons,
+    }
+
+    impl LdtkEnum for Faction {
+        fn from_ldtk_enum_value(value: &str) -> Option<Self> {
+            match value {
+                "Goblins" => Some(Faction::Goblins),
+                "Skeletons" => Some(Faction::Skeletons),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+    enum Weather {
+        Sunny,
+    }
+
+    impl LdtkEnum for Weather {
+        fn from_ldtk_enum_value(value: &str) -> Option<Self> {
+            match value {
+                "Sunny" => Some(Weather::Sunny),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_typed_field_accessors() {
+        let entity_instance = EntityInstance {
+            field_instances: vec![
+                field_instance("hp", "Int", FieldValue::Int(Some(3))),
+                field_instance("armor", "Int", FieldValue::Int(None)),
+                field_instance(
+                    "name",
+                    "String",
+                    FieldValue::String(Some("Bob".to_string())),
+                ),
+                field_instance("target", "Point", FieldValue::Point(Some(IVec2::new(2, 4)))),
+                field_instance(
+                    "faction",
+                    "LocalEnum.Faction",
+                    FieldValue::Enum(Some("Skeletons".to_string())),
+                ),
+                field_instance(
+                    "route",
+                    "Array<Point>",
+                    FieldValue::Points(vec![Some(IVec2::new(0, 1)), None]),
+                ),
+                field_instance(
+                    "icon",
+                    "Tile",
+                    FieldValue::Tile(Some(TilesetRectangle {
+                        tileset_uid: 2,
+                        x: 16,
+                        y: 0,
+                        w: 16,
+                        h: 16,
+                    })),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(entity_instance.get_int_field("hp"), Ok(3));
+        assert_eq!(entity_instance.get_maybe_int_field("armor"), Ok(None));
+        assert_eq!(entity_instance.get_string_field("name"), Ok("Bob"));
+        assert_eq!(
+            entity_instance.get_point_field("target"),
+            Ok(IVec2::new(2, 4))
+        );
+        assert_eq!(
+            entity_instance
+                .get_tile_field("icon")
+                .map(|t| t.tileset_uid),
+            Ok(2)
+        );
+
+        assert_eq!(
+            entity_instance.get_int_field("armor"),
+            Err(LdtkFieldsError::UnexpectedNull {
+                identifier: "armor".to_string()
+            })
+        );
+        assert_eq!(
+            entity_instance.get_float_field("hp"),
+            Err(LdtkFieldsError::WrongFieldType {
+                identifier: "hp".to_string(),
+                field_type: "Int".to_string()
+            })
+        );
+        assert_eq!(
+            entity_instance.get_enum_field_as::<Faction>("faction"),
+            Ok(Faction::Skeletons)
+        );
+        assert_eq!(
+            entity_instance.get_enum_field_as::<Weather>("faction"),
+            Err(LdtkFieldsError::UnknownEnumVariant {
+                identifier: "faction".to_string(),
+                value: "Skeletons".to_string()
+            })
+        );
+        assert_eq!(Weather::from_ldtk_enum_value("Sunny"), Some(Weather::Sunny));
+        assert_eq!(
+            entity_instance.get_bool_field("missing"),
+            Err(LdtkFieldsError::FieldNotFound {
+                identifier: "missing".to_string()
+            })
+        );
+    }
+}
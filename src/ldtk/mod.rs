@@ -20,6 +20,19 @@
 //! 10. Comment at the top of the file has been replaced with this documentation.
 //! 11. Some "coordinate" fields on [LevelBackgroundPosition], [EntityInstance], and [TileInstance]
 //!     have been changed from vectors to [IVec2] and [Vec2].
+//! 12. The `iid` field has been added to [Level] and [EntityInstance], defaulting to an empty
+//!     string for files saved before LDtk introduced it.
+//! 13. [LdtkFields] has been added in its own module, and is re-exported here.
+//! 14. [IntGridValueGroupDefinition] has been added, along with the fields referencing it on
+//!     [LayerDefinition] and [IntGridValueDefinition], defaulting to no groups for files saved
+//!     before LDtk introduced them.
+//! 15. The `tileset_uid` field has been added to [AutoLayerRuleDefinition], defaulting to [None]
+//!     for rules using the tileset of their layer.
+//! 16. The `iid` field has been added to [LayerInstance], defaulting to an empty string for files
+//!     saved before LDtk introduced it.
+//! 17. The `parallax_factor_x`, `parallax_factor_y` and `parallax_scaling` fields have been added
+//!     to [LayerDefinition], defaulting to no parallax for files saved before LDtk introduced
+//!     them.
 
 use bevy::prelude::{IVec2, Vec2};
 use serde::{Deserialize, Serialize};
@@ -29,8 +42,10 @@ use std::collections::HashMap;
 use crate::prelude::LdtkEntity;
 
 mod field_instance;
+mod ldtk_fields;
 
 pub use field_instance::*;
+pub use ldtk_fields::*;
 
 /// This file is a JSON schema of files created by LDtk level editor <https://ldtk.io>.
 ///
@@ -402,6 +417,10 @@ pub struct EnumValueDefinition {
     pub tile_id: Option<i32>,
 }
 
+fn default_parallax_scaling() -> bool {
+    true
+}
+
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct LayerDefinition {
     /// Type of the layer (*IntGrid, Entities, Tiles or AutoLayer*)
@@ -442,6 +461,25 @@ pub struct LayerDefinition {
     #[serde(rename = "intGridValues")]
     pub int_grid_values: Vec<IntGridValueDefinition>,
 
+    /// Group informations for IntGrid values
+    #[serde(rename = "intGridValuesGroups", default)]
+    pub int_grid_values_groups: Vec<IntGridValueGroupDefinition>,
+
+    /// Parallax horizontal factor (from -1 to 1, defaults to 0) which affects the scrolling
+    /// speed of this layer, creating a fake 3D (parallax) effect.
+    #[serde(rename = "parallaxFactorX", default)]
+    pub parallax_factor_x: f32,
+
+    /// Parallax vertical factor (from -1 to 1, defaults to 0) which affects the scrolling speed
+    /// of this layer, creating a fake 3D (parallax) effect.
+    #[serde(rename = "parallaxFactorY", default)]
+    pub parallax_factor_y: f32,
+
+    /// If true (default), a layer with a parallax factor will also be scaled up/down
+    /// accordingly.
+    #[serde(rename = "parallaxScaling", default = "default_parallax_scaling")]
+    pub parallax_scaling: bool,
+
     /// X offset of the layer, in pixels (IMPORTANT: this should be added to the `LayerInstance`
     /// optional offset)
     #[serde(rename = "pxOffsetX")]
@@ -574,6 +612,10 @@ pub struct AutoLayerRuleDefinition {
     #[serde(rename = "tileMode")]
     pub tile_mode: TileMode,
 
+    /// Tileset the rule places its tiles from, if it overrides the tileset of its layer
+    #[serde(rename = "tilesetUid", default)]
+    pub tileset_uid: Option<i32>,
+
     /// Unique Int identifier
     #[serde(rename = "uid")]
     pub uid: i32,
@@ -600,6 +642,26 @@ pub struct IntGridValueDefinition {
     /// The IntGrid value itself
     #[serde(rename = "value")]
     pub value: i32,
+
+    /// Parent group identifier (0 if none)
+    #[serde(rename = "groupUid", default)]
+    pub group_uid: i32,
+}
+
+/// IntGrid value group definition
+#[derive(Eq, PartialEq, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IntGridValueGroupDefinition {
+    /// User defined color
+    #[serde(rename = "color")]
+    pub color: Option<String>,
+
+    /// User defined string identifier
+    #[serde(rename = "identifier")]
+    pub identifier: Option<String>,
+
+    /// Group unique ID
+    #[serde(rename = "uid")]
+    pub uid: i32,
 }
 
 /// The `Tileset` definition is the most important part among project definitions. It
@@ -730,6 +792,10 @@ pub struct Level {
     #[serde(rename = "identifier")]
     pub identifier: String,
 
+    /// Unique instance identifier
+    #[serde(default, rename = "iid")]
+    pub iid: String,
+
     /// An array containing all Layer instances. **IMPORTANT**: if the project option "*Save
     /// levels separately*" is enabled, this field will be `null`.<br/>  This array is **sorted
     /// in display order**: the 1st layer is the top-most and the last is behind.
@@ -838,6 +904,10 @@ pub struct LayerInstance {
     #[serde(rename = "gridTiles")]
     pub grid_tiles: Vec<TileInstance>,
 
+    /// Unique layer instance identifier
+    #[serde(default, rename = "iid")]
+    pub iid: String,
+
     /// **WARNING**: this deprecated value will be *removed* completely on version 0.10.0+
     /// Replaced by: `intGridCsv`
     #[serde(rename = "intGrid")]
@@ -950,6 +1020,10 @@ pub struct EntityInstance {
     #[serde(rename = "height")]
     pub height: i32,
 
+    /// Unique instance identifier
+    #[serde(default, rename = "iid")]
+    pub iid: String,
+
     /// Pixel coordinates (`[x,y]` format) in current level coordinate space. Don't forget
     /// optional layer offsets, if they exist!
     #[serde(rename = "px")]
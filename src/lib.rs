@@ -29,10 +29,7 @@
 //! fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
 //!     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
 //!
-//!     commands.spawn_bundle(LdtkWorldBundle {
-//!         ldtk_handle: asset_server.load("my_project.ldtk"),
-//!         ..Default::default()
-//!     });
+//!     commands.spawn_bundle(LdtkWorldBundle::from_path(&asset_server, "my_project.ldtk"));
 //! }
 //!
 //! # #[derive(Default, Component)]
@@ -100,15 +97,42 @@ use bevy_ecs_tilemap::prelude::*;
 
 pub mod app;
 mod assets;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod climbable;
+pub mod collision;
 mod components;
+#[cfg(feature = "debug")]
+pub mod debug;
+pub mod fixtures;
+pub mod fluid;
+mod grid_level;
+#[cfg(feature = "3d")]
+pub mod layers_3d;
 pub mod ldtk;
+pub mod merged_shapes;
+pub mod parallax;
+#[cfg(feature = "particles")]
+pub mod particles;
+pub mod physics;
+#[cfg(feature = "rapier")]
+pub mod rapier;
 mod resources;
+#[cfg(feature = "scripting")]
+pub mod scripting;
 pub mod systems;
+pub mod tile_animations;
 mod tile_makers;
+pub mod tile_variants;
+#[cfg(feature = "tiled")]
+pub mod tmx;
 pub mod utils;
+pub mod validation;
+pub mod views;
 
 pub use assets::*;
 pub use components::*;
+pub use grid_level::*;
 pub use plugin::*;
 pub use resources::*;
 
@@ -125,6 +149,16 @@ mod plugin {
     pub enum LdtkSystemLabel {
         LevelSelection,
         PreSpawn,
+        /// Spawns the contents of pending levels in [CoreStage::PostUpdate].
+        ///
+        /// All the entities of a level are spawned by a single system, in a single update unless
+        /// [LdtkSettings::level_spawn_budget](resources::LdtkSettings::level_spawn_budget) is
+        /// set.
+        /// With a budget, levels spawn a few layers per update, so the entities of their first
+        /// layers exist before the level is complete.
+        /// Systems that shouldn't observe levels that are still pending can use the
+        /// [systems::levels_spawned] run criteria, or wait for
+        /// [LevelEvent::Spawned](resources::LevelEvent::Spawned).
         LevelSpawning,
         FrameDelay,
         Other,
@@ -140,13 +174,28 @@ mod plugin {
         fn build(&self, app: &mut App) {
             app.add_plugin(TilemapPlugin)
                 .init_non_send_resource::<app::LdtkEntityMap>()
+                .init_non_send_resource::<app::LdtkEntityNamespaceMap>()
                 .init_non_send_resource::<app::LdtkIntCellMap>()
+                .init_non_send_resource::<app::LdtkIntCellIdentifierMap>()
+                .init_non_send_resource::<app::LdtkTileEnumTagMap>()
+                .init_non_send_resource::<app::LdtkEntityAnchorMap>()
+                .init_non_send_resource::<app::LdtkIntCellPromotionMap>()
+                .init_non_send_resource::<app::LdtkReflectedEntityMap>()
+                .init_non_send_resource::<app::LdtkDespawnPolicyMap>()
+                .init_non_send_resource::<app::LdtkFilePathFieldMap>()
                 .init_resource::<resources::LdtkSettings>()
+                .init_resource::<resources::EntityIidMap>()
+                .init_resource::<resources::ExtrudedTilesets>()
+                .init_resource::<physics::LevelPhysics>()
+                .init_resource::<tile_animations::TileAnimationSettings>()
                 .add_asset::<assets::LdtkAsset>()
                 .init_asset_loader::<assets::LdtkLoader>()
                 .add_asset::<assets::LdtkLevel>()
                 .init_asset_loader::<assets::LdtkLevelLoader>()
                 .add_event::<resources::LevelEvent>()
+                .add_event::<resources::PendingDespawnEvent>()
+                .add_event::<resources::LevelSpawnReport>()
+                .add_event::<resources::LevelSpawnError>()
                 .add_system_to_stage(
                     CoreStage::PreUpdate,
                     systems::process_ldtk_world.label(LdtkSystemLabel::PreSpawn),
@@ -155,20 +204,88 @@ mod plugin {
                     CoreStage::PreUpdate,
                     systems::choose_levels.label(LdtkSystemLabel::LevelSelection),
                 )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::stream_levels.label(LdtkSystemLabel::LevelSelection),
+                )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    // New worlds have no children for apply_level_set to diff against yet, so
+                    // their initial level is spawned by process_ldtk_world
+                    systems::apply_initial_level_selections
+                        .label(LdtkSystemLabel::LevelSelection)
+                        .before(systems::process_ldtk_world),
+                )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::record_level_history.label(LdtkSystemLabel::LevelSelection),
+                )
                 .add_system_to_stage(
                     CoreStage::PreUpdate,
                     systems::apply_level_set
                         .label(LdtkSystemLabel::PreSpawn)
                         .after(LdtkSystemLabel::LevelSelection),
                 )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::respawn_modified_levels
+                        .label(LdtkSystemLabel::PreSpawn)
+                        .before(systems::process_ldtk_world),
+                )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::respawn_levels_with_changed_settings
+                        .label(LdtkSystemLabel::PreSpawn)
+                        .before(systems::process_ldtk_world),
+                )
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    systems::respawn_levels.label(LdtkSystemLabel::PreSpawn),
+                )
                 .add_system_to_stage(
                     CoreStage::PreUpdate,
                     systems::set_ldtk_texture_filters_to_nearest.label(LdtkSystemLabel::Other),
                 )
+                .add_system(systems::update_census.label(LdtkSystemLabel::Other))
+                .add_system(systems::update_clear_color.label(LdtkSystemLabel::Other))
+                .add_system(
+                    systems::respawn_levels_with_resized_tilesets.label(LdtkSystemLabel::Other),
+                )
                 .add_system_to_stage(
                     CoreStage::PreUpdate,
                     systems::worldly_adoption.label(LdtkSystemLabel::Other),
                 )
+                .add_system(systems::update_entity_iid_map.label(LdtkSystemLabel::Other))
+                .add_system(systems::update_level_entity_indices.label(LdtkSystemLabel::Other))
+                .add_system(systems::apply_y_sort.label(LdtkSystemLabel::Other))
+                .add_system(
+                    systems::apply_layer_z
+                        .label(LdtkSystemLabel::Other)
+                        .before(systems::apply_y_sort),
+                )
+                .add_system(systems::apply_tileset_filters.label(LdtkSystemLabel::Other))
+                .add_system(systems::update_entity_refs.label(LdtkSystemLabel::Other))
+                .add_system(systems::despawn_released_entities.label(LdtkSystemLabel::Other))
+                .add_system(systems::apply_content_markers.label(LdtkSystemLabel::Other))
+                .add_system(
+                    systems::apply_layer_opacity
+                        .label(LdtkSystemLabel::Other)
+                        .before(systems::fade_in_levels),
+                )
+                .add_system(systems::fade_in_levels.label(LdtkSystemLabel::Other))
+                .add_system(systems::build_level_background_images.label(LdtkSystemLabel::Other))
+                .add_system(systems::update_chunk_activation.label(LdtkSystemLabel::Other))
+                .add_system(systems::update_level_lods.label(LdtkSystemLabel::Other))
+                .add_system(physics::update_level_physics.label(LdtkSystemLabel::Other))
+                .add_system(fluid::spawn_fluid_volumes.label(LdtkSystemLabel::Other))
+                .add_system(climbable::spawn_climbable_regions.label(LdtkSystemLabel::Other))
+                .add_system_to_stage(
+                    CoreStage::PostUpdate,
+                    climbable::update_climbable_regions
+                        .label(LdtkSystemLabel::Other)
+                        .after(bevy::transform::TransformSystem::TransformPropagate),
+                )
+                .add_system(parallax::apply_parallax.label(LdtkSystemLabel::Other))
                 .add_system_to_stage(
                     CoreStage::PostUpdate,
                     systems::detect_level_spawned_events
@@ -179,6 +296,69 @@ mod plugin {
                 .add_system_to_stage(
                     CoreStage::PostUpdate,
                     systems::process_ldtk_levels.label(LdtkSystemLabel::LevelSpawning),
+                )
+                .add_system_to_stage(
+                    CoreStage::PostUpdate,
+                    systems::complete_level_swaps
+                        .label(LdtkSystemLabel::Other)
+                        .after(LdtkSystemLabel::LevelSpawning),
+                );
+
+            app.add_system(tile_variants::apply_tile_variant_sets.label(LdtkSystemLabel::Other))
+                .add_system(
+                    systems::insert_tile_enum_tag_bundles
+                        .label(LdtkSystemLabel::Other)
+                        .before(tile_variants::apply_tile_variant_sets),
+                )
+                .add_system(
+                    tile_animations::insert_tile_animations
+                        .label(LdtkSystemLabel::Other)
+                        .before(tile_variants::apply_tile_variant_sets),
+                )
+                .add_system(
+                    tile_animations::animate_tiles
+                        .label(LdtkSystemLabel::Other)
+                        .after(tile_variants::apply_tile_variant_sets),
+                );
+
+            #[cfg(feature = "atlas")]
+            app.add_system_to_stage(
+                CoreStage::PreUpdate,
+                systems::extrude_ldtk_tilesets.label(LdtkSystemLabel::Other),
+            )
+            .add_system(systems::refresh_reloaded_tilesets.label(LdtkSystemLabel::Other));
+
+            #[cfg(feature = "tiled")]
+            app.init_asset_loader::<tmx::TiledLoader>();
+
+            #[cfg(feature = "audio")]
+            app.add_system(audio::play_sound_emitters.label(LdtkSystemLabel::Other));
+
+            #[cfg(feature = "3d")]
+            app.add_system(layers_3d::spawn_layer_quads.label(LdtkSystemLabel::Other));
+
+            #[cfg(feature = "particles")]
+            app.add_system(particles::spawn_particle_emitters.label(LdtkSystemLabel::Other));
+
+            #[cfg(feature = "debug")]
+            app.init_resource::<debug::CollisionDebugSettings>()
+                .add_system(debug::spawn_collision_debug_meshes.label(LdtkSystemLabel::Other))
+                .add_system(debug::toggle_collision_debug_meshes.label(LdtkSystemLabel::Other));
+
+            #[cfg(feature = "rapier")]
+            app.add_system(rapier::spawn_int_grid_colliders.label(LdtkSystemLabel::Other))
+                .add_system(rapier::update_int_grid_colliders.label(LdtkSystemLabel::Other))
+                .add_system(rapier::spawn_level_boundaries.label(LdtkSystemLabel::Other))
+                .add_system(rapier::insert_level_rigid_bodies.label(LdtkSystemLabel::Other))
+                .add_system(rapier::sync_level_rigid_bodies.label(LdtkSystemLabel::Other));
+
+            #[cfg(feature = "heron")]
+            app.add_system(physics::insert_heron_level_bodies.label(LdtkSystemLabel::Other))
+                .add_system(physics::spawn_heron_level_boundaries.label(LdtkSystemLabel::Other))
+                .add_system(
+                    physics::apply_level_gravity
+                        .label(LdtkSystemLabel::Other)
+                        .after(physics::update_level_physics),
                 );
         }
     }
@@ -186,16 +366,30 @@ mod plugin {
 
 pub mod prelude {
     //! `use bevy_ecs_ldtk::prelude::*;` to import commonly used items.
+    //!
+    //! This covers the plugin, the world bundle and the components/resources for controlling it,
+    //! the registration traits and their derive macros, and the most commonly used [ldtk] types.
+    //! Feature-specific items stay in their own modules.
 
     pub use crate::{
-        app::{LdtkEntity, LdtkIntCell, RegisterLdtkObjects},
+        app::{LdtkEntity, LdtkIntCell, PromotedTile, ReflectLdtkDefault, RegisterLdtkObjects},
         assets::{LdtkAsset, LdtkLevel},
-        components::{EntityInstance, IntGridCell, LdtkWorldBundle, LevelSet, Worldly},
-        ldtk::{self, FieldValue, LayerInstance, TilesetDefinition},
-        plugin::LdtkPlugin,
-        resources::{LdtkSettings, LevelEvent, LevelSelection},
+        components::{
+            ChunkActivationTarget, ContentMarker, EntityIid, EntityInstance, GridCoords,
+            InitialLevelSelection, IntGridCell, IntGridChunk, LayerMetadata, LayerOpacity,
+            LayerZSlot, LdtkAssetRoot, LdtkWorldBundle, LevelEntityIndex, LevelIdentifier,
+            LevelIid, LevelLod, LevelSet, LevelStreamingTarget, PixelCoords, Respawn,
+            SpawnedByLdtk, TilesetRemap, Worldly, YSort,
+        },
+        ldtk::{self, FieldValue, LayerInstance, LdtkEnum, LdtkFields, Level, TilesetDefinition},
+        plugin::{LdtkPlugin, LdtkSystemLabel},
+        resources::{
+            Census, ChunkActivationSettings, EntityIidMap, LdtkSettings, LevelEvent, LevelHistory,
+            LevelHistoryEntry, LevelSelection, LevelStreamingSettings,
+        },
+        views::{EntityData, LayerData, LevelData},
     };
 
     #[cfg(feature = "derive")]
-    pub use crate::{LdtkEntity, LdtkIntCell};
+    pub use crate::{LdtkEntity, LdtkEnum, LdtkIntCell};
 }
@@ -0,0 +1,220 @@
+//! Merged collision geometry for the cells of IntGrid layers.
+//!
+//! Spawning one collider per IntGrid cell quickly becomes expensive for physics engines.
+//! The functions in this module merge contiguous cells with the same value into a few large
+//! shapes instead:
+//! - [merge_int_grid_rects] greedily covers the cells of each value with rectangles.
+//! - [merge_int_grid_edge_chains] traces the outlines of the regions of each value, like
+//!   [trace_int_grid_outlines] does for a single set of solid values.
+//!
+//! Like [trace_int_grid_outlines], the geometry is in grid units, with the origin at the
+//! bottom-left corner of the layer.
+//! With the "rapier" feature, the `Rectangles` collider shape of the `rapier` module spawns
+//! colliders from [merge_int_grid_rects].
+
+use crate::collision::trace_int_grid_outlines;
+use bevy::prelude::*;
+use std::collections::BTreeSet;
+
+/// A rectangle of IntGrid cells, in grid units.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct GridRect {
+    /// The bottom-left corner of the rectangle.
+    pub min: IVec2,
+    /// The top-right corner of the rectangle, i.e. one more than the coordinates of its top-right
+    /// cell.
+    pub max: IVec2,
+}
+
+impl GridRect {
+    pub fn size(&self) -> IVec2 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max).as_vec2() / 2.
+    }
+
+    pub fn contains(&self, cell: IVec2) -> bool {
+        cell.cmpge(self.min).all() && cell.cmplt(self.max).all()
+    }
+
+    /// Returns the cells of the rectangle, row by row from the bottom.
+    pub fn cells(&self) -> impl Iterator<Item = IVec2> {
+        let GridRect { min, max } = *self;
+        (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| IVec2::new(x, y)))
+    }
+}
+
+/// An IntGrid value and a merged shape covering some of its cells.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct MergedShape<S> {
+    pub value: i32,
+    pub shape: S,
+}
+
+/// Covers the cells of an IntGrid layer with as few rectangles as the greedy approach finds,
+/// without rectangles spanning cells of different values.
+///
+/// Empty cells, and cells whose value doesn't satisfy `include`, are skipped.
+/// Rectangles are grown right first, then up, starting from the bottom-left-most uncovered cell,
+/// so the result is sorted by the row then the column of each rectangle's bottom-left cell.
+pub fn merge_int_grid_rects(
+    int_grid_csv: &[i32],
+    layer_width_in_tiles: i32,
+    layer_height_in_tiles: i32,
+    include: impl Fn(i32) -> bool,
+) -> Vec<MergedShape<GridRect>> {
+    let value_at = |x: i32, y: i32| -> Option<i32> {
+        int_grid_csv
+            .get(((layer_height_in_tiles - 1 - y) * layer_width_in_tiles + x) as usize)
+            .copied()
+            .filter(|v| *v != 0 && include(*v))
+    };
+
+    let mut covered = vec![false; (layer_width_in_tiles * layer_height_in_tiles).max(0) as usize];
+    let index = |x: i32, y: i32| (y * layer_width_in_tiles + x) as usize;
+
+    let mut rects = Vec::new();
+    for y in 0..layer_height_in_tiles {
+        for x in 0..layer_width_in_tiles {
+            let value = match value_at(x, y) {
+                Some(value) if !covered[index(x, y)] => value,
+                _ => continue,
+            };
+
+            let free = |x: i32, y: i32, covered: &[bool]| {
+                value_at(x, y) == Some(value) && !covered[index(x, y)]
+            };
+
+            let mut max_x = x + 1;
+            while max_x < layer_width_in_tiles && free(max_x, y, &covered) {
+                max_x += 1;
+            }
+
+            let mut max_y = y + 1;
+            while max_y < layer_height_in_tiles && (x..max_x).all(|x| free(x, max_y, &covered)) {
+                max_y += 1;
+            }
+
+            for covered_y in y..max_y {
+                for covered_x in x..max_x {
+                    covered[index(covered_x, covered_y)] = true;
+                }
+            }
+
+            rects.push(MergedShape {
+                value,
+                shape: GridRect {
+                    min: IVec2::new(x, y),
+                    max: IVec2::new(max_x, max_y),
+                },
+            });
+        }
+    }
+
+    rects
+}
+
+/// Traces the outlines of the regions of each IntGrid value satisfying `include`.
+///
+/// Unlike [trace_int_grid_outlines], neighboring cells with different values are outlined
+/// separately.
+/// The outlines follow the winding conventions of [trace_int_grid_outlines], and are sorted by
+/// value.
+pub fn merge_int_grid_edge_chains(
+    int_grid_csv: &[i32],
+    layer_width_in_tiles: i32,
+    layer_height_in_tiles: i32,
+    include: impl Fn(i32) -> bool,
+) -> Vec<MergedShape<Vec<IVec2>>> {
+    let values: BTreeSet<i32> = int_grid_csv
+        .iter()
+        .copied()
+        .filter(|v| *v != 0 && include(*v))
+        .collect();
+
+    values
+        .into_iter()
+        .flat_map(|value| {
+            trace_int_grid_outlines(
+                int_grid_csv,
+                layer_width_in_tiles,
+                layer_height_in_tiles,
+                |v| v == value,
+            )
+            .into_iter()
+            .map(move |shape| MergedShape { value, shape })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_int_grid_rects() {
+        let int_grid_csv = vec![
+            1, 1, 0, 2, //
+            1, 1, 1, 2, //
+            1, 1, 1, 2,
+        ];
+
+        let rects = merge_int_grid_rects(&int_grid_csv, 4, 3, |v| v != 0);
+
+        assert_eq!(
+            rects,
+            vec![
+                MergedShape {
+                    value: 1,
+                    shape: GridRect {
+                        min: IVec2::new(0, 0),
+                        max: IVec2::new(3, 2),
+                    },
+                },
+                MergedShape {
+                    value: 2,
+                    shape: GridRect {
+                        min: IVec2::new(3, 0),
+                        max: IVec2::new(4, 3),
+                    },
+                },
+                MergedShape {
+                    value: 1,
+                    shape: GridRect {
+                        min: IVec2::new(0, 2),
+                        max: IVec2::new(2, 3),
+                    },
+                },
+            ]
+        );
+
+        assert_eq!(rects[0].shape.size(), IVec2::new(3, 2));
+        assert_eq!(rects[0].shape.center(), Vec2::new(1.5, 1.));
+        assert!(rects[0].shape.contains(IVec2::new(2, 1)));
+        assert!(!rects[0].shape.contains(IVec2::new(3, 1)));
+        assert_eq!(
+            rects[2].shape.cells().collect::<Vec<_>>(),
+            vec![IVec2::new(0, 2), IVec2::new(1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_merge_int_grid_edge_chains() {
+        let int_grid_csv = vec![
+            1, 2, //
+            1, 2,
+        ];
+
+        let chains = merge_int_grid_edge_chains(&int_grid_csv, 2, 2, |_| true);
+
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains[0].value, 1);
+        assert_eq!(chains[0].shape.len(), 4);
+        assert_eq!(chains[1].value, 2);
+        assert!(chains[1].shape.contains(&IVec2::new(2, 2)));
+
+        assert!(merge_int_grid_edge_chains(&int_grid_csv, 2, 2, |v| v == 3).is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! Parallax scrolling of layers, as authored with the parallax settings of layer definitions in
+//! LDtk.
+//!
+//! Every tilemap layer spawned by the plugin gets a [ParallaxLayer] component with the parallax
+//! factor and scaling of its layer definition.
+//! When there is a [ParallaxCamera], layers with a parallax factor scroll and scale relative to it
+//! like they do in the editor:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{parallax::ParallaxCamera, prelude::*};
+//!
+//! fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+//!     commands
+//!         .spawn_bundle(OrthographicCameraBundle::new_2d())
+//!         .insert(ParallaxCamera);
+//!
+//!     commands.spawn_bundle(LdtkWorldBundle {
+//!         ldtk_handle: asset_server.load("my_project.ldtk"),
+//!         ..Default::default()
+//!     });
+//! }
+//! ```
+//! Without a [ParallaxCamera], layers stay where they spawned, and games can apply the
+//! [ParallaxLayer] values with their own camera logic instead.
+
+use crate::assets::LdtkLevel;
+use bevy::prelude::*;
+
+/// [Component] marking the camera that [ParallaxLayer]s scroll relative to.
+///
+/// Only one camera should have it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct ParallaxCamera;
+
+/// [Component] storing the parallax settings of a tilemap layer, inserted on the tilemap layer
+/// entities of spawned levels.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
+pub struct ParallaxLayer {
+    /// Fraction of the camera's movement the layer follows, on each axis.
+    ///
+    /// `0` doesn't scroll the layer, positive values make it appear further away, and negative
+    /// values closer.
+    pub factor: Vec2,
+    /// Whether the layer is also scaled down, or up for negative factors, according to its
+    /// factor.
+    pub scaling: bool,
+    /// The [Transform] the layer spawned with, relative to its level.
+    ///
+    /// With a [ParallaxCamera], [apply_parallax] derives the layer's [Transform] from this, so
+    /// games that move or scale a layer should edit its origin rather than its [Transform].
+    pub origin: Transform,
+}
+
+impl ParallaxLayer {
+    /// Returns the [Transform] of the layer for a camera at `camera_offset` from `level_center`,
+    /// both in the space of the level.
+    pub fn transform(&self, camera_offset: Vec2, level_center: Vec2) -> Transform {
+        let mut transform = self.origin;
+
+        if self.scaling {
+            let scale = Vec2::ONE - self.factor;
+            let origin = self.origin.translation.truncate();
+
+            transform.translation =
+                (level_center + (origin - level_center) * scale).extend(self.origin.translation.z);
+            transform.scale = (self.origin.scale.truncate() * scale).extend(self.origin.scale.z);
+        }
+
+        transform.translation += (camera_offset * self.factor).extend(0.);
+        transform
+    }
+}
+
+/// Moves [ParallaxLayer]s according to the position of the [ParallaxCamera] relative to the center
+/// of their level.
+///
+/// Layers are only updated when the camera, their level or their [ParallaxLayer] moved, and their
+/// [Transform] is only written when it differs, so it isn't marked as changed every frame.
+/// Since the [Transform] is overwritten, edit [ParallaxLayer::origin] to move a layer instead.
+///
+/// Does nothing unless there is exactly one [ParallaxCamera].
+pub fn apply_parallax(
+    camera_query: Query<(&GlobalTransform, ChangeTrackers<GlobalTransform>), With<ParallaxCamera>>,
+    mut layer_query: Query<(
+        &ParallaxLayer,
+        ChangeTrackers<ParallaxLayer>,
+        &Parent,
+        &mut Transform,
+    )>,
+    level_query: Query<(
+        &GlobalTransform,
+        ChangeTrackers<GlobalTransform>,
+        &Handle<LdtkLevel>,
+    )>,
+    level_assets: Res<Assets<LdtkLevel>>,
+) {
+    let (camera_translation, camera_changed) = match camera_query.get_single() {
+        Ok((camera_transform, camera_tracker)) => {
+            (camera_transform.translation, camera_tracker.is_changed())
+        }
+        Err(_) => return,
+    };
+
+    for (parallax_layer, layer_tracker, parent, mut transform) in layer_query.iter_mut() {
+        let (level_transform, level_tracker, level_handle) = match level_query.get(parent.0) {
+            Ok(level) => level,
+            Err(_) => continue,
+        };
+
+        if !camera_changed && !layer_tracker.is_changed() && !level_tracker.is_changed() {
+            continue;
+        }
+
+        let level = match level_assets.get(level_handle) {
+            Some(ldtk_level) => &ldtk_level.level,
+            None => continue,
+        };
+
+        let level_center = Vec2::new(level.px_wid as f32, level.px_hei as f32) / 2.;
+        let camera_offset = level_transform
+            .compute_matrix()
+            .inverse()
+            .transform_point3(camera_translation)
+            .truncate()
+            - level_center;
+
+        // Comparing before writing keeps layers that didn't move out of Changed<Transform>
+        let parallax_transform = parallax_layer.transform(camera_offset, level_center);
+        if *transform != parallax_transform {
+            *transform = parallax_transform;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallax_layer_transform() {
+        let origin = Transform::from_xyz(0., 0., 2.);
+        let level_center = Vec2::new(50., 40.);
+
+        let parallax_layer = ParallaxLayer {
+            factor: Vec2::new(0.5, 0.),
+            scaling: false,
+            origin,
+        };
+        assert_eq!(parallax_layer.transform(Vec2::ZERO, level_center), origin);
+        assert_eq!(
+            parallax_layer.transform(Vec2::new(20., 10.), level_center),
+            Transform::from_xyz(10., 0., 2.)
+        );
+
+        let parallax_layer = ParallaxLayer {
+            scaling: true,
+            ..parallax_layer
+        };
+        assert_eq!(
+            parallax_layer.transform(Vec2::new(20., 10.), level_center),
+            Transform::from_xyz(35., 0., 2.).with_scale(Vec3::new(0.5, 1., 1.))
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Support for placing [bevy_hanabi] particle effects in LDtk via a "particle emitter" entity
+//! convention.
+//!
+//! *Requires the "particles" feature*
+//!
+//! Any LDtk entity can become a particle emitter by registering [ParticleEmitterBundle] for its
+//! identifier.
+//! Since effects are built in code, the effects themselves are looked up by name in the
+//! [ParticleEffectLibrary] resource:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{prelude::*, particles::*};
+//! use bevy_hanabi::prelude::*;
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .add_plugin(HanabiPlugin)
+//!         .add_plugin(LdtkPlugin)
+//!         .init_resource::<ParticleEffectLibrary>()
+//!         .add_startup_system(setup)
+//!         .register_ldtk_entity::<ParticleEmitterBundle>("ParticleEmitter")
+//!         .run();
+//! }
+//!
+//! fn setup(mut library: ResMut<ParticleEffectLibrary>, mut effects: ResMut<Assets<EffectAsset>>) {
+//!     let torch_smoke = effects.add(EffectAsset::default());
+//!     library.effects.insert("torch_smoke".to_string(), torch_smoke);
+//! }
+//! ```
+//! The fields of the LDtk entity are then mapped to a [ParticleEmitter] component.
+//! The expected field identifiers are:
+//! - `effect`: a `String` or `Enum` field with the name of the effect in the
+//!   [ParticleEffectLibrary],
+//! - `rate`: an optional `Float` field with the number of particles spawned per second.
+//!   If absent, the spawner of the effect asset is used as is.
+
+use crate::{
+    app::LdtkEntity,
+    ldtk::{EntityInstance, FieldValue, LayerInstance, TilesetDefinition},
+};
+use bevy::prelude::*;
+use bevy_hanabi::prelude::*;
+use std::collections::HashMap;
+
+/// Field identifier for the effect name of a [ParticleEmitter].
+pub const PARTICLE_EMITTER_EFFECT_FIELD: &str = "effect";
+/// Field identifier for the spawn rate of a [ParticleEmitter].
+pub const PARTICLE_EMITTER_RATE_FIELD: &str = "rate";
+
+/// Resource associating effect names used in LDtk with [EffectAsset]s.
+#[derive(Clone, Debug, Default)]
+pub struct ParticleEffectLibrary {
+    pub effects: HashMap<String, Handle<EffectAsset>>,
+}
+
+/// [Component] describing a particle effect placed in LDtk.
+///
+/// The plugin inserts the corresponding [ParticleEffect] on entities with this component as long
+/// as the effect can be found in the [ParticleEffectLibrary].
+#[derive(Clone, PartialEq, Debug, Default, Component)]
+pub struct ParticleEmitter {
+    pub effect: String,
+    pub rate: Option<f32>,
+}
+
+/// [Bundle] implementing [LdtkEntity] by reading the particle emitter fields of the LDtk entity.
+///
+/// See the [module-level documentation](self) for the field convention.
+#[derive(Clone, Default, Bundle)]
+pub struct ParticleEmitterBundle {
+    pub particle_emitter: ParticleEmitter,
+}
+
+impl LdtkEntity for ParticleEmitterBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let mut particle_emitter = ParticleEmitter::default();
+
+        for field_instance in &entity_instance.field_instances {
+            match (field_instance.identifier.as_str(), &field_instance.value) {
+                (
+                    PARTICLE_EMITTER_EFFECT_FIELD,
+                    FieldValue::String(Some(effect)) | FieldValue::Enum(Some(effect)),
+                ) => particle_emitter.effect = effect.clone(),
+                (PARTICLE_EMITTER_RATE_FIELD, FieldValue::Float(rate)) => {
+                    particle_emitter.rate = *rate
+                }
+                _ => (),
+            }
+        }
+
+        ParticleEmitterBundle { particle_emitter }
+    }
+}
+
+/// Inserts [ParticleEffect]s on newly spawned [ParticleEmitter]s.
+pub fn spawn_particle_emitters(
+    mut commands: Commands,
+    particle_emitter_query: Query<(Entity, &ParticleEmitter), Added<ParticleEmitter>>,
+    library: Option<Res<ParticleEffectLibrary>>,
+) {
+    for (entity, particle_emitter) in particle_emitter_query.iter() {
+        match library
+            .as_ref()
+            .and_then(|l| l.effects.get(&particle_emitter.effect))
+        {
+            Some(handle) => {
+                let mut particle_effect = ParticleEffect::new(handle.clone());
+
+                if let Some(rate) = particle_emitter.rate {
+                    particle_effect = particle_effect.with_spawner(Spawner::rate(rate.into()));
+                }
+
+                commands.entity(entity).insert(particle_effect);
+            }
+            None => warn!(
+                "Particle effect \"{}\" not found in the ParticleEffectLibrary",
+                particle_emitter.effect
+            ),
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! Level-scoped physics settings authored as level fields in LDtk.
+//!
+//! Levels can override physics settings via a level field convention, so per-room gravity or
+//! water levels can be authored entirely in the editor.
+//! The expected field identifiers are:
+//! - `gravity_scale`: an optional `Float` field scaling the gravity of the level, defaulting to
+//!   `1.0`.
+//!
+//! Whenever a level becomes active, i.e. is chosen by the [LevelSelection] resource, the plugin
+//! updates the [LevelPhysics] resource with that level's settings.
+//! This resource is physics-engine agnostic, so it can be applied to any engine's configuration,
+//! like `bevy_rapier`'s `RapierConfiguration`:
+//! ```
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::physics::LevelPhysics;
+//!
+//! # struct RapierConfiguration { gravity: Vec2 }
+//! fn apply_level_gravity(
+//!     level_physics: Res<LevelPhysics>,
+//!     mut rapier_config: ResMut<RapierConfiguration>,
+//! ) {
+//!     if level_physics.is_changed() {
+//!         rapier_config.gravity = Vec2::new(0., -9.81) * level_physics.gravity_scale;
+//!     }
+//! }
+//! ```
+//!
+//! With the "heron" feature enabled, the plugin applies the gravity scale to heron's `Gravity`
+//! itself, as long as the [BaseGravity] resource is inserted.
+//!
+//! For levels that move at runtime, inserting the [LevelRigidBodySettings] resource attaches a
+//! single kinematic rigid body to each level entity, given a [LevelRigidBody] component.
+//! With the "rapier" feature, the colliders generated by the plugin are then attached to this body
+//! in the space of the level, and the body follows the [GlobalTransform] of its level, so entire
+//! rooms can be moved or rotated physically.
+//! With the "heron" feature, the body is heron's `RigidBody::KinematicPositionBased`, which any
+//! `CollisionShape` spawned as a child of the level is attached to.
+//!
+//! Similarly, inserting the [LevelBoundarySettings] resource spawns sensors along the outer edges
+//! of levels, with a [LevelBoundary] component identifying the neighbors on that side, with either
+//! the "rapier" or the "heron" feature.
+//! Room transitions can then be triggered by the collision events of these sensors.
+
+use crate::{
+    assets::LdtkAsset,
+    ldtk::{FieldValue, Level},
+    resources::{LevelEvent, LevelSelection},
+};
+use bevy::prelude::*;
+
+#[cfg(feature = "heron")]
+use crate::{assets::LdtkLevel, components::LayerTilesets};
+#[cfg(feature = "heron")]
+use heron::{CollisionShape, Gravity, RigidBody};
+
+/// Field identifier for the gravity scale of a level.
+pub const LEVEL_GRAVITY_SCALE_FIELD: &str = "gravity_scale";
+
+/// Resource holding the physics settings of the active level.
+///
+/// See the [module-level documentation](self) for the field convention.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LevelPhysics {
+    /// The `uid` of the level these settings were read from, if any level is active.
+    pub level_uid: Option<i32>,
+    pub gravity_scale: f32,
+}
+
+impl Default for LevelPhysics {
+    fn default() -> Self {
+        LevelPhysics {
+            level_uid: None,
+            gravity_scale: 1.,
+        }
+    }
+}
+
+impl From<&Level> for LevelPhysics {
+    fn from(level: &Level) -> Self {
+        let mut level_physics = LevelPhysics {
+            level_uid: Some(level.uid),
+            ..Default::default()
+        };
+
+        for field_instance in &level.field_instances {
+            if let (LEVEL_GRAVITY_SCALE_FIELD, FieldValue::Float(Some(gravity_scale))) =
+                (field_instance.identifier.as_str(), &field_instance.value)
+            {
+                level_physics.gravity_scale = *gravity_scale;
+            }
+        }
+
+        level_physics
+    }
+}
+
+/// Updates the [LevelPhysics] resource when the [LevelSelection] changes, or when the selected
+/// level spawns.
+pub fn update_level_physics(
+    level_selection: Option<Res<LevelSelection>>,
+    mut level_events: EventReader<LevelEvent>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    mut level_physics: ResMut<LevelPhysics>,
+) {
+    let level_spawned = level_events
+        .iter()
+        .any(|e| matches!(e, LevelEvent::Spawned(_)));
+
+    if let Some(level_selection) = level_selection {
+        if level_selection.is_changed() || level_spawned {
+            let selected_level = ldtk_query
+                .iter()
+                .filter_map(|ldtk_handle| ldtk_assets.get(ldtk_handle))
+                .find_map(|ldtk_asset| ldtk_asset.get_level(&level_selection));
+
+            if let Some(level) = selected_level {
+                let new_level_physics = LevelPhysics::from(level);
+                if *level_physics != new_level_physics {
+                    *level_physics = new_level_physics;
+                }
+            }
+        }
+    }
+}
+
+/// Resource for the gravity of levels with a `gravity_scale` of `1.0`.
+///
+/// *Requires the "heron" feature*
+///
+/// When inserted, heron's [Gravity] is set to this value scaled by the active [LevelPhysics].
+#[cfg(feature = "heron")]
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct BaseGravity(pub Vec3);
+
+/// Applies the gravity scale of the active [LevelPhysics] to heron's [Gravity].
+///
+/// *Requires the "heron" feature*
+///
+/// Does nothing unless the [BaseGravity] resource is inserted.
+#[cfg(feature = "heron")]
+pub fn apply_level_gravity(
+    mut commands: Commands,
+    level_physics: Res<LevelPhysics>,
+    base_gravity: Option<Res<BaseGravity>>,
+) {
+    if let Some(base_gravity) = base_gravity {
+        if level_physics.is_changed() || base_gravity.is_changed() {
+            commands.insert_resource(Gravity::from(base_gravity.0 * level_physics.gravity_scale));
+        }
+    }
+}
+
+/// Optional resource attaching a kinematic rigid body to every spawned level.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct LevelRigidBodySettings;
+
+/// [Component] marking levels that the plugin attached a kinematic rigid body to.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct LevelRigidBody;
+
+/// Inserts heron's `RigidBody::KinematicPositionBased` on newly spawned levels.
+///
+/// *Requires the "heron" feature*
+///
+/// Does nothing unless the [LevelRigidBodySettings] resource is inserted.
+#[cfg(feature = "heron")]
+pub fn insert_heron_level_bodies(
+    mut commands: Commands,
+    body_settings: Option<Res<LevelRigidBodySettings>>,
+    level_query: Query<Entity, (Changed<LayerTilesets>, Without<LevelRigidBody>)>,
+) {
+    if body_settings.is_none() {
+        return;
+    }
+
+    for level_entity in level_query.iter() {
+        commands
+            .entity(level_entity)
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(LevelRigidBody);
+    }
+}
+
+/// Optional resource enabling the [LevelBoundary] sensors of levels.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LevelBoundarySettings {
+    /// Thickness of the sensors, in pixels.
+    ///
+    /// The sensors lie just inside the edges of their level.
+    pub thickness: f32,
+}
+
+impl Default for LevelBoundarySettings {
+    fn default() -> Self {
+        LevelBoundarySettings { thickness: 1. }
+    }
+}
+
+/// A side of a level.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum LevelSide {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl LevelSide {
+    pub const ALL: [LevelSide; 4] = [
+        LevelSide::North,
+        LevelSide::South,
+        LevelSide::East,
+        LevelSide::West,
+    ];
+
+    /// Converts the `dir` of a [NeighbourLevel](crate::ldtk::NeighbourLevel).
+    pub fn from_dir(dir: &str) -> Option<LevelSide> {
+        match dir {
+            "n" => Some(LevelSide::North),
+            "s" => Some(LevelSide::South),
+            "e" => Some(LevelSide::East),
+            "w" => Some(LevelSide::West),
+            _ => None,
+        }
+    }
+}
+
+/// [Component] added to the sensors spawned along the edges of levels when the
+/// [LevelBoundarySettings] resource is inserted.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Component)]
+pub struct LevelBoundary {
+    pub side: LevelSide,
+    /// Iids of the levels touching this side, sorted.
+    ///
+    /// Empty if there are none, or if the neighbors were saved before LDtk introduced iids.
+    pub neighbour_iids: Vec<String>,
+}
+
+/// Returns the center and half extents of the boundary sensor of a side of a level, in pixels
+/// relative to the bottom-left corner of the level.
+pub(crate) fn level_boundary_cuboid(
+    level_size: Vec2,
+    side: LevelSide,
+    thickness: f32,
+) -> (Vec2, Vec2) {
+    let half_thickness = thickness / 2.;
+
+    match side {
+        LevelSide::North => (
+            Vec2::new(level_size.x / 2., level_size.y - half_thickness),
+            Vec2::new(level_size.x / 2., half_thickness),
+        ),
+        LevelSide::South => (
+            Vec2::new(level_size.x / 2., half_thickness),
+            Vec2::new(level_size.x / 2., half_thickness),
+        ),
+        LevelSide::East => (
+            Vec2::new(level_size.x - half_thickness, level_size.y / 2.),
+            Vec2::new(half_thickness, level_size.y / 2.),
+        ),
+        LevelSide::West => (
+            Vec2::new(half_thickness, level_size.y / 2.),
+            Vec2::new(half_thickness, level_size.y / 2.),
+        ),
+    }
+}
+
+/// Returns the sorted iids of the neighbors of `level` on `side`, looked up in `ldtk_asset`.
+pub(crate) fn level_neighbour_iids(
+    level: &Level,
+    ldtk_asset: Option<&LdtkAsset>,
+    side: LevelSide,
+) -> Vec<String> {
+    let mut neighbour_iids: Vec<String> = level
+        .neighbours
+        .iter()
+        .filter(|n| LevelSide::from_dir(&n.dir) == Some(side))
+        .filter_map(|n| {
+            ldtk_asset?
+                .project
+                .levels
+                .iter()
+                .find(|l| l.uid == n.level_uid)
+                .map(|l| l.iid.clone())
+        })
+        .filter(|iid| !iid.is_empty())
+        .collect();
+    neighbour_iids.sort();
+
+    neighbour_iids
+}
+
+/// Spawns heron [LevelBoundary] sensors along the edges of newly spawned levels, as their
+/// children.
+///
+/// *Requires the "heron" feature*
+///
+/// The sensors are heron's `RigidBody::Sensor`, with a cuboid `CollisionShape` in pixels placed
+/// relative to their level, so they follow it when it moves.
+///
+/// Does nothing unless the [LevelBoundarySettings] resource is inserted.
+#[cfg(feature = "heron")]
+pub fn spawn_heron_level_boundaries(
+    mut commands: Commands,
+    boundary_settings: Option<Res<LevelBoundarySettings>>,
+    level_query: Query<(Entity, &Handle<LdtkLevel>, &Parent), Changed<LayerTilesets>>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+) {
+    let boundary_settings = match boundary_settings {
+        Some(boundary_settings) => boundary_settings,
+        None => return,
+    };
+
+    for (level_entity, level_handle, parent) in level_query.iter() {
+        let level = match level_assets.get(level_handle) {
+            Some(ldtk_level) => &ldtk_level.level,
+            None => continue,
+        };
+
+        let ldtk_asset = ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|h| ldtk_assets.get(h));
+
+        let level_size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+
+        commands.entity(level_entity).with_children(|commands| {
+            for side in LevelSide::ALL {
+                let (center, half_extents) =
+                    level_boundary_cuboid(level_size, side, boundary_settings.thickness);
+
+                commands
+                    .spawn()
+                    .insert(Transform::from_translation(center.extend(0.)))
+                    .insert(GlobalTransform::default())
+                    .insert(RigidBody::Sensor)
+                    .insert(CollisionShape::Cuboid {
+                        half_extends: half_extents.extend(0.),
+                        border_radius: None,
+                    })
+                    .insert(LevelBoundary {
+                        side,
+                        neighbour_iids: level_neighbour_iids(level, ldtk_asset, side),
+                    });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ldtk::FieldInstance;
+
+    #[test]
+    fn test_level_physics_from_level() {
+        let mut level = Level {
+            uid: 4,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            LevelPhysics::from(&level),
+            LevelPhysics {
+                level_uid: Some(4),
+                gravity_scale: 1.,
+            }
+        );
+
+        level.field_instances.push(FieldInstance {
+            identifier: LEVEL_GRAVITY_SCALE_FIELD.to_string(),
+            field_instance_type: "Float".to_string(),
+            value: FieldValue::Float(Some(0.25)),
+            def_uid: 0,
+            real_editor_values: Vec::new(),
+        });
+
+        assert_eq!(LevelPhysics::from(&level).gravity_scale, 0.25);
+    }
+
+    #[test]
+    fn test_level_boundary_cuboid() {
+        let level_size = Vec2::new(64., 32.);
+
+        assert_eq!(
+            level_boundary_cuboid(level_size, LevelSide::North, 2.),
+            (Vec2::new(32., 31.), Vec2::new(32., 1.))
+        );
+        assert_eq!(
+            level_boundary_cuboid(level_size, LevelSide::West, 2.),
+            (Vec2::new(1., 16.), Vec2::new(1., 16.))
+        );
+        assert_eq!(LevelSide::from_dir("e"), Some(LevelSide::East));
+        assert_eq!(LevelSide::from_dir("x"), None);
+    }
+}
@@ -0,0 +1,918 @@
+//! Rapier colliders generated from IntGrid layers.
+//!
+//! *Requires the "rapier" feature*
+//!
+//! Most platformers need colliders for the solid parts of their levels, which are usually painted
+//! with IntGrid values.
+//! When the [RapierColliderSettings] resource is inserted, the plugin spawns `bevy_rapier2d`
+//! colliders for the configured values whenever a level spawns:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{prelude::*, rapier::*};
+//! use bevy_rapier2d::prelude::*;
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .add_plugin(LdtkPlugin)
+//!         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+//!         .insert_resource(RapierColliderSettings {
+//!             int_grid_values: [
+//!                 (1, IntGridColliderConfig::default()),
+//!                 (
+//!                     2,
+//!                     IntGridColliderConfig {
+//!                         shape: IntGridColliderShape::Outline,
+//!                         friction: 0.,
+//!                         ..Default::default()
+//!                     },
+//!                 ),
+//!             ]
+//!             .into_iter()
+//!             .collect(),
+//!         })
+//!         .run();
+//! }
+//! ```
+//!
+//! The colliders are children of their level, with an [IntGridCollider] component describing what
+//! they were generated from.
+//! Changing the value of [IntGridCell]s at runtime, like for destructible terrain, updates the
+//! colliders around them, see [update_int_grid_colliders].
+//! They aren't attached to a rigid body, so rapier treats them as static.
+//! For levels that move at runtime, inserting the [LevelRigidBodySettings] resource attaches them
+//! to a kinematic rigid body on their level instead, which follows the [GlobalTransform] of the
+//! level.
+//!
+//! Similarly, inserting the [LevelBoundarySettings] resource spawns sensors along the outer edges
+//! of levels, with a [LevelBoundary] component identifying the neighbors on that side.
+//! Room transitions can then be triggered by the intersection events of these sensors, instead of
+//! checking the position of the player against the bounds of the level.
+
+use crate::{
+    assets::{LdtkAsset, LdtkLevel},
+    collision::trace_int_grid_outlines,
+    components::{GridCoords, IntGridCell, IntGridChunk, LayerMetadata, LayerTilesets},
+    ldtk::{LayerInstance, Type},
+    merged_shapes::{merge_int_grid_rects, GridRect},
+    physics::{
+        level_boundary_cuboid, level_neighbour_iids, LevelBoundary, LevelBoundarySettings,
+        LevelRigidBody, LevelRigidBodySettings, LevelSide,
+    },
+    resources::LevelEvent,
+};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// The shape of the colliders generated for an IntGrid value.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum IntGridColliderShape {
+    /// One cuboid collider per cell.
+    Cells,
+    /// One cuboid collider per rectangle merged by [merge_int_grid_rects].
+    Rectangles,
+    /// One polyline collider per outline traced by [trace_int_grid_outlines].
+    ///
+    /// Characters don't get snagged on the seams between cells with these.
+    Outline,
+}
+
+impl Default for IntGridColliderShape {
+    fn default() -> Self {
+        IntGridColliderShape::Cells
+    }
+}
+
+/// The collider configuration of an IntGrid value.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct IntGridColliderConfig {
+    pub shape: IntGridColliderShape,
+    pub friction: f32,
+    pub restitution: f32,
+    /// Whether the colliders only detect intersections instead of generating contacts.
+    pub sensor: bool,
+}
+
+impl Default for IntGridColliderConfig {
+    fn default() -> Self {
+        IntGridColliderConfig {
+            shape: IntGridColliderShape::default(),
+            friction: 0.5,
+            restitution: 0.,
+            sensor: false,
+        }
+    }
+}
+
+/// Optional resource configuring the colliders generated for each IntGrid value.
+///
+/// Values without an entry don't get colliders.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RapierColliderSettings {
+    pub int_grid_values: HashMap<i32, IntGridColliderConfig>,
+}
+
+/// [Component] describing what an IntGrid collider spawned by the plugin was generated from.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct IntGridCollider {
+    pub layer_identifier: String,
+    pub value: i32,
+    /// The cells covered by the collider, or [None] for outlines.
+    pub rect: Option<GridRect>,
+}
+
+/// [Component] storing the IntGrid values that the colliders of a level were generated from,
+/// inserted on levels along with their colliders.
+///
+/// This is how [update_int_grid_colliders] finds out which cells changed.
+#[derive(Clone, PartialEq, Debug, Default, Component)]
+pub struct IntGridColliderLayers {
+    layers: HashMap<String, ColliderLayer>,
+}
+
+/// The IntGrid values of a layer, or of a region of it.
+#[derive(Clone, PartialEq, Debug, Default)]
+struct ColliderLayer {
+    int_grid_csv: Vec<i32>,
+    c_wid: i32,
+    c_hei: i32,
+    grid_size: i32,
+    /// Offset of the layer from the bottom-left corner of its level, in pixels.
+    offset: Vec2,
+    /// The cell of the whole layer at the bottom-left corner of this one, for regions of layers.
+    origin: IVec2,
+}
+
+impl From<&LayerInstance> for ColliderLayer {
+    fn from(layer_instance: &LayerInstance) -> Self {
+        ColliderLayer {
+            int_grid_csv: layer_instance.int_grid_csv.clone(),
+            c_wid: layer_instance.c_wid,
+            c_hei: layer_instance.c_hei,
+            grid_size: layer_instance.grid_size,
+            offset: Vec2::new(
+                layer_instance.px_total_offset_x as f32,
+                -layer_instance.px_total_offset_y as f32,
+            ),
+            origin: IVec2::ZERO,
+        }
+    }
+}
+
+impl ColliderLayer {
+    /// Returns the index in `int_grid_csv` of a cell, with the origin at the bottom-left cell.
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        if cell.x < 0 || cell.y < 0 || cell.x >= self.c_wid || cell.y >= self.c_hei {
+            None
+        } else {
+            Some(((self.c_hei - 1 - cell.y) * self.c_wid + cell.x) as usize)
+        }
+    }
+
+    /// Returns the values of the `cells` inside `region`, as a layer the size of the region whose
+    /// other cells are empty.
+    fn masked(&self, region: GridRect, cells: &HashSet<IVec2>) -> ColliderLayer {
+        let size = region.size();
+        let mut masked = ColliderLayer {
+            int_grid_csv: vec![0; (size.x * size.y).max(0) as usize],
+            c_wid: size.x,
+            c_hei: size.y,
+            grid_size: self.grid_size,
+            offset: self.offset + region.min.as_vec2() * self.grid_size as f32,
+            origin: self.origin + region.min,
+        };
+
+        for cell in cells {
+            if let (Some(i), Some(masked_i)) = (self.index(*cell), masked.index(*cell - region.min))
+            {
+                masked.int_grid_csv[masked_i] = self.int_grid_csv[i];
+            }
+        }
+
+        masked
+    }
+
+    /// Returns the shapes of the colliders of an IntGrid value, along with the cells of the whole
+    /// layer they cover.
+    fn collider_shapes(
+        &self,
+        value: i32,
+        shape: IntGridColliderShape,
+    ) -> Vec<(Option<GridRect>, LayerColliderShape)> {
+        let grid_size = self.grid_size as f32;
+
+        let cuboid = |rect: GridRect| {
+            (
+                Some(GridRect {
+                    min: rect.min + self.origin,
+                    max: rect.max + self.origin,
+                }),
+                LayerColliderShape::Cuboid {
+                    center: rect.center() * grid_size,
+                    half_extents: rect.size().as_vec2() * grid_size / 2.,
+                },
+            )
+        };
+
+        match shape {
+            IntGridColliderShape::Cells => self
+                .int_grid_csv
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| **v == value)
+                .map(|(i, _)| {
+                    let cell = IVec2::new(
+                        i as i32 % self.c_wid,
+                        self.c_hei - 1 - i as i32 / self.c_wid,
+                    );
+
+                    cuboid(GridRect {
+                        min: cell,
+                        max: cell + IVec2::ONE,
+                    })
+                })
+                .collect(),
+            IntGridColliderShape::Rectangles => {
+                merge_int_grid_rects(&self.int_grid_csv, self.c_wid, self.c_hei, |v| v == value)
+                    .into_iter()
+                    .map(|rect| cuboid(rect.shape))
+                    .collect()
+            }
+            IntGridColliderShape::Outline => {
+                trace_int_grid_outlines(&self.int_grid_csv, self.c_wid, self.c_hei, |v| v == value)
+                    .into_iter()
+                    .map(|outline| {
+                        let mut points: Vec<Vec2> =
+                            outline.iter().map(|p| p.as_vec2() * grid_size).collect();
+                        points.extend(points.first().copied());
+                        (None, LayerColliderShape::Polyline(points))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A collider shape, with its points in pixels relative to the bottom-left corner of its layer.
+#[derive(Clone, PartialEq, Debug)]
+enum LayerColliderShape {
+    Cuboid { center: Vec2, half_extents: Vec2 },
+    Polyline(Vec<Vec2>),
+}
+
+/// Returns the isometry of the rigid body of a level with the given [GlobalTransform].
+fn level_isometry(level_transform: &GlobalTransform, physics_scale: f32) -> Isometry<Real> {
+    let translation = level_transform.translation.truncate() / physics_scale;
+    let (angle, _, _) = level_transform.rotation.to_euler(EulerRot::ZYX);
+
+    Isometry::new(Vector::new(translation.x, translation.y), angle)
+}
+
+/// Places a point in pixels relative to the bottom-left corner of a level into physics space.
+///
+/// Points of colliders attached to a [LevelRigidBody] stay relative to the level, only scaled by
+/// it, while the others are placed in world space.
+fn level_point_to_physics(
+    level_transform: &GlobalTransform,
+    point: Vec2,
+    level_body: bool,
+    physics_scale: f32,
+) -> Vec2 {
+    if level_body {
+        point * level_transform.scale.truncate() / physics_scale
+    } else {
+        level_transform.mul_vec3(point.extend(0.)).truncate() / physics_scale
+    }
+}
+
+/// Returns the [ColliderParentComponent] attaching a collider at `position` to the rigid body of
+/// `level_entity`.
+fn level_collider_parent(level_entity: Entity, position: Vec2) -> ColliderParentComponent {
+    ColliderParent {
+        handle: level_entity.handle(),
+        pos_wrt_parent: Isometry::translation(position.x, position.y),
+    }
+    .into()
+}
+
+/// Level entities that have finished spawning, waiting for the [LevelEvent::Transformed] event of
+/// their level.
+///
+/// [LevelEvent]s only carry the uid of their level, which is shared by the copies of a level in
+/// other worlds loading the same project, so the systems of this module only process the levels
+/// whose [LayerTilesets] have changed since.
+#[derive(Debug, Default)]
+pub struct TransformedLevels {
+    pending: HashMap<Entity, i32>,
+}
+
+impl TransformedLevels {
+    /// Records the newly `spawned_levels` with their uid, and returns the recorded levels whose
+    /// [LevelEvent::Transformed] event is in `level_events`.
+    fn update(
+        &mut self,
+        spawned_levels: impl IntoIterator<Item = (Entity, i32)>,
+        level_events: &mut EventReader<LevelEvent>,
+    ) -> Vec<Entity> {
+        self.pending.extend(spawned_levels);
+
+        let transformed_uids: HashSet<i32> = level_events
+            .iter()
+            .filter_map(|e| match e {
+                LevelEvent::Transformed(uid) => Some(*uid),
+                _ => None,
+            })
+            .collect();
+
+        if transformed_uids.is_empty() {
+            return Vec::new();
+        }
+
+        let mut transformed_levels = Vec::new();
+        self.pending.retain(|level_entity, uid| {
+            if transformed_uids.contains(uid) {
+                transformed_levels.push(*level_entity);
+                false
+            } else {
+                true
+            }
+        });
+
+        transformed_levels
+    }
+}
+
+/// Returns the entities and uids of the levels that have just finished spawning.
+fn spawned_levels<'a>(
+    spawned_level_query: &'a Query<(Entity, &Handle<LdtkLevel>), Changed<LayerTilesets>>,
+    level_assets: &'a Assets<LdtkLevel>,
+) -> impl Iterator<Item = (Entity, i32)> + 'a {
+    spawned_level_query
+        .iter()
+        .filter_map(|(level_entity, level_handle)| {
+            Some((level_entity, level_assets.get(level_handle)?.level.uid))
+        })
+}
+
+/// Inserts a kinematic rigid body, and a [LevelRigidBody] component, on newly spawned levels.
+///
+/// Like colliders, the bodies are inserted on [LevelEvent::Transformed].
+///
+/// Does nothing unless the [LevelRigidBodySettings] and [RapierConfiguration] resources are
+/// inserted.
+#[allow(clippy::too_many_arguments)]
+pub fn insert_level_rigid_bodies(
+    mut commands: Commands,
+    body_settings: Option<Res<LevelRigidBodySettings>>,
+    rapier_config: Option<Res<RapierConfiguration>>,
+    mut level_events: EventReader<LevelEvent>,
+    mut transformed_levels: Local<TransformedLevels>,
+    spawned_level_query: Query<(Entity, &Handle<LdtkLevel>), Changed<LayerTilesets>>,
+    level_query: Query<&GlobalTransform>,
+    level_assets: Res<Assets<LdtkLevel>>,
+) {
+    let rapier_config = match (body_settings, rapier_config) {
+        (Some(_), Some(rapier_config)) => rapier_config,
+        _ => return,
+    };
+
+    let spawned_levels = spawned_levels(&spawned_level_query, &level_assets);
+    for level_entity in transformed_levels.update(spawned_levels, &mut level_events) {
+        let level_transform = match level_query.get(level_entity) {
+            Ok(level_transform) => level_transform,
+            Err(_) => continue,
+        };
+
+        commands
+            .entity(level_entity)
+            .insert_bundle(RigidBodyBundle {
+                body_type: RigidBodyType::KinematicPositionBased.into(),
+                position: level_isometry(level_transform, rapier_config.scale).into(),
+                ..Default::default()
+            })
+            .insert(LevelRigidBody);
+    }
+}
+
+/// Moves the rigid bodies of [LevelRigidBody] levels to the [GlobalTransform] of their level.
+///
+/// Changes of the scale of a level aren't applied to its colliders.
+pub fn sync_level_rigid_bodies(
+    rapier_config: Option<Res<RapierConfiguration>>,
+    mut level_query: Query<
+        (&GlobalTransform, &mut RigidBodyPositionComponent),
+        (With<LevelRigidBody>, Changed<GlobalTransform>),
+    >,
+) {
+    let rapier_config = match rapier_config {
+        Some(rapier_config) => rapier_config,
+        None => return,
+    };
+
+    for (level_transform, mut body_position) in level_query.iter_mut() {
+        body_position.next_position = level_isometry(level_transform, rapier_config.scale);
+    }
+}
+
+/// Where the colliders of a level are placed in physics space.
+struct LevelColliderPlacement<'a> {
+    level_entity: Entity,
+    level_transform: &'a GlobalTransform,
+    level_body: bool,
+    rapier_scale: f32,
+}
+
+/// Spawns the collider of a shape of an IntGrid `layer`.
+fn spawn_int_grid_collider(
+    commands: &mut ChildBuilder,
+    placement: &LevelColliderPlacement,
+    layer: &ColliderLayer,
+    layer_identifier: &str,
+    value: i32,
+    config: &IntGridColliderConfig,
+    (rect, shape): (Option<GridRect>, LayerColliderShape),
+) {
+    let to_physics = |point: Vec2| -> Vec2 {
+        level_point_to_physics(
+            placement.level_transform,
+            layer.offset + point,
+            placement.level_body,
+            placement.rapier_scale,
+        )
+    };
+    let physics_scale = placement.level_transform.scale.truncate() / placement.rapier_scale;
+
+    let (shape, position) = match shape {
+        LayerColliderShape::Cuboid {
+            center,
+            half_extents,
+        } => (
+            ColliderShape::cuboid(
+                (half_extents * physics_scale).x.abs(),
+                (half_extents * physics_scale).y.abs(),
+            ),
+            to_physics(center),
+        ),
+        LayerColliderShape::Polyline(points) => (
+            ColliderShape::polyline(
+                points
+                    .into_iter()
+                    .map(|p| {
+                        let p = to_physics(p);
+                        Point::new(p.x, p.y)
+                    })
+                    .collect(),
+                None,
+            ),
+            Vec2::ZERO,
+        ),
+    };
+
+    let mut collider = commands.spawn_bundle(ColliderBundle {
+        collider_type: if config.sensor {
+            ColliderType::Sensor
+        } else {
+            ColliderType::Solid
+        }
+        .into(),
+        shape: shape.into(),
+        position: position.into(),
+        material: ColliderMaterial {
+            friction: config.friction,
+            restitution: config.restitution,
+            ..Default::default()
+        }
+        .into(),
+        ..Default::default()
+    });
+
+    collider.insert(IntGridCollider {
+        layer_identifier: layer_identifier.to_string(),
+        value,
+        rect,
+    });
+
+    if placement.level_body {
+        collider.insert(level_collider_parent(placement.level_entity, position));
+    }
+}
+
+/// Spawns rapier colliders for the configured IntGrid values of newly spawned levels, as their
+/// children.
+///
+/// Colliders are spawned on [LevelEvent::Transformed], when the [GlobalTransform] of the level is
+/// up to date, and are placed in world space according to [RapierConfiguration::scale].
+/// Like the transform of the level itself, they don't follow levels that are moved afterwards,
+/// unless the [LevelRigidBodySettings] resource is inserted, in which case they're attached to the
+/// rigid body of their level.
+///
+/// Does nothing unless the [RapierColliderSettings] and [RapierConfiguration] resources are
+/// inserted.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_int_grid_colliders(
+    mut commands: Commands,
+    collider_settings: Option<Res<RapierColliderSettings>>,
+    body_settings: Option<Res<LevelRigidBodySettings>>,
+    rapier_config: Option<Res<RapierConfiguration>>,
+    mut level_events: EventReader<LevelEvent>,
+    mut transformed_levels: Local<TransformedLevels>,
+    spawned_level_query: Query<(Entity, &Handle<LdtkLevel>), Changed<LayerTilesets>>,
+    level_query: Query<(&Handle<LdtkLevel>, &GlobalTransform)>,
+    level_assets: Res<Assets<LdtkLevel>>,
+) {
+    let (collider_settings, rapier_config) = match (collider_settings, rapier_config) {
+        (Some(collider_settings), Some(rapier_config)) => (collider_settings, rapier_config),
+        _ => return,
+    };
+
+    let spawned_levels = spawned_levels(&spawned_level_query, &level_assets);
+    for level_entity in transformed_levels.update(spawned_levels, &mut level_events) {
+        let (level_handle, level_transform) = match level_query.get(level_entity) {
+            Ok(level) => level,
+            Err(_) => continue,
+        };
+
+        let level = match level_assets.get(level_handle) {
+            Some(ldtk_level) => &ldtk_level.level,
+            None => continue,
+        };
+
+        let placement = LevelColliderPlacement {
+            level_entity,
+            level_transform,
+            level_body: body_settings.is_some(),
+            rapier_scale: rapier_config.scale,
+        };
+
+        let mut collider_layers = IntGridColliderLayers::default();
+
+        for layer_instance in level.layer_instances.iter().flatten() {
+            if layer_instance.layer_instance_type != Type::IntGrid {
+                continue;
+            }
+
+            let layer = ColliderLayer::from(layer_instance);
+            let values: HashSet<i32> = layer.int_grid_csv.iter().copied().collect();
+
+            commands.entity(level_entity).with_children(|commands| {
+                for value in values {
+                    let config = match collider_settings.int_grid_values.get(&value) {
+                        Some(config) => config,
+                        None => continue,
+                    };
+
+                    for shape in layer.collider_shapes(value, config.shape) {
+                        spawn_int_grid_collider(
+                            commands,
+                            &placement,
+                            &layer,
+                            &layer_instance.identifier,
+                            value,
+                            config,
+                            shape,
+                        );
+                    }
+                }
+            });
+
+            collider_layers
+                .layers
+                .insert(layer_instance.identifier.clone(), layer);
+        }
+
+        commands.entity(level_entity).insert(collider_layers);
+    }
+}
+
+/// Updates the IntGrid colliders of levels when the values of their [IntGridCell]s are changed at
+/// runtime, like for destructible terrain.
+///
+/// Only the colliders covering the changed cells are rebuilt:
+/// - [IntGridColliderShape::Cells] and [IntGridColliderShape::Rectangles] colliders containing a
+///   changed cell are despawned, and the cells they covered are merged again on their own, so
+///   the cost depends on the size of these colliders rather than the size of the level.
+/// - [IntGridColliderShape::Outline] colliders are traced again for the old and new values of the
+///   changed cells, on their layer only.
+///
+/// Cells promoted to entities aren't children of their layer, so changing them has no effect.
+///
+/// Does nothing unless the [RapierColliderSettings] and [RapierConfiguration] resources are
+/// inserted.
+#[allow(clippy::too_many_arguments)]
+pub fn update_int_grid_colliders(
+    mut commands: Commands,
+    collider_settings: Option<Res<RapierColliderSettings>>,
+    body_settings: Option<Res<LevelRigidBodySettings>>,
+    rapier_config: Option<Res<RapierConfiguration>>,
+    cell_query: Query<(&IntGridCell, &GridCoords, &Parent), Changed<IntGridCell>>,
+    chunk_query: Query<&Parent, With<IntGridChunk>>,
+    layer_query: Query<(&LayerMetadata, &Parent)>,
+    mut level_query: Query<(&GlobalTransform, &mut IntGridColliderLayers)>,
+    collider_query: Query<(Entity, &IntGridCollider, &Parent)>,
+) {
+    let (collider_settings, rapier_config) = match (collider_settings, rapier_config) {
+        (Some(collider_settings), Some(rapier_config)) => (collider_settings, rapier_config),
+        _ => return,
+    };
+
+    let mut changed_cells: HashMap<(Entity, String), Vec<(IVec2, i32)>> = HashMap::new();
+    for (cell, grid_coords, chunk) in cell_query.iter() {
+        let layer_entity = match chunk_query.get(chunk.0) {
+            Ok(layer_entity) => layer_entity.0,
+            Err(_) => continue,
+        };
+
+        if let Ok((layer_metadata, level_entity)) = layer_query.get(layer_entity) {
+            changed_cells
+                .entry((level_entity.0, layer_metadata.identifier.clone()))
+                .or_default()
+                .push((IVec2::from(*grid_coords), cell.value));
+        }
+    }
+
+    for ((level_entity, layer_identifier), cells) in changed_cells {
+        let (level_transform, mut collider_layers) = match level_query.get_mut(level_entity) {
+            Ok(level) => level,
+            Err(_) => continue,
+        };
+
+        let layer = match collider_layers.layers.get_mut(&layer_identifier) {
+            Some(layer) => layer,
+            None => continue,
+        };
+
+        // Newly spawned cells are "changed" too, but match the values the colliders were
+        // generated from
+        let mut changed = Vec::new();
+        let mut changed_values = HashSet::new();
+        for (cell, value) in cells {
+            if let Some(i) = layer.index(cell) {
+                let old_value = layer.int_grid_csv[i];
+                if old_value != value {
+                    layer.int_grid_csv[i] = value;
+                    changed.push(cell);
+                    changed_values.extend([old_value, value]);
+                }
+            }
+        }
+
+        if changed.is_empty() {
+            continue;
+        }
+
+        let mut freed_cells: HashSet<IVec2> = changed.iter().copied().collect();
+        for (collider_entity, collider, parent) in collider_query.iter() {
+            if parent.0 != level_entity || collider.layer_identifier != layer_identifier {
+                continue;
+            }
+
+            match collider.rect {
+                Some(rect) if changed.iter().any(|cell| rect.contains(*cell)) => {
+                    freed_cells.extend(rect.cells());
+                }
+                None if changed_values.contains(&collider.value) => (),
+                _ => continue,
+            }
+
+            commands.entity(collider_entity).despawn_recursive();
+        }
+
+        let region = GridRect {
+            min: freed_cells
+                .iter()
+                .fold(IVec2::splat(i32::MAX), |a, c| a.min(*c)),
+            max: freed_cells
+                .iter()
+                .fold(IVec2::splat(i32::MIN), |a, c| a.max(*c))
+                + IVec2::ONE,
+        };
+        let freed_layer = layer.masked(region, &freed_cells);
+        let freed_values: HashSet<i32> = freed_layer.int_grid_csv.iter().copied().collect();
+
+        let placement = LevelColliderPlacement {
+            level_entity,
+            level_transform,
+            level_body: body_settings.is_some(),
+            rapier_scale: rapier_config.scale,
+        };
+
+        commands.entity(level_entity).with_children(|commands| {
+            for (value, config) in collider_settings.int_grid_values.iter() {
+                let (shapes_layer, shapes) = match config.shape {
+                    IntGridColliderShape::Outline if changed_values.contains(value) => {
+                        (&*layer, layer.collider_shapes(*value, config.shape))
+                    }
+                    IntGridColliderShape::Cells | IntGridColliderShape::Rectangles
+                        if freed_values.contains(value) =>
+                    {
+                        (
+                            &freed_layer,
+                            freed_layer.collider_shapes(*value, config.shape),
+                        )
+                    }
+                    _ => continue,
+                };
+
+                for shape in shapes {
+                    spawn_int_grid_collider(
+                        commands,
+                        &placement,
+                        shapes_layer,
+                        &layer_identifier,
+                        *value,
+                        config,
+                        shape,
+                    );
+                }
+            }
+        });
+    }
+}
+
+/// Spawns [LevelBoundary] sensors along the edges of newly spawned levels, as their children.
+///
+/// Like [spawn_int_grid_colliders], the sensors are spawned on [LevelEvent::Transformed], placed
+/// according to [RapierConfiguration::scale], and attached to the rigid body of their level when
+/// the [LevelRigidBodySettings] resource is inserted.
+///
+/// Does nothing unless the [LevelBoundarySettings] and [RapierConfiguration] resources are
+/// inserted.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_level_boundaries(
+    mut commands: Commands,
+    boundary_settings: Option<Res<LevelBoundarySettings>>,
+    body_settings: Option<Res<LevelRigidBodySettings>>,
+    rapier_config: Option<Res<RapierConfiguration>>,
+    mut level_events: EventReader<LevelEvent>,
+    mut transformed_levels: Local<TransformedLevels>,
+    spawned_level_query: Query<(Entity, &Handle<LdtkLevel>), Changed<LayerTilesets>>,
+    level_query: Query<(&Handle<LdtkLevel>, &GlobalTransform, &Parent)>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+) {
+    let (boundary_settings, rapier_config) = match (boundary_settings, rapier_config) {
+        (Some(boundary_settings), Some(rapier_config)) => (boundary_settings, rapier_config),
+        _ => return,
+    };
+
+    let spawned_levels = spawned_levels(&spawned_level_query, &level_assets);
+    for level_entity in transformed_levels.update(spawned_levels, &mut level_events) {
+        let (level_handle, level_transform, parent) = match level_query.get(level_entity) {
+            Ok(level) => level,
+            Err(_) => continue,
+        };
+
+        let level = match level_assets.get(level_handle) {
+            Some(ldtk_level) => &ldtk_level.level,
+            None => continue,
+        };
+
+        let ldtk_asset = ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|h| ldtk_assets.get(h));
+
+        let level_size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+        let level_body = body_settings.is_some();
+        let physics_scale = level_transform.scale.truncate() / rapier_config.scale;
+
+        commands.entity(level_entity).with_children(|commands| {
+            for side in LevelSide::ALL {
+                let neighbour_iids = level_neighbour_iids(level, ldtk_asset, side);
+
+                let (center, half_extents) =
+                    level_boundary_cuboid(level_size, side, boundary_settings.thickness);
+                let position = level_point_to_physics(
+                    level_transform,
+                    center,
+                    level_body,
+                    rapier_config.scale,
+                );
+
+                let mut sensor = commands.spawn_bundle(ColliderBundle {
+                    collider_type: ColliderType::Sensor.into(),
+                    shape: ColliderShape::cuboid(
+                        (half_extents * physics_scale).x.abs(),
+                        (half_extents * physics_scale).y.abs(),
+                    )
+                    .into(),
+                    position: position.into(),
+                    flags: ColliderFlags {
+                        active_events: ActiveEvents::INTERSECTION_EVENTS,
+                        ..Default::default()
+                    }
+                    .into(),
+                    ..Default::default()
+                });
+
+                sensor.insert(LevelBoundary {
+                    side,
+                    neighbour_iids,
+                });
+
+                if level_body {
+                    sensor.insert(level_collider_parent(level_entity, position));
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::LayerInstanceBuilder;
+
+    #[test]
+    fn test_collider_layer_shapes() {
+        let layer = ColliderLayer::from(
+            &LayerInstanceBuilder::new("Collisions", Type::IntGrid, 2, 2, 16)
+                .int_grid_csv(vec![1, 0, 1, 1])
+                .build(),
+        );
+
+        let collider_shapes = |layer: &ColliderLayer, shape| -> Vec<LayerColliderShape> {
+            layer
+                .collider_shapes(1, shape)
+                .into_iter()
+                .map(|(_, shape)| shape)
+                .collect()
+        };
+
+        assert_eq!(
+            collider_shapes(&layer, IntGridColliderShape::Cells),
+            vec![
+                LayerColliderShape::Cuboid {
+                    center: Vec2::new(8., 24.),
+                    half_extents: Vec2::splat(8.),
+                },
+                LayerColliderShape::Cuboid {
+                    center: Vec2::new(8., 8.),
+                    half_extents: Vec2::splat(8.),
+                },
+                LayerColliderShape::Cuboid {
+                    center: Vec2::new(24., 8.),
+                    half_extents: Vec2::splat(8.),
+                },
+            ]
+        );
+
+        assert_eq!(
+            collider_shapes(&layer, IntGridColliderShape::Rectangles),
+            vec![
+                LayerColliderShape::Cuboid {
+                    center: Vec2::new(16., 8.),
+                    half_extents: Vec2::new(16., 8.),
+                },
+                LayerColliderShape::Cuboid {
+                    center: Vec2::new(8., 24.),
+                    half_extents: Vec2::splat(8.),
+                },
+            ]
+        );
+
+        match &collider_shapes(&layer, IntGridColliderShape::Outline)[..] {
+            [LayerColliderShape::Polyline(points)] => {
+                assert_eq!(points.len(), 7);
+                assert_eq!(points.first(), points.last());
+            }
+            shapes => panic!("expected a single polyline, got {:?}", shapes),
+        }
+
+        let region = GridRect {
+            min: IVec2::new(1, 0),
+            max: IVec2::new(2, 1),
+        };
+        let masked = layer.masked(region, &HashSet::from([IVec2::new(1, 0)]));
+        assert_eq!(masked.int_grid_csv, vec![1]);
+        assert_eq!(
+            masked.collider_shapes(1, IntGridColliderShape::Rectangles),
+            vec![(
+                Some(region),
+                LayerColliderShape::Cuboid {
+                    center: Vec2::splat(8.),
+                    half_extents: Vec2::splat(8.),
+                }
+            )]
+        );
+        assert_eq!(masked.offset, Vec2::new(16., 0.));
+    }
+
+    #[test]
+    fn test_level_point_to_physics() {
+        let level_transform = GlobalTransform::from_xyz(100., 50., 0.)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2))
+            .with_scale(Vec3::splat(2.));
+        let point = Vec2::new(10., 0.);
+
+        assert_eq!(
+            level_point_to_physics(&level_transform, point, true, 10.),
+            Vec2::new(2., 0.)
+        );
+
+        let world_point = level_point_to_physics(&level_transform, point, false, 10.);
+        assert!((world_point - Vec2::new(10., 7.)).length() < 1e-4);
+    }
+}
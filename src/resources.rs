@@ -1,12 +1,25 @@
 //! Resources and events used by the plugin.
 
-use crate::ldtk::Level;
+use crate::{
+    assets::{find_entities_in_levels, LdtkAsset},
+    ldtk::Level,
+};
+use bevy::{
+    prelude::{Entity, Handle, Image, Vec2},
+    render::render_resource::FilterMode,
+    utils::Duration,
+};
+use bevy_ecs_tilemap::prelude::{ChunkSize, TilemapMeshType};
+use std::collections::{BTreeSet, HashMap};
 
 #[allow(unused_imports)]
 use bevy::prelude::GlobalTransform;
 
 #[allow(unused_imports)]
-use crate::components::{LdtkWorldBundle, LevelSet};
+use crate::components::{
+    ChunkActivationTarget, DespawnPolicy, EntityIid, EntityInstance, InactiveChunk, IntGridCell,
+    IntGridChunk, LdtkWorldBundle, LevelSet, LevelStreamingTarget, PendingDespawn, SwappedOutLevel,
+};
 
 /// Resource for choosing which level(s) to spawn.
 ///
@@ -24,6 +37,11 @@ pub enum LevelSelection {
     Index(usize),
     /// Spawn level with the given level `uid`.
     Uid(i32),
+    /// Spawn level with the given level `iid`.
+    ///
+    /// Unlike the other variants, the `iid` of a level doesn't change when levels are reordered
+    /// or renamed in LDtk, making it the most robust way to reference levels, e.g. in save files.
+    Iid(String),
 }
 
 impl Default for LevelSelection {
@@ -38,12 +56,217 @@ impl LevelSelection {
             LevelSelection::Identifier(s) => *s == level.identifier,
             LevelSelection::Index(i) => *i == *index,
             LevelSelection::Uid(u) => *u == level.uid,
+            LevelSelection::Iid(i) => !level.iid.is_empty() && *i == level.iid,
+        }
+    }
+}
+
+/// A level visited by the player, see [LevelHistory].
+#[derive(Clone, PartialEq, Debug)]
+pub struct LevelHistoryEntry {
+    pub level: LevelSelection,
+    /// Where the player entered the level, if recorded with [LevelHistory::set_entry_position].
+    pub entry_position: Option<Vec2>,
+}
+
+/// Optional resource keeping track of previously selected levels, for features like returning to
+/// the previous room or backtracking through doors.
+///
+/// When inserted alongside [LevelSelection], the plugin records every change of the selection.
+/// Games can record where the player entered the current level with
+/// [LevelHistory::set_entry_position], and return to the previous level with
+/// [LevelHistory::go_back]:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// # #[derive(Component)]
+/// # struct Player;
+/// fn return_to_previous_room(
+///     input: Res<Input<KeyCode>>,
+///     mut level_history: ResMut<LevelHistory>,
+///     mut level_selection: ResMut<LevelSelection>,
+///     mut player_query: Query<&mut Transform, With<Player>>,
+/// ) {
+///     if input.just_pressed(KeyCode::Back) {
+///         if let Some(entry) = level_history.go_back(&mut level_selection) {
+///             if let (Some(position), Ok(mut transform)) =
+///                 (entry.entry_position, player_query.get_single_mut())
+///             {
+///                 transform.translation = position.extend(transform.translation.z);
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LevelHistory {
+    /// Maximum number of previous levels kept, dropping the oldest ones first.
+    ///
+    /// `None` keeps all of them.
+    pub max_len: Option<usize>,
+    previous: Vec<LevelHistoryEntry>,
+    current: Option<LevelHistoryEntry>,
+}
+
+impl LevelHistory {
+    /// Returns the currently selected level.
+    pub fn current(&self) -> Option<&LevelHistoryEntry> {
+        self.current.as_ref()
+    }
+
+    /// Returns the previously selected levels, from oldest to most recent.
+    pub fn previous(&self) -> &[LevelHistoryEntry] {
+        &self.previous
+    }
+
+    /// Records where the player entered the currently selected level.
+    pub fn set_entry_position(&mut self, entry_position: Vec2) {
+        if let Some(current) = &mut self.current {
+            current.entry_position = Some(entry_position);
+        }
+    }
+
+    /// Records a newly selected level, pushing the current one onto the history.
+    ///
+    /// Called by the plugin when [LevelSelection] changes, and does nothing if the level is
+    /// already the current one.
+    pub fn record(&mut self, level: LevelSelection) {
+        if self.current.as_ref().map(|c| &c.level) == Some(&level) {
+            return;
+        }
+
+        if let Some(current) = self.current.take() {
+            self.previous.push(current);
+
+            if let Some(max_len) = self.max_len {
+                let excess = self.previous.len().saturating_sub(max_len);
+                self.previous.drain(..excess);
+            }
+        }
+
+        self.current = Some(LevelHistoryEntry {
+            level,
+            entry_position: None,
+        });
+    }
+
+    /// Pops the most recent previous level from the history and selects it again.
+    ///
+    /// Returns the popped entry, whose `entry_position` can be used to place the player, or
+    /// [None] if there is no previous level.
+    pub fn go_back(&mut self, level_selection: &mut LevelSelection) -> Option<LevelHistoryEntry> {
+        let entry = self.previous.pop()?;
+
+        *level_selection = entry.level.clone();
+        self.current = Some(entry.clone());
+
+        Some(entry)
+    }
+
+    /// Forgets all previous levels, keeping the current one.
+    pub fn clear(&mut self) {
+        self.previous.clear();
+    }
+}
+
+/// Optional resource counting the entities with some identifiers in each level and in the whole
+/// project, for things like completion percentages.
+///
+/// When inserted, the plugin counts the entities of the projects of all [LdtkWorldBundle]s, and
+/// counts them again whenever a project or level asset changes:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::prelude::*;
+/// fn setup_census(mut commands: Commands) {
+///     commands.insert_resource(Census::new(["Coin", "Secret"]));
+/// }
+///
+/// fn show_coin_count(census: Res<Census>, level_selection: Res<LevelSelection>) {
+///     if let LevelSelection::Uid(level_uid) = *level_selection {
+///         info!(
+///             "{} of the {} coins are in this level",
+///             census.count_in_level(level_uid, "Coin"),
+///             census.total("Coin")
+///         );
+///     }
+/// }
+/// ```
+/// The counts are of authored entities, so they don't change as entities are despawned.
+/// Levels are identified by their uid, since projects saved before LDtk 1.0 have no level iids.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Census {
+    identifiers: BTreeSet<String>,
+    levels: HashMap<i32, HashMap<String, usize>>,
+    totals: HashMap<String, usize>,
+}
+
+impl Census {
+    /// Creates a [Census] counting the entities with the given identifiers.
+    pub fn new(identifiers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Census {
+            identifiers: identifiers.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the entity identifiers that are counted.
+    pub fn identifiers(&self) -> &BTreeSet<String> {
+        &self.identifiers
+    }
+
+    /// Returns the number of entities with the given identifier in the whole project.
+    pub fn total(&self, identifier: &str) -> usize {
+        self.totals.get(identifier).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of entities with the given identifier in the level with the given uid.
+    pub fn count_in_level(&self, level_uid: i32, identifier: &str) -> usize {
+        self.levels
+            .get(&level_uid)
+            .and_then(|counts| counts.get(identifier))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Replaces the counts with those of the given levels.
+    ///
+    /// Called by the plugin, with the levels of [LdtkAsset::iter_levels_with_layers].
+    pub fn recount<'a>(&mut self, levels: impl IntoIterator<Item = &'a Level>) {
+        self.levels.clear();
+        self.totals.clear();
+
+        for level in levels {
+            for identifier in &self.identifiers {
+                let count = find_entities_in_levels([level], identifier).len();
+
+                *self
+                    .levels
+                    .entry(level.uid)
+                    .or_default()
+                    .entry(identifier.clone())
+                    .or_default() += count;
+                *self.totals.entry(identifier.clone()).or_default() += count;
+            }
         }
     }
 }
 
 /// Settings resource for the plugin.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+///
+/// Most settings are spawn-time settings, which only apply to levels as they spawn.
+/// Changing them triggers a [Respawn](crate::Respawn) of the spawned levels, see
+/// [LdtkSettings::requires_respawn].
+///
+/// The others are runtime-adjustable, and changes to them apply without respawning:
+/// - [LdtkSettings::load_level_neighbors]
+/// - [LdtkSettings::level_spawn_budget]
+/// - [LdtkSettings::layer_z]
+/// - [LdtkSettings::tileset_filter]
+/// - [LdtkSettings::clear_color_from_level]
+/// - [LdtkSettings::level_swap]
+///
+/// [LdtkSettings::tileset_extrusion] is applied when tilesets load instead.
+#[derive(Clone, PartialEq, Debug, Default)]
 pub struct LdtkSettings {
     /// Newly spawned levels will be spawned with translations like their location in the LDtk
     /// world.
@@ -53,13 +276,429 @@ pub struct LdtkSettings {
     /// When used with the [LevelSelection] resource, levels in the `__level_neighbors` list of
     /// the selected level will be spawned in addition to the selected level.
     ///
+    /// Neighbors are despawned when they stop being neighbors of the selected level.
+    /// Toggling this setting applies to the current selection immediately.
+    ///
     /// This is best used with [LdtkSettings::use_level_world_translations].
     pub load_level_neighbors: bool,
+    /// Number of frames over which the tiles of newly spawned levels fade in, from transparent to
+    /// opaque.
+    ///
+    /// Smooths the transition when levels stream in while the game is running, like with
+    /// [LdtkSettings::load_level_neighbors].
+    /// `0` disables fading, so levels appear at full opacity immediately.
+    pub level_fade_in_frames: u32,
+    /// Thresholds for the size of spawned levels.
+    ///
+    /// Exceeding them doesn't prevent levels from spawning, but adds warnings to their
+    /// [LevelSpawnReport].
+    pub level_budget: LevelBudget,
+    /// Time the plugin may spend spawning levels in each update.
+    ///
+    /// Levels that don't fit in the budget keep spawning in the following updates, layer by
+    /// layer, and [LevelEvent::Spawned] is sent once they're complete.
+    /// At least one layer is spawned per update, so large layers can still exceed the budget.
+    /// [None] spawns levels completely in the update their assets become available, which is the
+    /// default.
+    pub level_spawn_budget: Option<Duration>,
+    /// Explicit draw-order indices for layers, by layer identifier.
+    ///
+    /// By default, layers are drawn in the order they have in LDtk, with index `0` being the
+    /// bottom layer.
+    /// Layers in this map are moved to the given index instead, and drawn above any layer that
+    /// already has it, which resolves disputes between entity and tile layers without reordering
+    /// layers in the editor.
+    /// See [layer_draw_order](crate::utils::layer_draw_order) for more details.
+    pub layer_draw_order: HashMap<String, usize>,
+    /// The z translations of layers, relative to their level.
+    ///
+    /// Changing it also moves the layers and entities of spawned levels, see
+    /// [LayerZSlot](crate::LayerZSlot).
+    ///
+    /// See [LayerZ] for more details.
+    pub layer_z: LayerZ,
+    /// Newly spawned entities of Entities layers get a [YSort](crate::YSort) component, so entities
+    /// further down are drawn in front, like in top-down games.
+    ///
+    /// Their z ranges from the z of their layer at the top of the level, to half of the
+    /// [LayerZ::step] above it at the bottom, so they stay below the next layer.
+    /// Otherwise, all entities of a layer share its z.
+    pub y_sort_entities: bool,
+    /// Number of pixels each tile of the tilesets used by tile layers is extruded by, to prevent
+    /// bleeding between tiles under linear filtering or camera zoom.
+    ///
+    /// `1` or `2` is usually enough, and `0` disables extrusion.
+    /// Extruded copies of the tilesets are made when they load, or when this changes, see
+    /// [ExtrudedTilesets].
+    ///
+    /// *Only has an effect with the "atlas" feature*, since tiles stored in texture arrays
+    /// don't bleed into each other.
+    pub tileset_extrusion: u32,
+    /// Filter used when sampling the tileset images of LDtk projects, both when magnifying and
+    /// minifying them.
+    ///
+    /// Applied to tilesets when they load, and to every loaded tileset whenever it changes.
+    /// [None] leaves the filters of tileset images as they're loaded, which is the default.
+    /// Changing it back to [None] keeps the last filter applied until tilesets reload.
+    pub tileset_filter: Option<FilterMode>,
+    /// Newly spawned LDtk entities will be placed at the center of the grid cell containing their
+    /// pivot, instead of their exact pixel position.
+    ///
+    /// Either way, both positions are available on the entities as [GridCoords](crate::GridCoords)
+    /// and [PixelCoords](crate::PixelCoords) components.
+    pub snap_entities_to_grid: bool,
+    /// Newly spawned levels will get a [LevelBackground](crate::LevelBackground) sprite of their
+    /// background color, covering the whole level behind its layers.
+    pub level_background_color: bool,
+    /// Newly spawned levels will get a [LevelBackground](crate::LevelBackground) sprite of their
+    /// background image, if they have one, positioned, cropped and scaled like in the editor.
+    pub level_background_image: bool,
+    /// The [ClearColor] is kept in sync with the background color of the selected level, or with
+    /// the background color of the project when no level is selected, so the area outside of
+    /// levels looks like it does in the editor.
+    pub clear_color_from_level: bool,
+    /// How the cells of IntGrid layers without a tileset are spawned.
+    ///
+    /// See [IntGridCellSpawning] for more details.
+    pub int_grid_cell_spawning: IntGridCellSpawning,
+    /// How the tilemaps of tile layers are meshed, by layer identifier.
+    ///
+    /// Layers that aren't in this map use the default [LayerMeshSettings].
+    pub layer_mesh_settings: HashMap<String, LayerMeshSettings>,
+    /// When levels leaving the [LevelSet] are despawned.
+    ///
+    /// See [LevelSwap] for more details.
+    pub level_swap: LevelSwap,
+    /// Levels are spawned without any rendering work, for servers and tools that only need their
+    /// logic: IntGrid cells, entities, and their fields.
+    ///
+    /// No tilemaps are built, so Tile and AutoLayer layers aren't spawned, and the cells of IntGrid
+    /// layers are plain entities like with [IntGridCellSpawning::PlainEntities], even when the
+    /// layer has a tileset.
+    /// Level backgrounds aren't spawned either.
+    ///
+    /// If it's enabled when the [LdtkPlugin](crate::LdtkPlugin) is added, the tileset and
+    /// background images of LDtk projects aren't loaded at all.
+    /// So insert the settings before adding the plugin.
+    ///
+    /// The sprites of registered bundles are left at their defaults, as are their
+    /// `#[ldtk_tile_field]`s, and the layer quads of the "3d" feature aren't spawned.
+    ///
+    /// The plugin still uses the asset types of Bevy's rendering plugins, so those need to be
+    /// added, even though nothing is rendered.
+    pub headless: bool,
+}
+
+impl LdtkSettings {
+    /// Returns whether changing the settings from `previous` to these affects how levels spawn, so
+    /// spawned levels need a [Respawn](crate::Respawn) to reflect the change.
+    ///
+    /// Settings that only affect the spawning process, like [LdtkSettings::level_fade_in_frames]
+    /// and [LdtkSettings::level_budget], don't require a respawn, and neither do the
+    /// runtime-adjustable ones.
+    pub fn requires_respawn(&self, previous: &LdtkSettings) -> bool {
+        self.use_level_world_translations != previous.use_level_world_translations
+            || self.layer_draw_order != previous.layer_draw_order
+            || self.y_sort_entities != previous.y_sort_entities
+            || self.snap_entities_to_grid != previous.snap_entities_to_grid
+            || self.level_background_color != previous.level_background_color
+            || self.level_background_image != previous.level_background_image
+            || self.int_grid_cell_spawning != previous.int_grid_cell_spawning
+            || self.layer_mesh_settings != previous.layer_mesh_settings
+            || self.headless != previous.headless
+            || self.tileset_extrusion != previous.tileset_extrusion
+    }
+}
+
+/// When levels leaving the [LevelSet] are despawned, see [LdtkSettings::level_swap].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum LevelSwap {
+    /// Levels are despawned as soon as they leave the [LevelSet].
+    ///
+    /// Since the levels replacing them spawn at least one update later, or more if their assets
+    /// are still loading, nothing is shown in between.
+    Immediate,
+    /// Levels are kept with a [SwappedOutLevel] component until none of the levels of their world
+    /// are pending, then despawned in the same update as the last level to spawn.
+    ///
+    /// This avoids blank frames when changing the [LevelSelection], at the cost of both levels
+    /// existing for a while.
+    /// [LevelEvent::Despawned] is sent when swapped out levels actually despawn.
+    DoubleBuffered,
+}
+
+impl Default for LevelSwap {
+    fn default() -> Self {
+        LevelSwap::Immediate
+    }
+}
+
+/// The z translations of the layers of spawned levels, see [LdtkSettings::layer_z].
+///
+/// Layers are spaced `step` apart from `base`, in draw order, unless their identifier has an
+/// override.
+/// The default matches the ids of the tilemap layers, starting at `0` with a step of `1`, which
+/// leaves no room for other sprites between layers.
+/// A smaller step like `0.1`, or a base further from `0`, leaves headroom for interleaving them.
+///
+/// LDtk layers spawned as several tilemap layers, like auto-layers using several tilesets, use
+/// consecutive steps, except that they all share the z of their override.
+/// Entity layers share the z of the layer drawn above them.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LayerZ {
+    /// The z of the bottom layer.
+    pub base: f32,
+    /// The difference in z between consecutive layers.
+    pub step: f32,
+    /// The z of layers by identifier, overriding `base` and `step`.
+    pub overrides: HashMap<String, f32>,
+}
+
+impl Default for LayerZ {
+    fn default() -> Self {
+        LayerZ {
+            base: 0.,
+            step: 1.,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl LayerZ {
+    /// Returns the z of the layer with the given identifier and layer id.
+    pub fn z(&self, layer_identifier: &str, layer_id: u32) -> f32 {
+        match self.overrides.get(layer_identifier) {
+            Some(z) => *z,
+            None => self.base + self.step * layer_id as f32,
+        }
+    }
+}
+
+/// Size of the chunks of tilemap layers, and of the [IntGridChunk]s IntGrid cells are grouped in.
+pub const CHUNK_SIZE: ChunkSize = ChunkSize(32, 32);
+
+/// How the tilemap of a tile layer is split into chunks and meshed, see
+/// [LdtkSettings::layer_mesh_settings].
+///
+/// Each chunk is a single mesh, which is rebuilt whenever one of its tiles changes.
+/// Large chunks suit decorative layers that never change, since they're drawn in fewer batches,
+/// while small chunks keep edits cheap on frequently modified layers, like destructible terrain.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct LayerMeshSettings {
+    /// Size of the chunks in tiles, [CHUNK_SIZE] by default.
+    ///
+    /// The [IntGridChunk]s of IntGrid layers follow this size too.
+    pub chunk_size: ChunkSize,
+    /// The mesher used for the chunks, [TilemapMeshType::Square] by default.
+    ///
+    /// Since LDtk layers are square grids, other meshers are only useful with tilesets authored
+    /// for them.
+    pub mesh_type: TilemapMeshType,
+}
+
+impl Default for LayerMeshSettings {
+    fn default() -> Self {
+        LayerMeshSettings {
+            chunk_size: CHUNK_SIZE,
+            mesh_type: TilemapMeshType::Square,
+        }
+    }
+}
+
+/// How the cells of IntGrid layers without a tileset are spawned, see
+/// [LdtkSettings::int_grid_cell_spawning].
+///
+/// IntGrid layers with a tileset have visible tiles, so their cells are always tiles.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum IntGridCellSpawning {
+    /// Every non-zero cell is an invisible tile of the layer's tilemap, so it can be looked up
+    /// with `bevy_ecs_tilemap`'s APIs.
+    Tiles,
+    /// Cells are plain child entities of the layer's [IntGridChunk]s, without tilemap components.
+    ///
+    /// Saves the overhead of the tilemap for layers that are only used for their data.
+    PlainEntities,
+    /// Like [IntGridCellSpawning::PlainEntities], but cells whose value doesn't match any
+    /// registered [LdtkIntCell](crate::app::LdtkIntCell) aren't spawned at all.
+    ///
+    /// Best for giant maps where only a few values need entities.
+    RegisteredOnly,
+}
+
+impl Default for IntGridCellSpawning {
+    fn default() -> Self {
+        IntGridCellSpawning::Tiles
+    }
+}
+
+/// Maximum numbers of objects a level should spawn, to catch levels that would hitch low-end
+/// targets.
+///
+/// [None] means there is no limit, which is the default.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct LevelBudget {
+    /// Maximum number of tiles and IntGrid cells, across all layers of a level.
+    pub max_tiles: Option<usize>,
+    /// Maximum number of LDtk entities in a level.
+    pub max_entities: Option<usize>,
+}
+
+impl LevelBudget {
+    /// Checks the counts of a [LevelSpawnReport] against this budget.
+    pub fn check(&self, spawn_report: &LevelSpawnReport) -> Vec<BudgetWarning> {
+        let mut budget_warnings = Vec::new();
+
+        let tile_count = spawn_report.tile_count();
+        if let Some(max_tiles) = self.max_tiles.filter(|max| tile_count > *max) {
+            budget_warnings.push(BudgetWarning::TooManyTiles {
+                count: tile_count,
+                max: max_tiles,
+            });
+        }
+
+        let entity_count = spawn_report.entity_count();
+        if let Some(max_entities) = self.max_entities.filter(|max| entity_count > *max) {
+            budget_warnings.push(BudgetWarning::TooManyEntities {
+                count: entity_count,
+                max: max_entities,
+            });
+        }
+
+        budget_warnings
+    }
+}
+
+/// Event fired when a level hits the limits of the `u16` ids used by `bevy_ecs_tilemap`, so it
+/// couldn't be spawned completely.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum LevelSpawnError {
+    /// The `uid` of the level doesn't fit in the id of its tilemap, so the level wasn't spawned.
+    LevelUidOutOfRange { level_uid: i32 },
+    /// The level needs more tilemap layers than there are layer ids, so the given layer and the
+    /// layers above it weren't spawned.
+    TooManyLayers {
+        level_uid: i32,
+        layer_identifier: String,
+    },
+}
+
+/// A [LevelBudget] threshold exceeded by a level.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum BudgetWarning {
+    TooManyTiles { count: usize, max: usize },
+    TooManyEntities { count: usize, max: usize },
+}
+
+/// Optional resource for deactivating the chunks of large levels that are far from the
+/// [ChunkActivationTarget]s.
+///
+/// When inserted, tilemap chunks and [IntGridChunk]s whose centers are farther than `radius` from
+/// every target are marked with [InactiveChunk], and hidden.
+/// They are restored as soon as a target is within range again.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct ChunkActivationSettings {
+    /// Distance from a target, in world units, within which chunks are active.
+    pub radius: f32,
+}
+
+/// Optional resource for spawning the levels that are near the [LevelStreamingTarget]s, and
+/// despawning the others, using the layout of the LDtk world.
+///
+/// When inserted, the plugin replaces the [LevelSet] of every world with the levels whose
+/// rectangle is within `spawn_distance` of a target, keeping those already in it until they're
+/// farther than `despawn_distance`.
+/// Keep `despawn_distance` larger than `spawn_distance`, so levels don't flicker in and out when
+/// a target hovers around the limit.
+///
+/// This is meant for free-roaming GridVania projects, and takes the place of [LevelSelection], so
+/// both shouldn't be used at the same time.
+/// Levels are positioned according to their world coordinates, so
+/// [LdtkSettings::use_level_world_translations] should be enabled.
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_ecs_ldtk::prelude::*;
+///
+/// fn main() {
+///     App::new()
+///         .add_plugins(DefaultPlugins)
+///         .add_plugin(LdtkPlugin)
+///         .insert_resource(LdtkSettings {
+///             use_level_world_translations: true,
+///             ..Default::default()
+///         })
+///         .insert_resource(LevelStreamingSettings {
+///             spawn_distance: 256.,
+///             despawn_distance: 512.,
+///         })
+///         .add_startup_system(setup)
+///         .run();
+/// }
+///
+/// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     commands
+///         .spawn_bundle(OrthographicCameraBundle::new_2d())
+///         .insert(LevelStreamingTarget);
+///
+///     commands.spawn_bundle(LdtkWorldBundle {
+///         ldtk_handle: asset_server.load("my_project.ldtk"),
+///         ..Default::default()
+///     });
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct LevelStreamingSettings {
+    /// Distance from a target to a level's rectangle, in world units, within which the level is
+    /// spawned.
+    pub spawn_distance: f32,
+    /// Distance from every target to a level's rectangle, in world units, beyond which the level
+    /// is despawned.
+    pub despawn_distance: f32,
+}
+
+/// Resource associating tileset images with their extruded copies.
+///
+/// Maintained by the plugin when [LdtkSettings::tileset_extrusion] is used.
+/// Tile layers use the extruded copy of their tileset if it's available when they spawn, while
+/// entities keep using the original image.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ExtrudedTilesets {
+    pub tilesets: HashMap<Handle<Image>, ExtrudedTileset>,
+}
+
+/// An extruded copy of a tileset image, see [ExtrudedTilesets].
+///
+/// Tiles are laid out in the same order as the original, with no padding, and with a spacing of
+/// twice the extrusion.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ExtrudedTileset {
+    pub image: Handle<Image>,
+    pub extrusion: u32,
+    /// Size of the extruded image, in pixels.
+    pub size: Vec2,
+}
+
+/// Resource mapping the iids of spawned LDtk entities to their [Entity].
+///
+/// Maintained by the plugin from the [EntityIid] components of LDtk entities, so it's updated one
+/// update after entities spawn or despawn.
+/// Until then, the entities of despawned levels may still be returned, so check that they exist
+/// before using them.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct EntityIidMap {
+    pub entities: HashMap<String, Entity>,
+}
+
+impl EntityIidMap {
+    /// Returns the spawned [Entity] of the LDtk entity with the given iid.
+    pub fn get(&self, iid: &str) -> Option<Entity> {
+        self.entities.get(iid).copied()
+    }
 }
 
 /// Events fired by the plugin related to level spawning/despawning.
 ///
 /// Each variant stores the level's `uid` in LDtk.
+/// The stable `iid` of the level can be looked up with [LevelEvent::level_iid].
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum LevelEvent {
     /// Indicates that a level has been triggered to spawn, but hasn't been spawned yet.
@@ -78,3 +717,232 @@ pub enum LevelEvent {
     /// Indicates that a level has despawned.
     Despawned(i32),
 }
+
+impl LevelEvent {
+    /// Returns the `uid` of the level this event is about.
+    pub fn level_uid(&self) -> i32 {
+        match self {
+            LevelEvent::SpawnTriggered(uid)
+            | LevelEvent::Spawned(uid)
+            | LevelEvent::Transformed(uid)
+            | LevelEvent::Despawned(uid) => *uid,
+        }
+    }
+
+    /// Looks up the `iid` of the level this event is about in the given [LdtkAsset].
+    ///
+    /// Returns [None] if the level isn't in the asset, or if it was saved before LDtk introduced
+    /// iids.
+    pub fn level_iid<'a>(&self, ldtk_asset: &'a LdtkAsset) -> Option<&'a str> {
+        ldtk_asset
+            .get_level(&LevelSelection::Uid(self.level_uid()))
+            .map(|l| l.iid.as_str())
+            .filter(|iid| !iid.is_empty())
+    }
+}
+
+/// Event fired when an LDtk entity with [DespawnPolicy::AfterEvent] receives a [PendingDespawn]
+/// component, because its level has despawned.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct PendingDespawnEvent {
+    /// The entity waiting to be despawned.
+    pub entity: Entity,
+    /// The `uid` of the level the entity belonged to.
+    pub level_uid: i32,
+}
+
+/// Event fired when a level has spawned, summarizing what the plugin spawned for it.
+///
+/// This gives designers feedback about how their level was interpreted, like entities that were
+/// placed in LDtk but never registered to the app:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::LevelSpawnReport;
+/// fn log_spawn_reports(mut reports: EventReader<LevelSpawnReport>) {
+///     for report in reports.iter() {
+///         for identifier in &report.unmatched_entity_identifiers {
+///             warn!("{} has unregistered entity {}", report.level_identifier, identifier);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct LevelSpawnReport {
+    /// The `uid` of the level.
+    pub level_uid: i32,
+    /// The identifier of the level.
+    pub level_identifier: String,
+    /// Number of entities, IntGrid cells, and tiles spawned for each layer identifier.
+    pub layer_counts: HashMap<String, usize>,
+    /// Number of entities spawned for each LDtk entity identifier.
+    pub entity_counts: HashMap<String, usize>,
+    /// Identifiers of LDtk entities that didn't match any registration, so they were only spawned
+    /// with an [EntityInstance].
+    pub unmatched_entity_identifiers: BTreeSet<String>,
+    /// Layer identifiers and IntGrid values of cells that didn't match any registration, so they
+    /// were only spawned with an [IntGridCell].
+    pub unmatched_int_grid_values: BTreeSet<(String, i32)>,
+    /// Time spent spawning the level.
+    pub duration: Duration,
+    /// Thresholds of [LdtkSettings::level_budget] exceeded by the level.
+    pub budget_warnings: Vec<BudgetWarning>,
+    /// Limits of `bevy_ecs_tilemap` hit by the level, which prevented it from spawning
+    /// completely.
+    ///
+    /// Each of these is also sent as a [LevelSpawnError] event.
+    pub errors: Vec<LevelSpawnError>,
+}
+
+impl LevelSpawnReport {
+    /// Total number of LDtk entities spawned for the level.
+    pub fn entity_count(&self) -> usize {
+        self.entity_counts.values().sum()
+    }
+
+    /// Total number of tiles and IntGrid cells spawned for the level.
+    pub fn tile_count(&self) -> usize {
+        self.layer_counts.values().sum::<usize>() - self.entity_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_z() {
+        let mut layer_z = LayerZ::default();
+        assert_eq!(layer_z.z("Tiles", 3), 3.);
+
+        layer_z.base = 10.;
+        layer_z.step = 0.5;
+        layer_z.overrides.insert("Foreground".to_string(), 100.);
+        assert_eq!(layer_z.z("Tiles", 3), 11.5);
+        assert_eq!(layer_z.z("Foreground", 3), 100.);
+    }
+
+    #[test]
+    fn test_requires_respawn() {
+        let previous = LdtkSettings::default();
+
+        let runtime_change = LdtkSettings {
+            load_level_neighbors: true,
+            layer_z: LayerZ {
+                step: 0.1,
+                ..Default::default()
+            },
+            tileset_filter: Some(FilterMode::Linear),
+            ..Default::default()
+        };
+        assert!(!runtime_change.requires_respawn(&previous));
+
+        let spawn_time_change = LdtkSettings {
+            snap_entities_to_grid: true,
+            ..runtime_change.clone()
+        };
+        assert!(spawn_time_change.requires_respawn(&previous));
+        assert!(spawn_time_change.requires_respawn(&runtime_change));
+    }
+
+    #[test]
+    fn test_census() {
+        use crate::fixtures::LevelBuilder;
+
+        let entity = |identifier: &str| EntityInstance {
+            identifier: identifier.to_string(),
+            ..Default::default()
+        };
+
+        let levels = vec![
+            LevelBuilder::new(0)
+                .entity(entity("Coin"))
+                .entity(entity("Coin"))
+                .entity(entity("Secret"))
+                .entity(entity("Player"))
+                .build(),
+            LevelBuilder::new(1).entity(entity("Coin")).build(),
+        ];
+
+        let mut census = Census::new(["Coin", "Secret"]);
+        census.recount(&levels);
+
+        assert_eq!(census.total("Coin"), 3);
+        assert_eq!(census.total("Secret"), 1);
+        assert_eq!(census.total("Player"), 0);
+        // Levels saved before LDtk 1.0 have no iids, so they're told apart by uid
+        assert!(levels.iter().all(|level| level.iid.is_empty()));
+        assert_eq!(census.count_in_level(0, "Coin"), 2);
+        assert_eq!(census.count_in_level(1, "Coin"), 1);
+        assert_eq!(census.count_in_level(1, "Secret"), 0);
+        assert_eq!(census.count_in_level(2, "Coin"), 0);
+    }
+
+    #[test]
+    fn test_level_history() {
+        let mut level_history = LevelHistory {
+            max_len: Some(2),
+            ..Default::default()
+        };
+        let mut level_selection = LevelSelection::Index(0);
+
+        assert_eq!(level_history.go_back(&mut level_selection), None);
+
+        level_history.record(LevelSelection::Index(0));
+        level_history.set_entry_position(Vec2::new(1., 2.));
+        level_history.record(LevelSelection::Index(0));
+        level_history.record(LevelSelection::Index(1));
+        level_history.record(LevelSelection::Index(2));
+        level_history.record(LevelSelection::Index(3));
+
+        assert_eq!(level_history.previous().len(), 2);
+        assert_eq!(level_history.previous()[0].level, LevelSelection::Index(1));
+
+        level_history.max_len = None;
+        level_history.record(LevelSelection::Index(4));
+        level_history.record(LevelSelection::Index(0));
+        level_history.record(LevelSelection::Index(1));
+
+        assert_eq!(
+            level_history.go_back(&mut level_selection),
+            Some(LevelHistoryEntry {
+                level: LevelSelection::Index(0),
+                entry_position: None,
+            })
+        );
+        assert_eq!(level_selection, LevelSelection::Index(0));
+
+        // recording the selection changed by go_back doesn't push onto the history
+        level_history.record(level_selection.clone());
+        assert_eq!(level_history.previous().len(), 4);
+        assert_eq!(
+            level_history.current().map(|c| &c.level),
+            Some(&LevelSelection::Index(0))
+        );
+    }
+
+    #[test]
+    fn test_level_budget_check() {
+        let spawn_report = LevelSpawnReport {
+            layer_counts: HashMap::from([("Entities".to_string(), 3), ("Tiles".to_string(), 100)]),
+            entity_counts: HashMap::from([("Goblin".to_string(), 2), ("Door".to_string(), 1)]),
+            ..Default::default()
+        };
+
+        assert_eq!(spawn_report.tile_count(), 100);
+        assert_eq!(spawn_report.entity_count(), 3);
+
+        assert_eq!(LevelBudget::default().check(&spawn_report), Vec::new());
+
+        let level_budget = LevelBudget {
+            max_tiles: Some(50),
+            max_entities: Some(3),
+        };
+        assert_eq!(
+            level_budget.check(&spawn_report),
+            vec![BudgetWarning::TooManyTiles {
+                count: 100,
+                max: 50
+            }]
+        );
+    }
+}
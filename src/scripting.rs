@@ -0,0 +1,383 @@
+//! Data-driven entity behaviors through a scripting bridge.
+//!
+//! *Requires the "scripting" feature*
+//!
+//! When an LDtk entity has a `script` field, the plugin inserts an [LdtkScript] component on the
+//! spawned entity.
+//! A `FilePath` field is loaded as a [ScriptSource] asset, relative to the LDtk file like other
+//! `FilePath` fields, while a `String` field is used as inline source code.
+//! Script files are loaded by the extensions of the [ScriptHost].
+//! Once the source is available, it is handed to the [ScriptHost] of the app along with the
+//! spawned [Entity], so the host can bind the script to it.
+//!
+//! A reference host for [rhai](https://rhai.rs) is provided as [RhaiScriptHost]:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::{prelude::*, scripting::*};
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(DefaultPlugins)
+//!         .add_plugin(LdtkPlugin)
+//!         .add_plugin(LdtkScriptingPlugin::<RhaiScriptHost>::default())
+//!         .run();
+//! }
+//! ```
+
+use crate::ldtk::{EntityInstance, FieldValue};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use std::{collections::HashMap, marker::PhantomData, path::Path};
+
+/// Field identifier for the script of an LDtk entity.
+pub const SCRIPT_FIELD: &str = "script";
+
+/// Asset containing the source code of a script file.
+#[derive(Clone, Debug, TypeUuid)]
+#[uuid = "7d3c2a6e-4f31-4c8e-9b7a-2f0c51d9e8a4"]
+pub struct ScriptSource {
+    pub source: String,
+}
+
+/// Loads the script files of the [ScriptHost] `H` as [ScriptSource]s, by its
+/// [extensions](ScriptHost::EXTENSIONS).
+pub struct ScriptSourceLoader<H: ScriptHost> {
+    script_host: PhantomData<fn() -> H>,
+}
+
+impl<H: ScriptHost> Default for ScriptSourceLoader<H> {
+    fn default() -> Self {
+        ScriptSourceLoader {
+            script_host: PhantomData,
+        }
+    }
+}
+
+impl<H: ScriptHost> AssetLoader for ScriptSourceLoader<H> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let script_source = ScriptSource {
+                source: String::from_utf8(bytes.to_vec())?,
+            };
+            load_context.set_default_asset(LoadedAsset::new(script_source));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        H::EXTENSIONS
+    }
+}
+
+/// Where the source code of an [LdtkScript] comes from.
+#[derive(Clone, PartialEq, Debug)]
+pub enum LdtkScriptSource {
+    /// Script loaded from a `FilePath` field.
+    Asset(Handle<ScriptSource>),
+    /// Script provided directly in a `String` field.
+    Inline(String),
+}
+
+/// [Component] inserted on LDtk entities that have a `script` field.
+///
+/// Stores the [EntityInstance] as well, so the [ScriptHost] has access to the other fields of
+/// the entity when binding the script.
+#[derive(Clone, PartialEq, Debug, Component)]
+pub struct LdtkScript {
+    pub source: LdtkScriptSource,
+    pub entity_instance: EntityInstance,
+}
+
+impl LdtkScript {
+    /// Creates an [LdtkScript] from the `script` field of the given [EntityInstance], if it has
+    /// one.
+    ///
+    /// Script files are relative to `ldtk_directory`, the directory of the LDtk project.
+    pub fn from_entity_instance(
+        entity_instance: &EntityInstance,
+        asset_server: &AssetServer,
+        ldtk_directory: &Path,
+    ) -> Option<LdtkScript> {
+        entity_instance
+            .field_instances
+            .iter()
+            .find(|f| f.identifier == SCRIPT_FIELD)
+            .and_then(|f| match &f.value {
+                FieldValue::FilePath(Some(path)) => Some(LdtkScriptSource::Asset(
+                    asset_server.load(ldtk_directory.join(path)),
+                )),
+                FieldValue::String(Some(source)) => Some(LdtkScriptSource::Inline(source.clone())),
+                _ => None,
+            })
+            .map(|source| LdtkScript {
+                source,
+                entity_instance: entity_instance.clone(),
+            })
+    }
+}
+
+/// [Component] marking entities whose [LdtkScript] has been handed to the [ScriptHost].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct ScriptBound;
+
+/// Trait for scripting backends that LDtk scripts can be bound to.
+///
+/// The host is stored as a non-send resource, so it doesn't need to be thread safe.
+pub trait ScriptHost: 'static {
+    /// Extensions of the script files of this host, without the leading dot.
+    const EXTENSIONS: &'static [&'static str];
+
+    /// Binds the script `source` to the spawned `entity`.
+    ///
+    /// Called once per entity, as soon as the source of its script is available.
+    fn bind(&mut self, entity: Entity, source: &str, entity_instance: &EntityInstance);
+
+    /// Releases any state associated with the despawned `entity`.
+    fn unbind(&mut self, entity: Entity);
+}
+
+/// Adds the systems and assets needed to bind LDtk scripts to the [ScriptHost] `H`.
+pub struct LdtkScriptingPlugin<H: ScriptHost + Default> {
+    script_host: PhantomData<H>,
+}
+
+impl<H: ScriptHost + Default> Default for LdtkScriptingPlugin<H> {
+    fn default() -> Self {
+        LdtkScriptingPlugin {
+            script_host: PhantomData,
+        }
+    }
+}
+
+impl<H: ScriptHost + Default> Plugin for LdtkScriptingPlugin<H> {
+    fn build(&self, app: &mut App) {
+        app.insert_non_send_resource(H::default())
+            .add_asset::<ScriptSource>()
+            .init_asset_loader::<ScriptSourceLoader<H>>()
+            .add_system(bind_scripts::<H>)
+            // Last, so entities despawned in PostUpdate are unbound in the same update
+            .add_system_to_stage(CoreStage::Last, unbind_scripts::<H>);
+    }
+}
+
+/// Hands the sources of newly spawned [LdtkScript]s to the [ScriptHost] once they're available.
+pub fn bind_scripts<H: ScriptHost>(
+    mut commands: Commands,
+    mut script_host: NonSendMut<H>,
+    script_query: Query<(Entity, &LdtkScript), Without<ScriptBound>>,
+    script_sources: Res<Assets<ScriptSource>>,
+) {
+    for (entity, script) in script_query.iter() {
+        let source = match &script.source {
+            LdtkScriptSource::Asset(handle) => match script_sources.get(handle) {
+                Some(script_source) => script_source.source.as_str(),
+                None => continue,
+            },
+            LdtkScriptSource::Inline(source) => source.as_str(),
+        };
+
+        script_host.bind(entity, source, &script.entity_instance);
+        commands.entity(entity).insert(ScriptBound);
+    }
+}
+
+/// Lets the [ScriptHost] release the state of scripted entities that have despawned.
+pub fn unbind_scripts<H: ScriptHost>(
+    mut script_host: NonSendMut<H>,
+    removed_scripts: RemovedComponents<ScriptBound>,
+) {
+    for entity in removed_scripts.iter() {
+        script_host.unbind(entity);
+    }
+}
+
+/// Reference [ScriptHost] implementation using [rhai].
+///
+/// Each script is compiled and, if it defines an `on_spawn(entity, identifier)` function, that
+/// function is called with the bits of the [Entity] and the LDtk entity identifier.
+/// The compiled scripts are kept in [RhaiScriptHost::scripts] so game systems can call into them
+/// later.
+#[derive(Default)]
+pub struct RhaiScriptHost {
+    pub engine: rhai::Engine,
+    pub scripts: HashMap<Entity, rhai::AST>,
+}
+
+impl ScriptHost for RhaiScriptHost {
+    const EXTENSIONS: &'static [&'static str] = &["rhai"];
+
+    fn bind(&mut self, entity: Entity, source: &str, entity_instance: &EntityInstance) {
+        let ast = match self.engine.compile(source) {
+            Ok(ast) => ast,
+            Err(e) => {
+                warn!(
+                    "Failed to compile script of \"{}\": {}",
+                    entity_instance.identifier, e
+                );
+                return;
+            }
+        };
+
+        if ast.iter_functions().any(|f| f.name == "on_spawn") {
+            let mut scope = rhai::Scope::new();
+            if let Err(e) = self.engine.call_fn::<()>(
+                &mut scope,
+                &ast,
+                "on_spawn",
+                (
+                    entity.to_bits() as rhai::INT,
+                    entity_instance.identifier.clone(),
+                ),
+            ) {
+                warn!(
+                    "Failed to run on_spawn for \"{}\": {}",
+                    entity_instance.identifier, e
+                );
+            }
+        }
+
+        self.scripts.insert(entity, ast);
+    }
+
+    fn unbind(&mut self, entity: Entity) {
+        self.scripts.remove(&entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+
+    #[derive(Default)]
+    struct TestScriptHost {
+        bound: Vec<(Entity, String)>,
+        unbound: Vec<Entity>,
+    }
+
+    impl ScriptHost for TestScriptHost {
+        const EXTENSIONS: &'static [&'static str] = &["lua"];
+
+        fn bind(&mut self, entity: Entity, source: &str, _: &EntityInstance) {
+            self.bound.push((entity, source.to_string()));
+        }
+
+        fn unbind(&mut self, entity: Entity) {
+            self.unbound.push(entity);
+        }
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_plugin(LdtkScriptingPlugin::<TestScriptHost>::default());
+        app
+    }
+
+    fn entity_instance(value: FieldValue) -> EntityInstance {
+        EntityInstance {
+            identifier: "Door".to_string(),
+            field_instances: vec![crate::ldtk::FieldInstance {
+                identifier: SCRIPT_FIELD.to_string(),
+                field_instance_type: "FilePath".to_string(),
+                value,
+                def_uid: 0,
+                real_editor_values: Vec::new(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_loader_extensions_come_from_host() {
+        assert_eq!(
+            ScriptSourceLoader::<TestScriptHost>::default().extensions(),
+            &["lua"]
+        );
+        assert_eq!(
+            ScriptSourceLoader::<RhaiScriptHost>::default().extensions(),
+            &["rhai"]
+        );
+    }
+
+    #[test]
+    fn test_script_files_are_relative_to_ldtk_file() {
+        let app = app();
+        let asset_server = app.world.get_resource::<AssetServer>().unwrap();
+
+        let script = LdtkScript::from_entity_instance(
+            &entity_instance(FieldValue::FilePath(Some("scripts/door.lua".to_string()))),
+            asset_server,
+            Path::new("levels"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            script.source,
+            LdtkScriptSource::Asset(asset_server.get_handle("levels/scripts/door.lua"))
+        );
+
+        let script = LdtkScript::from_entity_instance(
+            &entity_instance(FieldValue::String(Some("open()".to_string()))),
+            asset_server,
+            Path::new("levels"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            script.source,
+            LdtkScriptSource::Inline("open()".to_string())
+        );
+
+        assert!(LdtkScript::from_entity_instance(
+            &entity_instance(FieldValue::FilePath(None)),
+            asset_server,
+            Path::new("levels"),
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_scripts_are_bound_once_and_unbound_on_despawn() {
+        let mut app = app();
+
+        let entity = app
+            .world
+            .spawn()
+            .insert(LdtkScript {
+                source: LdtkScriptSource::Inline("open()".to_string()),
+                entity_instance: EntityInstance::default(),
+            })
+            .id();
+
+        app.update();
+        app.update();
+
+        let script_host = app.world.get_non_send_resource::<TestScriptHost>().unwrap();
+        assert_eq!(script_host.bound, vec![(entity, "open()".to_string())]);
+        assert!(script_host.unbound.is_empty());
+
+        // Despawned in PostUpdate, so the removal is only visible to later stages
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            |mut commands: Commands, query: Query<Entity, With<ScriptBound>>| {
+                for entity in query.iter() {
+                    commands.entity(entity).despawn();
+                }
+            },
+        );
+        app.update();
+
+        let script_host = app.world.get_non_send_resource::<TestScriptHost>().unwrap();
+        assert_eq!(script_host.unbound, vec![entity]);
+    }
+}
@@ -2,25 +2,47 @@
 
 use crate::{
     app::{
-        LdtkEntity, LdtkEntityMap, LdtkIntCellMap, PhantomLdtkEntity, PhantomLdtkEntityTrait,
-        PhantomLdtkIntCell, PhantomLdtkIntCellTrait,
+        promoted_entity_instance, InsertReflectedComponents, LdtkDespawnPolicyMap, LdtkEntity,
+        LdtkEntityAnchorMap, LdtkEntityContext, LdtkEntityMap, LdtkFilePathFieldMap,
+        LdtkIntCellIdentifierMap, LdtkIntCellMap, LdtkIntCellPromotionMap, LdtkReflectedEntityMap,
+        LdtkTileEnumTagMap, PhantomLdtkEntity, PhantomLdtkEntityTrait, PhantomLdtkIntCell,
+        PhantomLdtkIntCellTrait,
     },
-    assets::{LdtkAsset, LdtkLevel, TilesetMap},
+    assets::{ldtk_directory, LayerSettingsCache, LdtkAsset, LdtkLevel, TilesetMap},
     components::*,
-    ldtk::{EntityDefinition, Level, TileInstance, TilesetDefinition, Type},
-    resources::{LdtkSettings, LevelEvent, LevelSelection},
+    grid_level::GridLevel,
+    ldtk::{
+        EntityDefinition, FieldValue, LayerDefinition, Level, TileInstance, TilesetDefinition, Type,
+    },
+    parallax::ParallaxLayer,
+    resources::{
+        Census, ChunkActivationSettings, EntityIidMap, ExtrudedTileset, ExtrudedTilesets,
+        IntGridCellSpawning, LayerZ, LdtkSettings, LevelEvent, LevelHistory, LevelSelection,
+        LevelSpawnError, LevelSpawnReport, LevelStreamingSettings, LevelSwap, PendingDespawnEvent,
+    },
     tile_makers::*,
     utils::*,
 };
 
 use bevy::{
+    app::ManualEventReader,
+    asset::HandleId,
+    ecs::{entity::Entities, schedule::ShouldRun},
     prelude::*,
-    render::{render_resource::TextureUsages, texture::DEFAULT_IMAGE_HANDLE},
+    render::{
+        render_resource::{FilterMode, TextureUsages},
+        texture::DEFAULT_IMAGE_HANDLE,
+    },
+    utils::Instant,
 };
 use bevy_ecs_tilemap::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
-const CHUNK_SIZE: ChunkSize = ChunkSize(32, 32);
+#[cfg(feature = "3d")]
+use crate::layers_3d::Layer3dSettings;
 
 pub fn choose_levels(
     level_selection: Option<Res<LevelSelection>>,
@@ -29,42 +51,205 @@ pub fn choose_levels(
     mut level_set_query: Query<(&Handle<LdtkAsset>, &mut LevelSet)>,
 ) {
     if let Some(level_selection) = level_selection {
-        if level_selection.is_changed() {
+        if level_selection.is_changed() || ldtk_settings.is_changed() {
             for (ldtk_handle, mut level_set) in level_set_query.iter_mut() {
                 if let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) {
-                    if let Some(level) = ldtk_asset.get_level(&level_selection) {
-                        level_set.uids.clear();
+                    select_level(&mut level_set, ldtk_asset, &level_selection, &ldtk_settings);
+                }
+            }
+        }
+    }
+}
 
-                        level_set.uids.insert(level.uid);
+/// Adds the [InitialLevelSelection] of worlds to their [LevelSet] once their project is loaded.
+pub fn apply_initial_level_selections(
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    mut level_set_query: Query<(
+        &Handle<LdtkAsset>,
+        &mut LevelSet,
+        &mut InitialLevelSelection,
+    )>,
+) {
+    for (ldtk_handle, mut level_set, mut initial_level_selection) in level_set_query.iter_mut() {
+        if initial_level_selection.0.is_none() {
+            continue;
+        }
 
-                        if ldtk_settings.load_level_neighbors {
-                            level_set
-                                .uids
-                                .extend(level.neighbours.iter().map(|n| n.level_uid));
-                        }
-                    }
+        if let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) {
+            if let Some(level_selection) = initial_level_selection.0.take() {
+                if !level_set.insert_selection(&level_selection, ldtk_asset) {
+                    warn!("No level matches the selection {:?}", level_selection);
                 }
             }
         }
     }
 }
 
+/// Updates the [LevelSet] of every world with the levels near the [LevelStreamingTarget]s, if the
+/// [LevelStreamingSettings] resource is inserted.
+pub fn stream_levels(
+    level_streaming_settings: Option<Res<LevelStreamingSettings>>,
+    target_query: Query<&GlobalTransform, With<LevelStreamingTarget>>,
+    mut ldtk_world_query: Query<(&Handle<LdtkAsset>, &GlobalTransform, &mut LevelSet)>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+) {
+    let settings = match level_streaming_settings {
+        Some(settings) => settings,
+        None => return,
+    };
+
+    for (ldtk_handle, world_transform, mut level_set) in ldtk_world_query.iter_mut() {
+        let ldtk_asset = match ldtk_assets.get(ldtk_handle) {
+            Some(ldtk_asset) => ldtk_asset,
+            None => continue,
+        };
+
+        let world_inverse = world_transform.compute_matrix().inverse();
+        let targets: Vec<Vec2> = target_query
+            .iter()
+            .map(|t| world_inverse.transform_point3(t.translation).truncate())
+            .collect();
+
+        let world_height = ldtk_asset.world_height();
+
+        let uids: HashSet<i32> = ldtk_asset
+            .project
+            .levels
+            .iter()
+            .filter(|level| {
+                let (min, max) = level_world_bounds(level, world_height);
+                let distance = targets
+                    .iter()
+                    .map(|t| distance_to_rect(*t, min, max))
+                    .fold(f32::INFINITY, f32::min);
+
+                distance <= settings.spawn_distance
+                    || (distance <= settings.despawn_distance
+                        && level_set.uids.contains(&level.uid))
+            })
+            .map(|level| level.uid)
+            .collect();
+
+        if level_set.uids != uids {
+            level_set.uids = uids;
+        }
+    }
+}
+
+/// Records changes of the [LevelSelection] in the [LevelHistory], if both resources are inserted.
+pub fn record_level_history(
+    level_selection: Option<Res<LevelSelection>>,
+    level_history: Option<ResMut<LevelHistory>>,
+) {
+    if let (Some(level_selection), Some(mut level_history)) = (level_selection, level_history) {
+        if level_selection.is_changed() {
+            level_history.record(level_selection.clone());
+        }
+    }
+}
+
+/// Counts the entities of the [Census] resource, if it exists, when it's inserted and whenever
+/// a project, a level asset, or the project of a world changes.
+pub fn update_census(
+    census: Option<ResMut<Census>>,
+    mut ldtk_events: EventReader<AssetEvent<LdtkAsset>>,
+    mut level_events: EventReader<AssetEvent<LdtkLevel>>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    changed_ldtk_query: Query<(), Changed<Handle<LdtkAsset>>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+) {
+    let assets_changed = ldtk_events.iter().count() + level_events.iter().count() > 0
+        || changed_ldtk_query.iter().next().is_some();
+
+    let mut census = match census {
+        Some(census) if census.is_added() || assets_changed => census,
+        _ => return,
+    };
+
+    let ldtk_handles: HashSet<&Handle<LdtkAsset>> = ldtk_query.iter().collect();
+
+    census.recount(
+        ldtk_handles
+            .into_iter()
+            .filter_map(|h| ldtk_assets.get(h))
+            .flat_map(|ldtk_asset| ldtk_asset.iter_levels_with_layers(&level_assets)),
+    );
+}
+
+/// Replaces the contents of the [LevelSet] with the selected level and, if
+/// [LdtkSettings::load_level_neighbors] is enabled, its neighbors.
+///
+/// Neighbors of the previous selection that aren't neighbors of the new one are removed, so they
+/// despawn when the selection moves on.
+fn select_level(
+    level_set: &mut LevelSet,
+    ldtk_asset: &LdtkAsset,
+    level_selection: &LevelSelection,
+    ldtk_settings: &LdtkSettings,
+) {
+    if let Some(level) = ldtk_asset.get_level(level_selection) {
+        level_set.uids.clear();
+
+        level_set.uids.insert(level.uid);
+
+        if ldtk_settings.load_level_neighbors {
+            level_set
+                .uids
+                .extend(level.neighbours.iter().map(|n| n.level_uid));
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn apply_level_set(
     mut commands: Commands,
-    ldtk_world_query: Query<(Entity, &LevelSet, &Children, &Handle<LdtkAsset>), Changed<LevelSet>>,
-    ldtk_level_query: Query<&Handle<LdtkLevel>>,
+    ldtk_world_query: Query<
+        (
+            Entity,
+            &LevelSet,
+            &Children,
+            &Handle<LdtkAsset>,
+            Option<&LdtkAssetRoot>,
+        ),
+        Changed<LevelSet>,
+    >,
+    ldtk_level_query: Query<(
+        &Handle<LdtkLevel>,
+        Option<&PendingLevelSpawn>,
+        Option<&SwappedOutLevel>,
+        Option<&LevelSpawnProgress>,
+    )>,
+    level_children_query: Query<(&Transform, &Children), With<Handle<LdtkLevel>>>,
+    despawn_policy_query: Query<(&DespawnPolicy, &Transform)>,
     ldtk_assets: Res<Assets<LdtkAsset>>,
     level_assets: Res<Assets<LdtkLevel>>,
     ldtk_settings: Res<LdtkSettings>,
+    asset_server: Res<AssetServer>,
     mut map_query: MapQuery,
     mut level_events: EventWriter<LevelEvent>,
+    mut pending_despawn_events: EventWriter<PendingDespawnEvent>,
 ) {
-    for (world_entity, level_set, children, ldtk_asset_handle) in ldtk_world_query.iter() {
+    for (world_entity, level_set, children, ldtk_asset_handle, asset_root) in
+        ldtk_world_query.iter()
+    {
         let mut previous_level_map = HashMap::new();
+        let mut pending_level_map = HashMap::new();
         for child in children.iter() {
-            if let Ok(level_handle) = ldtk_level_query.get(*child) {
-                if let Some(ldtk_level) = level_assets.get(level_handle) {
+            if let Ok((level_handle, pending_level_spawn, swapped_out_level, progress)) =
+                ldtk_level_query.get(*child)
+            {
+                if let Some(swapped_out_level) = swapped_out_level {
+                    // Swapped out levels are already on their way out, unless they're back in
+                    // the level set
+                    if level_set.uids.contains(&swapped_out_level.level_uid) {
+                        commands.entity(*child).remove::<SwappedOutLevel>();
+                        previous_level_map.insert(swapped_out_level.level_uid, child);
+                    }
+                } else if let Some(pending_level_spawn) = pending_level_spawn {
+                    previous_level_map.insert(pending_level_spawn.level_uid, child);
+                    pending_level_map.insert(pending_level_spawn.level_uid, (child, progress));
+                } else if let Some(ldtk_level) = level_assets.get(level_handle) {
                     previous_level_map.insert(ldtk_level.level.uid, child);
                 }
             }
@@ -78,19 +263,180 @@ pub fn apply_level_set(
                 commands.entity(world_entity).with_children(|c| {
                     for uid in uids_to_spawn {
                         level_events.send(LevelEvent::SpawnTriggered(*uid));
-                        pre_spawn_level(c, ldtk_asset, *uid, &ldtk_settings);
+                        pre_spawn_level(
+                            c,
+                            ldtk_asset,
+                            *uid,
+                            &ldtk_settings,
+                            asset_root,
+                            &asset_server,
+                        );
                     }
                 });
             }
         }
 
         for uid in previous_uids.difference(&level_set.uids) {
-            map_query.despawn(&mut commands, *uid as u16);
+            match pending_level_map.get(uid) {
+                // The level has started spawning its map across several updates, so its
+                // tiles need to be despawned too
+                Some((_, Some(_))) => map_query.despawn(&mut commands, *uid as u16),
+                // The level hasn't spawned its map yet, so despawning its entity cancels it
+                Some((level_entity, None)) => commands.entity(**level_entity).despawn_recursive(),
+                None if ldtk_settings.level_swap == LevelSwap::DoubleBuffered => {
+                    commands
+                        .entity(*previous_level_map[uid])
+                        .insert(SwappedOutLevel { level_uid: *uid });
+                    continue;
+                }
+                None => despawn_spawned_level(
+                    &mut commands,
+                    world_entity,
+                    *previous_level_map[uid],
+                    *uid,
+                    &level_children_query,
+                    &despawn_policy_query,
+                    &mut map_query,
+                    &mut pending_despawn_events,
+                ),
+            }
             level_events.send(LevelEvent::Despawned(*uid));
         }
     }
 }
 
+/// Despawns a level whose map has spawned, applying the [DespawnPolicy] of its children.
+#[allow(clippy::too_many_arguments)]
+fn despawn_spawned_level(
+    commands: &mut Commands,
+    world_entity: Entity,
+    level_entity: Entity,
+    level_uid: i32,
+    level_children_query: &Query<(&Transform, &Children), With<Handle<LdtkLevel>>>,
+    despawn_policy_query: &Query<(&DespawnPolicy, &Transform)>,
+    map_query: &mut MapQuery,
+    pending_despawn_events: &mut EventWriter<PendingDespawnEvent>,
+) {
+    if let Ok((level_transform, level_children)) = level_children_query.get(level_entity) {
+        for child in level_children.iter() {
+            if let Ok((DespawnPolicy::AfterEvent, transform)) = despawn_policy_query.get(*child) {
+                // Move the entity out of the level so it survives the level's despawning until
+                // the game is done with it
+                commands.entity(level_entity).remove_children(&[*child]);
+                commands.entity(world_entity).push_children(&[*child]);
+                commands
+                    .entity(*child)
+                    .insert(level_transform.mul_transform(*transform))
+                    .insert(PendingDespawn)
+                    .insert(SpawnedByLdtk);
+
+                pending_despawn_events.send(PendingDespawnEvent {
+                    entity: *child,
+                    level_uid,
+                });
+            }
+        }
+    }
+
+    map_query.despawn(commands, level_uid as u16)
+}
+
+/// Despawns the [SwappedOutLevel]s of worlds that don't have pending levels anymore, for
+/// [LevelSwap::DoubleBuffered].
+///
+/// Runs after the levels spawned in this update have sent their [LevelEvent::Spawned], so the
+/// swapped out levels are despawned in the same update as their replacements appear.
+#[allow(clippy::too_many_arguments)]
+pub fn complete_level_swaps(
+    mut commands: Commands,
+    ldtk_world_query: Query<(Entity, &Children), With<Handle<LdtkAsset>>>,
+    ldtk_level_query: Query<(Option<&PendingLevelSpawn>, Option<&SwappedOutLevel>)>,
+    level_children_query: Query<(&Transform, &Children), With<Handle<LdtkLevel>>>,
+    despawn_policy_query: Query<(&DespawnPolicy, &Transform)>,
+    mut map_query: MapQuery,
+    mut level_events: ResMut<Events<LevelEvent>>,
+    mut level_event_reader: Local<ManualEventReader<LevelEvent>>,
+    mut pending_despawn_events: EventWriter<PendingDespawnEvent>,
+) {
+    let spawned_uids: HashSet<i32> = level_event_reader
+        .iter(&level_events)
+        .filter_map(|event| match event {
+            LevelEvent::Spawned(uid) => Some(*uid),
+            _ => None,
+        })
+        .collect();
+
+    for (world_entity, children) in ldtk_world_query.iter() {
+        let mut swapped_out_levels = Vec::new();
+        let mut pending = false;
+
+        for child in children.iter() {
+            match ldtk_level_query.get(*child) {
+                Ok((_, Some(swapped_out_level))) => {
+                    swapped_out_levels.push((*child, swapped_out_level.level_uid))
+                }
+                // Levels that spawned in this update still have their component until the end
+                // of the stage
+                Ok((Some(pending_level_spawn), None)) => {
+                    pending |= !spawned_uids.contains(&pending_level_spawn.level_uid)
+                }
+                _ => (),
+            }
+        }
+
+        if pending {
+            continue;
+        }
+
+        for (level_entity, level_uid) in swapped_out_levels {
+            despawn_spawned_level(
+                &mut commands,
+                world_entity,
+                level_entity,
+                level_uid,
+                &level_children_query,
+                &despawn_policy_query,
+                &mut map_query,
+                &mut pending_despawn_events,
+            );
+            level_events.send(LevelEvent::Despawned(level_uid));
+        }
+    }
+}
+
+/// Despawns entities once their [PendingDespawn] component has been removed.
+pub fn despawn_released_entities(
+    mut commands: Commands,
+    released_entities: RemovedComponents<PendingDespawn>,
+    entity_query: Query<Entity>,
+) {
+    for entity in released_entities.iter() {
+        if entity_query.get(entity).is_ok() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Run criteria that only runs systems when no level is pending spawn.
+///
+/// Systems using this run criteria never observe a level that has been triggered to spawn but
+/// isn't complete yet, even when [LdtkSettings::level_spawn_budget] spreads its spawning over
+/// several updates, like when initializing gameplay for a new room:
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_ldtk::{prelude::*, systems::levels_spawned};
+/// # fn setup_room() {}
+/// # let mut app = App::new();
+/// app.add_system(setup_room.with_run_criteria(levels_spawned));
+/// ```
+pub fn levels_spawned(pending_level_query: Query<(), With<PendingLevelSpawn>>) -> ShouldRun {
+    if pending_level_query.is_empty() {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
 /// Detects [LdtkAsset] events and spawns levels as children of the [LdtkWorldBundle].
 
 #[allow(clippy::too_many_arguments)]
@@ -100,12 +446,22 @@ pub fn process_ldtk_world(
     mut level_events: EventWriter<LevelEvent>,
     new_ldtks: Query<&Handle<LdtkAsset>, Added<Handle<LdtkAsset>>>,
     mut ldtk_level_query: Query<&mut Map, With<Handle<LdtkLevel>>>,
-    mut ldtk_world_query: Query<(Entity, &Handle<LdtkAsset>, &mut LevelSet, Option<&Children>)>,
+    mut ldtk_world_query: Query<(
+        Entity,
+        &Handle<LdtkAsset>,
+        &mut LevelSet,
+        Option<&Children>,
+        Option<&Respawn>,
+        Option<&LdtkAssetRoot>,
+    )>,
     level_selection: Option<Res<LevelSelection>>,
     ldtk_assets: Res<Assets<LdtkAsset>>,
     ldtk_settings: Res<LdtkSettings>,
+    asset_server: Res<AssetServer>,
+    spawned_query: Query<(), With<SpawnedByLdtk>>,
     layer_query: Query<&Layer>,
     chunk_query: Query<&Chunk>,
+    mut ldtk_checksums: Local<HashMap<Handle<LdtkAsset>, u64>>,
 ) {
     // This function uses code from the bevy_ecs_tilemap ldtk example
     // https://github.com/StarArawn/bevy_ecs_tilemap/blob/main/examples/ldtk/ldtk.rs
@@ -117,11 +473,19 @@ pub fn process_ldtk_world(
                 changed_ldtks.push(handle.clone());
             }
             AssetEvent::Modified { handle } => {
-                info!("LDtk asset modification detected.");
-                changed_ldtks.push(handle.clone());
+                // editors may save several times, or save without changes, so worlds are only
+                // respawned if the content has actually changed
+                let checksum = ldtk_assets.get(handle).map(LdtkAsset::checksum);
+                if checksum.is_some() && ldtk_checksums.get(handle) == checksum.as_ref() {
+                    debug!("LDtk asset modification without changes ignored.");
+                } else {
+                    info!("LDtk asset modification detected.");
+                    changed_ldtks.push(handle.clone());
+                }
             }
             AssetEvent::Removed { handle } => {
                 info!("LDtk asset removal detected.");
+                ldtk_checksums.remove(handle);
                 // if mesh was modified and removed in the same update, ignore the modification
                 // events are ordered so future modification events are ok
                 changed_ldtks = changed_ldtks
@@ -136,46 +500,225 @@ pub fn process_ldtk_world(
         changed_ldtks.push(new_ldtk_handle.clone());
     }
 
-    for changed_ldtk in changed_ldtks {
-        for (ldtk_entity, ldtk_handle, mut level_set, children) in ldtk_world_query
-            .iter_mut()
-            .filter(|(_, l, _, _)| **l == changed_ldtk)
-        {
-            if let Some(children) = children {
-                for child in children.iter() {
-                    if let Ok(mut map) = ldtk_level_query.get_mut(*child) {
-                        clear_map(&mut commands, &mut map, &layer_query, &chunk_query);
-                        map.despawn(&mut commands);
-                        level_events.send(LevelEvent::Despawned(map.id as i32));
-                    } else {
-                        commands.entity(*child).despawn_recursive();
-                    }
+    for changed_ldtk in &changed_ldtks {
+        if let Some(ldtk_asset) = ldtk_assets.get(changed_ldtk) {
+            ldtk_checksums.insert(changed_ldtk.clone(), ldtk_asset.checksum());
+        }
+    }
+
+    for (ldtk_entity, ldtk_handle, mut level_set, children, respawn, asset_root) in ldtk_world_query
+        .iter_mut()
+        .filter(|(_, l, _, _, r, _)| r.is_some() || changed_ldtks.contains(l))
+    {
+        if respawn.is_some() {
+            commands.entity(ldtk_entity).remove::<Respawn>();
+        }
+
+        if let Some(children) = children {
+            for child in children.iter() {
+                if let Ok(mut map) = ldtk_level_query.get_mut(*child) {
+                    clear_map(&mut commands, &mut map, &layer_query, &chunk_query);
+                    map.despawn(&mut commands);
+                    level_events.send(LevelEvent::Despawned(map.id as i32));
+                } else if spawned_query.get(*child).is_ok() {
+                    commands.entity(*child).despawn_recursive();
                 }
             }
+        }
 
-            if let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) {
-                if let Some(level_selection) = &level_selection {
-                    if let Some(level) = ldtk_asset.get_level(level_selection) {
-                        level_set.uids.clear();
+        if let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) {
+            if let Some(level_selection) = &level_selection {
+                select_level(&mut level_set, ldtk_asset, level_selection, &ldtk_settings);
+            }
 
-                        level_set.uids.insert(level.uid);
+            commands.entity(ldtk_entity).with_children(|c| {
+                for level_uid in &level_set.uids {
+                    level_events.send(LevelEvent::SpawnTriggered(*level_uid));
+                    pre_spawn_level(
+                        c,
+                        ldtk_asset,
+                        *level_uid,
+                        &ldtk_settings,
+                        asset_root,
+                        &asset_server,
+                    )
+                }
+            });
+        }
+    }
+}
 
-                        if ldtk_settings.load_level_neighbors {
-                            level_set
-                                .uids
-                                .extend(level.neighbours.iter().map(|n| n.level_uid));
-                        }
-                    }
+/// Triggers a [Respawn] of the spawned levels whose external level file has been modified, so
+/// editing the external level files of a project updates the levels of running games.
+///
+/// Like worlds, levels are only respawned if the content of their asset has changed, see
+/// [LdtkLevel::checksum].
+/// Levels that are still pending aren't affected, since they spawn with the new data anyway, and
+/// neither are [SwappedOutLevel]s.
+pub fn respawn_modified_levels(
+    mut commands: Commands,
+    mut level_asset_events: EventReader<AssetEvent<LdtkLevel>>,
+    level_query: Query<
+        (Entity, &Handle<LdtkLevel>),
+        (Without<PendingLevelSpawn>, Without<SwappedOutLevel>),
+    >,
+    level_assets: Res<Assets<LdtkLevel>>,
+    asset_server: Res<AssetServer>,
+    mut level_checksums: Local<HashMap<Handle<LdtkLevel>, u64>>,
+) {
+    // Levels stored in the project file are labeled assets of the project, and are already
+    // respawned by process_ldtk_world when it's modified
+    let is_external = |handle: &Handle<LdtkLevel>| {
+        asset_server
+            .get_handle_path(handle)
+            .map_or(false, |asset_path| asset_path.label().is_none())
+    };
+
+    let mut modified_levels: Vec<&Handle<LdtkLevel>> = Vec::new();
+    for event in level_asset_events.iter() {
+        match event {
+            AssetEvent::Created { handle } => {
+                if let Some(ldtk_level) = level_assets.get(handle) {
+                    level_checksums.insert(handle.clone(), ldtk_level.checksum());
+                }
+            }
+            AssetEvent::Modified { handle } if is_external(handle) => {
+                // a level without a recorded checksum counts as changed
+                let checksum = level_assets.get(handle).map(LdtkLevel::checksum);
+                if checksum.is_some() && level_checksums.get(handle) == checksum.as_ref() {
+                    debug!("LDtk level asset modification without changes ignored.");
+                    continue;
                 }
 
-                commands.entity(ldtk_entity).with_children(|c| {
-                    for level_uid in &level_set.uids {
-                        level_events.send(LevelEvent::SpawnTriggered(*level_uid));
-                        pre_spawn_level(c, ldtk_asset, *level_uid, &ldtk_settings)
-                    }
-                });
+                if let Some(checksum) = checksum {
+                    level_checksums.insert(handle.clone(), checksum);
+                }
+                modified_levels.push(handle);
+            }
+            AssetEvent::Modified { .. } => (),
+            AssetEvent::Removed { handle } => {
+                level_checksums.remove(handle);
+            }
+        }
+    }
+
+    if modified_levels.is_empty() {
+        return;
+    }
+
+    for (level_entity, level_handle) in level_query.iter() {
+        if modified_levels.contains(&level_handle) {
+            info!("LDtk level asset modification detected.");
+            commands.entity(level_entity).insert(Respawn);
+        }
+    }
+}
+
+/// Triggers a [Respawn] of the spawned levels when spawn-time [LdtkSettings] change, see
+/// [LdtkSettings::requires_respawn].
+pub fn respawn_levels_with_changed_settings(
+    mut commands: Commands,
+    ldtk_settings: Res<LdtkSettings>,
+    mut previous_settings: Local<Option<LdtkSettings>>,
+    level_query: Query<
+        Entity,
+        (
+            With<Handle<LdtkLevel>>,
+            Without<PendingLevelSpawn>,
+            Without<SwappedOutLevel>,
+        ),
+    >,
+) {
+    if !ldtk_settings.is_changed() {
+        return;
+    }
+
+    let previous_settings = match previous_settings.replace(ldtk_settings.clone()) {
+        Some(previous_settings) => previous_settings,
+        None => return,
+    };
+
+    if ldtk_settings.requires_respawn(&previous_settings) {
+        info!("LdtkSettings change requiring a respawn detected.");
+        for level_entity in level_query.iter() {
+            commands.entity(level_entity).insert(Respawn);
+        }
+    }
+}
+
+/// Despawns levels with a [Respawn] component and spawns them again in new level entities.
+#[allow(clippy::type_complexity)]
+pub fn respawn_levels(
+    mut commands: Commands,
+    mut level_events: EventWriter<LevelEvent>,
+    mut level_query: Query<
+        (
+            Entity,
+            &Handle<LdtkLevel>,
+            &Parent,
+            Option<&mut Map>,
+            Option<&PendingLevelSpawn>,
+        ),
+        With<Respawn>,
+    >,
+    ldtk_query: Query<(&Handle<LdtkAsset>, Option<&LdtkAssetRoot>)>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    ldtk_settings: Res<LdtkSettings>,
+    asset_server: Res<AssetServer>,
+    layer_query: Query<&Layer>,
+    chunk_query: Query<&Chunk>,
+) {
+    for (level_entity, level_handle, parent, map, pending_level_spawn) in level_query.iter_mut() {
+        let level_uid = match (pending_level_spawn, level_assets.get(level_handle)) {
+            (Some(pending_level_spawn), _) => pending_level_spawn.level_uid,
+            (None, Some(ldtk_level)) => ldtk_level.level.uid,
+            _ => continue,
+        };
+
+        let (ldtk_asset, asset_root) = match ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|(h, r)| Some((ldtk_assets.get(h)?, r)))
+        {
+            Some(world) => world,
+            None => continue,
+        };
+
+        match map {
+            Some(mut map) => {
+                clear_map(&mut commands, &mut map, &layer_query, &chunk_query);
+                map.despawn(&mut commands);
             }
+            None => commands.entity(level_entity).despawn_recursive(),
         }
+        level_events.send(LevelEvent::Despawned(level_uid));
+
+        commands.entity(parent.0).with_children(|c| {
+            level_events.send(LevelEvent::SpawnTriggered(level_uid));
+            pre_spawn_level(
+                c,
+                ldtk_asset,
+                level_uid,
+                &ldtk_settings,
+                asset_root,
+                &asset_server,
+            );
+        });
+    }
+}
+
+/// Returns the translation of a level relative to its world, which is the position of the level
+/// in the LDtk world if [LdtkSettings::use_level_world_translations] is enabled, and the origin
+/// otherwise.
+fn level_translation(level: &Level, ldtk_asset: &LdtkAsset, ldtk_settings: &LdtkSettings) -> Vec2 {
+    if ldtk_settings.use_level_world_translations {
+        ldtk_pixel_coords_to_translation(
+            IVec2::new(level.world_x, level.world_y + level.px_hei),
+            ldtk_asset.world_height(),
+        )
+    } else {
+        Vec2::ZERO
     }
 }
 
@@ -184,33 +727,50 @@ fn pre_spawn_level(
     ldtk_asset: &LdtkAsset,
     level_uid: i32,
     ldtk_settings: &LdtkSettings,
+    asset_root: Option<&LdtkAssetRoot>,
+    asset_server: &AssetServer,
 ) {
     if let Some(level_handle) = ldtk_asset.level_map.get(&level_uid) {
         let mut translation = Vec3::ZERO;
 
-        if ldtk_settings.use_level_world_translations {
-            if let Some(level) = ldtk_asset
-                .project
-                .levels
-                .iter()
-                .find(|l| l.uid == level_uid)
-            {
-                let level_coords = ldtk_pixel_coords_to_translation(
-                    IVec2::new(level.world_x, level.world_y + level.px_hei),
-                    ldtk_asset.world_height(),
-                );
-                translation.x = level_coords.x;
-                translation.y = level_coords.y;
+        let level = ldtk_asset
+            .project
+            .levels
+            .iter()
+            .find(|l| l.uid == level_uid);
+
+        let level_handle = match (asset_root, level.and_then(|l| l.external_rel_path.as_ref())) {
+            (Some(asset_root), Some(external_rel_path)) => {
+                match asset_root.resolve(external_rel_path) {
+                    Some(path) => asset_server.load(path.as_str()),
+                    None => level_handle.clone(),
+                }
             }
+            _ => level_handle.clone(),
+        };
+
+        if let Some(level) = level {
+            translation = level_translation(level, ldtk_asset, ldtk_settings).extend(0.);
         }
 
-        child_builder
-            .spawn()
-            .insert(level_handle.clone())
+        let mut entity_commands = child_builder.spawn();
+
+        entity_commands
+            .insert(level_handle)
+            .insert(PendingLevelSpawn { level_uid })
+            .insert(SpawnedByLdtk)
             .insert_bundle((
                 Transform::from_translation(translation),
                 GlobalTransform::default(),
             ));
+
+        if let Some(level) = level {
+            entity_commands.insert(LevelIdentifier(level.identifier.clone()));
+
+            if !level.iid.is_empty() {
+                entity_commands.insert(LevelIid(level.iid.clone()));
+            }
+        }
     }
 }
 
@@ -247,8 +807,73 @@ fn clear_map(
     }
 }
 
-/// Performs all the spawning of levels, layers, chunks, bundles, entities, tiles, etc. when an
-/// LdtkLevelBundle is added.
+/// [Component] storing the state of levels whose spawning is spread across several updates, with
+/// [LdtkSettings::level_spawn_budget].
+///
+/// Inserted on pending level entities after their first update of spawning, and removed once they
+/// have spawned completely.
+#[derive(Clone, Debug, Component)]
+pub struct LevelSpawnProgress {
+    layers_spawned: usize,
+    layer_ids: LayerIdAllocator,
+    layer_tilesets: LayerTilesets,
+    spawn_report: LevelSpawnReport,
+    tile_alpha: f32,
+    // Anchored entities are reparented once all layers have spawned, since their IntGrid layer
+    // may come after their Entity layer
+    anchored_entities: Vec<(Entity, String, PixelCoords, Transform)>,
+    anchor_layers: HashMap<String, (IVec2, Vec2)>,
+    anchor_cells: HashMap<(String, GridCoords), (Entity, Transform)>,
+}
+
+impl LevelSpawnProgress {
+    fn new(level_uid: i32, tile_alpha: f32) -> Self {
+        LevelSpawnProgress {
+            layers_spawned: 0,
+            layer_ids: LayerIdAllocator::default(),
+            layer_tilesets: LayerTilesets::default(),
+            spawn_report: LevelSpawnReport {
+                level_uid,
+                ..Default::default()
+            },
+            tile_alpha,
+            anchored_entities: Vec::new(),
+            anchor_layers: HashMap::new(),
+            anchor_cells: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of layers of the level that have spawned so far.
+    pub fn layers_spawned(&self) -> usize {
+        self.layers_spawned
+    }
+}
+
+/// The [TilesetMap] a world spawns its levels with, cached by [process_ldtk_levels] across
+/// updates, since building it loads the tilesets of [LdtkAssetRoot] overrides.
+///
+/// Rebuilt when the [LdtkAsset], [TilesetRemap] or [LdtkAssetRoot] of the world change.
+#[derive(Clone, Debug)]
+pub struct WorldTilesetMap {
+    ldtk_handle: Handle<LdtkAsset>,
+    tileset_remap: Option<TilesetRemap>,
+    asset_root: Option<LdtkAssetRoot>,
+    tileset_map: TilesetMap,
+}
+
+/// The definition maps of a world's [LdtkAsset] used to spawn its levels.
+struct WorldSpawnMaps<'a> {
+    tileset_definition_map: HashMap<i32, &'a TilesetDefinition>,
+    entity_definition_map: HashMap<i32, &'a EntityDefinition>,
+    layer_definition_map: HashMap<i32, &'a LayerDefinition>,
+    ldtk_directory: PathBuf,
+}
+
+/// Performs all the spawning of levels, layers, chunks, bundles, entities, tiles, etc. for level
+/// entities with a [PendingLevelSpawn] component, once their assets are available.
+///
+/// With [LdtkSettings::level_spawn_budget], levels are spawned a few layers at a time, until the
+/// budget of the update is spent, and continue in the next update with a [LevelSpawnProgress].
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn process_ldtk_levels(
     mut commands: Commands,
@@ -257,230 +882,856 @@ pub fn process_ldtk_levels(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     ldtk_assets: Res<Assets<LdtkAsset>>,
     level_assets: Res<Assets<LdtkLevel>>,
-    ldtk_entity_map: NonSend<LdtkEntityMap>,
-    ldtk_int_cell_map: NonSend<LdtkIntCellMap>,
-    ldtk_query: Query<&Handle<LdtkAsset>>,
-    level_query: Query<(Entity, &Handle<LdtkLevel>, &Parent), Added<Handle<LdtkLevel>>>,
+    // Grouped in a tuple, since systems can't have more than 16 parameters
+    ldtk_registrations: (
+        NonSend<LdtkEntityMap>,
+        NonSend<LdtkIntCellMap>,
+        NonSend<LdtkIntCellIdentifierMap>,
+        NonSend<LdtkIntCellPromotionMap>,
+        NonSend<LdtkReflectedEntityMap>,
+        NonSend<LdtkDespawnPolicyMap>,
+        NonSend<LdtkEntityAnchorMap>,
+        NonSend<LdtkFilePathFieldMap>,
+    ),
+    ldtk_query: Query<(
+        &Handle<LdtkAsset>,
+        Option<&TilesetRemap>,
+        Option<&LdtkAssetRoot>,
+    )>,
+    mut level_query: Query<
+        (
+            Entity,
+            &Handle<LdtkLevel>,
+            &Parent,
+            Option<&mut Map>,
+            Option<&mut LevelSpawnProgress>,
+        ),
+        With<PendingLevelSpawn>,
+    >,
     worldly_query: Query<&Worldly>,
-    mut level_events: EventWriter<LevelEvent>,
+    ldtk_settings: Res<LdtkSettings>,
+    extruded_tilesets: Res<ExtrudedTilesets>,
+    spawn_events: (
+        EventWriter<LevelEvent>,
+        EventWriter<LevelSpawnReport>,
+        EventWriter<LevelSpawnError>,
+    ),
+    tileset_map_cache: (
+        Local<HashMap<Entity, WorldTilesetMap>>,
+        EventReader<AssetEvent<LdtkAsset>>,
+    ),
+    #[cfg(feature = "3d")] layer_3d_settings: Option<Res<Layer3dSettings>>,
 ) {
     // This function uses code from the bevy_ecs_tilemap ldtk example
     // https://github.com/StarArawn/bevy_ecs_tilemap/blob/main/examples/ldtk/ldtk.rs
 
-    for (ldtk_entity, level_handle, parent) in level_query.iter() {
-        if let Ok(ldtk_handle) = ldtk_query.get(parent.0) {
+    let (
+        ldtk_entity_map,
+        ldtk_int_cell_map,
+        ldtk_int_cell_identifier_map,
+        ldtk_int_cell_promotion_map,
+        ldtk_reflected_entity_map,
+        ldtk_despawn_policy_map,
+        ldtk_entity_anchor_map,
+        ldtk_file_path_field_map,
+    ) = ldtk_registrations;
+    let (mut level_events, mut spawn_reports, mut spawn_errors) = spawn_events;
+    let (mut tileset_map_cache, mut ldtk_events) = tileset_map_cache;
+
+    for event in ldtk_events.iter() {
+        if let AssetEvent::Modified { handle } | AssetEvent::Removed { handle } = event {
+            tileset_map_cache.retain(|_, cached| cached.ldtk_handle != *handle);
+        }
+    }
+    // Dropped along with their worlds, so their tilesets can be unloaded
+    tileset_map_cache.retain(|world_entity, _| ldtk_query.get(*world_entity).is_ok());
+
+    // Built once per update for each world with pending levels
+    let mut world_spawn_maps: HashMap<Entity, WorldSpawnMaps> = HashMap::new();
+    let mut worldly_set: Option<HashSet<Worldly>> = None;
+
+    // Layers drawn as 3D quads don't need tilemaps, which only 2D cameras draw
+    #[cfg(feature = "3d")]
+    let layer_quads = layer_3d_settings.is_some();
+    #[cfg(not(feature = "3d"))]
+    let layer_quads = false;
+    let spawn_tilemaps = !ldtk_settings.headless && !layer_quads;
+
+    let deadline = ldtk_settings
+        .level_spawn_budget
+        .map(|level_spawn_budget| Instant::now() + level_spawn_budget);
+
+    for (ldtk_entity, level_handle, parent, map, progress) in level_query.iter_mut() {
+        if deadline.map_or(false, |d| Instant::now() >= d) {
+            break;
+        }
+
+        if let Ok((ldtk_handle, tileset_remap, asset_root)) = ldtk_query.get(parent.0) {
             if let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) {
-                let tileset_definition_map: HashMap<i32, &TilesetDefinition> = ldtk_asset
-                    .project
-                    .defs
-                    .tilesets
-                    .iter()
-                    .map(|t| (t.uid, t))
-                    .collect();
+                let remap = |uid: i32| tileset_remap.map(|r| r.target(uid)).unwrap_or(uid);
+
+                let maps = world_spawn_maps.entry(parent.0).or_insert_with(|| {
+                    let project_tilesets: HashMap<i32, &TilesetDefinition> = ldtk_asset
+                        .project
+                        .defs
+                        .tilesets
+                        .iter()
+                        .map(|t| (t.uid, t))
+                        .collect();
+
+                    WorldSpawnMaps {
+                        tileset_definition_map: project_tilesets
+                            .keys()
+                            .filter_map(|uid| Some((*uid, *project_tilesets.get(&remap(*uid))?)))
+                            .collect(),
+                        entity_definition_map: create_entity_definition_map(
+                            &ldtk_asset.project.defs.entities,
+                        ),
+                        layer_definition_map: create_layer_definition_map(
+                            &ldtk_asset.project.defs.layers,
+                        ),
+                        // FilePath fields are relative to the LDtk file, or to the asset root
+                        // override
+                        ldtk_directory: match asset_root.and_then(|r| r.0.as_ref()) {
+                            Some(root) => root.into(),
+                            None => asset_server
+                                .get_handle_path(ldtk_handle)
+                                .map(|p| ldtk_directory(p.path()).to_path_buf())
+                                .unwrap_or_default(),
+                        },
+                    }
+                });
+
+                let cached = tileset_map_cache.get(&parent.0).map_or(false, |cached| {
+                    cached.ldtk_handle == *ldtk_handle
+                        && cached.tileset_remap.as_ref() == tileset_remap
+                        && cached.asset_root.as_ref() == asset_root
+                });
+
+                if !cached {
+                    let tileset_map = ldtk_asset
+                        .tileset_map
+                        .keys()
+                        .filter_map(|uid| {
+                            let path = asset_root.and_then(|r| {
+                                r.resolve(&maps.tileset_definition_map.get(uid)?.rel_path)
+                            });
+
+                            match path {
+                                Some(path) => Some((*uid, asset_server.load(path.as_str()))),
+                                None => {
+                                    Some((*uid, ldtk_asset.tileset_map.get(&remap(*uid))?.clone()))
+                                }
+                            }
+                        })
+                        .collect();
+
+                    tileset_map_cache.insert(
+                        parent.0,
+                        WorldTilesetMap {
+                            ldtk_handle: ldtk_handle.clone_weak(),
+                            tileset_remap: tileset_remap.cloned(),
+                            asset_root: asset_root.cloned(),
+                            tileset_map,
+                        },
+                    );
+                }
 
-                let entity_definition_map =
-                    create_entity_definition_map(&ldtk_asset.project.defs.entities);
+                let tileset_map = &tileset_map_cache[&parent.0].tileset_map;
 
-                let worldly_set = worldly_query.iter().cloned().collect();
+                let worldly_set =
+                    worldly_set.get_or_insert_with(|| worldly_query.iter().cloned().collect());
 
                 if let Some(level) = level_assets.get(level_handle) {
-                    spawn_level(
-                        &level.level,
+                    if u16::try_from(level.level.uid).is_err() {
+                        error!(
+                            "Level {} can't be spawned, its uid {} doesn't fit in a tilemap id",
+                            level.level.identifier, level.level.uid
+                        );
+                        spawn_errors.send(LevelSpawnError::LevelUidOutOfRange {
+                            level_uid: level.level.uid,
+                        });
+                        commands.entity(ldtk_entity).remove::<PendingLevelSpawn>();
+                        continue;
+                    }
+
+                    let mut new_map = None;
+                    let map = match map {
+                        Some(map) => map.into_inner(),
+                        None => new_map.insert(Map::new(level.level.uid as u16, ldtk_entity)),
+                    };
+
+                    let mut new_progress = None;
+                    let progress = match progress {
+                        Some(progress) => progress.into_inner(),
+                        None => {
+                            // Tiles of fading levels spawn transparent, and the fade starts once
+                            // the level has spawned completely
+                            let tile_alpha = if ldtk_settings.level_fade_in_frames > 0 {
+                                0.
+                            } else {
+                                1.
+                            };
+                            new_progress
+                                .insert(LevelSpawnProgress::new(level.level.uid, tile_alpha))
+                        }
+                    };
+
+                    let spawn_start = Instant::now();
+                    let spawned = spawn_level(
+                        GridLevel::from(&level.level),
+                        Some(&level.level),
                         &mut commands,
                         &asset_server,
                         &mut texture_atlases,
                         &mut meshes,
                         &ldtk_entity_map,
                         &ldtk_int_cell_map,
-                        &entity_definition_map,
-                        &ldtk_asset.tileset_map,
-                        &tileset_definition_map,
+                        &ldtk_int_cell_identifier_map,
+                        &ldtk_int_cell_promotion_map,
+                        &ldtk_reflected_entity_map,
+                        &ldtk_despawn_policy_map,
+                        &ldtk_entity_anchor_map,
+                        &ldtk_file_path_field_map,
+                        &maps.ldtk_directory,
+                        &maps.entity_definition_map,
+                        &maps.layer_definition_map,
+                        &ldtk_asset.layer_settings_cache,
+                        tileset_map,
+                        &maps.tileset_definition_map,
+                        &extruded_tilesets,
                         worldly_set,
                         ldtk_entity,
+                        &ldtk_settings,
+                        spawn_tilemaps,
+                        map,
+                        progress,
+                        deadline,
                     );
+                    progress.spawn_report.duration += spawn_start.elapsed();
+
+                    if let Some(map) = new_map {
+                        commands
+                            .entity(ldtk_entity)
+                            .insert(map)
+                            .insert(LevelEntityIndex::default());
+                    }
+
+                    if !spawned {
+                        if let Some(progress) = new_progress {
+                            commands.entity(ldtk_entity).insert(progress);
+                        }
+                        continue;
+                    }
+
+                    let mut spawn_report = std::mem::take(&mut progress.spawn_report);
+                    if new_progress.is_none() {
+                        commands.entity(ldtk_entity).remove::<LevelSpawnProgress>();
+                    }
+
+                    if ldtk_settings.level_fade_in_frames > 0 {
+                        commands.entity(ldtk_entity).insert(LevelFadeIn::default());
+                    }
+
+                    spawn_report.level_identifier = level.level.identifier.clone();
+                    spawn_report.budget_warnings = ldtk_settings.level_budget.check(&spawn_report);
+
+                    for budget_warning in &spawn_report.budget_warnings {
+                        warn!(
+                            "Level {} exceeds its budget: {:?}",
+                            spawn_report.level_identifier, budget_warning
+                        );
+                    }
+
+                    for spawn_error in &spawn_report.errors {
+                        error!(
+                            "Level {} couldn't be spawned completely: {:?}",
+                            spawn_report.level_identifier, spawn_error
+                        );
+                        spawn_errors.send(spawn_error.clone());
+                    }
+
+                    if ldtk_settings.level_background_color && !ldtk_settings.headless {
+                        spawn_level_background(&mut commands, &level.level, ldtk_entity);
+                    }
+
+                    if ldtk_settings.level_background_image && !ldtk_settings.headless {
+                        if let Some(image) = ldtk_asset.level_background_map.get(&level.level.uid) {
+                            spawn_level_background_image(
+                                &mut commands,
+                                &level.level,
+                                image.clone(),
+                                ldtk_entity,
+                            );
+                        }
+                    }
+
+                    let mut level_commands = commands.entity(ldtk_entity);
+                    for file_path_fields in
+                        ldtk_file_path_field_map.get(&None).into_iter().flatten()
+                    {
+                        file_path_fields.evaluate(
+                            &mut level_commands,
+                            &level.level.field_instances,
+                            &asset_server,
+                            &maps.ldtk_directory,
+                        );
+                    }
+
+                    level_commands.remove::<PendingLevelSpawn>();
                     level_events.send(LevelEvent::Spawned(level.level.uid));
+                    spawn_reports.send(spawn_report);
                 }
             }
         }
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn spawn_level(
-    level: &Level,
+/// Z translation of [LevelBackground]s relative to their level, so they're drawn below its layers.
+pub const LEVEL_BACKGROUND_Z: f32 = -1.;
+
+/// Z translation of [LevelBackgroundImage]s relative to their level, so they're drawn above the
+/// background color but below the layers.
+pub const LEVEL_BACKGROUND_IMAGE_Z: f32 = -0.5;
+
+fn spawn_level_background_image(
     commands: &mut Commands,
-    asset_server: &AssetServer,
-    texture_atlases: &mut Assets<TextureAtlas>,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    ldtk_entity_map: &LdtkEntityMap,
-    ldtk_int_cell_map: &LdtkIntCellMap,
-    entity_definition_map: &HashMap<i32, &EntityDefinition>,
-    tileset_map: &TilesetMap,
-    tileset_definition_map: &HashMap<i32, &TilesetDefinition>,
-    worldly_set: HashSet<Worldly>,
-    ldtk_entity: Entity,
+    level: &Level,
+    image: Handle<Image>,
+    level_entity: Entity,
 ) {
-    let mut map = Map::new(level.uid as u16, ldtk_entity);
+    let bg_pos = match &level.bg_pos {
+        Some(bg_pos) if bg_pos.crop_rect.len() == 4 => bg_pos,
+        _ => {
+            warn!(
+                "Missing background image position for level {}",
+                level.identifier
+            );
+            return;
+        }
+    };
 
-    if let Some(layer_instances) = &level.layer_instances {
-        let mut layer_id = 0;
-        for layer_instance in layer_instances.iter().rev() {
-            match layer_instance.layer_instance_type {
-                Type::Entities => {
-                    commands.entity(ldtk_entity).with_children(|commands| {
-                        for entity_instance in &layer_instance.entity_instances {
-                            let transform = calculate_transform_from_entity_instance(
-                                entity_instance,
-                                entity_definition_map,
-                                level.px_hei,
-                                layer_id as f32,
-                            );
-                            // Note: entities do not seem to be affected visually by layer offsets in
-                            // the editor, so no layer offset is added to the transform here.
+    let crop_position = Vec2::new(bg_pos.crop_rect[0], bg_pos.crop_rect[1]);
+    let crop_size = Vec2::new(bg_pos.crop_rect[2], bg_pos.crop_rect[3]);
+    let size = crop_size * bg_pos.scale;
 
-                            let mut entity_commands = commands.spawn();
+    // topLeftPx already accounts for the pivot of the image
+    let top_left = bg_pos.top_left_px.as_vec2();
+    let translation = Vec2::new(
+        top_left.x + size.x / 2.,
+        level.px_hei as f32 - top_left.y - size.y / 2.,
+    );
 
-                            let (tileset, tileset_definition) = match &entity_instance.tile {
-                                Some(t) => (
-                                    tileset_map.get(&t.tileset_uid),
-                                    tileset_definition_map.get(&t.tileset_uid).copied(),
-                                ),
-                                None => (None, None),
-                            };
+    commands.entity(level_entity).with_children(|commands| {
+        commands
+            .spawn()
+            .insert(LevelBackground)
+            .insert(LevelBackgroundImage {
+                image,
+                crop_position,
+                crop_size,
+            })
+            .insert_bundle((
+                Transform::from_translation(translation.extend(LEVEL_BACKGROUND_IMAGE_Z))
+                    .with_scale(bg_pos.scale.extend(1.)),
+                GlobalTransform::default(),
+            ));
+    });
+}
+
+/// Gives [LevelBackgroundImage]s a texture atlas of their cropped image once it has loaded.
+pub fn build_level_background_images(
+    mut commands: Commands,
+    background_query: Query<(Entity, &LevelBackgroundImage), Without<Handle<TextureAtlas>>>,
+    images: Res<Assets<Image>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    for (entity, background_image) in background_query.iter() {
+        if let Some(image) = images.get(&background_image.image) {
+            let mut texture_atlas =
+                TextureAtlas::new_empty(background_image.image.clone(), image.size());
+            texture_atlas.add_texture(bevy::sprite::Rect {
+                min: background_image.crop_position,
+                max: background_image.crop_position + background_image.crop_size,
+            });
+
+            commands.entity(entity).insert_bundle((
+                TextureAtlasSprite::new(0),
+                texture_atlases.add(texture_atlas),
+                Visibility::default(),
+            ));
+        }
+    }
+}
+
+/// Sets the [ClearColor] to the background color of the selected level, or of the project if no
+/// level is selected, when [LdtkSettings::clear_color_from_level] is enabled.
+pub fn update_clear_color(
+    ldtk_settings: Res<LdtkSettings>,
+    level_selection: Option<Res<LevelSelection>>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    clear_color: Option<ResMut<ClearColor>>,
+) {
+    let mut clear_color = match clear_color {
+        Some(clear_color) if ldtk_settings.clear_color_from_level => clear_color,
+        _ => return,
+    };
+
+    let ldtk_asset = match ldtk_query.iter().find_map(|h| ldtk_assets.get(h)) {
+        Some(ldtk_asset) => ldtk_asset,
+        None => return,
+    };
+
+    let bg_color = match level_selection
+        .as_ref()
+        .and_then(|level_selection| ldtk_asset.get_level(level_selection))
+    {
+        Some(level) => &level.bg_color,
+        None => &ldtk_asset.project.bg_color,
+    };
+
+    if let Ok(color) = Color::hex(bg_color.trim_start_matches('#')) {
+        if clear_color.0 != color {
+            clear_color.0 = color;
+        }
+    }
+}
+
+fn spawn_level_background(commands: &mut Commands, level: &Level, level_entity: Entity) {
+    let color = match Color::hex(level.bg_color.trim_start_matches('#')) {
+        Ok(color) => color,
+        Err(_) => {
+            warn!(
+                "Invalid background color {:?} for level {}",
+                level.bg_color, level.identifier
+            );
+            return;
+        }
+    };
+
+    let size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+
+    commands.entity(level_entity).with_children(|commands| {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation((size / 2.).extend(LEVEL_BACKGROUND_Z)),
+                ..Default::default()
+            })
+            .insert(LevelBackground);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_level(
+    level: GridLevel,
+    source_level: Option<&Level>,
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    texture_atlases: &mut Assets<TextureAtlas>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    ldtk_entity_map: &LdtkEntityMap,
+    ldtk_int_cell_map: &LdtkIntCellMap,
+    ldtk_int_cell_identifier_map: &LdtkIntCellIdentifierMap,
+    ldtk_int_cell_promotion_map: &LdtkIntCellPromotionMap,
+    ldtk_reflected_entity_map: &LdtkReflectedEntityMap,
+    ldtk_despawn_policy_map: &LdtkDespawnPolicyMap,
+    ldtk_entity_anchor_map: &LdtkEntityAnchorMap,
+    ldtk_file_path_field_map: &LdtkFilePathFieldMap,
+    ldtk_directory: &Path,
+    entity_definition_map: &HashMap<i32, &EntityDefinition>,
+    layer_definition_map: &HashMap<i32, &LayerDefinition>,
+    layer_settings_cache: &LayerSettingsCache,
+    tileset_map: &TilesetMap,
+    tileset_definition_map: &HashMap<i32, &TilesetDefinition>,
+    extruded_tilesets: &ExtrudedTilesets,
+    worldly_set: &HashSet<Worldly>,
+    ldtk_entity: Entity,
+    ldtk_settings: &LdtkSettings,
+    spawn_tilemaps: bool,
+    map: &mut Map,
+    progress: &mut LevelSpawnProgress,
+    deadline: Option<Instant>,
+) -> bool {
+    let tile_alpha = progress.tile_alpha;
+    let LevelSpawnProgress {
+        layers_spawned,
+        layer_ids,
+        layer_tilesets,
+        spawn_report,
+        anchored_entities,
+        anchor_layers,
+        anchor_cells,
+        ..
+    } = progress;
+
+    let layer_instances = layer_draw_order(level.layer_instances, &ldtk_settings.layer_draw_order);
+    let first_layer = *layers_spawned;
+
+    'layers: for (layer_index, layer_instance) in
+        layer_instances.into_iter().enumerate().skip(first_layer)
+    {
+        // At least one layer is spawned per update, so levels always make progress
+        if layer_index > first_layer && deadline.map_or(false, |d| Instant::now() >= d) {
+            *layers_spawned = layer_index;
+            return false;
+        }
+
+        match layer_instance.layer_instance_type {
+            Type::Entities => {
+                commands.entity(ldtk_entity).with_children(|commands| {
+                    for entity_instance in &layer_instance.entity_instances {
+                        let mut transform = calculate_transform_from_entity_instance(
+                            entity_instance,
+                            entity_definition_map,
+                            level.px_hei,
+                            ldtk_settings
+                                .layer_z
+                                .z(&layer_instance.identifier, layer_ids.peek()),
+                        );
+                        transform.translation = entity_instance_level_translation(
+                            entity_instance,
+                            layer_instance,
+                            level.px_hei,
+                            ldtk_settings.snap_entities_to_grid,
+                        )
+                        .extend(transform.translation.z);
+
+                        let grid_coords = ldtk_grid_coords_to_grid_coords(
+                            entity_instance.grid,
+                            layer_instance.c_hei,
+                        );
+                        let pixel_coords = PixelCoords::new(
+                            entity_instance.px.x,
+                            level.px_hei - entity_instance.px.y,
+                        );
 
-                            let predicted_worldly = Worldly::bundle_entity(
+                        let mut entity_commands = commands.spawn();
+
+                        let entity_tileset_uid =
+                            entity_instance.tile.as_ref().map(|t| t.tileset_uid).or_else(|| {
+                                entity_instance.field_instances.iter().find_map(|f| {
+                                    match &f.value {
+                                        FieldValue::Tile(tile) => {
+                                            tile.as_ref().map(|t| t.tileset_uid)
+                                        }
+                                        _ => None,
+                                    }
+                                })
+                            });
+
+                        let (tileset, tileset_definition) = match entity_tileset_uid {
+                            Some(tileset_uid) => (
+                                tileset_map.get(&tileset_uid),
+                                tileset_definition_map.get(&tileset_uid).copied(),
+                            ),
+                            None => (None, None),
+                        };
+
+                        let predicted_worldly = Worldly::bundle_entity(
+                            entity_instance,
+                            layer_instance,
+                            tileset,
+                            tileset_definition,
+                            asset_server,
+                            texture_atlases,
+                        );
+
+                        if !worldly_set.contains(&predicted_worldly) {
+                            // Inserted before the registered bundle, so it can be overridden
+                            entity_commands
+                                .insert(calculate_aabb_from_entity_instance(
+                                    entity_instance,
+                                    entity_definition_map,
+                                ))
+                                .insert(grid_coords)
+                                .insert(pixel_coords);
+
+                            let default_ldtk_entity: Box<dyn PhantomLdtkEntityTrait> =
+                                Box::new(PhantomLdtkEntity::<EntityInstanceBundle>::new());
+
+                            let ldtk_entity_registration = ldtk_map_get(
+                                layer_instance.identifier.clone(),
+                                entity_instance.identifier.clone(),
+                                ldtk_entity_map,
+                            );
+
+                            if ldtk_entity_registration.is_none() {
+                                spawn_report
+                                    .unmatched_entity_identifiers
+                                    .insert(entity_instance.identifier.clone());
+                            }
+
+                            *spawn_report
+                                .layer_counts
+                                .entry(layer_instance.identifier.clone())
+                                .or_default() += 1;
+                            *spawn_report
+                                .entity_counts
+                                .entry(entity_instance.identifier.clone())
+                                .or_default() += 1;
+
+                            ldtk_entity_registration
+                                .unwrap_or(&default_ldtk_entity)
+                                .evaluate(
+                                &mut entity_commands,
                                 entity_instance,
                                 layer_instance,
-                                tileset,
-                                tileset_definition,
+                                &LdtkEntityContext {
+                                    tileset,
+                                    tileset_definition,
+                                    tileset_map,
+                                    tileset_definition_map,
+                                    headless: ldtk_settings.headless,
+                                    ldtk_directory,
+                                    level: source_level,
+                                },
                                 asset_server,
                                 texture_atlases,
                             );
 
-                            if !worldly_set.contains(&predicted_worldly) {
-                                let default_ldtk_entity: Box<dyn PhantomLdtkEntityTrait> =
-                                    Box::new(PhantomLdtkEntity::<EntityInstanceBundle>::new());
+                            if let Some(despawn_policy) =
+                                ldtk_despawn_policy_map.get(&entity_instance.identifier)
+                            {
+                                entity_commands.insert(*despawn_policy);
+                            }
+
+                            if !entity_instance.iid.is_empty() {
+                                entity_commands.insert(EntityIid(entity_instance.iid.clone()));
+                            }
 
-                                ldtk_map_get_or_default(
-                                    layer_instance.identifier.clone(),
-                                    entity_instance.identifier.clone(),
-                                    &default_ldtk_entity,
-                                    ldtk_entity_map,
-                                )
-                                .evaluate(
+                            for file_path_fields in ldtk_file_path_field_map
+                                .get(&Some(entity_instance.identifier.clone()))
+                                .into_iter()
+                                .flatten()
+                            {
+                                file_path_fields.evaluate(
                                     &mut entity_commands,
-                                    entity_instance,
-                                    layer_instance,
-                                    tileset,
-                                    tileset_definition,
+                                    &entity_instance.field_instances,
                                     asset_server,
-                                    texture_atlases,
+                                    ldtk_directory,
                                 );
+                            }
+
+                            if let Some(components) =
+                                ldtk_reflected_entity_map.get(&entity_instance.identifier)
+                            {
+                                let entity = entity_commands.id();
+                                entity_commands.commands().add(InsertReflectedComponents {
+                                    entity,
+                                    components: components.iter().map(|c| c.clone_value()).collect(),
+                                });
+                            }
+
+                            #[cfg(feature = "scripting")]
+                            if let Some(script) =
+                                crate::scripting::LdtkScript::from_entity_instance(
+                                    entity_instance,
+                                    asset_server,
+                                    ldtk_directory,
+                                )
+                            {
+                                entity_commands.insert(script);
+                            }
+
+                            if ldtk_settings.y_sort_entities {
+                                let y_sort = YSort {
+                                    base_z: transform.translation.z,
+                                    spread: ldtk_settings.layer_z.step / 2.,
+                                    height: level.px_hei as f32,
+                                };
+                                transform.translation.z = y_sort.z(transform.translation.y);
 
-                                entity_commands
-                                    .insert(transform)
-                                    .insert(GlobalTransform::default());
+                                entity_commands.insert(y_sort);
+                            }
+
+                            entity_commands
+                                .insert(transform)
+                                .insert(GlobalTransform::default())
+                                .insert(LayerZSlot {
+                                    layer_identifier: layer_instance.identifier.clone(),
+                                    layer_id: layer_ids.peek(),
+                                });
+
+                            if let Some(anchor_layer) =
+                                ldtk_entity_anchor_map.get(&entity_instance.identifier)
+                            {
+                                anchored_entities.push((
+                                    entity_commands.id(),
+                                    anchor_layer.clone(),
+                                    pixel_coords,
+                                    transform,
+                                ));
                             }
                         }
-                    });
-                }
-                _ => {
-                    // The remaining layers have a lot of shared code.
-                    // This is because:
-                    // 1. There is virtually no difference between AutoTile and Tile layers
-                    // 2. IntGrid layers can sometimes have AutoTile functionality
-
-                    let map_size = MapSize(
-                        (layer_instance.c_wid as f32 / CHUNK_SIZE.0 as f32).ceil() as u32,
-                        (layer_instance.c_hei as f32 / CHUNK_SIZE.1 as f32).ceil() as u32,
-                    );
+                    }
+                });
+            }
+            _ => {
+                // The remaining layers have a lot of shared code.
+                // This is because:
+                // 1. There is virtually no difference between AutoTile and Tile layers
+                // 2. IntGrid layers can sometimes have AutoTile functionality
+
+                let mesh_settings = ldtk_settings
+                    .layer_mesh_settings
+                    .get(&layer_instance.identifier)
+                    .copied()
+                    .unwrap_or_default();
+                let chunk_size = mesh_settings.chunk_size;
+
+                let map_size = MapSize(
+                    (layer_instance.c_wid as f32 / chunk_size.0 as f32).ceil() as u32,
+                    (layer_instance.c_hei as f32 / chunk_size.1 as f32).ceil() as u32,
+                );
 
-                    let tileset_definition = layer_instance
-                        .tileset_def_uid
-                        .map(|u| tileset_definition_map.get(&u).unwrap());
+                *spawn_report
+                    .layer_counts
+                    .entry(layer_instance.identifier.clone())
+                    .or_default() += layer_instance.grid_tiles.len()
+                    + layer_instance.auto_layer_tiles.len()
+                    + layer_instance
+                        .int_grid_csv
+                        .iter()
+                        .filter(|v| **v != 0)
+                        .count();
+
+                let rule_tileset_uids = layer_definition_map
+                    .get(&layer_instance.layer_def_uid)
+                    .copied()
+                    .map(auto_rule_tileset_uids)
+                    .unwrap_or_default();
+
+                // Tiles placed by auto-layer rules with their own tileset get their own layers,
+                // since each layer can only be rendered with one texture
+                for (tileset_index, (tileset_uid, grid_tiles)) in
+                    group_tiles_by_tileset(layer_instance, &rule_tileset_uids)
+                        .into_iter()
+                        .enumerate()
+                {
+                    let tileset_definition =
+                        tileset_uid.map(|u| tileset_definition_map.get(&u).unwrap());
+
+                    let layer_offset = Vec3::new(
+                        layer_instance.px_total_offset_x as f32,
+                        -layer_instance.px_total_offset_y as f32,
+                        0.,
+                    );
 
-                    let tile_size = match tileset_definition {
-                        Some(tileset_definition) => TileSize(
-                            tileset_definition.tile_grid_size as f32,
-                            tileset_definition.tile_grid_size as f32,
+                    let cached_layer_settings = layer_settings_cache
+                        .get(&(layer_instance.layer_def_uid, tileset_uid))
+                        .filter(|_| tileset_definition.map(|t| t.uid) == tileset_uid);
+
+                    let (mut settings, layer_scale) = match cached_layer_settings {
+                        Some((settings, layer_scale)) => (
+                            LayerSettings {
+                                map_size,
+                                ..*settings
+                            },
+                            *layer_scale,
                         ),
-                        None => TileSize(
-                            layer_instance.grid_size as f32,
-                            layer_instance.grid_size as f32,
+                        None => layer_settings_and_scale(
+                            map_size,
+                            layer_instance.grid_size,
+                            tileset_definition.copied(),
                         ),
                     };
 
-                    let texture_size = match tileset_definition {
-                        Some(tileset_definition) => TextureSize(
-                            tileset_definition.px_wid as f32,
-                            tileset_definition.px_hei as f32,
-                        ),
-                        None => TextureSize(0., 0.),
-                    };
+                    settings.chunk_size = chunk_size;
+                    settings.mesh_type = mesh_settings.mesh_type;
 
-                    let mut settings =
-                        LayerSettings::new(map_size, CHUNK_SIZE, tile_size, texture_size);
+                    if tileset_definition.map_or(false, |t| t.spacing != 0) {
+                        warn!("Tile spacing currently not supported for AutoTile and Tile layers");
 
-                    if let Some(tileset_definition) = tileset_definition {
-                        settings.grid_size = Vec2::splat(layer_instance.grid_size as f32);
-                        if tileset_definition.spacing != 0 {
-                            warn!(
-                                "Tile spacing currently not supported for AutoTile and Tile layers"
-                            );
+                        // This causes a crash after bevy_ecs_tilemap switched to texture
+                        // arrays
+                        //settings.tile_spacing = Vec2::splat(tileset_definition.spacing as f32);
+                    }
 
-                            // This causes a crash after bevy_ecs_tilemap switched to texture
-                            // arrays
-                            //settings.tile_spacing = Vec2::splat(tileset_definition.spacing as f32);
-                        }
+                    // Tilesets aren't loaded in headless mode
+                    let mut image_handle = tileset_definition
+                        .and_then(|tileset_definition| tileset_map.get(&tileset_definition.uid))
+                        .cloned()
+                        .unwrap_or_else(|| DEFAULT_IMAGE_HANDLE.typed());
+
+                    if let Some(extruded_tileset) = extruded_tilesets.tilesets.get(&image_handle) {
+                        image_handle = extruded_tileset.image.clone();
+                        settings.texture_size =
+                            TextureSize(extruded_tileset.size.x, extruded_tileset.size.y);
+                        settings.tile_spacing = Vec2::splat(2. * extruded_tileset.extrusion as f32);
                     }
 
-                    // The change to the settings.grid_size above is supposed to help handle cases
-                    // where the tileset's tile size and the layer's tile size are different.
-                    // However, changing the grid_size doesn't have any affect with the current
-                    // bevy_ecs_tilemap, so the workaround is to scale up the entire layer.
-                    let layer_scale = (settings.grid_size
-                        / Vec2::new(settings.tile_size.0 as f32, settings.tile_size.1 as f32))
-                    .extend(1.);
-
-                    let image_handle = match tileset_definition {
-                        Some(tileset_definition) => {
-                            tileset_map.get(&tileset_definition.uid).unwrap().clone()
-                        }
-                        None => DEFAULT_IMAGE_HANDLE.typed(),
-                    };
+                    for (i, grid_tiles) in layer_grid_tiles(grid_tiles).into_iter().enumerate() {
+                        let layer_id = match layer_ids.allocate() {
+                            Some(layer_id) => layer_id,
+                            None => {
+                                spawn_report.errors.push(LevelSpawnError::TooManyLayers {
+                                    level_uid: level.uid,
+                                    layer_identifier: layer_instance.identifier.clone(),
+                                });
+                                break 'layers;
+                            }
+                        };
 
-                    let mut grid_tiles = layer_instance.grid_tiles.clone();
-                    grid_tiles.extend(layer_instance.auto_layer_tiles.clone());
+                        // Tilemap chunks, and the IntGrid cells below, are placed at the z of their
+                        // layer id, so the layer is offset to move them to its configured z
+                        let layer_z = ldtk_settings
+                            .layer_z
+                            .z(&layer_instance.identifier, layer_id as u32);
+                        let layer_offset = layer_offset + Vec3::Z * (layer_z - layer_id as f32);
+
+                        // Without tilemaps, levels only need the IntGrid cells, which are spawned
+                        // with the first sub-layer
+                        if !spawn_tilemaps
+                            && (layer_instance.layer_instance_type != Type::IntGrid
+                                || tileset_index != 0
+                                || i != 0)
+                        {
+                            continue;
+                        }
 
-                    for (i, grid_tiles) in layer_grid_tiles(grid_tiles).into_iter().enumerate() {
                         let layer_entity = if layer_instance.layer_instance_type == Type::IntGrid {
                             // The current spawning of IntGrid layers doesn't allow using
                             // LayerBuilder::new_batch().
                             // So, the actual LayerBuilder usage diverges greatly here
 
-                            let (mut layer_builder, layer_entity) = LayerBuilder::<TileBundle>::new(
-                                commands,
-                                settings,
-                                map.id,
-                                layer_id as u16,
-                            );
+                            let (mut layer_builder, layer_entity) = if !spawn_tilemaps {
+                                (None, commands.spawn().id())
+                            } else {
+                                let (layer_builder, layer_entity) = LayerBuilder::<TileBundle>::new(
+                                    commands, settings, map.id, layer_id,
+                                );
+                                (Some(layer_builder), layer_entity)
+                            };
 
-                            match tileset_definition {
-                                Some(_) => {
-                                    let tile_maker = tile_pos_to_tile_maker(
-                                        layer_instance.c_hei,
-                                        layer_instance.grid_size,
-                                        grid_tiles,
+                            match (&mut layer_builder, tileset_definition) {
+                                (None, _) => (),
+                                (Some(layer_builder), Some(_)) => {
+                                    let tile_maker = tile_maker_with_alpha(
+                                        tile_pos_to_tile_maker(
+                                            layer_instance.c_hei,
+                                            layer_instance.grid_size,
+                                            grid_tiles,
+                                        ),
+                                        tile_alpha * layer_instance.opacity,
                                     );
 
                                     set_all_tiles_with_func(
-                                        &mut layer_builder,
+                                        layer_builder,
                                         tile_pos_to_tile_bundle_maker(tile_maker),
                                     );
                                 }
-                                None => {
+                                (Some(layer_builder), None)
+                                    if ldtk_settings.int_grid_cell_spawning
+                                        == IntGridCellSpawning::Tiles =>
+                                {
                                     set_all_tiles_with_func(
-                                        &mut layer_builder,
+                                        layer_builder,
                                         tile_pos_to_tile_bundle_if_int_grid_nonzero_maker(
                                             tile_pos_to_invisible_tile,
                                             &layer_instance.int_grid_csv,
@@ -489,9 +1740,28 @@ fn spawn_level(
                                         ),
                                     );
                                 }
+                                // Cells are spawned as plain entities instead of invisible tiles
+                                (Some(_), None) => (),
                             }
 
-                            if i == 0 {
+                            let plain_int_grid_cells = !spawn_tilemaps
+                                || (tileset_definition.is_none()
+                                    && ldtk_settings.int_grid_cell_spawning
+                                        != IntGridCellSpawning::Tiles);
+
+                            // IntGrid cells are only spawned with the first sub-layer of the
+                            // layer's own tileset
+                            if tileset_index == 0 && i == 0 {
+                                let mut int_grid_chunks = IntGridChunks::default();
+
+                                let int_grid_value_identifiers: HashMap<i32, &str> =
+                                    layer_definition_map
+                                        .get(&layer_instance.layer_def_uid)
+                                        .into_iter()
+                                        .flat_map(|d| d.int_grid_values.iter())
+                                        .filter_map(|v| Some((v.value, v.identifier.as_deref()?)))
+                                        .collect();
+
                                 for (i, value) in layer_instance
                                     .int_grid_csv
                                     .iter()
@@ -504,8 +1774,93 @@ fn spawn_level(
                                 layer_instance.c_hei as u32,
                             ).expect("int_grid_csv indices should be within the bounds of 0..(layer_widthd * layer_height)");
 
-                                    let tile_entity =
-                                        layer_builder.get_tile_entity(commands, tile_pos).unwrap();
+                                    if let Some(promotion) = ldtk_int_cell_promotion_map
+                                        .get(&(Some(layer_instance.identifier.clone()), *value))
+                                        .or_else(|| {
+                                            ldtk_int_cell_promotion_map.get(&(None, *value))
+                                        })
+                                    {
+                                        let promoted_tileset_definition =
+                                            promotion.tile.as_ref().and_then(|tile| {
+                                                tileset_definition_map
+                                                    .values()
+                                                    .find(|t| {
+                                                        t.identifier == tile.tileset_identifier
+                                                    })
+                                                    .map(|t| (tile.tile_id, *t))
+                                            });
+
+                                        let entity_instance = promoted_entity_instance(
+                                            layer_instance,
+                                            i,
+                                            promoted_tileset_definition,
+                                        );
+
+                                        let (tileset, tileset_definition) =
+                                            match promoted_tileset_definition {
+                                                Some((_, t)) => (tileset_map.get(&t.uid), Some(t)),
+                                                None => (None, None),
+                                            };
+
+                                        let tile_scale = match tileset_definition {
+                                            Some(t) => {
+                                                layer_instance.grid_size as f32
+                                                    / t.tile_grid_size as f32
+                                            }
+                                            None => 1.,
+                                        };
+
+                                        let translation = ldtk_pixel_coords_to_translation_pivoted(
+                                            entity_instance.px,
+                                            level.px_hei,
+                                            IVec2::new(
+                                                entity_instance.width,
+                                                entity_instance.height,
+                                            ),
+                                            entity_instance.pivot,
+                                        )
+                                        .extend(layer_z);
+
+                                        commands.entity(ldtk_entity).with_children(|commands| {
+                                            let mut entity_commands = commands.spawn();
+
+                                            promotion.ldtk_entity.evaluate(
+                                                &mut entity_commands,
+                                                &entity_instance,
+                                                layer_instance,
+                                                &LdtkEntityContext {
+                                                    tileset,
+                                                    tileset_definition,
+                                                    tileset_map,
+                                                    tileset_definition_map,
+                                                    headless: ldtk_settings.headless,
+                                                    ldtk_directory,
+                                                    level: source_level,
+                                                },
+                                                asset_server,
+                                                texture_atlases,
+                                            );
+
+                                            entity_commands
+                                                .insert(IntGridCell { value: *value })
+                                                .insert(GridCoords::from(tile_pos))
+                                                .insert(
+                                                    Transform::from_translation(translation)
+                                                        .with_scale(Vec3::new(
+                                                            tile_scale, tile_scale, 1.,
+                                                        )),
+                                                )
+                                                .insert(GlobalTransform::default())
+                                                .insert(LayerZSlot {
+                                                    layer_identifier: layer_instance
+                                                        .identifier
+                                                        .clone(),
+                                                    layer_id: layer_id as u32,
+                                                });
+                                        });
+
+                                        continue;
+                                    }
 
                                     let mut translation = tile_pos_to_translation_centered(
                                         tile_pos,
@@ -515,41 +1870,133 @@ fn spawn_level(
 
                                     translation /= layer_scale;
 
-                                    let mut entity_commands = commands.entity(tile_entity);
-
                                     let default_ldtk_int_cell: Box<dyn PhantomLdtkIntCellTrait> =
                                         Box::new(PhantomLdtkIntCell::<IntGridCellBundle>::new());
 
-                                    ldtk_map_get_or_default(
+                                    let ldtk_int_cell_registration = int_cell_map_get(
                                         layer_instance.identifier.clone(),
                                         *value,
-                                        &default_ldtk_int_cell,
+                                        int_grid_value_identifiers.get(value).copied(),
                                         ldtk_int_cell_map,
-                                    )
-                                    .evaluate(
-                                        &mut entity_commands,
-                                        IntGridCell { value: *value },
-                                        layer_instance,
+                                        ldtk_int_cell_identifier_map,
                                     );
 
-                                    entity_commands
-                                        .insert(Transform::from_translation(translation))
+                                    if ldtk_int_cell_registration.is_none() {
+                                        spawn_report
+                                            .unmatched_int_grid_values
+                                            .insert((layer_instance.identifier.clone(), *value));
+
+                                        if plain_int_grid_cells
+                                            && ldtk_settings.int_grid_cell_spawning
+                                                == IntGridCellSpawning::RegisteredOnly
+                                        {
+                                            continue;
+                                        }
+                                    }
+
+                                    let tile_entity = match &mut layer_builder {
+                                        Some(layer_builder) if !plain_int_grid_cells => {
+                                            layer_builder
+                                                .get_tile_entity(commands, tile_pos)
+                                                .unwrap()
+                                        }
+                                        _ => commands.spawn().id(),
+                                    };
+
+                                    let mut entity_commands = commands.entity(tile_entity);
+
+                                    ldtk_int_cell_registration
+                                        .unwrap_or(&default_ldtk_int_cell)
+                                        .evaluate(
+                                            &mut entity_commands,
+                                            IntGridCell { value: *value },
+                                            layer_instance,
+                                        );
+
+                                    entity_commands.insert(GridCoords::from(tile_pos));
+
+                                    let chunk_position = UVec2::new(
+                                        tile_pos.0 / chunk_size.0,
+                                        tile_pos.1 / chunk_size.1,
+                                    );
+
+                                    let chunk_size_in_pixels =
+                                        Vec2::new(chunk_size.0 as f32, chunk_size.1 as f32)
+                                            * layer_instance.grid_size as f32;
+
+                                    let chunk_translation = ((chunk_position.as_vec2()
+                                        + Vec2::splat(0.5))
+                                        * chunk_size_in_pixels)
+                                        .extend(0.)
+                                        / layer_scale;
+
+                                    let chunk_entity = *int_grid_chunks
+                                        .chunks
+                                        .entry(chunk_position)
+                                        .or_insert_with(|| {
+                                            commands
+                                                .spawn()
+                                                .insert(IntGridChunk {
+                                                    position: chunk_position,
+                                                })
+                                                .insert(Transform::from_translation(
+                                                    chunk_translation,
+                                                ))
+                                                .insert(GlobalTransform::default())
+                                                .insert(Parent(layer_entity))
+                                                .id()
+                                        });
+
+                                    commands
+                                        .entity(tile_entity)
+                                        .insert(Transform::from_translation(
+                                            translation - chunk_translation,
+                                        ))
                                         .insert(GlobalTransform::default())
-                                        .insert(Parent(layer_entity));
+                                        .insert(Parent(chunk_entity));
+
+                                    anchor_cells.insert(
+                                        (
+                                            layer_instance.identifier.clone(),
+                                            GridCoords::from(tile_pos),
+                                        ),
+                                        (
+                                            tile_entity,
+                                            Transform::from_translation(
+                                                layer_offset + translation * layer_scale,
+                                            )
+                                            .with_scale(layer_scale),
+                                        ),
+                                    );
                                 }
+
+                                commands.entity(layer_entity).insert(int_grid_chunks);
+
+                                anchor_layers.insert(
+                                    layer_instance.identifier.clone(),
+                                    (
+                                        IVec2::splat(layer_instance.grid_size),
+                                        layer_offset.truncate(),
+                                    ),
+                                );
                             }
 
-                            let layer_bundle =
-                                layer_builder.build(commands, meshes, image_handle.clone());
+                            if let Some(layer_builder) = layer_builder {
+                                let layer_bundle =
+                                    layer_builder.build(commands, meshes, image_handle.clone());
 
-                            commands.entity(layer_entity).insert_bundle(layer_bundle);
+                                commands.entity(layer_entity).insert_bundle(layer_bundle);
+                            }
 
                             layer_entity
                         } else {
-                            let tile_maker = tile_pos_to_tile_maker(
-                                layer_instance.c_hei,
-                                layer_instance.grid_size,
-                                grid_tiles,
+                            let tile_maker = tile_maker_with_alpha(
+                                tile_pos_to_tile_maker(
+                                    layer_instance.c_hei,
+                                    layer_instance.grid_size,
+                                    grid_tiles,
+                                ),
+                                tile_alpha * layer_instance.opacity,
                             );
 
                             LayerBuilder::<TileBundle>::new_batch(
@@ -558,29 +2005,79 @@ fn spawn_level(
                                 meshes,
                                 image_handle.clone(),
                                 map.id,
-                                layer_id as u16,
+                                layer_id,
                                 tile_pos_to_tile_bundle_maker(tile_maker),
                             )
                         };
 
-                        let layer_offset = Vec3::new(
-                            layer_instance.px_total_offset_x as f32,
-                            -layer_instance.px_total_offset_y as f32,
-                            0.,
-                        );
+                        let layer_transform =
+                            Transform::from_translation(layer_offset).with_scale(layer_scale);
 
-                        commands.entity(layer_entity).insert(
-                            Transform::from_translation(layer_offset).with_scale(layer_scale),
-                        );
+                        if let Some(layer_definition) =
+                            layer_definition_map.get(&layer_instance.layer_def_uid)
+                        {
+                            commands.entity(layer_entity).insert(ParallaxLayer {
+                                factor: Vec2::new(
+                                    layer_definition.parallax_factor_x,
+                                    layer_definition.parallax_factor_y,
+                                ),
+                                scaling: layer_definition.parallax_scaling,
+                                origin: layer_transform,
+                            });
+                        }
 
-                        map.add_layer(commands, layer_id as u16, layer_entity);
-                        layer_id += 1;
+                        commands
+                            .entity(layer_entity)
+                            .insert(layer_transform)
+                            .insert(LayerMetadata {
+                                identifier: layer_instance.identifier.clone(),
+                                iid: layer_instance.iid.clone(),
+                                layer_def_uid: layer_instance.layer_def_uid,
+                            })
+                            .insert(LayerOpacity(layer_instance.opacity))
+                            .insert(LayerZSlot {
+                                layer_identifier: layer_instance.identifier.clone(),
+                                layer_id: layer_id as u32,
+                            });
+
+                        map.add_layer(commands, layer_id, layer_entity);
+
+                        if let Some(tileset_definition) = tileset_definition {
+                            layer_tilesets
+                                .tileset_uids
+                                .insert(layer_id, tileset_definition.uid);
+                        }
                     }
                 }
             }
         }
     }
-    commands.entity(ldtk_entity).insert(map);
+    for (entity, anchor_layer, pixel_coords, transform) in anchored_entities.drain(..) {
+        let anchor_cell = anchor_layers
+            .get(&anchor_layer)
+            .map(|(grid_size, layer_offset)| {
+                let pivot = IVec2::from(pixel_coords).as_vec2() - *layer_offset;
+                translation_to_grid_coords(pivot, *grid_size)
+            })
+            .and_then(|grid_coords| anchor_cells.get(&(anchor_layer, grid_coords)));
+
+        if let Some((cell_entity, cell_transform)) = anchor_cell {
+            commands.entity(entity).insert(Transform {
+                translation: (transform.translation - cell_transform.translation)
+                    / cell_transform.scale,
+                scale: transform.scale / cell_transform.scale,
+                ..transform
+            });
+            commands.entity(ldtk_entity).remove_children(&[entity]);
+            commands.entity(*cell_entity).push_children(&[entity]);
+        }
+    }
+
+    commands
+        .entity(ldtk_entity)
+        .insert(std::mem::take(layer_tilesets));
+
+    true
 }
 
 fn layer_grid_tiles(grid_tiles: Vec<TileInstance>) -> Vec<Vec<TileInstance>> {
@@ -602,14 +2099,71 @@ fn layer_grid_tiles(grid_tiles: Vec<TileInstance>) -> Vec<Vec<TileInstance>> {
     layered_grid_tiles
 }
 
+/// Inserts the bundles registered with
+/// [RegisterLdtkObjects::register_ldtk_tile_enum_tag](crate::app::RegisterLdtkObjects) on the
+/// tagged tiles of newly spawned levels.
+#[allow(clippy::too_many_arguments)]
+pub fn insert_tile_enum_tag_bundles(
+    mut commands: Commands,
+    level_query: Query<(&Handle<LdtkLevel>, &LayerTilesets, &Parent), Changed<LayerTilesets>>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    tile_query: Query<&Tile>,
+    map_query: MapQuery,
+    ldtk_tile_enum_tag_map: NonSend<LdtkTileEnumTagMap>,
+) {
+    if ldtk_tile_enum_tag_map.is_empty() {
+        return;
+    }
+
+    for (level_handle, layer_tilesets, parent) in level_query.iter() {
+        let ldtk_asset = match ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|h| ldtk_assets.get(h))
+        {
+            Some(ldtk_asset) => ldtk_asset,
+            None => continue,
+        };
+
+        let map_id = match level_assets.get(level_handle) {
+            Some(level) => level.level.uid as u16,
+            None => continue,
+        };
+
+        for tileset_layer in tileset_layers(map_id, layer_tilesets, ldtk_asset, &map_query) {
+            let enum_tags = tile_enum_tags(tileset_layer.tileset_definition);
+            if enum_tags.is_empty() {
+                continue;
+            }
+
+            for tile_entity in tileset_layer.tile_entities {
+                let enum_values = tile_query
+                    .get(tile_entity)
+                    .ok()
+                    .and_then(|tile| enum_tags.get(&(tile.texture_index as i32)));
+
+                for enum_value in enum_values.into_iter().flatten() {
+                    if let Some(registration) = ldtk_tile_enum_tag_map.get(enum_value) {
+                        registration.evaluate(&mut commands.entity(tile_entity));
+                    }
+                }
+            }
+        }
+    }
+}
+
 pub fn worldly_adoption(
-    mut worldly_query: Query<(&mut Transform, &mut Parent), Added<Worldly>>,
+    mut commands: Commands,
+    mut worldly_query: Query<(Entity, &mut Transform, &mut Parent), Added<Worldly>>,
     transform_query: Query<(&Transform, &Parent), Without<Worldly>>,
 ) {
-    for (mut transform, mut parent) in worldly_query.iter_mut() {
+    for (entity, mut transform, mut parent) in worldly_query.iter_mut() {
         if let Ok((level_transform, level_parent)) = transform_query.get(parent.0) {
             *transform = level_transform.mul_transform(*transform);
-            parent.0 = level_parent.0
+            parent.0 = level_parent.0;
+            commands.entity(entity).insert(SpawnedByLdtk);
         }
     }
 }
@@ -622,8 +2176,10 @@ pub fn set_ldtk_texture_filters_to_nearest(
     // Based on
     // https://github.com/StarArawn/bevy_ecs_tilemap/blob/main/examples/helpers/texture.rs,
     // except it only applies to the ldtk tilesets.
+    // Tilesets that are modified on disk are reloaded with the default usages, so they're set
+    // again.
     for event in texture_events.iter() {
-        if let AssetEvent::Created { handle } = event {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
             let mut set_texture_filters_to_nearest = false;
 
             for (_, ldtk_asset) in ldtk_assets.iter() {
@@ -633,17 +2189,376 @@ pub fn set_ldtk_texture_filters_to_nearest(
                 }
             }
 
-            if set_texture_filters_to_nearest {
+            let usage =
+                TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST;
+
+            // Only written when it differs, since the modification sends another event
+            let needs_usage = set_texture_filters_to_nearest
+                && textures
+                    .get(handle)
+                    .map_or(false, |texture| texture.texture_descriptor.usage != usage);
+
+            if needs_usage {
                 if let Some(mut texture) = textures.get_mut(handle) {
-                    texture.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
-                        | TextureUsages::COPY_SRC
-                        | TextureUsages::COPY_DST;
+                    texture.texture_descriptor.usage = usage;
                 }
             }
         }
     }
 }
 
+/// Applies [LdtkSettings::tileset_filter] to LDtk tilesets, and their extruded copies, when they
+/// load and whenever the setting changes.
+///
+/// Runs after [extrude_ldtk_tilesets], so the copies it makes are filtered in the same update.
+pub fn apply_tileset_filters(
+    mut texture_events: EventReader<AssetEvent<Image>>,
+    mut textures: ResMut<Assets<Image>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    ldtk_settings: Res<LdtkSettings>,
+    extruded_tilesets: Res<ExtrudedTilesets>,
+    mut previous_filter: Local<Option<FilterMode>>,
+) {
+    let loaded_handles: Vec<Handle<Image>> = texture_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                Some(handle.clone())
+            }
+            _ => None,
+        })
+        .collect();
+
+    let filter_changed = ldtk_settings.tileset_filter != *previous_filter;
+    *previous_filter = ldtk_settings.tileset_filter;
+
+    let filter = match ldtk_settings.tileset_filter {
+        Some(filter) => filter,
+        None => return,
+    };
+
+    let tileset_handles: HashSet<Handle<Image>> = ldtk_assets
+        .iter()
+        .flat_map(|(_, ldtk_asset)| ldtk_asset.tileset_map.values().cloned())
+        .flat_map(|handle| {
+            let extruded = extruded_tilesets
+                .tilesets
+                .get(&handle)
+                .map(|extruded| extruded.image.clone());
+            std::iter::once(handle).chain(extruded)
+        })
+        .collect();
+
+    let handles: Vec<Handle<Image>> = if filter_changed {
+        tileset_handles.into_iter().collect()
+    } else {
+        // Extruded copies are replaced without an event when their tileset is modified
+        loaded_handles
+            .into_iter()
+            .filter(|handle| tileset_handles.contains(handle))
+            .flat_map(|handle| {
+                let extruded = extruded_tilesets
+                    .tilesets
+                    .get(&handle)
+                    .map(|extruded| extruded.image.clone());
+                std::iter::once(handle).chain(extruded)
+            })
+            .collect()
+    };
+
+    for handle in handles {
+        // Filters that already match are skipped, or each write would be seen as a load next update
+        let needs_filter = textures.get(&handle).map_or(false, |texture| {
+            texture.sampler_descriptor.mag_filter != filter
+                || texture.sampler_descriptor.min_filter != filter
+        });
+
+        if needs_filter {
+            if let Some(texture) = textures.get_mut(&handle) {
+                texture.sampler_descriptor.mag_filter = filter;
+                texture.sampler_descriptor.min_filter = filter;
+            }
+        }
+    }
+}
+
+/// Makes extruded copies of LDtk tilesets when they load, for [LdtkSettings::tileset_extrusion].
+///
+/// Tilesets that loaded before their LDtk asset, or before the extrusion was set, are extruded
+/// once both are available.
+/// When a tileset is modified on disk, its existing extruded copy is replaced in place, so the
+/// layers using it pick up the change without respawning.
+///
+/// Only added to the app with the "atlas" feature.
+pub fn extrude_ldtk_tilesets(
+    mut texture_events: EventReader<AssetEvent<Image>>,
+    mut ldtk_events: EventReader<AssetEvent<LdtkAsset>>,
+    mut textures: ResMut<Assets<Image>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    ldtk_settings: Res<LdtkSettings>,
+    mut extruded_tilesets: ResMut<ExtrudedTilesets>,
+) {
+    let modified_textures: HashSet<Handle<Image>> = texture_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                Some(handle.clone())
+            }
+            _ => None,
+        })
+        .collect();
+
+    let ldtk_assets_changed = ldtk_events.iter().any(|event| {
+        matches!(
+            event,
+            AssetEvent::Created { .. } | AssetEvent::Modified { .. }
+        )
+    });
+
+    if ldtk_settings.tileset_extrusion == 0 {
+        return;
+    }
+
+    // Tilesets that are already extruded are only checked when something could have changed
+    if modified_textures.is_empty() && !ldtk_assets_changed && !ldtk_settings.is_changed() {
+        return;
+    }
+
+    for (_, ldtk_asset) in ldtk_assets.iter() {
+        for (uid, handle) in &ldtk_asset.tileset_map {
+            let up_to_date = extruded_tilesets
+                .tilesets
+                .get(handle)
+                .map_or(false, |e| e.extrusion == ldtk_settings.tileset_extrusion);
+
+            if up_to_date && !modified_textures.contains(handle) {
+                continue;
+            }
+
+            let (tileset_definition, texture) = match (
+                ldtk_asset.get_tileset_definition(*uid),
+                textures.get(handle),
+            ) {
+                (Some(tileset_definition), Some(texture)) => (tileset_definition, texture),
+                _ => continue,
+            };
+
+            let extruded = match extrude_tileset_image(
+                texture,
+                tileset_definition,
+                ldtk_settings.tileset_extrusion,
+            ) {
+                Some(extruded) => extruded,
+                None => {
+                    warn!(
+                        "Tileset {} doesn't fit the size of its image, so it isn't extruded",
+                        tileset_definition.identifier
+                    );
+                    continue;
+                }
+            };
+            let size = Vec2::new(
+                extruded.texture_descriptor.size.width as f32,
+                extruded.texture_descriptor.size.height as f32,
+            );
+
+            let image = match extruded_tilesets.tilesets.get(handle) {
+                Some(existing) => {
+                    textures.set_untracked(&existing.image, extruded);
+                    existing.image.clone()
+                }
+                None => textures.add(extruded),
+            };
+
+            extruded_tilesets.tilesets.insert(
+                handle.clone(),
+                ExtrudedTileset {
+                    image,
+                    extrusion: ldtk_settings.tileset_extrusion,
+                    size,
+                },
+            );
+        }
+    }
+}
+
+/// Triggers a [Respawn] of the spawned levels using a tileset whose image changed size when it was
+/// modified on disk, since their layers and entity atlases were built for the old dimensions.
+///
+/// The [TextureAtlas]es of LDtk entities aren't removed, since entities that aren't respawned, like
+/// [Worldly] ones, may still use them.
+/// Respawned entities get new atlases once the tileset definitions have the new dimensions.
+/// Tileset modifications that keep the size are handled in place by [refresh_reloaded_tilesets].
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn respawn_levels_with_resized_tilesets(
+    mut commands: Commands,
+    mut texture_events: EventReader<AssetEvent<Image>>,
+    mut ldtk_events: EventReader<AssetEvent<LdtkAsset>>,
+    textures: Res<Assets<Image>>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_query: Query<
+        (Entity, &Handle<LdtkLevel>, Option<&LayerTilesets>, &Parent),
+        (Without<PendingLevelSpawn>, Without<SwappedOutLevel>),
+    >,
+    level_assets: Res<Assets<LdtkLevel>>,
+    mut tileset_sizes: Local<HashMap<HandleId, Vec2>>,
+) {
+    let mut resized_tilesets = Vec::new();
+    let mut texture_events_received = false;
+
+    for event in texture_events.iter() {
+        texture_events_received = true;
+
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { handle } => {
+                tileset_sizes.remove(&handle.id);
+                continue;
+            }
+        };
+
+        let is_tileset = ldtk_assets
+            .iter()
+            .any(|(_, ldtk_asset)| ldtk_asset.tileset_map.values().any(|h| h == handle));
+
+        if let (true, Some(texture)) = (is_tileset, textures.get(handle)) {
+            let size = texture.size();
+            if let Some(previous_size) = tileset_sizes.insert(handle.id, size) {
+                if previous_size != size {
+                    info!("LDtk tileset resize detected.");
+                    resized_tilesets.push(handle.clone());
+                }
+            }
+        }
+    }
+
+    // Tilesets may have loaded before the project did, or before this system first ran, so their
+    // sizes are also recorded from the loaded images
+    if ldtk_events.iter().count() > 0 || texture_events_received {
+        for (_, ldtk_asset) in ldtk_assets.iter() {
+            for handle in ldtk_asset.tileset_map.values() {
+                if let Some(texture) = textures.get(handle) {
+                    tileset_sizes
+                        .entry(handle.id)
+                        .or_insert_with(|| texture.size());
+                }
+            }
+        }
+    }
+
+    if resized_tilesets.is_empty() {
+        return;
+    }
+
+    for (level_entity, level_handle, layer_tilesets, parent) in level_query.iter() {
+        let (ldtk_asset, level) = match (
+            ldtk_query
+                .get(parent.0)
+                .ok()
+                .and_then(|h| ldtk_assets.get(h)),
+            level_assets.get(level_handle),
+        ) {
+            (Some(ldtk_asset), Some(ldtk_level)) => (ldtk_asset, &ldtk_level.level),
+            _ => continue,
+        };
+
+        let uses_resized_tileset = |tileset_uid: &i32| {
+            ldtk_asset
+                .tileset_map
+                .get(tileset_uid)
+                .map_or(false, |h| resized_tilesets.contains(h))
+        };
+
+        let layers_use_resized_tileset = layer_tilesets.map_or(false, |layer_tilesets| {
+            layer_tilesets
+                .tileset_uids
+                .values()
+                .any(uses_resized_tileset)
+        });
+
+        let entities_use_resized_tileset = level
+            .layer_instances
+            .iter()
+            .flatten()
+            .flat_map(|layer_instance| &layer_instance.entity_instances)
+            .filter_map(|entity_instance| entity_instance.tile.as_ref())
+            .any(|tile| uses_resized_tileset(&tile.tileset_uid));
+
+        if layers_use_resized_tileset || entities_use_resized_tileset {
+            commands.entity(level_entity).insert(Respawn);
+        }
+    }
+}
+
+/// Refreshes the tilemap chunks of spawned layers whose tileset image was modified on disk, so
+/// the new texture shows up without respawning their levels.
+///
+/// Only added to the app with the "atlas" feature.
+/// Otherwise, `bevy_ecs_tilemap` builds a texture array from each tileset once, and keeps using
+/// it after the tileset is modified, so changes to tileset images need a restart to show up.
+pub fn refresh_reloaded_tilesets(
+    mut texture_events: EventReader<AssetEvent<Image>>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_query: Query<(&Handle<LdtkLevel>, &LayerTilesets, &Parent)>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    tile_query: Query<(&TilePos, &TileParent)>,
+    mut map_query: MapQuery,
+) {
+    let modified_handles: Vec<&Handle<Image>> = texture_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } => Some(handle),
+            _ => None,
+        })
+        .collect();
+
+    if modified_handles.is_empty() {
+        return;
+    }
+
+    let mut reloaded_layers = HashSet::new();
+    for (level_handle, layer_tilesets, parent) in level_query.iter() {
+        let ldtk_asset = match ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|h| ldtk_assets.get(h))
+        {
+            Some(ldtk_asset) => ldtk_asset,
+            None => continue,
+        };
+
+        let map_id = match level_assets.get(level_handle) {
+            Some(level) => level.level.uid as u16,
+            None => continue,
+        };
+
+        let modified_tileset_uids: HashSet<i32> = ldtk_asset
+            .tileset_map
+            .iter()
+            .filter(|(_, handle)| modified_handles.contains(handle))
+            .map(|(uid, _)| *uid)
+            .collect();
+
+        reloaded_layers.extend(
+            layer_tilesets
+                .layers_using(&modified_tileset_uids)
+                .map(|layer_id| (map_id, layer_id)),
+        );
+    }
+
+    if reloaded_layers.is_empty() {
+        return;
+    }
+
+    for (tile_pos, tile_parent) in tile_query.iter() {
+        if reloaded_layers.contains(&(tile_parent.map_id, tile_parent.layer_id)) {
+            map_query.notify_chunk_for_tile(*tile_pos, tile_parent.map_id, tile_parent.layer_id);
+        }
+    }
+}
+
 /// Returns the `uid`s of levels that have spawned in this update.
 ///
 /// Mean to be used in a chain with [fire_level_transformed_events].
@@ -669,3 +2584,755 @@ pub fn fire_level_transformed_events(
         writer.send(LevelEvent::Transformed(id));
     }
 }
+
+/// The state of [update_entity_refs]: the [EntityIidMap] as of its last run, and the entities
+/// referencing each iid.
+#[derive(Debug, Default)]
+pub struct EntityRefTracker {
+    iids: HashMap<String, Entity>,
+    referrers: HashMap<String, HashSet<Entity>>,
+}
+
+/// Keeps the [EntityRefs] of LDtk entities up to date with the [EntityIidMap].
+///
+/// Only applies to entities with an [EntityInstance] component.
+/// The references of new entities are resolved when they spawn, and the others are only resolved
+/// again when the entity of an iid they reference changes.
+pub fn update_entity_refs(
+    mut commands: Commands,
+    entity_iid_map: Res<EntityIidMap>,
+    changed_entity_query: Query<(Entity, &EntityInstance), Changed<EntityInstance>>,
+    entity_query: Query<(&EntityInstance, Option<&EntityRefs>)>,
+    entities: &Entities,
+    mut tracker: Local<EntityRefTracker>,
+) {
+    let EntityRefTracker { iids, referrers } = &mut *tracker;
+    let mut outdated: HashSet<Entity> = HashSet::new();
+
+    for (entity, entity_instance) in changed_entity_query.iter() {
+        for iid in referenced_entity_iids(entity_instance) {
+            referrers.entry(iid.to_string()).or_default().insert(entity);
+        }
+        outdated.insert(entity);
+    }
+
+    if entity_iid_map.is_changed() {
+        let changed_iids: Vec<String> = entity_iid_map
+            .entities
+            .iter()
+            .filter(|(iid, entity)| iids.get(*iid) != Some(*entity))
+            .map(|(iid, _)| iid.clone())
+            .chain(
+                iids.keys()
+                    .filter(|iid| !entity_iid_map.entities.contains_key(*iid))
+                    .cloned(),
+            )
+            .collect();
+
+        for iid in changed_iids {
+            if let Some(iid_referrers) = referrers.get_mut(&iid) {
+                iid_referrers.retain(|e| entities.contains(*e));
+                outdated.extend(iid_referrers.iter().copied());
+
+                if iid_referrers.is_empty() {
+                    referrers.remove(&iid);
+                }
+            }
+        }
+
+        *iids = entity_iid_map.entities.clone();
+    }
+
+    for entity in outdated {
+        let (entity_instance, entity_refs) = match entity_query.get(entity) {
+            Ok(components) => components,
+            Err(_) => continue,
+        };
+
+        match (
+            resolve_entity_refs(entity_instance, |iid| entity_iid_map.get(iid)),
+            entity_refs,
+        ) {
+            (Some(resolved), Some(entity_refs)) if resolved == *entity_refs => (),
+            (Some(resolved), _) => {
+                commands.entity(entity).insert(resolved);
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<EntityRefs>();
+            }
+            (None, None) => (),
+        }
+    }
+}
+
+/// Keeps the [EntityIidMap] up to date with the [EntityIid]s of spawned and despawned entities.
+pub fn update_entity_iid_map(
+    mut entity_iid_map: ResMut<EntityIidMap>,
+    entity_iid_query: Query<(Entity, &EntityIid), Changed<EntityIid>>,
+    removed_entity_iids: RemovedComponents<EntityIid>,
+    entities: &Entities,
+) {
+    // Removals are only tracked until the end of the update, so entities despawned after this
+    // system ran, like those of levels despawned by complete_level_swaps, are pruned here too
+    let removed: HashSet<Entity> = removed_entity_iids.iter().collect();
+    entity_iid_map
+        .entities
+        .retain(|_, entity| !removed.contains(entity) && entities.contains(*entity));
+
+    for (entity, entity_iid) in entity_iid_query.iter() {
+        entity_iid_map.entities.insert(entity_iid.0.clone(), entity);
+    }
+}
+
+/// Updates the z translation of [YSort] entities according to their y translation.
+pub fn apply_y_sort(
+    mut y_sort_query: Query<(&YSort, &mut Transform), Or<(Changed<YSort>, Changed<Transform>)>>,
+) {
+    for (y_sort, mut transform) in y_sort_query.iter_mut() {
+        let z = y_sort.z(transform.translation.y);
+
+        // Only written when it differs, so the change doesn't trigger this system again
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
+
+/// Moves the tilemap layers and entities of spawned levels to the z of their [LayerZSlot] when
+/// [LdtkSettings::layer_z] changes.
+///
+/// Entities with a [YSort] get a new [YSort::base_z] and [YSort::spread] instead, which
+/// [apply_y_sort] applies.
+pub fn apply_layer_z(
+    ldtk_settings: Res<LdtkSettings>,
+    mut previous_layer_z: Local<Option<LayerZ>>,
+    mut layer_query: Query<
+        (&LayerZSlot, &mut Transform, Option<&mut ParallaxLayer>),
+        With<LayerMetadata>,
+    >,
+    mut entity_query: Query<
+        (&LayerZSlot, &Parent, &mut Transform, Option<&mut YSort>),
+        Without<LayerMetadata>,
+    >,
+    level_query: Query<(), With<Handle<LdtkLevel>>>,
+) {
+    if !ldtk_settings.is_changed() {
+        return;
+    }
+
+    let layer_z = &ldtk_settings.layer_z;
+
+    // The first run only records the settings, since levels spawn with them applied
+    match previous_layer_z.replace(layer_z.clone()) {
+        Some(previous_layer_z) if previous_layer_z != *layer_z => (),
+        _ => return,
+    }
+
+    for (slot, mut transform, parallax_layer) in layer_query.iter_mut() {
+        // Tilemap chunks are placed at the z of their layer id, see spawn_level
+        let z = layer_z.z(&slot.layer_identifier, slot.layer_id) - slot.layer_id as f32;
+
+        transform.translation.z = z;
+        if let Some(mut parallax_layer) = parallax_layer {
+            parallax_layer.origin.translation.z = z;
+        }
+    }
+
+    for (slot, parent, mut transform, y_sort) in entity_query.iter_mut() {
+        if level_query.get(parent.0).is_err() {
+            continue;
+        }
+
+        let z = layer_z.z(&slot.layer_identifier, slot.layer_id);
+
+        match y_sort {
+            Some(mut y_sort) => {
+                y_sort.base_z = z;
+                y_sort.spread = layer_z.step / 2.;
+            }
+            None => transform.translation.z = z,
+        }
+    }
+}
+
+/// Keeps the [LevelEntityIndex] of levels up to date with the [GridCoords] of their entities.
+///
+/// Despawned entities are removed from the indices, whenever they were despawned.
+pub fn update_level_entity_indices(
+    mut level_query: Query<(Entity, &mut LevelEntityIndex)>,
+    entity_query: Query<
+        (Entity, &GridCoords, &Parent),
+        (
+            With<PixelCoords>,
+            Or<(Changed<GridCoords>, Changed<Parent>)>,
+        ),
+    >,
+    removed_grid_coords: RemovedComponents<GridCoords>,
+    entities: &Entities,
+) {
+    for entity in removed_grid_coords.iter() {
+        for (_, mut level_entity_index) in level_query.iter_mut() {
+            if level_entity_index.grid_coords(entity).is_some() {
+                level_entity_index.remove(entity);
+            }
+        }
+    }
+
+    // Catches entities despawned after the last run, see update_entity_iid_map
+    for (_, mut level_entity_index) in level_query.iter_mut() {
+        level_entity_index.retain(|entity| entities.contains(entity));
+    }
+
+    for (entity, grid_coords, parent) in entity_query.iter() {
+        for (level_entity, mut level_entity_index) in level_query.iter_mut() {
+            if level_entity == parent.0 {
+                level_entity_index.insert(entity, *grid_coords);
+            } else if level_entity_index.grid_coords(entity).is_some() {
+                // The entity was adopted by another parent, like the world of Worldly entities
+                level_entity_index.remove(entity);
+            }
+        }
+    }
+}
+
+/// Ramps up the alpha of the tiles of [LevelFadeIn] levels each frame, and removes the component
+/// once they're opaque.
+pub fn fade_in_levels(
+    mut commands: Commands,
+    mut level_query: Query<(Entity, &Handle<LdtkLevel>, &mut LevelFadeIn)>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    ldtk_settings: Res<LdtkSettings>,
+    layer_query: Query<(&LayerOpacity, &LayerZSlot, &Parent)>,
+    mut tile_query: Query<(&mut Tile, &TilePos)>,
+    mut map_query: MapQuery,
+) {
+    let fading_levels: HashMap<Entity, (u16, f32)> = level_query
+        .iter_mut()
+        .filter_map(|(level_entity, level_handle, mut level_fade_in)| {
+            level_fade_in.elapsed_frames += 1;
+
+            let alpha = if level_fade_in.elapsed_frames >= ldtk_settings.level_fade_in_frames {
+                commands.entity(level_entity).remove::<LevelFadeIn>();
+                1.
+            } else {
+                level_fade_in.elapsed_frames as f32 / ldtk_settings.level_fade_in_frames as f32
+            };
+
+            let map_id = level_assets.get(level_handle)?.level.uid as u16;
+            Some((level_entity, (map_id, alpha)))
+        })
+        .collect();
+
+    if fading_levels.is_empty() {
+        return;
+    }
+
+    for (opacity, layer_z_slot, parent) in layer_query.iter() {
+        let (map_id, alpha) = match fading_levels.get(&parent.0) {
+            Some(fading_level) => *fading_level,
+            None => continue,
+        };
+        let layer_id = layer_z_slot.layer_id as u16;
+
+        for tile_entity in layer_tile_entities(&map_query, map_id, layer_id) {
+            if let Ok((mut tile, tile_pos)) = tile_query.get_mut(tile_entity) {
+                tile.color.set_a(alpha * opacity.0);
+                map_query.notify_chunk_for_tile(*tile_pos, map_id, layer_id);
+            }
+        }
+    }
+}
+
+/// Applies changes of [LayerOpacity] components to the tiles of their layers.
+///
+/// The alpha of each tile is scaled by the change, so tiles keep their own alpha relative to the
+/// layer's, unless the opacity was 0.
+pub fn apply_layer_opacity(
+    layer_query: Query<
+        (
+            Entity,
+            &LayerOpacity,
+            ChangeTrackers<LayerOpacity>,
+            &LayerZSlot,
+            &Parent,
+        ),
+        Changed<LayerOpacity>,
+    >,
+    level_query: Query<&Handle<LdtkLevel>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    mut applied_opacities: Local<HashMap<Entity, f32>>,
+    mut tile_query: Query<(&mut Tile, &TilePos)>,
+    mut map_query: MapQuery,
+) {
+    for (layer_entity, opacity, opacity_tracker, layer_z_slot, parent) in layer_query.iter() {
+        let applied_opacity = applied_opacities.insert(layer_entity, opacity.0);
+
+        // Tiles spawn with the opacity of their layer already applied
+        if opacity_tracker.is_added() {
+            continue;
+        }
+
+        let map_id = match level_query
+            .get(parent.0)
+            .ok()
+            .and_then(|h| level_assets.get(h))
+        {
+            Some(level) => level.level.uid as u16,
+            None => continue,
+        };
+        let layer_id = layer_z_slot.layer_id as u16;
+        let applied_opacity = applied_opacity.unwrap_or(1.);
+
+        for tile_entity in layer_tile_entities(&map_query, map_id, layer_id) {
+            if let Ok((mut tile, tile_pos)) = tile_query.get_mut(tile_entity) {
+                let alpha = if applied_opacity > 0. {
+                    (tile.color.a() * opacity.0 / applied_opacity).clamp(0., 1.)
+                } else {
+                    opacity.0
+                };
+
+                if tile.color.a() != alpha {
+                    tile.color.set_a(alpha);
+                    map_query.notify_chunk_for_tile(*tile_pos, map_id, layer_id);
+                }
+            }
+        }
+    }
+}
+
+/// Applies the [ContentMarker] of LDtk worlds to the descendants of their levels whenever a level
+/// finishes spawning, and to all of their descendants when the marker changes.
+#[allow(clippy::type_complexity)]
+pub fn apply_content_markers(
+    mut commands: Commands,
+    world_query: Query<
+        (&ContentMarker, ChangeTrackers<ContentMarker>, &Children),
+        With<Handle<LdtkAsset>>,
+    >,
+    spawned_level_query: Query<(Entity, &Parent), Changed<LayerTilesets>>,
+    adopted_worldly_query: Query<(Entity, &Parent), (With<Worldly>, Changed<Parent>)>,
+    children_query: Query<&Children>,
+) {
+    let mut roots: Vec<(Entity, &ContentMarker)> = Vec::new();
+
+    for (content_marker, content_marker_tracker, children) in world_query.iter() {
+        if content_marker_tracker.is_changed() {
+            roots.extend(children.iter().map(|child| (*child, content_marker)));
+        }
+    }
+
+    // Worldly entities are adopted by their world after their level has spawned
+    for (root, parent) in spawned_level_query
+        .iter()
+        .chain(adopted_worldly_query.iter())
+    {
+        if let Ok((content_marker, content_marker_tracker, _)) = world_query.get(parent.0) {
+            if !content_marker_tracker.is_changed() {
+                roots.push((root, content_marker));
+            }
+        }
+    }
+
+    for (root, content_marker) in roots {
+        let mut descendants = vec![root];
+        while let Some(entity) = descendants.pop() {
+            content_marker.apply(&mut commands.entity(entity));
+
+            if let Ok(children) = children_query.get(entity) {
+                descendants.extend(children.iter().copied());
+            }
+        }
+    }
+}
+
+/// Hides tilemap chunks and [IntGridChunk]s, marking them as [InactiveChunk]s, when they are
+/// farther than the [ChunkActivationSettings] radius from every [ChunkActivationTarget], and
+/// restores them when a target approaches.
+///
+/// Does nothing unless the [ChunkActivationSettings] resource is inserted.
+#[allow(clippy::type_complexity)]
+pub fn update_chunk_activation(
+    mut commands: Commands,
+    chunk_activation_settings: Option<Res<ChunkActivationSettings>>,
+    target_query: Query<&GlobalTransform, With<ChunkActivationTarget>>,
+    mut tile_chunk_query: Query<(
+        Entity,
+        &Chunk,
+        &GlobalTransform,
+        &mut Visibility,
+        Option<&InactiveChunk>,
+    )>,
+    int_grid_chunk_query: Query<
+        (Entity, &GlobalTransform, &Children, Option<&InactiveChunk>),
+        With<IntGridChunk>,
+    >,
+    mut cell_visibility_query: Query<&mut Visibility, (With<IntGridCell>, Without<Chunk>)>,
+) {
+    let radius = match chunk_activation_settings {
+        Some(settings) => settings.radius,
+        None => return,
+    };
+
+    let targets: Vec<Vec2> = target_query
+        .iter()
+        .map(|t| t.translation.truncate())
+        .collect();
+
+    if targets.is_empty() {
+        return;
+    }
+
+    let is_near = |center: Vec2| targets.iter().any(|t| t.distance(center) <= radius);
+
+    for (entity, chunk, global_transform, mut visibility, inactive) in tile_chunk_query.iter_mut() {
+        let chunk_size = Vec2::new(
+            chunk.settings.size.0 as f32 * chunk.settings.tile_size.0,
+            chunk.settings.size.1 as f32 * chunk.settings.tile_size.1,
+        );
+        let center = global_transform
+            .mul_vec3((chunk_size / 2.).extend(0.))
+            .truncate();
+
+        match (is_near(center), inactive.is_some()) {
+            (true, true) => {
+                visibility.is_visible = true;
+                commands.entity(entity).remove::<InactiveChunk>();
+            }
+            (false, false) => {
+                visibility.is_visible = false;
+                commands.entity(entity).insert(InactiveChunk);
+            }
+            _ => (),
+        }
+    }
+
+    for (entity, global_transform, children, inactive) in int_grid_chunk_query.iter() {
+        let active = match (
+            is_near(global_transform.translation.truncate()),
+            inactive.is_some(),
+        ) {
+            (true, true) => {
+                commands.entity(entity).remove::<InactiveChunk>();
+                true
+            }
+            (false, false) => {
+                commands.entity(entity).insert(InactiveChunk);
+                false
+            }
+            _ => continue,
+        };
+
+        // Visibility isn't inherited, so the cells are hidden individually
+        for child in children.iter() {
+            if let Ok(mut visibility) = cell_visibility_query.get_mut(*child) {
+                visibility.is_visible = active;
+            }
+        }
+    }
+}
+
+/// Z translation of [LevelLodImage]s relative to their world, so they're drawn below spawned
+/// levels, including their backgrounds.
+pub const LEVEL_LOD_Z: f32 = -2.;
+
+/// Spawns [LevelLodImage]s for the levels of [LevelLod] worlds that aren't spawned, and despawns
+/// them once the levels are.
+///
+/// Levels that are pending keep their image until they have spawned.
+/// Only the images of levels that spawned or despawned are updated, unless the [LevelLod], the
+/// [LdtkAsset] or the [LdtkSettings] changed.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_level_lods(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut ldtk_events: EventReader<AssetEvent<LdtkAsset>>,
+    world_query: Query<(
+        Entity,
+        &Handle<LdtkAsset>,
+        &LevelLod,
+        ChangeTrackers<LevelLod>,
+    )>,
+    level_query: Query<(&Handle<LdtkLevel>, &Parent), Without<PendingLevelSpawn>>,
+    lod_image_query: Query<(Entity, &LevelLodImage, &Parent)>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    mut images: ResMut<Assets<Image>>,
+    ldtk_settings: Res<LdtkSettings>,
+    mut baked_images: Local<HashMap<(Handle<LdtkAsset>, i32), Handle<Image>>>,
+) {
+    let changed_ldtks: Vec<Handle<LdtkAsset>> = ldtk_events
+        .iter()
+        .filter_map(|e| match e {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                Some(handle.clone())
+            }
+            _ => None,
+        })
+        .collect();
+
+    baked_images.retain(|(ldtk_handle, _), _| !changed_ldtks.contains(ldtk_handle));
+
+    for (world_entity, ldtk_handle, level_lod, level_lod_tracker) in world_query.iter() {
+        let ldtk_asset = match ldtk_assets.get(ldtk_handle) {
+            Some(ldtk_asset) => ldtk_asset,
+            None => continue,
+        };
+
+        let respawn_all = level_lod_tracker.is_changed()
+            || ldtk_settings.is_changed()
+            || changed_ldtks.contains(ldtk_handle);
+
+        let spawned_uids: HashSet<i32> = level_query
+            .iter()
+            .filter(|(_, parent)| parent.0 == world_entity)
+            .filter_map(|(level_handle, _)| level_assets.get(level_handle))
+            .map(|ldtk_level| ldtk_level.level.uid)
+            .collect();
+
+        let mut shown_uids = HashSet::new();
+        for (lod_image_entity, lod_image, _) in lod_image_query
+            .iter()
+            .filter(|(_, _, parent)| parent.0 == world_entity)
+        {
+            if respawn_all || spawned_uids.contains(&lod_image.level_uid) {
+                commands.entity(lod_image_entity).despawn_recursive();
+            } else {
+                shown_uids.insert(lod_image.level_uid);
+            }
+        }
+
+        for level in ldtk_asset
+            .project
+            .levels
+            .iter()
+            .filter(|l| !spawned_uids.contains(&l.uid) && !shown_uids.contains(&l.uid))
+        {
+            let texture = match &level_lod.image_directory {
+                Some(image_directory) => asset_server
+                    .load(format!("{}/{}.png", image_directory, level.identifier).as_str()),
+                None => match baked_images.get(&(ldtk_handle.clone(), level.uid)) {
+                    Some(baked_image) => baked_image.clone(),
+                    None => {
+                        // External levels only have their layers in their own asset
+                        let level_with_layers = ldtk_asset
+                            .level_map
+                            .get(&level.uid)
+                            .and_then(|h| level_assets.get(h))
+                            .map_or(level, |ldtk_level| &ldtk_level.level);
+
+                        // Retried every update until the tilesets are loaded
+                        let baked_image = match bake_level_image(
+                            level_with_layers,
+                            ldtk_asset,
+                            &images,
+                            &ldtk_settings.layer_draw_order,
+                        ) {
+                            Some(image) => images.add(image),
+                            None => continue,
+                        };
+
+                        baked_images.insert((ldtk_handle.clone(), level.uid), baked_image.clone());
+                        baked_image
+                    }
+                },
+            };
+
+            let size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+            let translation = level_translation(level, ldtk_asset, &ldtk_settings) + size / 2.;
+            let transform = Transform::from_translation(translation.extend(LEVEL_LOD_Z));
+
+            commands
+                .entity(world_entity)
+                .with_children(|child_builder| {
+                    child_builder
+                        .spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: Some(size),
+                                ..Default::default()
+                            },
+                            texture,
+                            transform,
+                            ..Default::default()
+                        })
+                        .insert(LevelLodImage {
+                            level_uid: level.uid,
+                        })
+                        .insert(SpawnedByLdtk);
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+    use bevy::{
+        asset::{AssetPlugin, FileAssetIo},
+        ecs::system::SystemState,
+        tasks::TaskPool,
+    };
+
+    #[test]
+    fn test_initial_level_selection_spawns_level() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<LdtkAsset>()
+            .add_asset::<LdtkLevel>()
+            .init_resource::<LdtkSettings>()
+            .add_event::<LevelEvent>()
+            .add_event::<PendingDespawnEvent>()
+            .add_system_to_stage(CoreStage::PreUpdate, process_ldtk_world)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                apply_initial_level_selections.before(process_ldtk_world),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                apply_level_set.after(apply_initial_level_selections),
+            );
+
+        let levels: Vec<Level> = (0..2).map(|uid| LevelBuilder::new(uid).build()).collect();
+
+        let level_map = levels
+            .iter()
+            .map(|level| {
+                let handle = app
+                    .world
+                    .get_resource_mut::<Assets<LdtkLevel>>()
+                    .unwrap()
+                    .add(LdtkLevel {
+                        level: level.clone(),
+                    });
+                (level.uid, handle)
+            })
+            .collect();
+
+        let ldtk_handle = app
+            .world
+            .get_resource_mut::<Assets<LdtkAsset>>()
+            .unwrap()
+            .add(LdtkAsset {
+                project: ldtk_json(levels),
+                tileset_map: HashMap::new(),
+                level_map,
+                level_background_map: HashMap::new(),
+                layer_settings_cache: LayerSettingsCache::new(),
+            });
+
+        let world_entity = app
+            .world
+            .spawn()
+            .insert_bundle(
+                LdtkWorldBundle::new(ldtk_handle)
+                    .with_level_selection(LevelSelection::Identifier("Level_1".to_string())),
+            )
+            .id();
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let children = app.world.get::<Children>(world_entity).unwrap();
+        let pending_uids: Vec<i32> = children
+            .iter()
+            .filter_map(|child| app.world.get::<PendingLevelSpawn>(*child))
+            .map(|pending_level_spawn| pending_level_spawn.level_uid)
+            .collect();
+        assert_eq!(pending_uids, vec![1]);
+    }
+
+    #[test]
+    fn test_spawn_level_headless() {
+        let tileset_definition = tileset_definition(1, "Tiles", 2, 2, 16);
+
+        let layer_instances = vec![
+            LayerInstanceBuilder::new("Ground", Type::Tiles, 2, 2, 16)
+                .tileset(&tileset_definition)
+                .tile(IVec2::new(0, 0), 1)
+                .build(),
+            LayerInstanceBuilder::new("Walls", Type::IntGrid, 2, 2, 16)
+                .tileset(&tileset_definition)
+                .int_grid_csv(vec![1, 0, 2, 1])
+                .tile(IVec2::new(0, 0), 3)
+                .build(),
+        ];
+
+        let mut world = World::new();
+        let asset_server = AssetServer::new(FileAssetIo::new("assets"), TaskPool::new());
+        world.insert_resource(asset_server.register_asset_type::<TextureAtlas>());
+        world.insert_resource(asset_server.register_asset_type::<Mesh>());
+        world.insert_resource(asset_server);
+        let ldtk_entity = world.spawn().id();
+
+        let ldtk_settings = LdtkSettings {
+            headless: true,
+            ..Default::default()
+        };
+        let tileset_definition_map = HashMap::from([(1, &tileset_definition)]);
+        let mut map = Map::new(0, ldtk_entity);
+        let mut progress = LevelSpawnProgress::new(0, 1.);
+
+        let mut system_state: SystemState<(
+            Commands,
+            Res<AssetServer>,
+            ResMut<Assets<TextureAtlas>>,
+            ResMut<Assets<Mesh>>,
+        )> = SystemState::new(&mut world);
+
+        {
+            let (mut commands, asset_server, mut texture_atlases, mut meshes) =
+                system_state.get_mut(&mut world);
+
+            let spawned = spawn_level(
+                GridLevel {
+                    uid: 0,
+                    px_wid: 32,
+                    px_hei: 32,
+                    layer_instances: &layer_instances,
+                },
+                None,
+                &mut commands,
+                &asset_server,
+                &mut texture_atlases,
+                &mut meshes,
+                &LdtkEntityMap::new(),
+                &LdtkIntCellMap::new(),
+                &LdtkIntCellIdentifierMap::new(),
+                &LdtkIntCellPromotionMap::new(),
+                &LdtkReflectedEntityMap::new(),
+                &LdtkDespawnPolicyMap::new(),
+                &LdtkEntityAnchorMap::new(),
+                &LdtkFilePathFieldMap::new(),
+                Path::new(""),
+                &HashMap::new(),
+                &HashMap::new(),
+                &LayerSettingsCache::new(),
+                &TilesetMap::new(),
+                &tileset_definition_map,
+                &ExtrudedTilesets::default(),
+                &HashSet::new(),
+                ldtk_entity,
+                &ldtk_settings,
+                false,
+                &mut map,
+                &mut progress,
+                None,
+            );
+            assert!(spawned);
+        }
+        system_state.apply(&mut world);
+
+        assert_eq!(world.query::<&IntGridCell>().iter(&world).count(), 3);
+        assert_eq!(world.query::<&Tile>().iter(&world).count(), 0);
+        assert_eq!(world.query::<&Layer>().iter(&world).count(), 0);
+
+        let layer_identifiers: Vec<String> = world
+            .query::<&LayerMetadata>()
+            .iter(&world)
+            .map(|layer_metadata| layer_metadata.identifier.clone())
+            .collect();
+        assert_eq!(layer_identifiers, vec!["Walls".to_string()]);
+    }
+}
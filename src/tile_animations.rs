@@ -0,0 +1,245 @@
+//! Animated tiles, like waterfalls and torches.
+//!
+//! Animations are authored in LDtk with the custom data of tileset tiles.
+//! A line of a tile's custom data in the form `animation=<json>` animates that tile through the
+//! given frames, which are tile ids of the same tileset:
+//! ```text
+//! animation={"frames": [12, 13, 14, 15], "frame_duration": 0.15}
+//! ```
+//! `frame_duration` is in seconds, and defaults to [DEFAULT_FRAME_DURATION].
+//! Other lines are ignored, so the custom data can still be used for other purposes.
+//!
+//! Animations can also be registered in code with the [TileAnimationSettings] resource, which
+//! takes priority over the custom data:
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_ecs_ldtk::tile_animations::*;
+//! fn register_torch_animation(mut tile_animation_settings: ResMut<TileAnimationSettings>) {
+//!     tile_animation_settings.animations.insert(
+//!         ("Dungeon".to_string(), 40),
+//!         TileAnimation::new(vec![40, 41, 42, 43], 0.1),
+//!     );
+//! }
+//! ```
+//! Tiles whose original tile id has an animation are given a [TileAnimation] component when
+//! their level spawns, and their texture is updated over time by the plugin.
+
+use crate::{
+    assets::{LdtkAsset, LdtkLevel},
+    components::LayerTilesets,
+    ldtk::TilesetDefinition,
+    utils::tileset_layers,
+};
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Prefix of the custom data line defining a tile animation.
+pub const TILE_ANIMATION_PREFIX: &str = "animation=";
+
+/// Duration of each frame of animations that don't specify one, in seconds.
+pub const DEFAULT_FRAME_DURATION: f32 = 0.1;
+
+fn default_frame_duration() -> f32 {
+    DEFAULT_FRAME_DURATION
+}
+
+/// [Component] animating a tile through a sequence of tile ids of its tileset.
+#[derive(Clone, PartialEq, Debug, Default, Deserialize, Component)]
+pub struct TileAnimation {
+    pub frames: Vec<u16>,
+    /// Duration of each frame, in seconds.
+    #[serde(default = "default_frame_duration")]
+    pub frame_duration: f32,
+}
+
+impl TileAnimation {
+    pub fn new(frames: Vec<u16>, frame_duration: f32) -> Self {
+        TileAnimation {
+            frames,
+            frame_duration,
+        }
+    }
+
+    /// Returns the tile id of the frame shown after `elapsed_seconds`, looping the animation.
+    ///
+    /// Returns [None] if the animation has no frames.
+    pub fn frame_at(&self, elapsed_seconds: f64) -> Option<u16> {
+        if self.frames.is_empty() {
+            return None;
+        }
+
+        let frame_duration = (self.frame_duration as f64).max(f64::EPSILON);
+        let frame = (elapsed_seconds / frame_duration) as usize % self.frames.len();
+        Some(self.frames[frame])
+    }
+}
+
+/// Resource for registering tile animations in code.
+///
+/// Keys are the identifier of a tileset and the id of the animated tile.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TileAnimationSettings {
+    pub animations: HashMap<(String, u16), TileAnimation>,
+}
+
+/// Reads the tile animations defined in the custom data of a tileset, using the ids of the
+/// animated tiles as keys.
+///
+/// Lines with invalid JSON are skipped with a warning.
+pub fn tile_animations(tileset_definition: &TilesetDefinition) -> HashMap<u16, TileAnimation> {
+    let mut animations = HashMap::new();
+
+    for custom_data in &tileset_definition.custom_data {
+        let tile_id = custom_data
+            .get("tileId")
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_u64());
+        let data = custom_data
+            .get("data")
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_str());
+
+        if let (Some(tile_id), Some(data)) = (tile_id, data) {
+            for line in data.lines() {
+                if let Some(json) = line.trim().strip_prefix(TILE_ANIMATION_PREFIX) {
+                    match serde_json::from_str::<TileAnimation>(json) {
+                        Ok(animation) => {
+                            animations.insert(tile_id as u16, animation);
+                        }
+                        Err(e) => warn!(
+                            "Invalid animation for tile {} of tileset {}: {}",
+                            tile_id, tileset_definition.identifier, e
+                        ),
+                    }
+                }
+            }
+        }
+    }
+
+    animations
+}
+
+/// Inserts [TileAnimation]s on the animated tiles of newly spawned levels.
+#[allow(clippy::too_many_arguments)]
+pub fn insert_tile_animations(
+    mut commands: Commands,
+    level_query: Query<(&Handle<LdtkLevel>, &LayerTilesets, &Parent), Changed<LayerTilesets>>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    tile_animation_settings: Res<TileAnimationSettings>,
+    tile_query: Query<&Tile>,
+    map_query: MapQuery,
+) {
+    for (level_handle, layer_tilesets, parent) in level_query.iter() {
+        let ldtk_asset = match ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|h| ldtk_assets.get(h))
+        {
+            Some(ldtk_asset) => ldtk_asset,
+            None => continue,
+        };
+
+        let map_id = match level_assets.get(level_handle) {
+            Some(level) => level.level.uid as u16,
+            None => continue,
+        };
+
+        for tileset_layer in tileset_layers(map_id, layer_tilesets, ldtk_asset, &map_query) {
+            let tileset_definition = tileset_layer.tileset_definition;
+
+            let mut animations = tile_animations(tileset_definition);
+            animations.extend(
+                tile_animation_settings
+                    .animations
+                    .iter()
+                    .filter(|((tileset, _), _)| *tileset == tileset_definition.identifier)
+                    .map(|((_, tile_id), animation)| (*tile_id, animation.clone())),
+            );
+
+            if animations.is_empty() {
+                continue;
+            }
+
+            for tile_entity in tileset_layer.tile_entities {
+                if let Some(animation) = tile_query
+                    .get(tile_entity)
+                    .ok()
+                    .and_then(|tile| animations.get(&tile.texture_index))
+                {
+                    commands.entity(tile_entity).insert(animation.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Updates the texture of tiles with a [TileAnimation] to their current frame.
+pub fn animate_tiles(
+    time: Res<Time>,
+    mut tile_query: Query<(&mut Tile, &TilePos, &TileParent, &TileAnimation)>,
+    mut map_query: MapQuery,
+) {
+    let elapsed_seconds = time.seconds_since_startup();
+
+    for (mut tile, tile_pos, tile_parent, animation) in tile_query.iter_mut() {
+        if let Some(texture_index) = animation.frame_at(elapsed_seconds) {
+            if tile.texture_index != texture_index {
+                tile.texture_index = texture_index;
+                map_query.notify_chunk_for_tile(
+                    *tile_pos,
+                    tile_parent.map_id,
+                    tile_parent.layer_id,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_animations() {
+        let custom_data = |tile_id: i32, data: &str| {
+            HashMap::from([
+                ("tileId".to_string(), Some(serde_json::json!(tile_id))),
+                ("data".to_string(), Some(serde_json::json!(data))),
+            ])
+        };
+
+        let tileset_definition = TilesetDefinition {
+            custom_data: vec![
+                custom_data(
+                    1,
+                    r#"animation={"frames": [1, 2, 3], "frame_duration": 0.5}"#,
+                ),
+                custom_data(4, "solid\nanimation={\"frames\": [4, 5]}"),
+                custom_data(6, "animation=[6, 7]"),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            tile_animations(&tileset_definition),
+            HashMap::from([
+                (1, TileAnimation::new(vec![1, 2, 3], 0.5)),
+                (4, TileAnimation::new(vec![4, 5], DEFAULT_FRAME_DURATION)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_tile_animation_frame_at() {
+        let animation = TileAnimation::new(vec![1, 2, 3], 0.5);
+
+        assert_eq!(animation.frame_at(0.), Some(1));
+        assert_eq!(animation.frame_at(0.75), Some(2));
+        assert_eq!(animation.frame_at(1.6), Some(1));
+        assert_eq!(TileAnimation::default().frame_at(1.), None);
+    }
+}
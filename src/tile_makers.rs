@@ -52,17 +52,12 @@ pub fn tile_pos_to_tile_maker(
     move |tile_pos: TilePos| -> Option<Tile> {
         match grid_tile_map.get(&tile_pos) {
             Some(tile_instance) => {
-                let (flip_x, flip_y) = match tile_instance.f {
-                    1 => (true, false),
-                    2 => (false, true),
-                    3 => (true, true),
-                    _ => (false, false),
-                };
-
+                // Bit 2 is never set by LDtk, but stores the diagonal flips of Tiled maps.
                 Some(Tile {
                     texture_index: tile_instance.t as u16,
-                    flip_x,
-                    flip_y,
+                    flip_x: tile_instance.f & 1 != 0,
+                    flip_y: tile_instance.f & 2 != 0,
+                    flip_d: tile_instance.f & 4 != 0,
                     ..Default::default()
                 })
             }
@@ -71,6 +66,21 @@ pub fn tile_pos_to_tile_maker(
     }
 }
 
+/// Returns a tile maker that returns the results of the provided tile maker with the given alpha.
+///
+/// Used for spawning the tiles of levels that fade in.
+pub fn tile_maker_with_alpha(
+    mut tile_maker: impl FnMut(TilePos) -> Option<Tile>,
+    alpha: f32,
+) -> impl FnMut(TilePos) -> Option<Tile> {
+    move |tile_pos: TilePos| -> Option<Tile> {
+        tile_maker(tile_pos).map(|mut tile| {
+            tile.color.set_a(alpha);
+            tile
+        })
+    }
+}
+
 /// Returns a tile bundle maker that returns the bundled results of the provided tile maker if that
 /// cell in the int grid is not zero.
 ///
@@ -191,6 +201,13 @@ mod tests {
                 f: 3,
                 ..Default::default()
             },
+            TileInstance {
+                px: IVec2::new(64, 32),
+                src: IVec2::new(0, 0),
+                t: 0,
+                f: 5,
+                ..Default::default()
+            },
         ];
 
         let mut tile_maker = tile_pos_to_tile_maker(2, 32, grid_tiles);
@@ -206,6 +223,22 @@ mod tests {
 
         assert!(tile_maker(TilePos(2, 1)).unwrap().flip_x);
         assert!(tile_maker(TilePos(2, 1)).unwrap().flip_y);
+        assert!(!tile_maker(TilePos(2, 1)).unwrap().flip_d);
+
+        assert!(tile_maker(TilePos(2, 0)).unwrap().flip_x);
+        assert!(!tile_maker(TilePos(2, 0)).unwrap().flip_y);
+        assert!(tile_maker(TilePos(2, 0)).unwrap().flip_d);
+    }
+
+    #[test]
+    fn test_tile_maker_with_alpha() {
+        let mut tile_maker = tile_maker_with_alpha(
+            |tile_pos: TilePos| (tile_pos.0 == 0).then(Tile::default),
+            0.25,
+        );
+
+        assert_eq!(tile_maker(TilePos(0, 0)).unwrap().color.a(), 0.25);
+        assert!(tile_maker(TilePos(1, 0)).is_none());
     }
 
     #[test]
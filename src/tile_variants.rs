@@ -0,0 +1,207 @@
+//! Swapping the tiles of a level between variant sets, like day and night versions of a map.
+//!
+//! Variants are authored in LDtk with the custom data of tileset tiles.
+//! Each line of a tile's custom data in the form `variant.<set>=<tile id>` maps that tile to the
+//! given tile id when `<set>` is active:
+//! ```text
+//! variant.night=37
+//! variant.winter=112
+//! ```
+//! Other lines are ignored, so the custom data can still be used for other purposes.
+//!
+//! A variant set can then be activated for a spawned level with [set_tile_variant_set]:
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_ecs_ldtk::{prelude::*, tile_variants::*};
+//! fn nightfall(mut commands: Commands, level_query: Query<Entity, With<Handle<LdtkLevel>>>) {
+//!     for level_entity in level_query.iter() {
+//!         set_tile_variant_set(&mut commands, level_entity, "night");
+//!     }
+//! }
+//! ```
+//! Tiles without a variant in the active set keep their original texture.
+//!
+//! Variants don't apply to animated tiles, which keep playing their
+//! [TileAnimation](crate::tile_animations::TileAnimation).
+
+use crate::{
+    assets::{LdtkAsset, LdtkLevel},
+    components::LayerTilesets,
+    ldtk::TilesetDefinition,
+    tile_animations::TileAnimation,
+    utils::tileset_layers,
+};
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use std::collections::HashMap;
+
+/// Prefix of the custom data lines defining tile variants.
+pub const TILE_VARIANT_PREFIX: &str = "variant.";
+
+/// [Component] selecting the tile variant set of a level.
+///
+/// `None` uses the tiles as they were authored in LDtk.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct TileVariantSet(pub Option<String>);
+
+/// [Component] added to tiles that have been swapped, storing their original texture index.
+///
+/// Removed once the tile is restored to its original texture.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub struct TileVariantBase(pub u16);
+
+/// Activates the tile variant set `variant_set` for the given level entity.
+pub fn set_tile_variant_set(commands: &mut Commands, level: Entity, variant_set: &str) {
+    commands
+        .entity(level)
+        .insert(TileVariantSet(Some(variant_set.to_string())));
+}
+
+/// Restores the original tiles of the given level entity.
+pub fn reset_tile_variant_set(commands: &mut Commands, level: Entity) {
+    commands.entity(level).insert(TileVariantSet(None));
+}
+
+/// Reads the tile variants defined in the custom data of a tileset.
+///
+/// The result maps each variant set to its tile id replacements.
+pub fn tile_variants(tileset_definition: &TilesetDefinition) -> HashMap<String, HashMap<u16, u16>> {
+    let mut variants: HashMap<String, HashMap<u16, u16>> = HashMap::new();
+
+    for custom_data in &tileset_definition.custom_data {
+        let tile_id = custom_data
+            .get("tileId")
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_u64());
+        let data = custom_data
+            .get("data")
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_str());
+
+        if let (Some(tile_id), Some(data)) = (tile_id, data) {
+            for line in data.lines() {
+                if let Some((set, variant_id)) = line
+                    .trim()
+                    .strip_prefix(TILE_VARIANT_PREFIX)
+                    .and_then(|l| l.split_once('='))
+                {
+                    if let Ok(variant_id) = variant_id.trim().parse::<u16>() {
+                        variants
+                            .entry(set.trim().to_string())
+                            .or_default()
+                            .insert(tile_id as u16, variant_id);
+                    }
+                }
+            }
+        }
+    }
+
+    variants
+}
+
+/// Swaps the texture indices of levels whose [TileVariantSet] has changed.
+///
+/// Tiles of layers without variants in the active set, or with no active set, are restored to
+/// their original texture.
+/// Animated tiles are skipped, since their [TileAnimation] takes precedence over variants.
+#[allow(clippy::type_complexity)]
+pub fn apply_tile_variant_sets(
+    mut commands: Commands,
+    level_query: Query<
+        (&Handle<LdtkLevel>, &LayerTilesets, &TileVariantSet, &Parent),
+        Or<(Changed<TileVariantSet>, Changed<LayerTilesets>)>,
+    >,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    mut tile_query: Query<(&mut Tile, &TilePos, Option<&TileVariantBase>), Without<TileAnimation>>,
+    mut map_query: MapQuery,
+) {
+    for (level_handle, layer_tilesets, tile_variant_set, parent) in level_query.iter() {
+        let ldtk_asset = match ldtk_query
+            .get(parent.0)
+            .ok()
+            .and_then(|h| ldtk_assets.get(h))
+        {
+            Some(ldtk_asset) => ldtk_asset,
+            None => continue,
+        };
+
+        let map_id = match level_assets.get(level_handle) {
+            Some(level) => level.level.uid as u16,
+            None => continue,
+        };
+
+        for tileset_layer in tileset_layers(map_id, layer_tilesets, ldtk_asset, &map_query) {
+            let layer_id = tileset_layer.layer_id;
+
+            let variants = match &tile_variant_set.0 {
+                Some(set) => tile_variants(tileset_layer.tileset_definition)
+                    .remove(set)
+                    .unwrap_or_default(),
+                None => HashMap::new(),
+            };
+
+            for tile_entity in tileset_layer.tile_entities {
+                let (mut tile, tile_pos, base) = match tile_query.get_mut(tile_entity) {
+                    Ok(tile) => tile,
+                    Err(_) => continue,
+                };
+
+                let base_index = base.map_or(tile.texture_index, |base| base.0);
+
+                let texture_index = match variants.get(&base_index) {
+                    Some(variant_index) => {
+                        if base.is_none() {
+                            commands
+                                .entity(tile_entity)
+                                .insert(TileVariantBase(base_index));
+                        }
+                        *variant_index
+                    }
+                    None => {
+                        if base.is_some() {
+                            commands.entity(tile_entity).remove::<TileVariantBase>();
+                        }
+                        base_index
+                    }
+                };
+
+                if tile.texture_index != texture_index {
+                    tile.texture_index = texture_index;
+                    map_query.notify_chunk_for_tile(*tile_pos, map_id, layer_id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_variants() {
+        let custom_data = |tile_id: i32, data: &str| {
+            HashMap::from([
+                ("tileId".to_string(), Some(serde_json::json!(tile_id))),
+                ("data".to_string(), Some(serde_json::json!(data))),
+            ])
+        };
+
+        let tileset_definition = TilesetDefinition {
+            custom_data: vec![
+                custom_data(1, "variant.night=5\nvariant.winter = 9"),
+                custom_data(2, "solid\nvariant.night=6"),
+                custom_data(3, "variant.night=not a number"),
+            ],
+            ..Default::default()
+        };
+
+        let variants = tile_variants(&tileset_definition);
+
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants["night"], HashMap::from([(1, 5), (2, 6)]));
+        assert_eq!(variants["winter"], HashMap::from([(1, 9)]));
+    }
+}
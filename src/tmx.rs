@@ -0,0 +1,652 @@
+//! Compatibility layer for loading [Tiled](https://www.mapeditor.org/) maps.
+//!
+//! *Requires the "tiled" feature*
+//!
+//! Tiled maps are converted into the [ldtk](crate::ldtk) types used by the rest of the plugin, so they go
+//! through the exact same spawning and registration pipeline as LDtk projects.
+//! With the feature enabled, `.tmx` files can be loaded with the asset server into an
+//! [LdtkAsset] directly:
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ecs_ldtk::prelude::*;
+//!
+//! fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+//!     commands.spawn_bundle(LdtkWorldBundle {
+//!         ldtk_handle: asset_server.load("my_map.tmx"),
+//!         ..Default::default()
+//!     });
+//! }
+//! ```
+//!
+//! The conversion produces a project with a single level, whose uid is `0`:
+//! - Tile layers become `Tiles` layers.
+//!   Since LDtk layers only have one tileset, a Tiled layer using several tilesets is split into
+//!   one layer per tileset.
+//! - Object groups become `Entities` layers.
+//!   The object type (or its name, if it has no type) is used as the entity identifier, so
+//!   [RegisterLdtkObjects](crate::app::RegisterLdtkObjects) works as usual.
+//!   Object properties become field instances.
+//! - Each layer gets a [LayerDefinition] in the project's definitions, with the same uid and
+//!   identifier.
+//! - Tiled's tileset `margin` is LDtk's `padding`, and their `spacing` is the same.
+//! - Diagonally flipped tiles have bit 2 set in their [TileInstance::f], which LDtk never sets, and
+//!   are spawned with a diagonal flip.
+//!
+//! Only embedded tilesets are supported, and infinite maps are ignored.
+//! Since LDtk layers and tilesets have a single grid size, maps and tilesets with non-square tiles
+//! fail to load.
+
+use crate::{
+    assets::{create_layer_settings_cache, ldtk_path_to_asset_path, LdtkAsset, LdtkLevel},
+    ldtk::*,
+    resources::LdtkSettings,
+};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use std::collections::HashMap;
+use xml::reader::{EventReader, XmlEvent};
+
+fn tileset_columns(tileset: &::tiled::Tileset, image_width: i32) -> i32 {
+    let tile_width = tileset.tile_width as i32;
+    let spacing = tileset.spacing as i32;
+    (image_width - 2 * tileset.margin as i32 + spacing) / (tile_width + spacing)
+}
+
+fn tiled_tileset_to_tileset_definition(
+    uid: i32,
+    tileset: &::tiled::Tileset,
+) -> anyhow::Result<TilesetDefinition> {
+    if tileset.tile_width != tileset.tile_height {
+        anyhow::bail!(
+            "tileset \"{}\" has non-square tiles ({}x{}), which LDtk tilesets don't support",
+            tileset.name,
+            tileset.tile_width,
+            tileset.tile_height
+        );
+    }
+
+    let (rel_path, px_wid, px_hei) = match tileset.images.first() {
+        Some(image) => (image.source.clone(), image.width, image.height),
+        None => {
+            warn!(
+                "Tileset \"{}\" has no image, only single-image tilesets are supported",
+                tileset.name
+            );
+            (String::new(), 0, 0)
+        }
+    };
+
+    let tile_height = tileset.tile_height as i32;
+    let spacing = tileset.spacing as i32;
+
+    Ok(TilesetDefinition {
+        c_wid: tileset_columns(tileset, px_wid),
+        c_hei: (px_hei - 2 * tileset.margin as i32 + spacing) / (tile_height + spacing),
+        identifier: tileset.name.clone(),
+        padding: tileset.margin as i32,
+        px_hei,
+        px_wid,
+        rel_path,
+        spacing,
+        tile_grid_size: tileset.tile_width as i32,
+        uid,
+        ..Default::default()
+    })
+}
+
+fn property_to_field_instance(name: &str, property: &::tiled::PropertyValue) -> FieldInstance {
+    let (field_instance_type, value) = match property {
+        ::tiled::PropertyValue::BoolValue(b) => ("Bool", FieldValue::Bool(*b)),
+        ::tiled::PropertyValue::FloatValue(f) => ("Float", FieldValue::Float(Some(*f))),
+        ::tiled::PropertyValue::IntValue(i) => ("Int", FieldValue::Int(Some(*i))),
+        ::tiled::PropertyValue::ColorValue(c) => {
+            let [a, r, g, b] = c.to_be_bytes();
+            ("Color", FieldValue::Color(Color::rgba_u8(r, g, b, a)))
+        }
+        ::tiled::PropertyValue::StringValue(s) => ("String", FieldValue::String(Some(s.clone()))),
+        ::tiled::PropertyValue::FileValue(f) => ("FilePath", FieldValue::FilePath(Some(f.clone()))),
+    };
+
+    FieldInstance {
+        identifier: name.to_string(),
+        field_instance_type: field_instance_type.to_string(),
+        value,
+        def_uid: 0,
+        real_editor_values: Vec::new(),
+    }
+}
+
+fn empty_layer_instance(
+    identifier: String,
+    layer_instance_type: Type,
+    map: &::tiled::Map,
+    opacity: f32,
+    visible: bool,
+    offset: IVec2,
+    layer_def_uid: i32,
+) -> LayerInstance {
+    LayerInstance {
+        c_hei: map.height as i32,
+        c_wid: map.width as i32,
+        grid_size: map.tile_width as i32,
+        identifier,
+        opacity,
+        px_total_offset_x: offset.x,
+        px_total_offset_y: offset.y,
+        tileset_def_uid: None,
+        tileset_rel_path: None,
+        layer_instance_type,
+        auto_layer_tiles: Vec::new(),
+        entity_instances: Vec::new(),
+        grid_tiles: Vec::new(),
+        iid: String::new(),
+        int_grid: None,
+        int_grid_csv: Vec::new(),
+        layer_def_uid,
+        level_id: 0,
+        optional_rules: Vec::new(),
+        override_tileset_uid: None,
+        px_offset_x: offset.x,
+        px_offset_y: offset.y,
+        seed: 0,
+        visible,
+    }
+}
+
+fn layer_definition(layer_instance: &LayerInstance) -> LayerDefinition {
+    LayerDefinition {
+        layer_definition_type: match layer_instance.layer_instance_type {
+            Type::Entities => "Entities",
+            _ => "Tiles",
+        }
+        .to_string(),
+        auto_rule_groups: Vec::new(),
+        auto_source_layer_def_uid: None,
+        auto_tileset_def_uid: None,
+        display_opacity: layer_instance.opacity,
+        excluded_tags: Vec::new(),
+        grid_size: layer_instance.grid_size,
+        identifier: layer_instance.identifier.clone(),
+        int_grid_values: Vec::new(),
+        int_grid_values_groups: Vec::new(),
+        parallax_factor_x: 0.,
+        parallax_factor_y: 0.,
+        parallax_scaling: true,
+        px_offset_x: 0,
+        px_offset_y: 0,
+        required_tags: Vec::new(),
+        tile_pivot_x: 0.,
+        tile_pivot_y: 0.,
+        tileset_def_uid: layer_instance.tileset_def_uid,
+        purple_type: layer_instance.layer_instance_type.clone(),
+        uid: layer_instance.layer_def_uid,
+    }
+}
+
+/// Reads the `offsetx` and `offsety` of the top-level object groups of a `.tmx` file, in
+/// document order.
+///
+/// Unlike its tile layers, the object groups of the `tiled` crate don't expose their offsets.
+fn object_group_offsets(bytes: &[u8]) -> anyhow::Result<Vec<IVec2>> {
+    let mut offsets = Vec::new();
+    let mut depth = 0;
+
+    for event in EventReader::new(bytes) {
+        match event? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                if depth == 1 && name.local_name == "objectgroup" {
+                    let offset = |attribute_name: &str| {
+                        attributes
+                            .iter()
+                            .find(|a| a.name.local_name == attribute_name)
+                            .and_then(|a| a.value.parse::<f32>().ok())
+                            .unwrap_or(0.) as i32
+                    };
+
+                    offsets.push(IVec2::new(offset("offsetx"), offset("offsety")));
+                }
+                depth += 1;
+            }
+            XmlEvent::EndElement { .. } => depth -= 1,
+            _ => (),
+        }
+    }
+
+    Ok(offsets)
+}
+
+/// Converts the contents of a `.tmx` file into an [LdtkJson] project containing a single level.
+///
+/// See the [module-level documentation](self) for details about the conversion.
+pub fn tmx_to_ldtk_json(bytes: &[u8], level_identifier: &str) -> anyhow::Result<LdtkJson> {
+    let map = ::tiled::parse(bytes)?;
+    let object_group_offsets = object_group_offsets(bytes)?;
+
+    tiled_map_to_ldtk_json(&map, &object_group_offsets, level_identifier)
+}
+
+fn tiled_map_to_ldtk_json(
+    map: &::tiled::Map,
+    object_group_offsets: &[IVec2],
+    level_identifier: &str,
+) -> anyhow::Result<LdtkJson> {
+    if map.tile_width != map.tile_height {
+        anyhow::bail!(
+            "the map has non-square tiles ({}x{}), which LDtk layers don't support",
+            map.tile_width,
+            map.tile_height
+        );
+    }
+
+    let tilesets = map
+        .tilesets
+        .iter()
+        .enumerate()
+        .map(|(i, t)| tiled_tileset_to_tileset_definition(i as i32, t))
+        .collect::<anyhow::Result<Vec<TilesetDefinition>>>()?;
+
+    let tileset_index_for_gid = |gid: u32| -> Option<usize> {
+        map.tilesets
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.first_gid <= gid)
+            .max_by_key(|(_, t)| t.first_gid)
+            .map(|(i, _)| i)
+    };
+
+    // Tiled layers are ordered bottom to top, while LDtk layers are ordered top to bottom.
+    let mut layer_instances = Vec::new();
+    let mut entity_definitions: Vec<EntityDefinition> = Vec::new();
+    let mut layer_def_uid = 0;
+
+    for layer in &map.layers {
+        let rows = match &layer.tiles {
+            ::tiled::LayerData::Finite(rows) => rows,
+            ::tiled::LayerData::Infinite(_) => {
+                warn!(
+                    "Layer \"{}\" is infinite, which is not supported",
+                    layer.name
+                );
+                continue;
+            }
+        };
+
+        let mut tiles_per_tileset: HashMap<usize, Vec<TileInstance>> = HashMap::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate().filter(|(_, t)| t.gid != 0) {
+                if let Some(tileset_index) = tileset_index_for_gid(tile.gid) {
+                    let tileset = &map.tilesets[tileset_index];
+                    let tileset_definition = &tilesets[tileset_index];
+                    let t = (tile.gid - tileset.first_gid) as i32;
+
+                    let columns = tileset_definition.c_wid.max(1);
+                    let src = IVec2::new(t % columns, t / columns)
+                        * IVec2::new(
+                            tileset.tile_width as i32 + tileset_definition.spacing,
+                            tileset.tile_height as i32 + tileset_definition.spacing,
+                        )
+                        + IVec2::splat(tileset_definition.padding);
+
+                    tiles_per_tileset
+                        .entry(tileset_index)
+                        .or_default()
+                        .push(TileInstance {
+                            d: vec![(y * map.width as usize + x) as i32],
+                            f: tile.flip_h as i32
+                                | (tile.flip_v as i32) << 1
+                                | (tile.flip_d as i32) << 2,
+                            px: IVec2::new(
+                                x as i32 * map.tile_width as i32,
+                                y as i32 * map.tile_height as i32,
+                            ),
+                            src,
+                            t,
+                        });
+                }
+            }
+        }
+
+        let mut tileset_indices: Vec<usize> = tiles_per_tileset.keys().copied().collect();
+        tileset_indices.sort_unstable();
+
+        for tileset_index in tileset_indices {
+            let identifier = if tiles_per_tileset.len() > 1 {
+                format!("{}_{}", layer.name, tilesets[tileset_index].identifier)
+            } else {
+                layer.name.clone()
+            };
+
+            let mut layer_instance = empty_layer_instance(
+                identifier,
+                Type::Tiles,
+                map,
+                layer.opacity,
+                layer.visible,
+                IVec2::new(layer.offset_x as i32, layer.offset_y as i32),
+                layer_def_uid,
+            );
+            layer_instance.tileset_def_uid = Some(tilesets[tileset_index].uid);
+            layer_instance.tileset_rel_path = Some(tilesets[tileset_index].rel_path.clone());
+            layer_instance.grid_tiles = tiles_per_tileset.remove(&tileset_index).unwrap();
+
+            layer_instances.push((layer.layer_index, layer_instance));
+            layer_def_uid += 1;
+        }
+    }
+
+    for (i, object_group) in map.object_groups.iter().enumerate() {
+        let mut layer_instance = empty_layer_instance(
+            object_group.name.clone(),
+            Type::Entities,
+            map,
+            object_group.opacity,
+            object_group.visible,
+            object_group_offsets.get(i).copied().unwrap_or_default(),
+            layer_def_uid,
+        );
+
+        for object in &object_group.objects {
+            let identifier = if object.obj_type.is_empty() {
+                object.name.clone()
+            } else {
+                object.obj_type.clone()
+            };
+
+            let def_uid = match entity_definitions
+                .iter()
+                .find(|d| d.identifier == identifier)
+            {
+                Some(entity_definition) => entity_definition.uid,
+                None => {
+                    let uid = entity_definitions.len() as i32;
+                    entity_definitions.push(EntityDefinition {
+                        identifier: identifier.clone(),
+                        width: object.width as i32,
+                        height: object.height as i32,
+                        uid,
+                        ..Default::default()
+                    });
+                    uid
+                }
+            };
+
+            // Tile objects are positioned by their bottom-left corner in Tiled, other objects by
+            // their top-left corner.
+            let pivot = if object.gid != 0 {
+                Vec2::new(0., 1.)
+            } else {
+                Vec2::ZERO
+            };
+
+            let px = IVec2::new(object.x as i32, object.y as i32);
+
+            layer_instance.entity_instances.push(EntityInstance {
+                grid: px / IVec2::new(map.tile_width as i32, map.tile_height as i32),
+                identifier,
+                pivot,
+                tile: None,
+                def_uid,
+                field_instances: object
+                    .properties
+                    .iter()
+                    .map(|(name, property)| property_to_field_instance(name, property))
+                    .collect(),
+                height: object.height as i32,
+                px,
+                width: object.width as i32,
+            });
+        }
+
+        layer_instances.push((object_group.layer_index.unwrap_or(0), layer_instance));
+        layer_def_uid += 1;
+    }
+
+    layer_instances.sort_by_key(|(layer_index, _)| std::cmp::Reverse(*layer_index));
+    let layer_instances: Vec<LayerInstance> = layer_instances.into_iter().map(|(_, l)| l).collect();
+
+    let level = Level {
+        identifier: level_identifier.to_string(),
+        layer_instances: Some(layer_instances.clone()),
+        px_hei: (map.height * map.tile_height) as i32,
+        px_wid: (map.width * map.tile_width) as i32,
+        uid: 0,
+        ..Default::default()
+    };
+
+    Ok(LdtkJson {
+        backup_limit: 0,
+        backup_on_save: false,
+        bg_color: String::new(),
+        default_grid_size: map.tile_width as i32,
+        default_level_bg_color: String::new(),
+        default_level_height: level.px_hei,
+        default_level_width: level.px_wid,
+        default_pivot_x: 0.,
+        default_pivot_y: 0.,
+        defs: Definitions {
+            entities: entity_definitions,
+            layers: layer_instances.iter().map(layer_definition).collect(),
+            tilesets,
+            ..Default::default()
+        },
+        export_png: None,
+        export_tiled: false,
+        external_levels: false,
+        flags: Vec::new(),
+        image_export_mode: ImageExportMode::None,
+        json_version: String::new(),
+        level_name_pattern: String::new(),
+        levels: vec![level],
+        minify_json: false,
+        next_uid: 0,
+        png_file_pattern: None,
+        world_grid_height: map.tile_height as i32,
+        world_grid_width: map.tile_width as i32,
+        world_layout: WorldLayout::Free,
+    })
+}
+
+/// Loads `.tmx` files as [LdtkAsset]s.
+///
+/// See the [module-level documentation](self) for more details.
+#[derive(Copy, Clone, Debug)]
+pub struct TiledLoader {
+    /// Skips loading the tileset images, see [LdtkSettings::headless].
+    pub headless: bool,
+}
+
+impl FromWorld for TiledLoader {
+    fn from_world(world: &mut World) -> Self {
+        TiledLoader {
+            headless: world
+                .get_resource::<LdtkSettings>()
+                .map_or(false, |ldtk_settings| ldtk_settings.headless),
+        }
+    }
+}
+
+impl AssetLoader for TiledLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let level_identifier = load_context
+                .path()
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Level_0")
+                .to_string();
+
+            let project = tmx_to_ldtk_json(bytes, &level_identifier)?;
+
+            let mut level_map = HashMap::new();
+            for level in &project.levels {
+                let ldtk_level = LdtkLevel {
+                    level: level.clone(),
+                };
+                let level_handle =
+                    load_context.set_labeled_asset(&level.identifier, LoadedAsset::new(ldtk_level));
+
+                level_map.insert(level.uid, level_handle);
+            }
+
+            let mut tileset_rel_paths = Vec::new();
+            let mut tileset_map = HashMap::new();
+            if !self.headless {
+                for tileset in &project.defs.tilesets {
+                    let asset_path = ldtk_path_to_asset_path(load_context, &tileset.rel_path);
+
+                    tileset_rel_paths.push(asset_path.clone());
+                    tileset_map.insert(tileset.uid, load_context.get_handle(asset_path));
+                }
+            }
+
+            let ldtk_asset = LdtkAsset {
+                layer_settings_cache: create_layer_settings_cache(&project),
+                project,
+                tileset_map,
+                level_map,
+                level_background_map: HashMap::new(),
+            };
+            load_context.set_default_asset(
+                LoadedAsset::new(ldtk_asset).with_dependencies(tileset_rel_paths),
+            );
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 map with a tile layer over a 36x36 tileset image of 2x2 tiles, with a margin of 1 and
+    /// a spacing of 2, and an offset object group.
+    fn tmx(tile_width: u32, tile_height: u32) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.5" orientation="orthogonal" renderorder="right-down" width="2" height="2" tilewidth="{tile_width}" tileheight="{tile_height}" infinite="0">
+ <tileset firstgid="1" name="Tiles" tilewidth="{tile_width}" tileheight="{tile_height}" spacing="2" margin="1" tilecount="4" columns="2">
+  <image source="tiles.png" width="36" height="36"/>
+  <tile id="0">
+   <objectgroup draworder="index" offsetx="3" offsety="3">
+    <object id="1" x="0" y="0" width="16" height="16"/>
+   </objectgroup>
+  </tile>
+ </tileset>
+ <layer id="1" name="Ground" width="2" height="2">
+  <data encoding="csv">
+1,2,
+536870915,0
+</data>
+ </layer>
+ <objectgroup id="2" name="Objects" offsetx="4" offsety="8">
+  <object id="2" name="Player" type="Player" x="16" y="0" width="16" height="16"/>
+ </objectgroup>
+</map>"#
+        )
+    }
+
+    #[test]
+    fn test_layer_definitions() {
+        let project = tmx_to_ldtk_json(tmx(16, 16).as_bytes(), "Level_0").unwrap();
+        let layer_instances = project.levels[0].layer_instances.as_ref().unwrap();
+
+        assert_eq!(project.defs.layers.len(), layer_instances.len());
+        for layer_instance in layer_instances {
+            let layer_definition = project
+                .defs
+                .layers
+                .iter()
+                .find(|d| d.uid == layer_instance.layer_def_uid)
+                .unwrap();
+
+            assert_eq!(layer_definition.identifier, layer_instance.identifier);
+            assert_eq!(
+                layer_definition.purple_type,
+                layer_instance.layer_instance_type
+            );
+            assert_eq!(
+                layer_definition.tileset_def_uid,
+                layer_instance.tileset_def_uid
+            );
+        }
+
+        assert_eq!(
+            project.defs.layers[0].layer_definition_type,
+            "Entities".to_string()
+        );
+        assert_eq!(
+            project.defs.layers[1].layer_definition_type,
+            "Tiles".to_string()
+        );
+    }
+
+    #[test]
+    fn test_diagonally_flipped_tiles() {
+        let project = tmx_to_ldtk_json(tmx(16, 16).as_bytes(), "Level_0").unwrap();
+        let ground = &project.levels[0].layer_instances.as_ref().unwrap()[1];
+
+        let flips: Vec<(IVec2, i32)> = ground.grid_tiles.iter().map(|t| (t.px, t.f)).collect();
+        assert_eq!(
+            flips,
+            vec![
+                (IVec2::new(0, 0), 0),
+                (IVec2::new(16, 0), 0),
+                (IVec2::new(0, 16), 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_square_tiles_fail() {
+        assert!(tmx_to_ldtk_json(tmx(16, 16).as_bytes(), "Level_0").is_ok());
+        assert!(tmx_to_ldtk_json(tmx(16, 8).as_bytes(), "Level_0").is_err());
+    }
+
+    #[test]
+    fn test_margin_and_spacing() {
+        let project = tmx_to_ldtk_json(tmx(16, 16).as_bytes(), "Level_0").unwrap();
+
+        let tileset_definition = &project.defs.tilesets[0];
+        assert_eq!(tileset_definition.padding, 1);
+        assert_eq!(tileset_definition.spacing, 2);
+        assert_eq!(tileset_definition.c_wid, 2);
+        assert_eq!(tileset_definition.c_hei, 2);
+
+        let ground = &project.levels[0].layer_instances.as_ref().unwrap()[1];
+        let sources: Vec<IVec2> = ground.grid_tiles.iter().map(|t| t.src).collect();
+        assert_eq!(
+            sources,
+            vec![IVec2::new(1, 1), IVec2::new(19, 1), IVec2::new(1, 19)]
+        );
+    }
+
+    #[test]
+    fn test_object_group_offsets() {
+        let tmx = tmx(16, 16);
+        assert_eq!(
+            object_group_offsets(tmx.as_bytes()).unwrap(),
+            vec![IVec2::new(4, 8)]
+        );
+
+        let project = tmx_to_ldtk_json(tmx.as_bytes(), "Level_0").unwrap();
+        let objects = &project.levels[0].layer_instances.as_ref().unwrap()[0];
+
+        assert_eq!(objects.identifier, "Objects".to_string());
+        assert_eq!(objects.px_total_offset_x, 4);
+        assert_eq!(objects.px_total_offset_y, 8);
+        assert_eq!(objects.entity_instances[0].px, IVec2::new(16, 0));
+    }
+}
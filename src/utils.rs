@@ -3,11 +3,25 @@
 #[allow(unused_imports)]
 use crate::components::IntGridCell;
 
-use crate::ldtk::*;
-use bevy::prelude::*;
+use crate::{
+    assets::LdtkAsset,
+    components::{EntityRefs, GridCoords, LayerTilesets},
+    ldtk::*,
+    resources::{LdtkSettings, CHUNK_SIZE},
+};
+use bevy::{
+    prelude::*,
+    render::{
+        primitives::Aabb,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
 use bevy_ecs_tilemap::prelude::*;
 
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
 
 /// The `int_grid_csv` field of a [LayerInstance] is a 1-dimensional [Vec<i32>].
 /// This function can map the indices of this [Vec] to a corresponding [TilePos].
@@ -44,6 +58,96 @@ pub fn int_grid_index_to_tile_pos(
     }
 }
 
+/// Creates the [LayerSettings] for a tile layer with the given grid size and tileset, along with
+/// the scale its layer entities need.
+///
+/// The scale is a workaround for the tileset's tile size differing from the layer's grid size,
+/// since changing [LayerSettings::grid_size] doesn't have any affect with the current
+/// bevy_ecs_tilemap.
+pub fn layer_settings_and_scale(
+    map_size: MapSize,
+    grid_size: i32,
+    tileset_definition: Option<&TilesetDefinition>,
+) -> (LayerSettings, Vec3) {
+    let tile_size = match tileset_definition {
+        Some(tileset_definition) => TileSize(
+            tileset_definition.tile_grid_size as f32,
+            tileset_definition.tile_grid_size as f32,
+        ),
+        None => TileSize(grid_size as f32, grid_size as f32),
+    };
+
+    let texture_size = match tileset_definition {
+        Some(tileset_definition) => TextureSize(
+            tileset_definition.px_wid as f32,
+            tileset_definition.px_hei as f32,
+        ),
+        None => TextureSize(0., 0.),
+    };
+
+    let mut settings = LayerSettings::new(map_size, CHUNK_SIZE, tile_size, texture_size);
+
+    if tileset_definition.is_some() {
+        settings.grid_size = Vec2::splat(grid_size as f32);
+    }
+
+    let layer_scale = (settings.grid_size
+        / Vec2::new(settings.tile_size.0 as f32, settings.tile_size.1 as f32))
+    .extend(1.);
+
+    (settings, layer_scale)
+}
+
+/// Returns the tileset uids of the auto-layer rules of a layer that override the layer's tileset,
+/// keyed by rule uid.
+pub fn auto_rule_tileset_uids(layer_definition: &LayerDefinition) -> HashMap<i32, i32> {
+    layer_definition
+        .auto_rule_groups
+        .iter()
+        .flat_map(|group| group.rules.iter())
+        .filter_map(|rule| Some((rule.uid, rule.tileset_uid?)))
+        .collect()
+}
+
+/// Groups the tiles of a layer by the tileset they're from.
+///
+/// Auto-layer tiles placed by rules that override the layer's tileset (see
+/// [auto_rule_tileset_uids]) are split from the rest, which use the layer's tileset.
+/// The group of the layer's tileset always comes first, even if it has no tiles, followed by the
+/// other groups in order of tileset uid.
+pub fn group_tiles_by_tileset(
+    layer_instance: &LayerInstance,
+    rule_tileset_uids: &HashMap<i32, i32>,
+) -> Vec<(Option<i32>, Vec<TileInstance>)> {
+    let mut layer_tileset_tiles = layer_instance.grid_tiles.clone();
+    let mut other_tileset_tiles: BTreeMap<i32, Vec<TileInstance>> = BTreeMap::new();
+
+    for tile in &layer_instance.auto_layer_tiles {
+        // The first element of `d` is the uid of the rule that placed an auto-layer tile
+        match tile
+            .d
+            .first()
+            .and_then(|rule_uid| rule_tileset_uids.get(rule_uid))
+        {
+            Some(tileset_uid) if Some(*tileset_uid) != layer_instance.tileset_def_uid => {
+                other_tileset_tiles
+                    .entry(*tileset_uid)
+                    .or_default()
+                    .push(tile.clone());
+            }
+            _ => layer_tileset_tiles.push(tile.clone()),
+        }
+    }
+
+    std::iter::once((layer_instance.tileset_def_uid, layer_tileset_tiles))
+        .chain(
+            other_tileset_tiles
+                .into_iter()
+                .map(|(tileset_uid, tiles)| (Some(tileset_uid), tiles)),
+        )
+        .collect()
+}
+
 /// Simple conversion from a list of [EntityDefinition]s to a map using their Uids as the keys.
 pub fn create_entity_definition_map(
     entity_definitions: &[EntityDefinition],
@@ -51,6 +155,13 @@ pub fn create_entity_definition_map(
     entity_definitions.iter().map(|e| (e.uid, e)).collect()
 }
 
+/// Simple conversion from a list of [LayerDefinition]s to a map using their Uids as the keys.
+pub fn create_layer_definition_map(
+    layer_definitions: &[LayerDefinition],
+) -> HashMap<i32, &LayerDefinition> {
+    layer_definitions.iter().map(|l| (l.uid, l)).collect()
+}
+
 /// Performs [EntityInstance] to [Transform] conversion
 ///
 /// The `entity_definition_map` should be a map of [EntityDefinition] uids to [EntityDefinition]s.
@@ -62,12 +173,7 @@ pub fn calculate_transform_from_entity_instance(
     level_height: i32,
     z_value: f32,
 ) -> Transform {
-    let entity_definition = entity_definition_map.get(&entity_instance.def_uid).unwrap();
-
-    let def_size = match &entity_instance.tile {
-        Some(tile) => IVec2::new(tile.src_rect[2], tile.src_rect[3]),
-        None => IVec2::new(entity_definition.width, entity_definition.height),
-    };
+    let def_size = entity_definition_size(entity_instance, entity_definition_map);
 
     let size = IVec2::new(entity_instance.width, entity_instance.height);
 
@@ -82,6 +188,97 @@ pub fn calculate_transform_from_entity_instance(
     Transform::from_translation(translation.extend(z_value)).with_scale(scale.extend(1.))
 }
 
+/// Size of an entity before the scale of its [Transform] is applied: the size of its tile, or of
+/// its definition if it has no tile.
+fn entity_definition_size(
+    entity_instance: &EntityInstance,
+    entity_definition_map: &HashMap<i32, &EntityDefinition>,
+) -> IVec2 {
+    let entity_definition = entity_definition_map.get(&entity_instance.def_uid).unwrap();
+
+    match &entity_instance.tile {
+        Some(tile) => IVec2::new(tile.src_rect[2], tile.src_rect[3]),
+        None => IVec2::new(entity_definition.width, entity_definition.height),
+    }
+}
+
+/// Calculates the [Aabb] of an [EntityInstance], in the local space of the [Transform] calculated
+/// by [calculate_transform_from_entity_instance].
+///
+/// With this, bevy's frustum culling can skip entities that are off-screen.
+pub fn calculate_aabb_from_entity_instance(
+    entity_instance: &EntityInstance,
+    entity_definition_map: &HashMap<i32, &EntityDefinition>,
+) -> Aabb {
+    let half_size = entity_definition_size(entity_instance, entity_definition_map).as_vec2() / 2.;
+
+    Aabb::from_min_max(-half_size.extend(0.), half_size.extend(0.))
+}
+
+/// Calculates the translation of an [EntityInstance] relative to its level, as the plugin spawns
+/// it.
+///
+/// With `snap_to_grid`, the entity is placed at the center of its grid cell, see
+/// [LdtkSettings::snap_entities_to_grid].
+/// Like in LDtk's editor, the offset of the entity's layer doesn't move it.
+pub fn entity_instance_level_translation(
+    entity_instance: &EntityInstance,
+    layer_instance: &LayerInstance,
+    level_height: i32,
+    snap_to_grid: bool,
+) -> Vec2 {
+    if snap_to_grid {
+        grid_coords_to_translation_centered(
+            ldtk_grid_coords_to_grid_coords(entity_instance.grid, layer_instance.c_hei),
+            IVec2::splat(layer_instance.grid_size),
+        )
+    } else {
+        ldtk_pixel_coords_to_translation_pivoted(
+            entity_instance.px,
+            level_height,
+            IVec2::new(entity_instance.width, entity_instance.height),
+            entity_instance.pivot,
+        )
+    }
+}
+
+/// Calculates the position of the center of an [EntityInstance]'s visual in world space, as a
+/// single canonical alternative to combining the conversions of this module by hand.
+///
+/// Matches where the plugin spawns the entity, see [entity_instance_level_translation], and
+/// accounts for:
+/// - the y-flip between LDtk's y-down and bevy's y-up coordinates,
+/// - the pivot of the entity,
+/// - [LdtkSettings::snap_entities_to_grid], placing the entity at the center of its grid cell,
+/// - [LdtkSettings::use_level_world_translations], adding the translation of the level.
+///
+/// `world_height` is only used for the level translation, see
+/// [LdtkAsset::world_height](crate::assets::LdtkAsset::world_height).
+pub fn entity_instance_world_position(
+    entity_instance: &EntityInstance,
+    level: &Level,
+    layer_instance: &LayerInstance,
+    ldtk_settings: &LdtkSettings,
+    world_height: i32,
+) -> Vec2 {
+    let level_position = entity_instance_level_translation(
+        entity_instance,
+        layer_instance,
+        level.px_hei,
+        ldtk_settings.snap_entities_to_grid,
+    );
+
+    if ldtk_settings.use_level_world_translations {
+        level_position
+            + ldtk_pixel_coords_to_translation(
+                IVec2::new(level.world_x, level.world_y + level.px_hei),
+                world_height,
+            )
+    } else {
+        level_position
+    }
+}
+
 fn ldtk_coord_conversion(coords: IVec2, height: i32) -> IVec2 {
     IVec2::new(coords.x, height - coords.y)
 }
@@ -95,6 +292,29 @@ pub fn ldtk_pixel_coords_to_translation(ldtk_coords: IVec2, ldtk_pixel_height: i
     ldtk_coord_conversion(ldtk_coords, ldtk_pixel_height).as_vec2()
 }
 
+/// Returns the bottom-left and top-right corners of a level in the space of its world, like the
+/// translation it spawns with when [LdtkSettings::use_level_world_translations] is enabled.
+///
+/// `world_height` is the result of
+/// [LdtkAsset::world_height](crate::assets::LdtkAsset::world_height).
+pub fn level_world_bounds(level: &Level, world_height: i32) -> (Vec2, Vec2) {
+    let min = ldtk_pixel_coords_to_translation(
+        IVec2::new(level.world_x, level.world_y + level.px_hei),
+        world_height,
+    );
+
+    (
+        min,
+        min + Vec2::new(level.px_wid as f32, level.px_hei as f32),
+    )
+}
+
+/// Returns the distance from a point to the closest point of the rectangle with the given corners,
+/// which is `0` for points inside it.
+pub fn distance_to_rect(point: Vec2, min: Vec2, max: Vec2) -> f32 {
+    point.distance(point.clamp(min, max))
+}
+
 /// Performs translation to LDtk pixel coordinate conversion.
 pub fn translation_to_ldtk_pixel_coords(translation: Vec2, ldtk_pixel_height: i32) -> IVec2 {
     ldtk_coord_conversion(translation.as_ivec2(), ldtk_pixel_height)
@@ -123,6 +343,72 @@ pub fn tile_pos_to_ldtk_grid_coords(tile_pos: TilePos, ldtk_grid_height: i32) ->
     ldtk_coord_conversion_origin_adjusted(tile_coords.as_ivec2(), ldtk_grid_height)
 }
 
+/// Performs LDtk grid coordinate to [GridCoords] conversion.
+///
+/// Like [ldtk_grid_coords_to_tile_pos], both coordinates refer to the same tile.
+pub fn ldtk_grid_coords_to_grid_coords(ldtk_coords: IVec2, ldtk_grid_height: i32) -> GridCoords {
+    ldtk_coord_conversion_origin_adjusted(ldtk_coords, ldtk_grid_height).into()
+}
+
+/// Performs [GridCoords] to LDtk grid coordinate conversion.
+///
+/// Like [tile_pos_to_ldtk_grid_coords], both coordinates refer to the same tile.
+pub fn grid_coords_to_ldtk_grid_coords(grid_coords: GridCoords, ldtk_grid_height: i32) -> IVec2 {
+    ldtk_coord_conversion_origin_adjusted(grid_coords.into(), ldtk_grid_height)
+}
+
+/// Performs [GridCoords] to translation conversion, so that the resulting translation is in the
+/// center of the tile.
+///
+/// Assumes that the bottom-left corner of the origin tile is at [Vec2::ZERO], like the
+/// translations of the children of a level.
+pub fn grid_coords_to_translation_centered(grid_coords: GridCoords, tile_size: IVec2) -> Vec2 {
+    let tile_size = tile_size.as_vec2();
+    (tile_size * IVec2::from(grid_coords).as_vec2()) + (tile_size / Vec2::splat(2.))
+}
+
+/// Performs translation to [GridCoords] conversion, returning the coordinates of the tile
+/// containing the translation.
+///
+/// Assumes that the bottom-left corner of the origin tile is at [Vec2::ZERO], like the
+/// translations of the children of a level.
+pub fn translation_to_grid_coords(translation: Vec2, tile_size: IVec2) -> GridCoords {
+    (translation / tile_size.as_vec2())
+        .floor()
+        .as_ivec2()
+        .into()
+}
+
+/// Performs [GridCoords] to world translation conversion through the current [GlobalTransform] of
+/// a level, so that the resulting translation is in the center of the tile.
+///
+/// Unlike [grid_coords_to_translation_centered], this stays correct for levels whose [Transform]
+/// is animated after they spawn, like moving platforms or ship interiors.
+pub fn grid_coords_to_world_translation(
+    grid_coords: GridCoords,
+    tile_size: IVec2,
+    level_transform: &GlobalTransform,
+) -> Vec3 {
+    level_transform.mul_vec3(grid_coords_to_translation_centered(grid_coords, tile_size).extend(0.))
+}
+
+/// Performs world translation to [GridCoords] conversion through the current [GlobalTransform] of
+/// a level, returning the coordinates of the tile containing the translation.
+///
+/// This is the inverse of [grid_coords_to_world_translation].
+pub fn world_translation_to_grid_coords(
+    translation: Vec3,
+    tile_size: IVec2,
+    level_transform: &GlobalTransform,
+) -> GridCoords {
+    let level_translation = level_transform
+        .compute_matrix()
+        .inverse()
+        .transform_point3(translation);
+
+    translation_to_grid_coords(level_translation.truncate(), tile_size)
+}
+
 /// Performs LDtk grid coordinate to translation conversion, so that the resulting translation is
 /// in the center of the tile.
 pub fn ldtk_grid_coords_to_translation_centered(
@@ -134,6 +420,30 @@ pub fn ldtk_grid_coords_to_translation_centered(
         + Vec2::new(grid_size.x as f32 / 2., -grid_size.y as f32 / 2.)
 }
 
+/// Performs LDtk grid coordinate to translation conversion for an entity with the given size and
+/// pivot, so that the resulting translation is the center of the entity if it were placed on
+/// that tile in LDtk.
+///
+/// This is useful for `Point` fields, like teleport targets, which are stored as LDtk grid
+/// coordinates.
+/// The translation is relative to the level, like the translations of the entity itself.
+pub fn ldtk_grid_coords_to_translation_pivoted(
+    ldtk_coords: IVec2,
+    ldtk_grid_height: i32,
+    grid_size: i32,
+    entity_size: IVec2,
+    pivot: Vec2,
+) -> Vec2 {
+    let pivot_coords = ldtk_coords * grid_size + (pivot * grid_size as f32).as_ivec2();
+
+    ldtk_pixel_coords_to_translation_pivoted(
+        pivot_coords,
+        ldtk_grid_height * grid_size,
+        entity_size,
+        pivot,
+    )
+}
+
 /// Performs [TilePos] to translation conversion, so that the resulting translation is in the in
 /// the center of the tile.
 ///
@@ -191,6 +501,596 @@ pub fn set_all_tiles_with_func<T>(
     }
 }
 
+/// Returns the tile entities of the given tilemap layer, looked up through its chunks.
+///
+/// Returns nothing if the layer doesn't exist.
+pub fn layer_tile_entities(map_query: &MapQuery, map_id: u16, layer_id: u16) -> Vec<Entity> {
+    let settings = match map_query.get_layer(map_id, layer_id) {
+        Some((_, layer)) => layer.settings,
+        None => return Vec::new(),
+    };
+
+    let map_size: Vec2 = settings.map_size.into();
+    let chunk_size: Vec2 = settings.chunk_size.into();
+    let map_size_in_tiles = (map_size * chunk_size).as_uvec2();
+
+    let mut tile_entities = Vec::new();
+    for x in 0..map_size_in_tiles.x {
+        for y in 0..map_size_in_tiles.y {
+            if let Ok(tile_entity) = map_query.get_tile_entity(TilePos(x, y), map_id, layer_id) {
+                tile_entities.push(tile_entity);
+            }
+        }
+    }
+    tile_entities
+}
+
+/// A tilemap layer of a spawned level that has a tileset, see [tileset_layers].
+#[derive(Clone, Debug)]
+pub struct TilesetLayer<'a> {
+    pub layer_id: u16,
+    pub tileset_definition: &'a TilesetDefinition,
+    pub tile_entities: Vec<Entity>,
+}
+
+/// Returns the tilemap layers of a spawned level that have a tileset, with their tile entities.
+///
+/// `map_id` is the uid of the level, and `layer_tilesets` the [LayerTilesets] of its entity.
+/// Used by the systems setting up the tiles of levels that have just spawned, so they only visit
+/// the tiles of that level.
+pub fn tileset_layers<'a>(
+    map_id: u16,
+    layer_tilesets: &LayerTilesets,
+    ldtk_asset: &'a LdtkAsset,
+    map_query: &MapQuery,
+) -> Vec<TilesetLayer<'a>> {
+    layer_tilesets
+        .tileset_uids
+        .iter()
+        .filter_map(|(layer_id, tileset_uid)| {
+            Some(TilesetLayer {
+                layer_id: *layer_id,
+                tileset_definition: ldtk_asset.get_tileset_definition(*tileset_uid)?,
+                tile_entities: layer_tile_entities(map_query, map_id, *layer_id),
+            })
+        })
+        .collect()
+}
+
+/// Enum tag value marking fully solid tiles for [ground_height_at].
+pub const SOLID_TILE_TAG: &str = "Solid";
+/// Enum tag value marking slope tiles that rise from left to right for [ground_height_at].
+pub const SLOPE_UP_TILE_TAG: &str = "SlopeUp";
+/// Enum tag value marking slope tiles that descend from left to right for [ground_height_at].
+pub const SLOPE_DOWN_TILE_TAG: &str = "SlopeDown";
+
+/// Shape of the ground surface of a tile, used by [ground_height_at].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum TileShape {
+    /// The surface is the top of the tile.
+    Solid,
+    /// The surface rises from the bottom-left corner to the top-right corner.
+    SlopeUp,
+    /// The surface descends from the top-left corner to the bottom-right corner.
+    SlopeDown,
+}
+
+impl TileShape {
+    fn flipped_x(self) -> TileShape {
+        match self {
+            TileShape::Solid => TileShape::Solid,
+            TileShape::SlopeUp => TileShape::SlopeDown,
+            TileShape::SlopeDown => TileShape::SlopeUp,
+        }
+    }
+
+    /// Returns the shape of a tile with the given flip bits, as stored in [TileInstance::f].
+    ///
+    /// Vertically flipped slopes are ceilings, so the surface on top of them is flat.
+    fn flipped(self, flip_bits: i32) -> TileShape {
+        if flip_bits & 2 != 0 {
+            TileShape::Solid
+        } else if flip_bits & 1 != 0 {
+            self.flipped_x()
+        } else {
+            self
+        }
+    }
+
+    /// Height of the surface above the bottom of the tile at `x` pixels from its left edge.
+    pub fn surface_height(&self, tile_size: f32, x: f32) -> f32 {
+        let x = x.clamp(0., tile_size);
+        match self {
+            TileShape::Solid => tile_size,
+            TileShape::SlopeUp => x,
+            TileShape::SlopeDown => tile_size - x,
+        }
+    }
+}
+
+/// Reads the shapes of the tiles of a tileset from its enum tags.
+///
+/// Tiles tagged with [SOLID_TILE_TAG], [SLOPE_UP_TILE_TAG], or [SLOPE_DOWN_TILE_TAG] are
+/// included, using their tile ids as keys.
+pub fn tile_shapes_from_enum_tags(
+    tileset_definition: &TilesetDefinition,
+) -> HashMap<i32, TileShape> {
+    let mut tile_shapes = HashMap::new();
+
+    for enum_tag in &tileset_definition.enum_tags {
+        let shape = match enum_tag
+            .get("enumValueId")
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_str())
+        {
+            Some(SOLID_TILE_TAG) => TileShape::Solid,
+            Some(SLOPE_UP_TILE_TAG) => TileShape::SlopeUp,
+            Some(SLOPE_DOWN_TILE_TAG) => TileShape::SlopeDown,
+            _ => continue,
+        };
+
+        if let Some(tile_ids) = enum_tag
+            .get("tileIds")
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_array())
+        {
+            for tile_id in tile_ids.iter().filter_map(|t| t.as_i64()) {
+                tile_shapes.insert(tile_id as i32, shape);
+            }
+        }
+    }
+
+    tile_shapes
+}
+
+/// Reads the enum values each tile of a tileset is tagged with, using their tile ids as keys.
+///
+/// Tiles without tags are absent.
+pub fn tile_enum_tags(tileset_definition: &TilesetDefinition) -> HashMap<i32, Vec<String>> {
+    let mut tile_enum_tags: HashMap<i32, Vec<String>> = HashMap::new();
+
+    for enum_tag in &tileset_definition.enum_tags {
+        let enum_value = match enum_tag
+            .get("enumValueId")
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_str())
+        {
+            Some(enum_value) => enum_value,
+            None => continue,
+        };
+
+        if let Some(tile_ids) = enum_tag
+            .get("tileIds")
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.as_array())
+        {
+            for tile_id in tile_ids.iter().filter_map(|t| t.as_i64()) {
+                tile_enum_tags
+                    .entry(tile_id as i32)
+                    .or_default()
+                    .push(enum_value.to_string());
+            }
+        }
+    }
+
+    tile_enum_tags
+}
+
+/// Finds the height of the ground at `x`, accounting for slopes.
+///
+/// Coordinates are in the space of the level's children, like the translations of spawned LDtk
+/// entities: the origin is the bottom-left corner of the level and y points up.
+/// The tiles of all Tile and AutoLayer layers are considered, with their shapes looked up in
+/// `tile_shapes` (see [tile_shapes_from_enum_tags]).
+/// Horizontally flipped slopes are mirrored, while vertically flipped ones are ceilings, so the
+/// ground on top of them is flat.
+///
+/// Returns the highest ground surface at or below `from_y`, so a character controller can pass
+/// the y of its feet to snap down onto the ground beneath it.
+/// Returns [None] if there's no ground below.
+pub fn ground_height_at(
+    level: &Level,
+    tile_shapes: &HashMap<i32, TileShape>,
+    x: f32,
+    from_y: f32,
+) -> Option<f32> {
+    let mut ground_height: Option<f32> = None;
+
+    for layer_instance in level.layer_instances.iter().flatten() {
+        let tile_size = layer_instance.grid_size as f32;
+
+        for tile in layer_instance
+            .grid_tiles
+            .iter()
+            .chain(layer_instance.auto_layer_tiles.iter())
+        {
+            let shape = match tile_shapes.get(&tile.t) {
+                Some(shape) => shape.flipped(tile.f),
+                None => continue,
+            };
+
+            let left = (tile.px.x + layer_instance.px_total_offset_x) as f32;
+            if x < left || x > left + tile_size {
+                continue;
+            }
+
+            let top = (tile.px.y + layer_instance.px_total_offset_y) as f32;
+            let bottom = level.px_hei as f32 - top - tile_size;
+            let surface = bottom + shape.surface_height(tile_size, x - left);
+
+            if surface <= from_y && ground_height.map_or(true, |g| surface > g) {
+                ground_height = Some(surface);
+            }
+        }
+    }
+
+    ground_height
+}
+
+/// Creates a copy of a tileset image where each tile is extruded by `extrusion` pixels, by
+/// repeating its edge pixels.
+///
+/// Tiles are laid out in the same order as the original, with no padding, and with a spacing of
+/// `2 * extrusion` filled by the extruded edges of the tiles on either side.
+/// The outer edges of the image aren't extruded, since samplers clamp to them anyway.
+///
+/// Returns `None` if the tiles of the definition don't fit in the image, like when the image was
+/// resized after the definition was saved.
+pub fn extrude_tileset_image(
+    image: &Image,
+    tileset_definition: &TilesetDefinition,
+    extrusion: u32,
+) -> Option<Image> {
+    let pixel_size = image.texture_descriptor.format.describe().block_size as usize;
+    let source_width = image.texture_descriptor.size.width as usize;
+    let source_height = image.texture_descriptor.size.height as usize;
+
+    let tile_size = tileset_definition.tile_grid_size as usize;
+    let extrusion = extrusion as usize;
+    let stride = tile_size + 2 * extrusion;
+
+    let tiles_extent = |tiles: i32| {
+        tileset_definition.padding as usize
+            + (tiles.max(1) as usize - 1) * (tile_size + tileset_definition.spacing as usize)
+            + tile_size
+    };
+
+    if tiles_extent(tileset_definition.c_wid) > source_width
+        || tiles_extent(tileset_definition.c_hei) > source_height
+        || image.data.len() < source_width * source_height * pixel_size
+    {
+        return None;
+    }
+
+    let width = (tileset_definition.c_wid as usize * stride).saturating_sub(2 * extrusion);
+    let height = (tileset_definition.c_hei as usize * stride).saturating_sub(2 * extrusion);
+
+    let source_coord = |coord: usize| -> usize {
+        let (cell, local) = (coord / stride, coord % stride);
+        let (cell, local) = if local < tile_size {
+            (cell, local)
+        } else if local < tile_size + extrusion {
+            (cell, tile_size - 1)
+        } else {
+            (cell + 1, 0)
+        };
+
+        tileset_definition.padding as usize
+            + cell * (tile_size + tileset_definition.spacing as usize)
+            + local
+    };
+
+    let mut data = Vec::with_capacity(width * height * pixel_size);
+    for y in 0..height {
+        let source_y = source_coord(y);
+        for x in 0..width {
+            let source_index = (source_y * source_width + source_coord(x)) * pixel_size;
+            data.extend_from_slice(&image.data[source_index..source_index + pixel_size]);
+        }
+    }
+
+    let mut extruded = Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        image.texture_descriptor.format,
+    );
+    extruded.sampler_descriptor = image.sampler_descriptor.clone();
+
+    Some(extruded)
+}
+
+/// Converts an sRGB color channel to linear space.
+fn srgb_to_linear(channel: u8) -> f32 {
+    let channel = channel as f32 / 255.;
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear color channel to sRGB space.
+fn linear_to_srgb(channel: f32) -> u8 {
+    let channel = if channel <= 0.0031308 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1. / 2.4) - 0.055
+    };
+    (channel.clamp(0., 1.) * 255.).round() as u8
+}
+
+/// Draws the `tiles` of a layer onto `layer_image`, blending each tile over the previous ones.
+///
+/// Both images are expected to use a 4-byte sRGB RGBA format with unpremultiplied alpha, like
+/// tilesets loaded from PNG files, and `layer_image` to have the pixel size of the layer.
+/// Colors are blended in linear space, like the GPU blends overlapping sprites.
+/// Tiles are scaled from the tile size of their tileset to the `grid_size` of the layer with
+/// nearest sampling, flipped according to their flip bits, and faded by `opacity`.
+pub fn draw_layer_tiles(
+    layer_image: &mut Image,
+    tiles: &[TileInstance],
+    grid_size: i32,
+    tileset_image: &Image,
+    tileset_definition: &TilesetDefinition,
+    opacity: f32,
+) {
+    let layer_size = IVec2::new(
+        layer_image.texture_descriptor.size.width as i32,
+        layer_image.texture_descriptor.size.height as i32,
+    );
+    let tileset_size = IVec2::new(
+        tileset_image.texture_descriptor.size.width as i32,
+        tileset_image.texture_descriptor.size.height as i32,
+    );
+    let tile_size = tileset_definition.tile_grid_size;
+
+    for tile in tiles {
+        let flip = IVec2::new(tile.f & 1, (tile.f >> 1) & 1);
+
+        for y in 0..grid_size {
+            for x in 0..grid_size {
+                let layer_pixel = tile.px + IVec2::new(x, y);
+                let local = IVec2::new(
+                    if flip.x == 1 { grid_size - 1 - x } else { x },
+                    if flip.y == 1 { grid_size - 1 - y } else { y },
+                );
+                let source_pixel = tile.src + local * tile_size / grid_size;
+
+                if layer_pixel.cmplt(IVec2::ZERO).any()
+                    || layer_pixel.cmpge(layer_size).any()
+                    || source_pixel.cmplt(IVec2::ZERO).any()
+                    || source_pixel.cmpge(tileset_size).any()
+                {
+                    continue;
+                }
+
+                let source_index =
+                    ((source_pixel.y * tileset_size.x + source_pixel.x) * 4) as usize;
+                let source = &tileset_image.data[source_index..source_index + 4];
+
+                let alpha = source[3] as f32 / 255. * opacity;
+                if alpha <= 0. {
+                    continue;
+                }
+
+                let index = ((layer_pixel.y * layer_size.x + layer_pixel.x) * 4) as usize;
+                let destination = &mut layer_image.data[index..index + 4];
+
+                let destination_alpha = destination[3] as f32 / 255. * (1. - alpha);
+                let blended_alpha = alpha + destination_alpha;
+
+                for c in 0..3 {
+                    destination[c] = linear_to_srgb(
+                        (srgb_to_linear(source[c]) * alpha
+                            + srgb_to_linear(destination[c]) * destination_alpha)
+                            / blended_alpha,
+                    );
+                }
+                destination[3] = (blended_alpha * 255.).round() as u8;
+            }
+        }
+    }
+}
+
+/// Returns whether an image uses a 4-byte format, like the RGBA images [draw_layer_tiles] expects.
+pub(crate) fn is_rgba8(image: &Image) -> bool {
+    image.texture_descriptor.format.describe().block_size == 4
+}
+
+/// Bakes the visible tile layers of a level into a single image of the level's pixel size, like
+/// LDtk's PNG export of levels.
+///
+/// Layers are drawn at their offsets, in the [layer_draw_order] given by `overrides`.
+/// Returns [None] until the images of all the tilesets used by the level are loaded, or if one of
+/// them isn't in a 4-byte RGBA format.
+pub fn bake_level_image(
+    level: &Level,
+    ldtk_asset: &LdtkAsset,
+    images: &Assets<Image>,
+    overrides: &HashMap<String, usize>,
+) -> Option<Image> {
+    let layer_instances = level.layer_instances.as_deref().unwrap_or_default();
+
+    let mut layers = Vec::new();
+    for layer_instance in layer_draw_order(layer_instances, overrides) {
+        if layer_instance.layer_instance_type == Type::Entities || !layer_instance.visible {
+            continue;
+        }
+
+        let rule_tileset_uids = ldtk_asset
+            .get_layer_definition(layer_instance.layer_def_uid)
+            .map(auto_rule_tileset_uids)
+            .unwrap_or_default();
+
+        for (tileset_uid, tiles) in group_tiles_by_tileset(layer_instance, &rule_tileset_uids) {
+            let tileset_uid = match tileset_uid {
+                Some(tileset_uid) => tileset_uid,
+                None => continue,
+            };
+
+            let tileset_image = images.get(ldtk_asset.tileset_map.get(&tileset_uid)?)?;
+            if !is_rgba8(tileset_image) {
+                return None;
+            }
+
+            let offset = IVec2::new(
+                layer_instance.px_total_offset_x,
+                layer_instance.px_total_offset_y,
+            );
+            let tiles: Vec<TileInstance> = tiles
+                .into_iter()
+                .map(|tile| TileInstance {
+                    px: tile.px + offset,
+                    ..tile
+                })
+                .collect();
+
+            layers.push((
+                layer_instance,
+                tileset_image,
+                ldtk_asset.get_tileset_definition(tileset_uid)?,
+                tiles,
+            ));
+        }
+    }
+
+    let mut level_image = Image::new_fill(
+        Extent3d {
+            width: level.px_wid.max(0) as u32,
+            height: level.px_hei.max(0) as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    for (layer_instance, tileset_image, tileset_definition, tiles) in layers {
+        draw_layer_tiles(
+            &mut level_image,
+            &tiles,
+            layer_instance.grid_size,
+            tileset_image,
+            tileset_definition,
+            layer_instance.opacity,
+        );
+    }
+
+    Some(level_image)
+}
+
+/// Sorts the layers of a level from the bottom to the top of the draw order.
+///
+/// `layer_instances` is expected to be in LDtk's order, from the top layer to the bottom one.
+/// Layers are given their index in the reversed order, unless `overrides` has an index for their
+/// identifier.
+/// Layers are then sorted by index, with overridden layers above the other layers of the same
+/// index.
+pub fn layer_draw_order<'a>(
+    layer_instances: &'a [LayerInstance],
+    overrides: &HashMap<String, usize>,
+) -> Vec<&'a LayerInstance> {
+    let mut layers: Vec<(usize, bool, &LayerInstance)> = layer_instances
+        .iter()
+        .rev()
+        .enumerate()
+        .map(
+            |(i, layer_instance)| match overrides.get(&layer_instance.identifier) {
+                Some(index) => (*index, true, layer_instance),
+                None => (i, false, layer_instance),
+            },
+        )
+        .collect();
+
+    layers.sort_by_key(|(index, overridden, _)| (*index, *overridden));
+
+    layers.into_iter().map(|(_, _, l)| l).collect()
+}
+
+/// Allocates the ids of the tilemap layers of a level, in draw order.
+///
+/// Tile and IntGrid layers may need several tilemap layers, for stacked tiles and tiles of
+/// different tilesets, so ids are allocated one sub-layer at a time and never reused within a
+/// level.
+/// Allocation fails once the `u16` layer ids of `bevy_ecs_tilemap` are exhausted, instead of
+/// wrapping around onto the ids of previous layers.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct LayerIdAllocator {
+    next: u32,
+}
+
+impl LayerIdAllocator {
+    /// Returns the id the next layer will be allocated, without allocating it.
+    ///
+    /// Used as the z translation of entity layers, which don't have tilemap layers.
+    pub fn peek(&self) -> u32 {
+        self.next
+    }
+
+    /// Allocates the next layer id, or returns [None] if they are exhausted.
+    pub fn allocate(&mut self) -> Option<u16> {
+        let id = u16::try_from(self.next).ok()?;
+        self.next += 1;
+        Some(id)
+    }
+}
+
+/// Returns the iids referenced by the `EntityRef` fields of an [EntityInstance], skipping null
+/// references.
+pub fn referenced_entity_iids(entity_instance: &EntityInstance) -> Vec<&str> {
+    entity_instance
+        .field_instances
+        .iter()
+        .flat_map(|field_instance| match &field_instance.value {
+            FieldValue::EntityRef(reference) => reference.iter().collect(),
+            FieldValue::EntityRefs(references) => references.iter().flatten().collect(),
+            _ => Vec::new(),
+        })
+        .map(|reference| reference.entity_iid.as_str())
+        .collect()
+}
+
+/// Resolves the `EntityRef` fields of an [EntityInstance] to [Entity]s, using `get_entity` to look
+/// up the entity of each referenced iid.
+///
+/// Returns [None] if the entity has no `EntityRef` fields, or if some referenced entity can't be
+/// found yet.
+/// Null references are skipped.
+pub fn resolve_entity_refs(
+    entity_instance: &EntityInstance,
+    get_entity: impl Fn(&str) -> Option<Entity>,
+) -> Option<EntityRefs> {
+    let mut entity_refs = EntityRefs::default();
+
+    for field_instance in &entity_instance.field_instances {
+        let references: Vec<&FieldInstanceEntityReference> = match &field_instance.value {
+            FieldValue::EntityRef(reference) => reference.iter().collect(),
+            FieldValue::EntityRefs(references) => references.iter().flatten().collect(),
+            _ => continue,
+        };
+
+        let entities = references
+            .into_iter()
+            .map(|r| get_entity(&r.entity_iid))
+            .collect::<Option<Vec<Entity>>>()?;
+
+        entity_refs
+            .fields
+            .insert(field_instance.identifier.clone(), entities);
+    }
+
+    if entity_refs.fields.is_empty() {
+        None
+    } else {
+        Some(entity_refs)
+    }
+}
+
 /// Wraps `a` and `b` in an [Option] and tries each [Some]/[None] permutation as inputs to `func`,
 /// returning the first non-none result of `func`.
 ///
@@ -223,22 +1123,43 @@ where
 /// instance of an LDtk entity or int grid tile may match multiple registrations.
 /// This function is responsible for picking the correct registration while spawning these
 /// entities/tiles.
-pub(crate) fn ldtk_map_get_or_default<'a, A, B, L>(
+/// Returns [None] if no registration matches, in which case the plugin's default is used.
+pub(crate) fn ldtk_map_get<A, B, L>(
     a: A,
     b: B,
-    default: &'a L,
-    map: &'a HashMap<(Option<A>, Option<B>), L>,
-) -> &'a L
+    map: &HashMap<(Option<A>, Option<B>), L>,
+) -> Option<&L>
 where
     A: Hash + Eq + Clone,
     B: Hash + Eq + Clone,
 {
-    try_each_optional_permutation(a, b, |x, y| map.get(&(x, y))).unwrap_or(default)
+    try_each_optional_permutation(a, b, |x, y| map.get(&(x, y)))
+}
+
+/// The "get" function used on [bevy_ecs_ldtk::app::LdtkIntCellMap] and
+/// [bevy_ecs_ldtk::app::LdtkIntCellIdentifierMap] together.
+///
+/// Registrations made by IntGrid value identifier have the same priority as the ones made by
+/// value, but the latter are preferred when both match.
+pub(crate) fn int_cell_map_get<'a, L>(
+    layer_identifier: String,
+    value: i32,
+    value_identifier: Option<&str>,
+    int_cell_map: &'a HashMap<(Option<String>, Option<i32>), L>,
+    int_cell_identifier_map: &'a HashMap<(Option<String>, String), L>,
+) -> Option<&'a L> {
+    try_each_optional_permutation(layer_identifier, value, |l, v| match v {
+        Some(v) => int_cell_map
+            .get(&(l.clone(), Some(v)))
+            .or_else(|| int_cell_identifier_map.get(&(l, value_identifier?.to_string()))),
+        None => int_cell_map.get(&(l, None)),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::LayerInstanceBuilder;
 
     #[test]
     fn test_int_grid_index_to_tile_pos() {
@@ -253,6 +1174,23 @@ mod tests {
         assert_eq!(int_grid_index_to_tile_pos(35, 1, 100), Some(TilePos(0, 64)));
     }
 
+    #[test]
+    fn test_layer_id_allocator() {
+        let mut layer_ids = LayerIdAllocator::default();
+
+        assert_eq!(layer_ids.allocate(), Some(0));
+        assert_eq!(layer_ids.allocate(), Some(1));
+        assert_eq!(layer_ids.peek(), 2);
+
+        let mut layer_ids = LayerIdAllocator {
+            next: u16::MAX as u32,
+        };
+
+        assert_eq!(layer_ids.allocate(), Some(u16::MAX));
+        assert_eq!(layer_ids.allocate(), None);
+        assert_eq!(layer_ids.allocate(), None);
+    }
+
     #[test]
     fn test_int_grid_index_out_of_range() {
         assert_eq!(int_grid_index_to_tile_pos(3, 0, 5), None);
@@ -324,6 +1262,124 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ldtk_grid_coords_to_translation_pivoted() {
+        assert_eq!(
+            ldtk_grid_coords_to_translation_pivoted(
+                IVec2::new(0, 0),
+                10,
+                16,
+                IVec2::splat(16),
+                Vec2::splat(0.5)
+            ),
+            Vec2::new(8., 152.)
+        );
+
+        assert_eq!(
+            ldtk_grid_coords_to_translation_pivoted(
+                IVec2::new(2, 9),
+                10,
+                16,
+                IVec2::splat(16),
+                Vec2::ZERO
+            ),
+            Vec2::new(40., 8.)
+        );
+
+        assert_eq!(
+            ldtk_grid_coords_to_translation_pivoted(
+                IVec2::new(1, 1),
+                4,
+                16,
+                IVec2::new(32, 32),
+                Vec2::new(0.5, 1.)
+            ),
+            Vec2::new(24., 48.)
+        );
+    }
+
+    #[test]
+    fn test_calculate_aabb_from_entity_instance() {
+        let entity_definitions = vec![EntityDefinition {
+            uid: 0,
+            width: 32,
+            height: 16,
+            ..Default::default()
+        }];
+        let entity_definition_map = create_entity_definition_map(&entity_definitions);
+
+        let entity_instance = EntityInstance {
+            def_uid: 0,
+            width: 64,
+            height: 64,
+            ..Default::default()
+        };
+        let result = calculate_aabb_from_entity_instance(&entity_instance, &entity_definition_map);
+        assert_eq!(result.center, Vec3::ZERO);
+        assert_eq!(result.half_extents, Vec3::new(16., 8., 0.));
+
+        let entity_instance = EntityInstance {
+            def_uid: 0,
+            tile: Some(EntityInstanceTile {
+                src_rect: vec![0, 0, 8, 24],
+                tileset_uid: 0,
+            }),
+            ..entity_instance
+        };
+        let result = calculate_aabb_from_entity_instance(&entity_instance, &entity_definition_map);
+        assert_eq!(result.half_extents, Vec3::new(4., 12., 0.));
+    }
+
+    #[test]
+    fn test_entity_instance_world_position() {
+        let entity_instance = EntityInstance {
+            grid: IVec2::new(2, 3),
+            px: IVec2::new(40, 50),
+            width: 30,
+            height: 50,
+            pivot: Vec2::new(1., 1.),
+            ..Default::default()
+        };
+        let level = Level {
+            px_hei: 96,
+            world_x: 200,
+            world_y: 50,
+            ..Default::default()
+        };
+        // Layer offsets don't move entities
+        let layer_instance = LayerInstanceBuilder::new("Entities", Type::Entities, 10, 6, 16)
+            .offset(IVec2::new(4, 6))
+            .build();
+
+        let position = |ldtk_settings: &LdtkSettings| {
+            entity_instance_world_position(
+                &entity_instance,
+                &level,
+                &layer_instance,
+                ldtk_settings,
+                300,
+            )
+        };
+
+        assert_eq!(position(&LdtkSettings::default()), Vec2::new(25., 71.));
+
+        assert_eq!(
+            position(&LdtkSettings {
+                use_level_world_translations: true,
+                ..Default::default()
+            }),
+            Vec2::new(225., 225.)
+        );
+
+        assert_eq!(
+            position(&LdtkSettings {
+                snap_entities_to_grid: true,
+                ..Default::default()
+            }),
+            Vec2::new(40., 40.)
+        );
+    }
+
     #[test]
     fn test_calculate_transform_from_entity_instance_with_tile() {
         let entity_definitions = vec![EntityDefinition {
@@ -397,6 +1453,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_level_world_bounds() {
+        let level = Level {
+            world_x: 64,
+            world_y: 32,
+            px_wid: 128,
+            px_hei: 96,
+            ..Default::default()
+        };
+
+        let (min, max) = level_world_bounds(&level, 256);
+        assert_eq!(min, Vec2::new(64., 128.));
+        assert_eq!(max, Vec2::new(192., 224.));
+
+        assert_eq!(distance_to_rect(Vec2::new(100., 200.), min, max), 0.);
+        assert_eq!(distance_to_rect(Vec2::new(32., 200.), min, max), 32.);
+        assert_eq!(distance_to_rect(Vec2::new(195., 228.), min, max), 5.);
+    }
+
+    #[test]
+    fn test_grid_coords_to_world_translation() {
+        let level_transform = GlobalTransform::from_xyz(100., 50., 0.).with_scale(Vec3::splat(2.));
+
+        assert_eq!(
+            grid_coords_to_world_translation(
+                GridCoords::new(1, 2),
+                IVec2::splat(16),
+                &level_transform
+            ),
+            Vec3::new(148., 130., 0.)
+        );
+
+        assert_eq!(
+            world_translation_to_grid_coords(
+                Vec3::new(148., 130., 0.),
+                IVec2::splat(16),
+                &level_transform
+            ),
+            GridCoords::new(1, 2)
+        );
+    }
+
     #[test]
     fn test_tile_pos_to_translation_centered() {
         assert_eq!(
@@ -415,6 +1513,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_grid_coords_conversions() {
+        assert_eq!(
+            ldtk_grid_coords_to_grid_coords(IVec2::new(3, 0), 5),
+            GridCoords::new(3, 4)
+        );
+        assert_eq!(
+            grid_coords_to_ldtk_grid_coords(GridCoords::new(3, 4), 5),
+            IVec2::new(3, 0)
+        );
+        assert_eq!(
+            grid_coords_to_translation_centered(GridCoords::new(1, 2), IVec2::splat(16)),
+            Vec2::new(24., 40.)
+        );
+        assert_eq!(
+            translation_to_grid_coords(Vec2::new(24., 40.), IVec2::splat(16)),
+            GridCoords::new(1, 2)
+        );
+        assert_eq!(
+            translation_to_grid_coords(Vec2::new(-1., 0.), IVec2::splat(16)),
+            GridCoords::new(-1, 0)
+        );
+    }
+
     #[test]
     fn test_ldtk_pixel_coords_to_translation_pivoted() {
         assert_eq!(
@@ -448,6 +1570,429 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tile_shape_surface_height() {
+        assert_eq!(TileShape::Solid.surface_height(16., 4.), 16.);
+        assert_eq!(TileShape::SlopeUp.surface_height(16., 4.), 4.);
+        assert_eq!(TileShape::SlopeDown.surface_height(16., 4.), 12.);
+        assert_eq!(TileShape::SlopeUp.surface_height(16., 20.), 16.);
+        assert_eq!(TileShape::SlopeUp.flipped_x(), TileShape::SlopeDown);
+    }
+
+    #[test]
+    fn test_ground_height_at_flipped_slopes() {
+        let tileset_definition = crate::fixtures::tileset_definition(1, "Slopes", 1, 1, 16);
+        let layer_instance = LayerInstanceBuilder::new("Ground", Type::Tiles, 4, 1, 16)
+            .tileset(&tileset_definition)
+            .tile(IVec2::new(0, 0), 0)
+            .flipped_tile(IVec2::new(1, 0), 0, true, false)
+            .flipped_tile(IVec2::new(2, 0), 0, false, true)
+            .flipped_tile(IVec2::new(3, 0), 0, true, true)
+            .build();
+        let level = Level {
+            px_hei: 16,
+            layer_instances: Some(vec![layer_instance]),
+            ..Default::default()
+        };
+        let tile_shapes = HashMap::from([(0, TileShape::SlopeUp)]);
+
+        assert_eq!(ground_height_at(&level, &tile_shapes, 4., 100.), Some(4.));
+        assert_eq!(ground_height_at(&level, &tile_shapes, 20., 100.), Some(12.));
+        assert_eq!(ground_height_at(&level, &tile_shapes, 36., 100.), Some(16.));
+        assert_eq!(ground_height_at(&level, &tile_shapes, 52., 100.), Some(16.));
+        assert_eq!(ground_height_at(&level, &tile_shapes, 36., 10.), None);
+    }
+
+    #[test]
+    fn test_group_tiles_by_tileset() {
+        let tile = |x: i32, rule_uid: i32| TileInstance {
+            px: IVec2::new(x, 0),
+            d: vec![rule_uid, x],
+            ..Default::default()
+        };
+
+        let mut layer_instance = LayerInstanceBuilder::new("Ground", Type::AutoLayer, 4, 1, 16)
+            .tileset(&TilesetDefinition {
+                uid: 1,
+                ..Default::default()
+            })
+            .build();
+        layer_instance.auto_layer_tiles = vec![tile(0, 10), tile(16, 20), tile(32, 30)];
+
+        let rule_tileset_uids = HashMap::from([(20, 2), (30, 1)]);
+
+        let groups = group_tiles_by_tileset(&layer_instance, &rule_tileset_uids);
+        assert_eq!(
+            groups,
+            vec![
+                (Some(1), vec![tile(0, 10), tile(32, 30)]),
+                (Some(2), vec![tile(16, 20)]),
+            ]
+        );
+
+        layer_instance.auto_layer_tiles.clear();
+        assert_eq!(
+            group_tiles_by_tileset(&layer_instance, &rule_tileset_uids),
+            vec![(Some(1), Vec::new())]
+        );
+    }
+
+    #[test]
+    fn test_layer_settings_and_scale() {
+        let tileset_definition = TilesetDefinition {
+            tile_grid_size: 16,
+            px_wid: 64,
+            px_hei: 32,
+            ..Default::default()
+        };
+
+        let (settings, layer_scale) =
+            layer_settings_and_scale(MapSize(2, 1), 32, Some(&tileset_definition));
+        assert_eq!((settings.tile_size.0, settings.tile_size.1), (16., 16.));
+        assert_eq!(
+            (settings.texture_size.0, settings.texture_size.1),
+            (64., 32.)
+        );
+        assert_eq!(layer_scale, Vec3::new(2., 2., 1.));
+
+        let (settings, layer_scale) = layer_settings_and_scale(MapSize(2, 1), 32, None);
+        assert_eq!((settings.tile_size.0, settings.tile_size.1), (32., 32.));
+        assert_eq!(layer_scale, Vec3::ONE);
+    }
+
+    #[test]
+    fn test_draw_layer_tiles() {
+        use bevy::render::render_resource::TextureFormat;
+
+        let size = |width, height| Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        // An opaque red tile, and a half-transparent blue one
+        let tileset_image = Image::new(
+            size(2, 1),
+            TextureDimension::D2,
+            vec![255, 0, 0, 255, 0, 0, 255, 102],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let tileset_definition = TilesetDefinition {
+            c_wid: 2,
+            c_hei: 1,
+            tile_grid_size: 1,
+            ..Default::default()
+        };
+
+        let tile = |px: IVec2, src: IVec2| TileInstance {
+            px,
+            src,
+            ..Default::default()
+        };
+
+        let mut layer_image = Image::new_fill(
+            size(3, 1),
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        draw_layer_tiles(
+            &mut layer_image,
+            &[
+                tile(IVec2::new(0, 0), IVec2::new(0, 0)),
+                tile(IVec2::new(1, 0), IVec2::new(0, 0)),
+                tile(IVec2::new(1, 0), IVec2::new(1, 0)),
+                tile(IVec2::new(2, 0), IVec2::new(1, 0)),
+            ],
+            1,
+            &tileset_image,
+            &tileset_definition,
+            1.,
+        );
+
+        assert_eq!(
+            layer_image.data,
+            vec![
+                255, 0, 0, 255, //
+                203, 0, 170, 255, //
+                0, 0, 255, 102,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bake_level_image() {
+        use crate::fixtures::{ldtk_json, tileset_definition, LevelBuilder};
+        use bevy::asset::AssetPlugin;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Image>();
+
+        let tileset_definition = tileset_definition(1, "Tiles", 2, 1, 1);
+
+        let mut hidden = LayerInstanceBuilder::new("Hidden", Type::Tiles, 2, 1, 1)
+            .tileset(&tileset_definition)
+            .tile(IVec2::new(1, 0), 0)
+            .build();
+        hidden.visible = false;
+
+        let mut level = LevelBuilder::new(0)
+            .layer(
+                LayerInstanceBuilder::new("Bottom", Type::Tiles, 2, 1, 1)
+                    .tileset(&tileset_definition)
+                    .tile(IVec2::new(0, 0), 0)
+                    .build(),
+            )
+            .layer(
+                LayerInstanceBuilder::new("Top", Type::Tiles, 1, 1, 1)
+                    .tileset(&tileset_definition)
+                    .offset(IVec2::new(1, 0))
+                    .tile(IVec2::new(0, 0), 1)
+                    .build(),
+            )
+            .layer(hidden)
+            .build();
+        level.px_wid = 2;
+        level.px_hei = 1;
+
+        let mut project = ldtk_json(vec![level.clone()]);
+        project.defs.tilesets.push(tileset_definition);
+        let mut ldtk_asset = LdtkAsset {
+            project,
+            tileset_map: HashMap::new(),
+            level_map: HashMap::new(),
+            level_background_map: HashMap::new(),
+            layer_settings_cache: Default::default(),
+        };
+
+        let images = app.world.get_resource::<Assets<Image>>().unwrap();
+        assert!(bake_level_image(&level, &ldtk_asset, images, &HashMap::new()).is_none());
+
+        // An opaque red tile, and an opaque blue one
+        let tileset_image = Image::new(
+            Extent3d {
+                width: 2,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            vec![255, 0, 0, 255, 0, 0, 255, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let tileset_handle = app
+            .world
+            .get_resource_mut::<Assets<Image>>()
+            .unwrap()
+            .add(tileset_image);
+        ldtk_asset.tileset_map.insert(1, tileset_handle);
+
+        let images = app.world.get_resource::<Assets<Image>>().unwrap();
+        let level_image = bake_level_image(&level, &ldtk_asset, images, &HashMap::new()).unwrap();
+
+        assert_eq!(
+            (
+                level_image.texture_descriptor.size.width,
+                level_image.texture_descriptor.size.height
+            ),
+            (2, 1)
+        );
+        assert_eq!(level_image.data, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_extrude_tileset_image() {
+        use bevy::render::render_resource::{FilterMode, TextureFormat};
+
+        // 2x1 tiles of 2x2 pixels, with 1 pixel of spacing and padding
+        let pixels: Vec<u8> = vec![
+            0, 0, 0, 0, 0, 0, 0, //
+            0, 1, 2, 0, 5, 6, 0, //
+            0, 3, 4, 0, 7, 8, 0, //
+            0, 0, 0, 0, 0, 0, 0,
+        ];
+        let mut image = Image::new(
+            Extent3d {
+                width: 7,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            pixels.iter().flat_map(|p| [*p; 4]).collect(),
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        image.sampler_descriptor.mag_filter = FilterMode::Nearest;
+
+        let tileset_definition = TilesetDefinition {
+            c_wid: 2,
+            c_hei: 1,
+            tile_grid_size: 2,
+            spacing: 1,
+            padding: 1,
+            ..Default::default()
+        };
+
+        let extruded = extrude_tileset_image(&image, &tileset_definition, 1).unwrap();
+
+        assert_eq!(extruded.texture_descriptor.size.width, 6);
+        assert_eq!(extruded.texture_descriptor.size.height, 2);
+        assert_eq!(
+            extruded.data.chunks(4).map(|p| p[0]).collect::<Vec<u8>>(),
+            vec![
+                1, 2, 2, 5, 5, 6, //
+                3, 4, 4, 7, 7, 8,
+            ]
+        );
+        assert_eq!(extruded.sampler_descriptor.mag_filter, FilterMode::Nearest);
+
+        // A third column of tiles doesn't fit in the image
+        let tileset_definition = TilesetDefinition {
+            c_wid: 3,
+            ..tileset_definition
+        };
+        assert!(extrude_tileset_image(&image, &tileset_definition, 1).is_none());
+    }
+
+    #[test]
+    fn test_layer_draw_order() {
+        let layer =
+            |identifier: &str| LayerInstanceBuilder::new(identifier, Type::Tiles, 1, 1, 16).build();
+        let layer_instances = vec![layer("Entities"), layer("Foreground"), layer("Background")];
+
+        let identifiers = |overrides: &HashMap<String, usize>| -> Vec<String> {
+            layer_draw_order(&layer_instances, overrides)
+                .into_iter()
+                .map(|l| l.identifier.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            identifiers(&HashMap::new()),
+            vec!["Background", "Foreground", "Entities"]
+        );
+        assert_eq!(
+            identifiers(&HashMap::from([("Foreground".to_string(), 2)])),
+            vec!["Background", "Entities", "Foreground"]
+        );
+        assert_eq!(
+            identifiers(&HashMap::from([("Entities".to_string(), 0)])),
+            vec!["Background", "Entities", "Foreground"]
+        );
+    }
+
+    #[test]
+    fn test_tile_shapes_from_enum_tags() {
+        let enum_tag = |value: &str, tile_ids: Vec<i32>| {
+            HashMap::from([
+                ("enumValueId".to_string(), Some(serde_json::json!(value))),
+                ("tileIds".to_string(), Some(serde_json::json!(tile_ids))),
+            ])
+        };
+
+        let tileset_definition = TilesetDefinition {
+            enum_tags: vec![
+                enum_tag(SOLID_TILE_TAG, vec![0, 1]),
+                enum_tag(SLOPE_UP_TILE_TAG, vec![2]),
+                enum_tag(SLOPE_DOWN_TILE_TAG, vec![3]),
+                enum_tag("Water", vec![4]),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            tile_shapes_from_enum_tags(&tileset_definition),
+            HashMap::from([
+                (0, TileShape::Solid),
+                (1, TileShape::Solid),
+                (2, TileShape::SlopeUp),
+                (3, TileShape::SlopeDown),
+            ])
+        );
+
+        let tags = tile_enum_tags(&tileset_definition);
+        assert_eq!(tags.get(&1), Some(&vec![SOLID_TILE_TAG.to_string()]));
+        assert_eq!(tags.get(&4), Some(&vec!["Water".to_string()]));
+        assert_eq!(tags.get(&5), None);
+    }
+
+    #[test]
+    fn test_resolve_entity_refs() {
+        let reference = |iid: &str| FieldInstanceEntityReference {
+            entity_iid: iid.to_string(),
+            ..Default::default()
+        };
+
+        let entity_instance = EntityInstance {
+            field_instances: vec![
+                FieldInstance {
+                    identifier: "door".to_string(),
+                    field_instance_type: "EntityRef".to_string(),
+                    value: FieldValue::EntityRef(Some(reference("a"))),
+                    def_uid: 0,
+                    real_editor_values: Vec::new(),
+                },
+                FieldInstance {
+                    identifier: "lights".to_string(),
+                    field_instance_type: "Array<EntityRef>".to_string(),
+                    value: FieldValue::EntityRefs(vec![Some(reference("b")), None]),
+                    def_uid: 1,
+                    real_editor_values: Vec::new(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let get_entity = |iid: &str| match iid {
+            "a" => Some(Entity::from_raw(1)),
+            "b" => Some(Entity::from_raw(2)),
+            _ => None,
+        };
+
+        assert_eq!(referenced_entity_iids(&entity_instance), vec!["a", "b"]);
+
+        let entity_refs = resolve_entity_refs(&entity_instance, get_entity).unwrap();
+        assert_eq!(entity_refs.get("door"), Some(Entity::from_raw(1)));
+        assert_eq!(entity_refs.get_all("lights"), &[Entity::from_raw(2)]);
+
+        assert_eq!(
+            resolve_entity_refs(&entity_instance, |iid| get_entity(iid)
+                .filter(|_| iid != "b")),
+            None
+        );
+        assert_eq!(
+            resolve_entity_refs(&EntityInstance::default(), get_entity),
+            None
+        );
+    }
+
+    #[test]
+    fn test_int_cell_map_get() {
+        let int_cell_map =
+            HashMap::from([((Some("Walls".to_string()), Some(1)), 1), ((None, None), 4)]);
+        let int_cell_identifier_map = HashMap::from([
+            ((Some("Walls".to_string()), "wall".to_string()), 2),
+            ((None, "water".to_string()), 3),
+        ]);
+
+        let get = |layer: &str, value, identifier| {
+            int_cell_map_get(
+                layer.to_string(),
+                value,
+                identifier,
+                &int_cell_map,
+                &int_cell_identifier_map,
+            )
+            .copied()
+        };
+
+        assert_eq!(get("Walls", 1, Some("wall")), Some(1));
+        assert_eq!(get("Walls", 2, Some("wall")), Some(2));
+        assert_eq!(get("Other", 2, Some("wall")), Some(4));
+        assert_eq!(get("Walls", 3, Some("water")), Some(3));
+        assert_eq!(get("Walls", 3, None), Some(4));
+    }
+
     #[test]
     fn test_try_each_optional_permutation() {
         fn test_func(a: Option<i32>, b: Option<i32>) -> Option<i32> {
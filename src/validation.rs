@@ -0,0 +1,257 @@
+//! Content validation of LDtk projects, for running in test suites and CI.
+//!
+//! A [LevelCorpus] loads every level of a project straight from the disk, without an [App] or
+//! asset server, including the levels saved in external files.
+//! A [LevelValidator] then runs a list of named rules against every level of the corpus, and
+//! collects the levels breaking them in a [ValidationReport].
+//! In a test of the game:
+//! ```no_run
+//! use bevy_ecs_ldtk::validation::*;
+//!
+//! fn levels_are_valid() {
+//!     let corpus = LevelCorpus::load("assets/my_project.ldtk").unwrap();
+//!
+//!     let report = LevelValidator::new()
+//!         .rule("every Door has a target", |_, level| {
+//!             let doors_without_target = level
+//!                 .layers()
+//!                 .flat_map(|layer| layer.entities())
+//!                 .filter(|entity| entity.identifier() == "Door")
+//!                 .filter(|entity| entity.field("target").is_none())
+//!                 .count();
+//!
+//!             match doors_without_target {
+//!                 0 => Ok(()),
+//!                 n => Err(format!("{} doors without a target", n)),
+//!             }
+//!         })
+//!         .validate(&corpus);
+//!
+//!     report.assert_ok();
+//! }
+//! ```
+//!
+//! Rules get the whole corpus as well as the level they check, so they can also verify references
+//! between levels.
+//!
+//! [App]: bevy::prelude::App
+
+use crate::{
+    ldtk::{LdtkJson, Level},
+    views::LevelData,
+};
+use anyhow::Context;
+use std::{fmt, fs, path::Path};
+
+/// Every level of an LDtk project, loaded from the disk.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LevelCorpus {
+    project: LdtkJson,
+    levels: Vec<Level>,
+}
+
+impl LevelCorpus {
+    /// Loads the LDtk project at `path`, along with its external level files.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let project: LdtkJson = serde_json::from_slice(&bytes)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+
+        let project_directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let levels = project
+            .levels
+            .iter()
+            .map(|level| match &level.external_rel_path {
+                Some(external_rel_path) if project.external_levels => {
+                    let level_path = project_directory.join(external_rel_path);
+
+                    let bytes = fs::read(&level_path)
+                        .with_context(|| format!("failed to read {}", level_path.display()))?;
+                    serde_json::from_slice(&bytes)
+                        .with_context(|| format!("failed to parse {}", level_path.display()))
+                }
+                _ => Ok(level.clone()),
+            })
+            .collect::<anyhow::Result<Vec<Level>>>()?;
+
+        Ok(LevelCorpus { project, levels })
+    }
+
+    /// The project, whose levels may lack their layers if they're saved in external files.
+    ///
+    /// Use [LevelCorpus::levels] for the complete levels.
+    pub fn project(&self) -> &LdtkJson {
+        &self.project
+    }
+
+    /// Returns the levels of the project in order, with their layers.
+    pub fn levels(&self) -> impl Iterator<Item = LevelData<'_>> {
+        self.levels.iter().map(LevelData::from)
+    }
+
+    /// Returns the level with the given iid, with its layers.
+    pub fn level(&self, iid: &str) -> Option<LevelData> {
+        self.levels()
+            .find(|level| !iid.is_empty() && level.iid() == iid)
+    }
+}
+
+type LevelRule = Box<dyn Fn(&LevelCorpus, LevelData) -> Result<(), String>>;
+
+/// A list of named rules that every level of a [LevelCorpus] should satisfy.
+///
+/// See the [module-level documentation](self) for an example.
+#[derive(Default)]
+pub struct LevelValidator {
+    rules: Vec<(String, LevelRule)>,
+}
+
+impl LevelValidator {
+    pub fn new() -> Self {
+        LevelValidator::default()
+    }
+
+    /// Adds a rule, which returns an error message for the levels breaking it.
+    pub fn rule(
+        mut self,
+        name: impl Into<String>,
+        rule: impl Fn(&LevelCorpus, LevelData) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.rules.push((name.into(), Box::new(rule)));
+        self
+    }
+
+    /// Runs every rule against every level of the `corpus`.
+    pub fn validate(&self, corpus: &LevelCorpus) -> ValidationReport {
+        let mut failures = Vec::new();
+
+        for level in corpus.levels() {
+            for (rule, check) in &self.rules {
+                if let Err(message) = check(corpus, level) {
+                    failures.push(ValidationFailure {
+                        rule: rule.clone(),
+                        level_identifier: level.identifier().to_string(),
+                        level_iid: level.iid().to_string(),
+                        message,
+                    });
+                }
+            }
+        }
+
+        ValidationReport {
+            levels_checked: corpus.levels.len(),
+            rules_checked: self.rules.len(),
+            failures,
+        }
+    }
+}
+
+/// A level breaking a rule of a [LevelValidator].
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub struct ValidationFailure {
+    pub rule: String,
+    pub level_identifier: String,
+    /// Empty if the project was saved before LDtk introduced level iids.
+    pub level_iid: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: \"{}\" failed: {}",
+            self.level_identifier, self.rule, self.message
+        )
+    }
+}
+
+/// The results of [LevelValidator::validate].
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ValidationReport {
+    pub levels_checked: usize,
+    pub rules_checked: usize,
+    /// Sorted by level, then by the order the rules were added in.
+    pub failures: Vec<ValidationFailure>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Returns the failures of the rule with the given name.
+    pub fn failures_of(&self, rule: &str) -> impl Iterator<Item = &ValidationFailure> {
+        let rule = rule.to_string();
+        self.failures.iter().filter(move |f| f.rule == rule)
+    }
+
+    /// Panics with every failure if any level broke a rule.
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("{}", self);
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} failures checking {} levels against {} rules",
+            self.failures.len(),
+            self.levels_checked,
+            self.rules_checked
+        )?;
+
+        for failure in &self.failures {
+            write!(f, "\n  {}", failure)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_validator() {
+        let corpus = LevelCorpus::load(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/WorldMap_Free_layout.ldtk"),
+        )
+        .unwrap();
+
+        assert_eq!(corpus.levels().count(), 9);
+
+        let report = LevelValidator::new()
+            .rule("has layers", |_, level| match level.layers().count() {
+                0 => Err("no layers".to_string()),
+                _ => Ok(()),
+            })
+            .rule("isn't the pit", |_, level| match level.identifier() {
+                "Pit" => Err("it's the pit".to_string()),
+                _ => Ok(()),
+            })
+            .validate(&corpus);
+
+        assert!(!report.is_ok());
+        assert_eq!(report.levels_checked, 9);
+        assert_eq!(report.failures_of("has layers").count(), 0);
+        assert_eq!(
+            report.failures_of("isn't the pit").collect::<Vec<_>>(),
+            vec![&ValidationFailure {
+                rule: "isn't the pit".to_string(),
+                level_identifier: "Pit".to_string(),
+                level_iid: corpus.project().levels[7].iid.clone(),
+                message: "it's the pit".to_string(),
+            }]
+        );
+
+        assert!(LevelCorpus::load("missing.ldtk").is_err());
+    }
+}
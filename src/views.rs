@@ -0,0 +1,531 @@
+//! Read-only views of LDtk data that are stable across LDtk releases.
+//!
+//! The types in [ldtk](crate::ldtk) mirror the LDtk JSON schema, so they change whenever LDtk
+//! changes its file format.
+//! The views in this module wrap them with accessor methods instead, so code written against
+//! them keeps compiling when the schema is updated.
+//! Prefer these over the raw types unless you need data they don't expose.
+//!
+//! Views are cheap to create, since they only borrow the underlying data:
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_ecs_ldtk::{prelude::*, views::*};
+//! fn print_entities(level_query: Query<&Handle<LdtkLevel>>, levels: Res<Assets<LdtkLevel>>) {
+//!     for level in level_query.iter().filter_map(|h| levels.get(h)) {
+//!         for layer in level.data().layers() {
+//!             for entity in layer.entities() {
+//!                 println!("{} at {}", entity.identifier(), entity.grid_coords());
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::ldtk::{EntityInstance, FieldValue, LayerInstance, Level, Type};
+use bevy::prelude::*;
+
+/// Kind of an LDtk layer.
+///
+/// New kinds may be added when LDtk adds them, so matches need a wildcard arm.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+#[non_exhaustive]
+pub enum LayerKind {
+    IntGrid,
+    Entities,
+    Tiles,
+    AutoLayer,
+}
+
+impl From<&Type> for LayerKind {
+    fn from(layer_type: &Type) -> Self {
+        match layer_type {
+            Type::IntGrid => LayerKind::IntGrid,
+            Type::Entities => LayerKind::Entities,
+            Type::Tiles => LayerKind::Tiles,
+            Type::AutoLayer => LayerKind::AutoLayer,
+        }
+    }
+}
+
+/// Read-only view of an LDtk level.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LevelData<'a> {
+    level: &'a Level,
+}
+
+impl<'a> From<&'a Level> for LevelData<'a> {
+    fn from(level: &'a Level) -> Self {
+        LevelData { level }
+    }
+}
+
+impl<'a> LevelData<'a> {
+    /// Identifier of the level, as named in LDtk.
+    pub fn identifier(&self) -> &'a str {
+        &self.level.identifier
+    }
+
+    /// Unique identifier of the level within its project.
+    pub fn uid(&self) -> i32 {
+        self.level.uid
+    }
+
+    /// Unique instance identifier of the level, which stays stable when levels are reordered or
+    /// renamed.
+    ///
+    /// Empty for files saved before LDtk introduced iids.
+    pub fn iid(&self) -> &'a str {
+        &self.level.iid
+    }
+
+    /// Size of the level in pixels.
+    pub fn size(&self) -> IVec2 {
+        IVec2::new(self.level.px_wid, self.level.px_hei)
+    }
+
+    /// Position of the top-left corner of the level in the LDtk world, in pixels.
+    pub fn world_position(&self) -> IVec2 {
+        IVec2::new(self.level.world_x, self.level.world_y)
+    }
+
+    /// Background color of the level, as a hex string.
+    pub fn background_color(&self) -> &'a str {
+        &self.level.bg_color
+    }
+
+    /// Layers of the level in display order: the first layer is the top-most.
+    ///
+    /// Empty if the level's layers are stored in a separate file that hasn't been loaded.
+    pub fn layers(&self) -> impl Iterator<Item = LayerData<'a>> {
+        self.level
+            .layer_instances
+            .iter()
+            .flatten()
+            .map(LayerData::from)
+    }
+
+    /// Finds the layer with the given identifier.
+    pub fn layer(&self, identifier: &str) -> Option<LayerData<'a>> {
+        self.layers().find(|l| l.identifier() == identifier)
+    }
+
+    /// Finds the value of the level field with the given identifier.
+    pub fn field(&self, identifier: &str) -> Option<FieldData<'a>> {
+        self.level
+            .field_instances
+            .iter()
+            .find(|f| f.identifier == identifier)
+            .map(|f| FieldData::from(&f.value))
+    }
+
+    /// The raw LDtk data of the level.
+    pub fn raw(&self) -> &'a Level {
+        self.level
+    }
+}
+
+/// Read-only view of an LDtk layer instance.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LayerData<'a> {
+    layer_instance: &'a LayerInstance,
+}
+
+impl<'a> From<&'a LayerInstance> for LayerData<'a> {
+    fn from(layer_instance: &'a LayerInstance) -> Self {
+        LayerData { layer_instance }
+    }
+}
+
+impl<'a> LayerData<'a> {
+    /// Identifier of the layer definition.
+    pub fn identifier(&self) -> &'a str {
+        &self.layer_instance.identifier
+    }
+
+    /// Unique instance identifier of the layer.
+    ///
+    /// Empty for files saved before LDtk introduced layer iids.
+    pub fn iid(&self) -> &'a str {
+        &self.layer_instance.iid
+    }
+
+    /// Uid of the layer definition, which stays stable when the layer is renamed.
+    pub fn layer_def_uid(&self) -> i32 {
+        self.layer_instance.layer_def_uid
+    }
+
+    /// Kind of the layer.
+    pub fn kind(&self) -> LayerKind {
+        LayerKind::from(&self.layer_instance.layer_instance_type)
+    }
+
+    /// Size of a cell of the layer, in pixels.
+    pub fn grid_size(&self) -> i32 {
+        self.layer_instance.grid_size
+    }
+
+    /// Size of the layer in cells.
+    pub fn size_in_cells(&self) -> IVec2 {
+        IVec2::new(self.layer_instance.c_wid, self.layer_instance.c_hei)
+    }
+
+    /// Total offset of the layer in pixels, in LDtk's y-down coordinates.
+    pub fn offset(&self) -> IVec2 {
+        IVec2::new(
+            self.layer_instance.px_total_offset_x,
+            self.layer_instance.px_total_offset_y,
+        )
+    }
+
+    /// Opacity of the layer, from 0 to 1.
+    pub fn opacity(&self) -> f32 {
+        self.layer_instance.opacity
+    }
+
+    /// Whether the layer is visible in LDtk.
+    pub fn visible(&self) -> bool {
+        self.layer_instance.visible
+    }
+
+    /// Uid of the tileset used by the layer, if any.
+    pub fn tileset_uid(&self) -> Option<i32> {
+        self.layer_instance
+            .override_tileset_uid
+            .or(self.layer_instance.tileset_def_uid)
+    }
+
+    /// IntGrid value of the cell at the given grid coordinates, in LDtk's y-down coordinates.
+    ///
+    /// Returns [None] for empty cells, cells out of bounds, and layers other than IntGrid layers.
+    pub fn int_grid_value(&self, grid_coords: IVec2) -> Option<i32> {
+        let size = self.size_in_cells();
+        if grid_coords.x < 0
+            || grid_coords.y < 0
+            || grid_coords.x >= size.x
+            || grid_coords.y >= size.y
+        {
+            return None;
+        }
+
+        self.layer_instance
+            .int_grid_csv
+            .get((grid_coords.y * size.x + grid_coords.x) as usize)
+            .copied()
+            .filter(|v| *v != 0)
+    }
+
+    /// Entities of the layer. Empty for layers other than Entity layers.
+    pub fn entities(&self) -> impl Iterator<Item = EntityData<'a>> {
+        self.layer_instance
+            .entity_instances
+            .iter()
+            .map(EntityData::from)
+    }
+
+    /// The raw LDtk data of the layer.
+    pub fn raw(&self) -> &'a LayerInstance {
+        self.layer_instance
+    }
+}
+
+/// Read-only view of an LDtk entity instance.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EntityData<'a> {
+    entity_instance: &'a EntityInstance,
+}
+
+impl<'a> From<&'a EntityInstance> for EntityData<'a> {
+    fn from(entity_instance: &'a EntityInstance) -> Self {
+        EntityData { entity_instance }
+    }
+}
+
+impl<'a> EntityData<'a> {
+    /// Identifier of the entity definition.
+    pub fn identifier(&self) -> &'a str {
+        &self.entity_instance.identifier
+    }
+
+    /// Unique instance identifier of the entity.
+    ///
+    /// Empty for files saved before LDtk introduced iids.
+    pub fn iid(&self) -> &'a str {
+        &self.entity_instance.iid
+    }
+
+    /// Grid coordinates of the entity, in LDtk's y-down coordinates.
+    pub fn grid_coords(&self) -> IVec2 {
+        self.entity_instance.grid
+    }
+
+    /// Pixel coordinates of the entity's pivot in the level, in LDtk's y-down coordinates.
+    pub fn pixel_coords(&self) -> IVec2 {
+        self.entity_instance.px
+    }
+
+    /// Size of the entity in pixels.
+    pub fn size(&self) -> IVec2 {
+        IVec2::new(self.entity_instance.width, self.entity_instance.height)
+    }
+
+    /// Pivot of the entity, from 0 to 1 on both axes.
+    pub fn pivot(&self) -> Vec2 {
+        self.entity_instance.pivot
+    }
+
+    /// Finds the value of the entity field with the given identifier.
+    pub fn field(&self, identifier: &str) -> Option<FieldData<'a>> {
+        self.entity_instance
+            .field_instances
+            .iter()
+            .find(|f| f.identifier == identifier)
+            .map(|f| FieldData::from(&f.value))
+    }
+
+    /// The raw LDtk data of the entity.
+    pub fn raw(&self) -> &'a EntityInstance {
+        self.entity_instance
+    }
+}
+
+/// Read-only view of the value of an LDtk field.
+///
+/// The accessors return [None] if the field has a different type, or if it's null.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FieldData<'a> {
+    value: &'a FieldValue,
+}
+
+impl<'a> From<&'a FieldValue> for FieldData<'a> {
+    fn from(value: &'a FieldValue) -> Self {
+        FieldData { value }
+    }
+}
+
+impl<'a> FieldData<'a> {
+    /// Whether the field is a single value left null in LDtk.
+    ///
+    /// `Bool` and `Color` fields, and arrays, are never null.
+    pub fn is_null(&self) -> bool {
+        matches!(
+            self.value,
+            FieldValue::Int(None)
+                | FieldValue::Float(None)
+                | FieldValue::String(None)
+                | FieldValue::FilePath(None)
+                | FieldValue::Enum(None)
+                | FieldValue::Point(None)
+                | FieldValue::EntityRef(None)
+                | FieldValue::Tile(None)
+        )
+    }
+
+    /// Value of an `Int` field.
+    pub fn as_int(&self) -> Option<i32> {
+        match self.value {
+            FieldValue::Int(value) => *value,
+            _ => None,
+        }
+    }
+
+    /// Value of a `Float` field.
+    pub fn as_float(&self) -> Option<f32> {
+        match self.value {
+            FieldValue::Float(value) => *value,
+            _ => None,
+        }
+    }
+
+    /// Value of a `Bool` field.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.value {
+            FieldValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Value of a `String` or `Multilines` field.
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.value {
+            FieldValue::String(value) => value.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Value of a `Color` field.
+    pub fn as_color(&self) -> Option<Color> {
+        match self.value {
+            FieldValue::Color(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Path of a `FilePath` field, relative to the LDtk file.
+    pub fn as_file_path(&self) -> Option<&'a str> {
+        match self.value {
+            FieldValue::FilePath(value) => value.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Variant name of an enum field.
+    pub fn as_enum(&self) -> Option<&'a str> {
+        match self.value {
+            FieldValue::Enum(value) => value.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Grid coordinates of a `Point` field, in LDtk's y-down coordinates.
+    pub fn as_point(&self) -> Option<IVec2> {
+        match self.value {
+            FieldValue::Point(value) => *value,
+            _ => None,
+        }
+    }
+
+    /// Iid of the entity referenced by an `EntityRef` field.
+    pub fn as_entity_ref_iid(&self) -> Option<&'a str> {
+        match self.value {
+            FieldValue::EntityRef(Some(entity_ref)) => Some(entity_ref.entity_iid.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Values of an `Array<Int>` field, with [None] for null elements.
+    pub fn as_ints(&self) -> Option<&'a [Option<i32>]> {
+        match self.value {
+            FieldValue::Ints(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Values of an `Array<Float>` field, with [None] for null elements.
+    pub fn as_floats(&self) -> Option<&'a [Option<f32>]> {
+        match self.value {
+            FieldValue::Floats(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Values of an `Array<Bool>` field.
+    pub fn as_bools(&self) -> Option<&'a [bool]> {
+        match self.value {
+            FieldValue::Bools(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Values of an `Array<String>` or `Array<Multilines>` field, with [None] for null elements.
+    pub fn as_strs(&self) -> Option<impl Iterator<Item = Option<&'a str>>> {
+        match self.value {
+            FieldValue::Strings(values) => Some(values.iter().map(|v| v.as_deref())),
+            _ => None,
+        }
+    }
+
+    /// Values of an `Array<Color>` field.
+    pub fn as_colors(&self) -> Option<&'a [Color]> {
+        match self.value {
+            FieldValue::Colors(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Values of an `Array<Point>` field, with [None] for null elements.
+    pub fn as_points(&self) -> Option<&'a [Option<IVec2>]> {
+        match self.value {
+            FieldValue::Points(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// The raw LDtk data of the field.
+    pub fn raw(&self) -> &'a FieldValue {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_data_accessors() {
+        let level = Level {
+            identifier: "Level_0".to_string(),
+            iid: "a1b2".to_string(),
+            uid: 3,
+            px_wid: 256,
+            px_hei: 128,
+            world_x: 512,
+            world_y: -64,
+            ..Default::default()
+        };
+
+        let level_data = LevelData::from(&level);
+
+        assert_eq!(level_data.identifier(), "Level_0");
+        assert_eq!(level_data.iid(), "a1b2");
+        assert_eq!(level_data.uid(), 3);
+        assert_eq!(level_data.size(), IVec2::new(256, 128));
+        assert_eq!(level_data.world_position(), IVec2::new(512, -64));
+        assert_eq!(level_data.layers().count(), 0);
+        assert_eq!(level_data.field("missing"), None);
+    }
+
+    #[test]
+    fn test_field_data_accessors() {
+        let int = FieldValue::Int(Some(3));
+        let null_int = FieldValue::Int(None);
+        let string = FieldValue::String(Some("door".to_string()));
+        let points = FieldValue::Points(vec![Some(IVec2::new(1, 2)), None]);
+        let strings = FieldValue::Strings(vec![Some("a".to_string()), None]);
+
+        assert_eq!(FieldData::from(&int).as_int(), Some(3));
+        assert!(!FieldData::from(&int).is_null());
+        assert_eq!(FieldData::from(&int).as_float(), None);
+
+        assert_eq!(FieldData::from(&null_int).as_int(), None);
+        assert!(FieldData::from(&null_int).is_null());
+
+        assert_eq!(FieldData::from(&string).as_str(), Some("door"));
+        assert_eq!(FieldData::from(&string).as_enum(), None);
+
+        assert_eq!(
+            FieldData::from(&points).as_points(),
+            Some(&[Some(IVec2::new(1, 2)), None][..])
+        );
+        assert!(!FieldData::from(&points).is_null());
+
+        assert_eq!(
+            FieldData::from(&strings)
+                .as_strs()
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec![Some("a"), None]
+        );
+        assert!(FieldData::from(&int).as_strs().is_none());
+    }
+
+    #[test]
+    fn test_entity_data_accessors() {
+        let entity_instance = EntityInstance {
+            identifier: "Player".to_string(),
+            grid: IVec2::new(2, 5),
+            px: IVec2::new(40, 88),
+            width: 16,
+            height: 24,
+            pivot: Vec2::new(0.5, 1.),
+            ..Default::default()
+        };
+
+        let entity_data = EntityData::from(&entity_instance);
+
+        assert_eq!(entity_data.identifier(), "Player");
+        assert_eq!(entity_data.grid_coords(), IVec2::new(2, 5));
+        assert_eq!(entity_data.pixel_coords(), IVec2::new(40, 88));
+        assert_eq!(entity_data.size(), IVec2::new(16, 24));
+        assert_eq!(entity_data.pivot(), Vec2::new(0.5, 1.));
+    }
+}